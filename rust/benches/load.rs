@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use deltalake::bench_utils::LogGenerator;
+use tokio::runtime::Runtime;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+fn load_benchmarks(c: &mut Criterion) {
+    let rt = runtime();
+    let generator = LogGenerator::default();
+    let tmp_dir = tempdir::TempDir::new("bench_load").unwrap();
+    generator.generate(tmp_dir.path()).unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let latest_version = generator.num_commits as i64;
+
+    c.bench_function("load", |b| {
+        b.iter(|| rt.block_on(deltalake::open_table(table_uri)).unwrap())
+    });
+
+    c.bench_function("load_version", |b| {
+        b.iter(|| {
+            rt.block_on(deltalake::open_table_with_version(
+                table_uri,
+                latest_version / 2,
+            ))
+            .unwrap()
+        })
+    });
+
+    c.bench_function("update_10_commits", |b| {
+        b.iter_batched(
+            || {
+                rt.block_on(deltalake::open_table_with_version(
+                    table_uri,
+                    latest_version - 10,
+                ))
+                .unwrap()
+            },
+            |mut table| rt.block_on(table.update()).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let table = rt.block_on(deltalake::open_table(table_uri)).unwrap();
+    let filters = vec![deltalake::PartitionFilter {
+        key: "part",
+        value: deltalake::PartitionValue::Equal("3"),
+    }];
+    c.bench_function("get_files_by_partitions", |b| {
+        b.iter(|| table.get_files_by_partitions(&filters).unwrap())
+    });
+}
+
+fn churn_benchmarks(c: &mut Criterion) {
+    let rt = runtime();
+    let generator = LogGenerator {
+        num_commits: 200,
+        files_per_commit: 50,
+        removes_per_commit: 50,
+        ..Default::default()
+    };
+    let tmp_dir = tempdir::TempDir::new("bench_churn").unwrap();
+    generator.generate(tmp_dir.path()).unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+
+    c.bench_function("load_high_churn", |b| {
+        b.iter(|| rt.block_on(deltalake::open_table(table_uri)).unwrap())
+    });
+}
+
+criterion_group!(benches, load_benchmarks, churn_benchmarks);
+criterion_main!(benches);
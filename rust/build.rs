@@ -21,4 +21,10 @@ mod platform_cfg {
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     platform_cfg::set();
+
+    #[cfg(feature = "maintenance-server")]
+    {
+        println!("cargo:rerun-if-changed=proto/maintenance.proto");
+        tonic_build::compile_protos("proto/maintenance.proto").unwrap();
+    }
 }
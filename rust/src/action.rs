@@ -6,7 +6,7 @@ use std::collections::HashMap;
 
 use parquet::record::{ListAccessor, MapAccessor, RowAccessor};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use super::schema::*;
 
@@ -47,6 +47,27 @@ fn populate_hashmap_from_parquet_map(
     Ok(())
 }
 
+/// Like `populate_hashmap_from_parquet_map`, for partition values, which are null for null
+/// partitions.
+fn populate_partition_values_from_parquet_map(
+    map: &mut HashMap<String, Option<String>>,
+    pmap: &parquet::record::Map,
+) -> Result<(), &'static str> {
+    let keys = pmap.get_keys();
+    let values = pmap.get_values();
+    for j in 0..pmap.len() {
+        map.entry(
+            keys.get_string(j)
+                .map_err(|_| "key for HashMap in parquet has to be a string")?
+                .clone(),
+        )
+        // the values are strings, unless the partition value is null
+        .or_insert_with(|| values.get_string(j).ok().cloned());
+    }
+
+    Ok(())
+}
+
 fn gen_action_type_error(action: &str, field: &str, expected_type: &str) -> ActionError {
     ActionError::InvalidField(format!(
         "type for {} in {} action should be {}",
@@ -121,8 +142,13 @@ pub struct Stats {
     pub minValues: HashMap<String, ColumnValueStat>,
     /// Contains a value larger than all values present in the file for all columns.
     pub maxValues: HashMap<String, ColumnValueStat>,
-    /// The number of null values for all columns.
+    /// The number of null values for all columns. The null counts of the fields of struct columns
+    /// are nested under the name of the struct column.
     pub nullCount: HashMap<String, ColumnCountStat>,
+    /// Estimates of the number of distinct non-null values for some columns, nested like
+    /// `nullCount`. Only recorded by some writers, and left out when empty.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub distinctCount: HashMap<String, ColumnCountStat>,
 }
 
 /// File stats parsed from raw parquet format.
@@ -147,8 +173,9 @@ pub struct Add {
     pub path: String,
     /// The size of this file in bytes
     pub size: DeltaDataTypeLong,
-    /// A map from partition column to value for this file
-    pub partitionValues: HashMap<String, String>,
+    /// A map from partition column to value for this file. Null partition values are recorded as
+    /// `None` or, by some writers, as empty strings; see `get_partition_value`.
+    pub partitionValues: HashMap<String, Option<String>>,
     /// Partition values stored in raw parquet struct format. In this struct, the column names
     /// correspond to the partition columns and the values are stored in their corresponding data
     /// type. This is a required field when the table is partitioned and the table property
@@ -180,6 +207,43 @@ pub struct Add {
 }
 
 impl Add {
+    /// Returns the remove action taking this file out of the table at `deletion_timestamp`, in
+    /// milliseconds since the epoch. The partition values, size and tags of the file are kept as
+    /// extended file metadata. `data_change` is false when the data of the file is still in the
+    /// table after the commit, e.g. when compacting files.
+    pub fn into_remove(
+        self,
+        deletion_timestamp: DeltaDataTypeTimestamp,
+        data_change: bool,
+    ) -> Remove {
+        Remove {
+            path: self.path,
+            deletionTimestamp: deletion_timestamp,
+            dataChange: data_change,
+            extendedFileMetadata: Some(true),
+            partitionValues: Some(self.partitionValues),
+            size: Some(self.size),
+            tags: self.tags,
+        }
+    }
+
+    /// Returns the remove action of this file like `into_remove`, without consuming the add.
+    pub fn to_remove(
+        &self,
+        deletion_timestamp: DeltaDataTypeTimestamp,
+        data_change: bool,
+    ) -> Remove {
+        Remove {
+            path: self.path.clone(),
+            deletionTimestamp: deletion_timestamp,
+            dataChange: data_change,
+            extendedFileMetadata: Some(true),
+            partitionValues: Some(self.partitionValues.clone()),
+            size: Some(self.size),
+            tags: self.tags.clone(),
+        }
+    }
+
     fn from_parquet_record(record: &parquet::record::Row) -> Result<Self, ActionError> {
         let mut re = Self {
             ..Default::default()
@@ -212,7 +276,7 @@ impl Add {
                     let parquetMap = record
                         .get_map(i)
                         .map_err(|_| gen_action_type_error("add", "partitionValues", "map"))?;
-                    populate_hashmap_from_parquet_map(&mut re.partitionValues, parquetMap)
+                    populate_partition_values_from_parquet_map(&mut re.partitionValues, parquetMap)
                         .map_err(|estr| {
                             ActionError::InvalidField(format!(
                                 "Invalid partitionValues for add action: {}",
@@ -274,6 +338,16 @@ impl Add {
         Ok(re)
     }
 
+    /// Returns the value of the partition column for this file, or `None` when the value is
+    /// null. Null values recorded as empty strings or missing from `partitionValues` are
+    /// returned as `None` too.
+    pub fn get_partition_value(&self, column: &str) -> Option<&str> {
+        self.partitionValues
+            .get(column)
+            .and_then(|value| value.as_deref())
+            .filter(|value| !value.is_empty())
+    }
+
     /// Returns the serde_json representation of stats contained in the action if present.
     /// Since stats are defined as optional in the protocol, this may be None.
     pub fn get_stats(&self) -> Result<Option<Stats>, serde_json::error::Error> {
@@ -360,9 +434,29 @@ pub struct Format {
     options: Option<HashMap<String, String>>,
 }
 
+impl Format {
+    /// Creates a format with the given provider and options.
+    pub fn new(provider: &str, options: Option<HashMap<String, String>>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            options,
+        }
+    }
+
+    /// Name of the encoding for files in this table, e.g. `parquet`.
+    pub fn get_provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Configuration options for the format.
+    pub fn get_options(&self) -> Option<&HashMap<String, String>> {
+        self.options.as_ref()
+    }
+}
+
 /// Action that describes the metadata of the table.
 /// This is a top-level action in Delta log entries.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct MetaData {
     /// Unique identifier for this table
     pub id: Guid,
@@ -457,7 +551,7 @@ impl MetaData {
                             gen_action_type_error("metaData", "format.provider", "string")
                         })?
                         .clone();
-                    match record.get_map(1) {
+                    match format_record.get_map(1) {
                         Ok(options_map) => {
                             let mut options = HashMap::new();
                             populate_hashmap_from_parquet_map(&mut options, options_map).map_err(
@@ -509,7 +603,7 @@ pub struct Remove {
     /// When true the fields partitionValues, size, and tags are present
     pub extendedFileMetadata: Option<bool>,
     /// A map from partition column to value for this file.
-    pub partitionValues: Option<HashMap<String, String>>,
+    pub partitionValues: Option<HashMap<String, Option<String>>>,
     /// Size of this file in bytes
     pub size: Option<DeltaDataTypeLong>,
     /// Map containing metadata about this file
@@ -517,6 +611,25 @@ pub struct Remove {
 }
 
 impl Remove {
+    /// Returns the remove action of a file known only by its path, relative to the table root,
+    /// without extended file metadata. Prefer `Add::into_remove` when the add action of the file
+    /// is at hand.
+    pub fn new(
+        path: impl Into<String>,
+        deletion_timestamp: DeltaDataTypeTimestamp,
+        data_change: bool,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            deletionTimestamp: deletion_timestamp,
+            dataChange: data_change,
+            extendedFileMetadata: None,
+            partitionValues: None,
+            size: None,
+            tags: None,
+        }
+    }
+
     fn from_parquet_record(record: &parquet::record::Row) -> Result<Self, ActionError> {
         let mut re = Self {
             ..Default::default()
@@ -551,13 +664,16 @@ impl Remove {
                             gen_action_type_error("remove", "partitionValues", "map")
                         })?;
                         let mut partitionValues = HashMap::new();
-                        populate_hashmap_from_parquet_map(&mut partitionValues, parquetMap)
-                            .map_err(|estr| {
-                                ActionError::InvalidField(format!(
-                                    "Invalid partitionValues for remove action: {}",
-                                    estr,
-                                ))
-                            })?;
+                        populate_partition_values_from_parquet_map(
+                            &mut partitionValues,
+                            parquetMap,
+                        )
+                        .map_err(|estr| {
+                            ActionError::InvalidField(format!(
+                                "Invalid partitionValues for remove action: {}",
+                                estr,
+                            ))
+                        })?;
                         re.partitionValues = Some(partitionValues);
                     }
                     _ => re.partitionValues = None,
@@ -600,7 +716,7 @@ impl Remove {
 
 /// Action used by streaming systems to track progress using application-specific versions to
 /// enable idempotency.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Txn {
     /// A unique identifier for the application performing the transaction.
     pub appId: String,
@@ -650,7 +766,7 @@ impl Txn {
 
 /// Action used to increase the version of the Delta protocol required to read or write to the
 /// table.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Protocol {
     /// Minimum version of the Delta read protocol a client must implement to correctly read the
     /// table.
@@ -692,9 +808,91 @@ impl Protocol {
     }
 }
 
+/// Provenance information of a commit, as recorded in its `commitInfo` action. Writers record
+/// different information, so all the fields are optional and the fields without a typed
+/// counterpart are preserved in `info`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(from = "Value")]
+pub struct CommitInfo {
+    /// The version of the commit. Writers don't record it in the log, `DeltaTable::history` sets
+    /// it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<DeltaDataTypeVersion>,
+    /// The time of the commit, in milliseconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DeltaDataTypeTimestamp>,
+    /// The name of the committed operation, e.g. `WRITE`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    /// The parameters of the operation. Spark stores parameters that aren't strings JSON encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operationParameters: Option<Map<String, Value>>,
+    /// Metrics of the operation, e.g. the number of written files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operationMetrics: Option<Map<String, Value>>,
+    /// Metadata attached to the commit by the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userMetadata: Option<String>,
+    /// The name and version of the engine that made the commit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engineInfo: Option<String>,
+    /// The other fields of the commitInfo action.
+    #[serde(flatten)]
+    pub info: Map<String, Value>,
+}
+
+impl CommitInfo {
+    /// Returns the commit info of the given `commitInfo` action. A known field with an
+    /// unexpected type, e.g. a `timestamp` written as a string, is logged and kept untyped in
+    /// `info`, while the other fields are still typed.
+    pub fn from_value(value: &Value) -> Self {
+        let mut info = value.as_object().cloned().unwrap_or_default();
+        Self {
+            version: take_commit_info_field(&mut info, "version"),
+            timestamp: take_commit_info_field(&mut info, "timestamp"),
+            operation: take_commit_info_field(&mut info, "operation"),
+            operationParameters: take_commit_info_field(&mut info, "operationParameters"),
+            operationMetrics: take_commit_info_field(&mut info, "operationMetrics"),
+            userMetadata: take_commit_info_field(&mut info, "userMetadata"),
+            engineInfo: take_commit_info_field(&mut info, "engineInfo"),
+            info,
+        }
+    }
+}
+
+impl From<Value> for CommitInfo {
+    fn from(value: Value) -> Self {
+        Self::from_value(&value)
+    }
+}
+
+/// Removes the field `key` of a commitInfo action from `info` and returns its value. Null values
+/// are dropped, and values of another type than `T` are left in `info`.
+fn take_commit_info_field<T: serde::de::DeserializeOwned>(
+    info: &mut Map<String, Value>,
+    key: &str,
+) -> Option<T> {
+    let value = info.remove(key)?;
+    if value.is_null() {
+        return None;
+    }
+    match serde_json::from_value(value.clone()) {
+        Ok(typed) => Some(typed),
+        Err(e) => {
+            log::warn!(
+                "Keeping the {} field of a commitInfo action untyped: {}",
+                key,
+                e
+            );
+            info.insert(key.to_string(), value);
+            None
+        }
+    }
+}
+
 /// Represents an action in the Delta log. The Delta log is an aggregate of all actions performed
 /// on the table, so the full list of actions is required to properly read a table.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Action {
     /// Changes the current metadata of the table. Must be present in the first version of a table.
     /// Subsequent `metaData` actions completely overwrite previous metadata.
@@ -709,7 +907,52 @@ pub enum Action {
     /// Describes the minimum reader and writer versions required to read or write to the table.
     protocol(Protocol),
     /// Describes commit provenance information for the table.
-    commitInfo(Value),
+    commitInfo(CommitInfo),
+}
+
+/// The types of the actions represented by [`Action`], as they appear in the log.
+const KNOWN_ACTION_TYPES: [&str; 6] =
+    ["metaData", "add", "remove", "txn", "protocol", "commitInfo"];
+
+/// An action of a type this crate doesn't know, e.g. one added by a later version of the Delta
+/// protocol such as `cdc`, kept as it appears in the log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownAction {
+    /// The type of the action, e.g. `cdc`.
+    pub action_type: String,
+    /// The content of the action.
+    pub value: Value,
+}
+
+/// An action of a log entry, of a known type or not.
+#[derive(Debug, Clone)]
+pub enum LogAction {
+    /// An action of a known type.
+    Known(Action),
+    /// An action of a type this crate doesn't know.
+    Unknown(UnknownAction),
+}
+
+impl LogAction {
+    /// Parses a line of a log entry. A line that isn't an action of a known type is an unknown
+    /// action when it holds an object with a single field, named after a type that isn't known.
+    pub fn from_json(line: &str) -> Result<Self, serde_json::Error> {
+        let error = match serde_json::from_str(line) {
+            Ok(action) => return Ok(LogAction::Known(action)),
+            Err(e) => e,
+        };
+        // only parsed again on failure, to keep replaying known actions cheap
+        match serde_json::from_str(line) {
+            Ok(Value::Object(object)) if object.len() == 1 => {
+                let (action_type, value) = object.into_iter().next().unwrap();
+                if KNOWN_ACTION_TYPES.contains(&action_type.as_str()) {
+                    return Err(error);
+                }
+                Ok(LogAction::Unknown(UnknownAction { action_type, value }))
+            }
+            _ => Err(error),
+        }
+    }
 }
 
 impl Action {
@@ -769,7 +1012,7 @@ impl Action {
 
 /// Operation performed when creating a new log entry with one or more actions.
 /// This is a key element of the `CommitInfo` action.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum DeltaOperation {
     /// Represents a Delta `Write` operation.
     /// Write operations will typically only include `Add` actions.
@@ -790,11 +1033,104 @@ pub enum DeltaOperation {
         /// The epoch id of the written micro-batch.
         epochId: i64,
     },
+    /// Represents a Delta `ComputeStats` operation, which adds the statistics of files that were
+    /// committed without them.
+    ComputeStats,
+    /// Represents a Delta `Convert` operation, which creates a table from existing data files.
+    Convert,
+    /// Represents a Delta `Optimize` operation, which rewrites small files into larger ones
+    /// without changing the data.
+    Optimize {
+        /// The predicate selecting the partitions that were compacted.
+        predicate: Option<String>,
+        /// The size in bytes the rewritten files were targeting.
+        targetSize: DeltaDataTypeLong,
+    },
+    /// Represents a Delta `RepairPartitionValues` operation, which fills in the partition values
+    /// missing from add actions with the values found in the paths of their files.
+    RepairPartitionValues,
+    /// Represents a Delta `UpgradeProtocol` operation, which raises the protocol versions
+    /// required to read and write the table.
+    UpgradeProtocol {
+        /// The protocol the table was upgraded to.
+        newProtocol: Protocol,
+    },
+    /// Represents a Delta `Create` operation, which creates an empty table.
+    Create {
+        /// The description of the table.
+        description: Option<String>,
+        /// The columns the table is partitioned by.
+        partitionBy: Vec<String>,
+        /// The configuration of the table.
+        properties: HashMap<String, String>,
+    },
+    /// Represents a Delta `SetTableProperties` operation, which changes the configuration of the
+    /// table.
+    SetTableProperties {
+        /// The properties that were set.
+        properties: HashMap<String, String>,
+    },
+    /// Represents a Delta `Delete` operation, which removes the rows matching a predicate.
+    Delete {
+        /// The predicate of the deleted rows.
+        predicate: Option<String>,
+    },
+    /// Represents a Delta `Update` operation, which changes the values of the rows matching a
+    /// predicate.
+    Update {
+        /// The predicate of the updated rows.
+        predicate: Option<String>,
+    },
     // TODO: Add more operations
 }
 
+impl DeltaOperation {
+    /// Returns the name of the operation as recorded in the `operation` field of commitInfo.
+    pub fn name(&self) -> &str {
+        match self {
+            DeltaOperation::Write { .. } => "WRITE",
+            DeltaOperation::StreamingUpdate { .. } => "STREAMING UPDATE",
+            DeltaOperation::ComputeStats => "COMPUTE STATS",
+            DeltaOperation::Convert => "CONVERT",
+            DeltaOperation::Optimize { .. } => "OPTIMIZE",
+            DeltaOperation::RepairPartitionValues => "REPAIR PARTITION VALUES",
+            DeltaOperation::UpgradeProtocol { .. } => "UPGRADE PROTOCOL",
+            DeltaOperation::Create { .. } => "CREATE TABLE",
+            DeltaOperation::SetTableProperties { .. } => "SET TBLPROPERTIES",
+            DeltaOperation::Delete { .. } => "DELETE",
+            DeltaOperation::Update { .. } => "UPDATE",
+        }
+    }
+
+    /// Returns the parameters of the operation as recorded in the `operationParameters` field of
+    /// commitInfo. Like Spark, parameters that aren't strings are stored JSON encoded and unset
+    /// parameters are left out.
+    pub fn operation_parameters(&self) -> Result<Map<String, Value>, serde_json::Error> {
+        let mut parameters = Map::new();
+        if let Value::Object(operation) = serde_json::to_value(self)? {
+            for (_, fields) in operation {
+                if let Value::Object(fields) = fields {
+                    for (name, value) in fields {
+                        match value {
+                            Value::Null => {}
+                            Value::String(_) => {
+                                parameters.insert(name, value);
+                            }
+                            _ => {
+                                parameters.insert(name, Value::String(value.to_string()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+}
+
 /// The SaveMode used when performing a DeltaOperation
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum SaveMode {
     /// Files will be appended to the target location.
     Append,
@@ -807,7 +1143,7 @@ pub enum SaveMode {
 }
 
 /// The OutputMode used in streaming operations.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum OutputMode {
     /// Only new rows will be written when new data is available.
     Append,
@@ -823,6 +1159,74 @@ mod tests {
     use parquet::file::reader::{FileReader, SerializedFileReader};
     use std::fs::File;
 
+    #[test]
+    fn test_write_operation_parameters() {
+        let operation = DeltaOperation::Write {
+            mode: SaveMode::Append,
+            partitionBy: Some(vec!["year".to_string()]),
+            predicate: None,
+        };
+
+        assert_eq!(operation.name(), "WRITE");
+        let parameters = operation.operation_parameters().unwrap();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters["mode"], "Append");
+        assert_eq!(parameters["partitionBy"], "[\"year\"]");
+    }
+
+    #[test]
+    fn test_remove_from_add() {
+        let mut partition_values = HashMap::new();
+        partition_values.insert("year".to_string(), Some("2021".to_string()));
+        let add = Add {
+            path: "year=2021/part-00000.parquet".to_string(),
+            size: 1024,
+            partitionValues: partition_values.clone(),
+            modificationTime: 1_600_000_000_000,
+            dataChange: true,
+            ..Default::default()
+        };
+
+        let remove = add.to_remove(1_700_000_000_000, false);
+        assert_eq!(remove, add.clone().into_remove(1_700_000_000_000, false));
+        assert_eq!(remove.path, add.path);
+        assert_eq!(remove.deletionTimestamp, 1_700_000_000_000);
+        assert!(!remove.dataChange);
+        assert_eq!(remove.extendedFileMetadata, Some(true));
+        assert_eq!(remove.partitionValues, Some(partition_values));
+        assert_eq!(remove.size, Some(1024));
+
+        let remove = Remove::new("part-00001.parquet", 1_700_000_000_000, true);
+        assert!(remove.dataChange);
+        assert_eq!(remove.extendedFileMetadata, None);
+        assert_eq!(remove.size, None);
+    }
+
+    #[test]
+    fn test_commit_info_with_unexpected_types() {
+        let action: Action = serde_json::from_value(serde_json::json!({
+            "commitInfo": {
+                "timestamp": "1615043776198",
+                "operation": "WRITE",
+                "userMetadata": null,
+                "isBlindAppend": true,
+            }
+        }))
+        .unwrap();
+        let commit_info = match action {
+            Action::commitInfo(commit_info) => commit_info,
+            _ => panic!("expected a commitInfo action"),
+        };
+
+        // the string timestamp is kept untyped, the other fields are still typed
+        assert_eq!(commit_info.timestamp, None);
+        assert_eq!(commit_info.info["timestamp"], "1615043776198");
+        assert_eq!(commit_info.operation.as_deref(), Some("WRITE"));
+        assert_eq!(commit_info.userMetadata, None);
+        assert!(!commit_info.info.contains_key("userMetadata"));
+        assert_eq!(commit_info.info["isBlindAppend"], true);
+    }
+
     #[test]
     fn test_add_action_without_partition_values_and_stats() {
         let path = "./tests/data/delta-0.2.0/_delta_log/00000000000000000003.checkpoint.parquet";
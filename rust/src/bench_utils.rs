@@ -0,0 +1,161 @@
+//! Generators of synthetic Delta tables, used to benchmark loading and querying large tables.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::action::{self, Action};
+
+/// Base of the timestamps of the generated commits, in milliseconds since the Unix epoch.
+const BASE_TIMESTAMP: i64 = 1_600_000_000_000;
+
+/// Generates the transaction log of a synthetic table, with a configurable number of commits,
+/// files, partitions and churn. Only the log is written, the data files it references don't
+/// exist, so the generated tables can be loaded and their file lists filtered, but not scanned.
+///
+/// Checkpoints aren't generated since the crate can't write them yet.
+#[derive(Clone, Debug)]
+pub struct LogGenerator {
+    /// Number of commits after the first one, which only creates the table.
+    pub num_commits: usize,
+    /// Number of files added by each commit.
+    pub files_per_commit: usize,
+    /// Number of distinct values of the `part` partition column. The table isn't partitioned
+    /// when zero.
+    pub num_partitions: usize,
+    /// Number of the oldest files removed by each commit.
+    pub removes_per_commit: usize,
+    /// Whether to record statistics in the add actions.
+    pub with_stats: bool,
+}
+
+impl Default for LogGenerator {
+    fn default() -> Self {
+        Self {
+            num_commits: 100,
+            files_per_commit: 10,
+            num_partitions: 10,
+            removes_per_commit: 2,
+            with_stats: true,
+        }
+    }
+}
+
+impl LogGenerator {
+    /// Writes the transaction log of the table in the `_delta_log` directory of `table_dir`.
+    pub fn generate(&self, table_dir: &Path) -> io::Result<()> {
+        let log_dir = table_dir.join("_delta_log");
+        fs::create_dir_all(&log_dir)?;
+
+        let mut active_files = VecDeque::new();
+        for version in 0..=self.num_commits as i64 {
+            let actions = if version == 0 {
+                self.create_table_actions()
+            } else {
+                self.commit_actions(version, &mut active_files)
+            };
+            let mut log_entry = String::new();
+            for action in &actions {
+                log_entry.push_str(&serde_json::to_string(action)?);
+                log_entry.push('\n');
+            }
+            fs::write(log_dir.join(format!("{:020}.json", version)), log_entry)?;
+        }
+
+        Ok(())
+    }
+
+    fn create_table_actions(&self) -> Vec<Action> {
+        let mut fields = vec![
+            json!({"name": "id", "type": "long", "nullable": true, "metadata": {}}),
+            json!({"name": "value", "type": "string", "nullable": true, "metadata": {}}),
+        ];
+        let mut partition_columns = Vec::new();
+        if self.num_partitions > 0 {
+            fields
+                .push(json!({"name": "part", "type": "string", "nullable": true, "metadata": {}}));
+            partition_columns.push("part".to_string());
+        }
+        let schema = json!({"type": "struct", "fields": fields});
+
+        vec![
+            commit_info(0, "CREATE TABLE"),
+            Action::protocol(action::Protocol {
+                minReaderVersion: 1,
+                minWriterVersion: 2,
+            }),
+            Action::metaData(action::MetaData {
+                id: "00000000-0000-0000-0000-000000000000".to_string(),
+                format: action::Format::new("parquet", Some(HashMap::new())),
+                schemaString: schema.to_string(),
+                partitionColumns: partition_columns,
+                createdTime: BASE_TIMESTAMP,
+                ..Default::default()
+            }),
+        ]
+    }
+
+    fn commit_actions(&self, version: i64, active_files: &mut VecDeque<String>) -> Vec<Action> {
+        let timestamp = BASE_TIMESTAMP + version * 1000;
+        let mut actions = vec![commit_info(version, "WRITE")];
+
+        for _ in 0..self.removes_per_commit {
+            if let Some(path) = active_files.pop_front() {
+                actions.push(Action::remove(action::Remove::new(path, timestamp, true)));
+            }
+        }
+
+        for i in 0..self.files_per_commit {
+            let file_index = (version as usize - 1) * self.files_per_commit + i;
+            let file_name = format!("part-{:05}-{:05}.snappy.parquet", version, i);
+            let mut partition_values = HashMap::new();
+            let path = if self.num_partitions > 0 {
+                let part = (file_index % self.num_partitions).to_string();
+                let path = format!("part={}/{}", part, file_name);
+                partition_values.insert("part".to_string(), Some(part));
+                path
+            } else {
+                file_name
+            };
+            let stats = if self.with_stats {
+                let min_id = file_index as i64 * 1000;
+                Some(
+                    json!({
+                        "numRecords": 1000,
+                        "minValues": {"id": min_id, "value": "a"},
+                        "maxValues": {"id": min_id + 999, "value": "z"},
+                        "nullCount": {"id": 0, "value": 0},
+                    })
+                    .to_string(),
+                )
+            } else {
+                None
+            };
+
+            active_files.push_back(path.clone());
+            actions.push(Action::add(action::Add {
+                path,
+                size: 10_000,
+                partitionValues: partition_values,
+                modificationTime: timestamp,
+                dataChange: true,
+                stats,
+                ..Default::default()
+            }));
+        }
+
+        actions
+    }
+}
+
+fn commit_info(version: i64, operation: &str) -> Action {
+    Action::commitInfo(action::CommitInfo {
+        timestamp: Some(BASE_TIMESTAMP + version * 1000),
+        operation: Some(operation.to_string()),
+        operationParameters: Some(Default::default()),
+        ..Default::default()
+    })
+}
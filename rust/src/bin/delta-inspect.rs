@@ -36,6 +36,37 @@ async fn main() -> anyhow::Result<()> {
                         .about("specify table version"),
                 ]),
         )
+        .subcommand(
+            App::new("verify-stats")
+                .setting(AppSettings::ArgRequiredElseHelp)
+                .about("check the stats of add actions against the parquet footers of the files")
+                .args(&[
+                    Arg::new("path").about("Table path").required(true),
+                    Arg::new("sample")
+                        .takes_value(true)
+                        .long("sample")
+                        .short('n')
+                        .about("number of files to check, default to all files with stats"),
+                ]),
+        )
+        .subcommand(
+            App::new("export-log")
+                .setting(AppSettings::ArgRequiredElseHelp)
+                .about("copy the transaction log to a local directory, e.g. for a bug report")
+                .args(&[
+                    Arg::new("path").about("Table path").required(true),
+                    Arg::new("output")
+                        .takes_value(true)
+                        .required(true)
+                        .long("output")
+                        .short('o')
+                        .about("directory to write the _delta_log directory to"),
+                    Arg::new("redact")
+                        .takes_value(false)
+                        .long("redact")
+                        .about("replace paths, partition values, column stats and user info, leaving out checkpoints"),
+                ]),
+        )
         .get_matches();
 
     match matches.subcommand() {
@@ -65,6 +96,66 @@ async fn main() -> anyhow::Result<()> {
             let table = deltalake::open_table(table_path).await?;
             println!("{}", table);
         }
+        Some(("verify-stats", verify_matches)) => {
+            let table_path = verify_matches.value_of("path").unwrap();
+            let table = deltalake::open_table(table_path).await?;
+            let files: Vec<_> = table
+                .get_actions()
+                .iter()
+                .filter(|add| add.stats.is_some())
+                .collect();
+            let sample = match verify_matches.value_of_t::<usize>("sample") {
+                Ok(n) => n,
+                Err(clap::Error {
+                    kind: clap::ErrorKind::ArgumentNotFound,
+                    ..
+                }) => files.len(),
+                Err(e) => e.exit(),
+            };
+            // spread the sample evenly over the files
+            let step = std::cmp::max(1, files.len() / std::cmp::max(1, sample));
+
+            let mut num_mismatches = 0;
+            for add in files.iter().step_by(step).take(sample) {
+                let stats = match add.get_stats()? {
+                    Some(stats) => stats,
+                    None => continue,
+                };
+                let metadata = table.read_parquet_metadata(add).await?;
+                for mismatch in deltalake::writer::stats::verify_stats(&stats, &metadata) {
+                    num_mismatches += 1;
+                    println!(
+                        "{}: {} of column '{}' is {} but the file footer has {}",
+                        add.path,
+                        mismatch.stat,
+                        mismatch.column,
+                        mismatch.recorded,
+                        mismatch.actual
+                    );
+                }
+            }
+            if num_mismatches > 0 {
+                anyhow::bail!("found {} stats mismatches", num_mismatches);
+            }
+        }
+        Some(("export-log", export_matches)) => {
+            let table_path = export_matches.value_of("path").unwrap();
+            let output_dir = export_matches.value_of("output").unwrap();
+            let summary = deltalake::log_export::export_log(
+                table_path,
+                output_dir,
+                export_matches.is_present("redact"),
+            )
+            .await?;
+            for name in summary.skipped_files.iter() {
+                println!("skipped {}, it can't be redacted", name);
+            }
+            println!(
+                "exported {} log files to {}",
+                summary.exported_files.len(),
+                output_dir
+            );
+        }
         _ => unreachable!(),
     }
 
@@ -0,0 +1,47 @@
+extern crate anyhow;
+extern crate deltalake;
+
+use std::net::SocketAddr;
+
+use clap::{App, Arg};
+use deltalake::maintenance::{MaintenanceServerConfig, MaintenanceService};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let matches = App::new("Delta table maintenance server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Run maintenance operations on configured Delta tables over gRPC")
+        .args(&[
+            Arg::new("config")
+                .takes_value(true)
+                .long("config")
+                .short('c')
+                .required(true)
+                .about("JSON file with the tables and the bearer tokens of the server"),
+            Arg::new("bind")
+                .takes_value(true)
+                .long("bind")
+                .short('b')
+                .default_value("127.0.0.1:50051")
+                .about("Address to listen on"),
+        ])
+        .get_matches();
+
+    let addr: SocketAddr = matches.value_of_t("bind").unwrap_or_else(|e| e.exit());
+    let config: MaintenanceServerConfig =
+        serde_json::from_slice(&std::fs::read(matches.value_of("config").unwrap())?)?;
+    if config.bearer_tokens.is_empty() {
+        log::warn!("No bearer tokens configured, calls aren't authenticated");
+    }
+
+    log::info!(
+        "Serving maintenance of {} tables on {}",
+        config.tables.len(),
+        addr
+    );
+    MaintenanceService::new(config).serve(addr).await?;
+
+    Ok(())
+}
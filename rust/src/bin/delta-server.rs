@@ -0,0 +1,278 @@
+extern crate anyhow;
+extern crate deltalake;
+
+use std::collections::HashMap;
+use std::convert::{Infallible, TryFrom};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg};
+use deltalake::{DeltaTable, DeltaTableError, PartitionFilter, StorageError};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+
+/// Loaded tables keyed by table path. Entries are reloaded once they are older than `ttl`, and
+/// the oldest entry is evicted when the cache is full.
+struct SnapshotCache {
+    ttl: Duration,
+    capacity: usize,
+    tables: Mutex<HashMap<String, (Instant, Arc<DeltaTable>)>>,
+}
+
+impl SnapshotCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn get(&self, table_path: &str) -> Result<Arc<DeltaTable>, DeltaTableError> {
+        if let Some((loaded_at, table)) = self.tables.lock().unwrap().get(table_path) {
+            if loaded_at.elapsed() < self.ttl {
+                return Ok(table.clone());
+            }
+        }
+
+        let table = Arc::new(deltalake::open_table(table_path).await?);
+
+        let mut tables = self.tables.lock().unwrap();
+        if tables.len() >= self.capacity && !tables.contains_key(table_path) {
+            let oldest = tables
+                .iter()
+                .min_by_key(|(_, (loaded_at, _))| *loaded_at)
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                tables.remove(&oldest);
+            }
+        }
+        if self.capacity > 0 {
+            tables.insert(table_path.to_string(), (Instant::now(), table.clone()));
+        }
+
+        Ok(table)
+    }
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(b) => {
+                        decoded.push(b);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_query(query: Option<&str>) -> Vec<(String, String)> {
+    query
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = percent_decode(&parts.next().unwrap_or("").replace('+', " "));
+            let value = percent_decode(&parts.next().unwrap_or("").replace('+', " "));
+            (key, value)
+        })
+        .collect()
+}
+
+/// Parses a `partition` query parameter of the form `key=value` or `key!=value`.
+fn parse_partition_filter(filter: &str) -> Result<PartitionFilter<&str>, DeltaTableError> {
+    let (key, op, value) = match filter.find("!=") {
+        Some(idx) => (&filter[..idx], "!=", &filter[idx + 2..]),
+        None => match filter.find('=') {
+            Some(idx) => (&filter[..idx], "=", &filter[idx + 1..]),
+            None => {
+                return Err(DeltaTableError::InvalidPartitionFilter {
+                    partition_filter: filter.to_string(),
+                })
+            }
+        },
+    };
+    PartitionFilter::try_from((key, op, value))
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn error_response(err: DeltaTableError) -> Response<Body> {
+    let status = match &err {
+        DeltaTableError::NotATable
+        | DeltaTableError::StorageError {
+            source: StorageError::NotFound,
+        } => StatusCode::NOT_FOUND,
+        DeltaTableError::InvalidPartitionFilter { .. }
+        | DeltaTableError::LoadPartitions
+        | DeltaTableError::UriError { .. } => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    json_response(status, json!({ "error": err.to_string() }))
+}
+
+fn not_found() -> Response<Body> {
+    json_response(StatusCode::NOT_FOUND, json!({ "error": "Not found" }))
+}
+
+fn table_metadata(table: &DeltaTable) -> Result<Value, DeltaTableError> {
+    let metadata = table.get_metadata()?;
+    Ok(json!({
+        "version": table.version,
+        "id": metadata.id,
+        "name": metadata.name,
+        "description": metadata.description,
+        "format": metadata.format,
+        "partitionColumns": metadata.partition_columns,
+        "createdTime": metadata.created_time,
+        "configuration": metadata.configuration,
+        "minReaderVersion": table.get_min_reader_version(),
+        "minWriterVersion": table.get_min_writer_version(),
+        "numFiles": table.get_files().len(),
+    }))
+}
+
+fn table_files(table: &DeltaTable, query: &[(String, String)]) -> Result<Value, DeltaTableError> {
+    let filters = query
+        .iter()
+        .filter(|(key, _)| key == "partition")
+        .map(|(_, value)| parse_partition_filter(value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let files = if filters.is_empty() {
+        table.get_files_iter().map(str::to_string).collect()
+    } else {
+        table.get_files_by_partitions(&filters)?
+    };
+    Ok(json!({ "version": table.version, "files": files }))
+}
+
+async fn table_history(
+    table: &DeltaTable,
+    query: &[(String, String)],
+) -> Result<Value, DeltaTableError> {
+    let limit = query
+        .iter()
+        .find(|(key, _)| key == "limit")
+        .and_then(|(_, value)| value.parse::<usize>().ok());
+    let history = table.history(limit).await?;
+    Ok(json!({ "version": table.version, "history": history }))
+}
+
+fn table_schema(table: &DeltaTable) -> Result<Value, DeltaTableError> {
+    Ok(json!({ "version": table.version, "schema": table.get_schema()? }))
+}
+
+async fn handle(
+    cache: Arc<SnapshotCache>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::GET {
+        return Ok(json_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            json!({ "error": "Only GET requests are supported" }),
+        ));
+    }
+
+    // Routes are of the form `/tables/{path}/{endpoint}`, where `{path}` is the percent-encoded
+    // table uri.
+    let path = req.uri().path().trim_start_matches('/');
+    let (table_path, endpoint) = match (path.strip_prefix("tables/"), path.rfind('/')) {
+        (Some(_), Some(idx)) if idx > "tables/".len() => (
+            percent_decode(&path["tables/".len()..idx]),
+            &path[idx + 1..],
+        ),
+        _ => return Ok(not_found()),
+    };
+    if !matches!(endpoint, "metadata" | "files" | "history" | "schema") {
+        return Ok(not_found());
+    }
+    let query = parse_query(req.uri().query());
+
+    let table = match cache.get(&table_path).await {
+        Ok(table) => table,
+        Err(e) => return Ok(error_response(e)),
+    };
+
+    let result = match endpoint {
+        "metadata" => table_metadata(&table),
+        "files" => table_files(&table, &query),
+        "history" => table_history(&table, &query).await,
+        "schema" => table_schema(&table),
+        _ => return Ok(not_found()),
+    };
+
+    Ok(match result {
+        Ok(body) => json_response(StatusCode::OK, body),
+        Err(e) => error_response(e),
+    })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let matches = App::new("Delta table metadata server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Serve read-only metadata of Delta tables over HTTP")
+        .args(&[
+            Arg::new("bind")
+                .takes_value(true)
+                .long("bind")
+                .short('b')
+                .default_value("127.0.0.1:8080")
+                .about("Address to listen on"),
+            Arg::new("cache_ttl")
+                .takes_value(true)
+                .long("cache-ttl")
+                .default_value("60")
+                .about("Seconds a loaded table is served before it is reloaded"),
+            Arg::new("cache_size")
+                .takes_value(true)
+                .long("cache-size")
+                .default_value("16")
+                .about("Maximum number of loaded tables kept in memory"),
+        ])
+        .get_matches();
+
+    let addr: SocketAddr = matches.value_of_t("bind").unwrap_or_else(|e| e.exit());
+    let cache_ttl: u64 = matches.value_of_t("cache_ttl").unwrap_or_else(|e| e.exit());
+    let cache_size: usize = matches
+        .value_of_t("cache_size")
+        .unwrap_or_else(|e| e.exit());
+
+    let cache = Arc::new(SnapshotCache::new(
+        Duration::from_secs(cache_ttl),
+        cache_size,
+    ));
+    let make_svc = make_service_fn(move |_conn| {
+        let cache = cache.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(cache.clone(), req))) }
+    });
+
+    log::info!("Listening on http://{}", addr);
+    Server::bind(&addr).serve(make_svc).await?;
+
+    Ok(())
+}
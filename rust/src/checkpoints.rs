@@ -0,0 +1,347 @@
+//! Parquet schema of the checkpoints of a Delta Table.
+//!
+//! Each record of a checkpoint holds one action, stored in the column group named after the action
+//! type. The `partitionValues_parsed` and `stats_parsed` groups of the `add` actions hold the
+//! partition values and column stats of the files as typed columns, so their shape depends on the
+//! schema of the table. [`checkpoint_schema`] derives the full checkpoint schema from the metadata
+//! of a table, with the same layout as the checkpoints written by Spark.
+//!
+//! ```rust
+//! async {
+//!   let table = deltalake::open_table("./tests/data/simple_table").await.unwrap();
+//!   let schema = deltalake::checkpoints::checkpoint_schema(table.get_metadata().unwrap()).unwrap();
+//! };
+//! ```
+
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+use parquet::errors::ParquetError;
+use parquet::schema::types::{Type, TypePtr};
+use regex::Regex;
+
+use crate::delta::DeltaTableMetaData;
+use crate::schema::{SchemaDataType, SchemaField};
+
+/// Returns the parquet schema of the checkpoints of a table with the given metadata.
+///
+/// `partitionValues_parsed` is only present for partitioned tables. `stats_parsed` holds the
+/// record count of the files, the min and max values of their non-partition columns of types with
+/// an order, and the null counts of their non-partition columns other than lists and maps. Groups
+/// without any column are left out, since parquet doesn't allow empty groups.
+pub fn checkpoint_schema(metadata: &DeltaTableMetaData) -> Result<TypePtr, ParquetError> {
+    let fields = vec![
+        txn_schema()?,
+        add_schema(metadata)?,
+        remove_schema()?,
+        metadata_schema()?,
+        protocol_schema()?,
+    ];
+    Ok(Arc::new(group("spark_schema", None, fields)?))
+}
+
+fn txn_schema() -> Result<TypePtr, ParquetError> {
+    optional_group(
+        "txn",
+        vec![
+            string("appId")?,
+            primitive("version", PhysicalType::INT64, None)?,
+            primitive("lastUpdated", PhysicalType::INT64, None)?,
+        ],
+    )
+}
+
+fn add_schema(metadata: &DeltaTableMetaData) -> Result<TypePtr, ParquetError> {
+    let mut fields = vec![
+        string("path")?,
+        string_map("partitionValues")?,
+        primitive("size", PhysicalType::INT64, None)?,
+        primitive("modificationTime", PhysicalType::INT64, None)?,
+        primitive("dataChange", PhysicalType::BOOLEAN, None)?,
+        string("stats")?,
+        string_map("tags")?,
+    ];
+
+    let (partition_fields, data_fields): (Vec<&SchemaField>, Vec<&SchemaField>) =
+        metadata.schema.get_fields().iter().partition(|field| {
+            metadata
+                .partition_columns
+                .iter()
+                .any(|column| column == field.get_name())
+        });
+    if !partition_fields.is_empty() {
+        let partition_values = partition_fields
+            .iter()
+            .map(|field| column_type(field.get_name(), field.get_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        fields.push(optional_group("partitionValues_parsed", partition_values)?);
+    }
+
+    let mut stats = vec![primitive("numRecords", PhysicalType::INT64, None)?];
+    let value_stats = value_stats_fields(&data_fields)?;
+    if !value_stats.is_empty() {
+        stats.push(optional_group("minValues", value_stats.clone())?);
+        stats.push(optional_group("maxValues", value_stats)?);
+    }
+    let null_counts = null_count_fields(&data_fields)?;
+    if !null_counts.is_empty() {
+        stats.push(optional_group("nullCount", null_counts)?);
+    }
+    fields.push(optional_group("stats_parsed", stats)?);
+
+    optional_group("add", fields)
+}
+
+fn remove_schema() -> Result<TypePtr, ParquetError> {
+    optional_group(
+        "remove",
+        vec![
+            string("path")?,
+            primitive("deletionTimestamp", PhysicalType::INT64, None)?,
+            primitive("dataChange", PhysicalType::BOOLEAN, None)?,
+            primitive("extendedFileMetadata", PhysicalType::BOOLEAN, None)?,
+            string_map("partitionValues")?,
+            primitive("size", PhysicalType::INT64, None)?,
+            string_map("tags")?,
+        ],
+    )
+}
+
+fn metadata_schema() -> Result<TypePtr, ParquetError> {
+    optional_group(
+        "metaData",
+        vec![
+            string("id")?,
+            string("name")?,
+            string("description")?,
+            optional_group("format", vec![string("provider")?, string_map("options")?])?,
+            string("schemaString")?,
+            list("partitionColumns", string("element")?)?,
+            string_map("configuration")?,
+            primitive("createdTime", PhysicalType::INT64, None)?,
+        ],
+    )
+}
+
+fn protocol_schema() -> Result<TypePtr, ParquetError> {
+    optional_group(
+        "protocol",
+        vec![
+            primitive("minReaderVersion", PhysicalType::INT32, None)?,
+            primitive("minWriterVersion", PhysicalType::INT32, None)?,
+        ],
+    )
+}
+
+/// Returns the min/max value columns of the stats of the given fields, recursing into structs.
+fn value_stats_fields(fields: &[&SchemaField]) -> Result<Vec<TypePtr>, ParquetError> {
+    let mut stats = Vec::new();
+    for field in fields {
+        match field.get_type() {
+            SchemaDataType::primitive(p) if p == "binary" || p == "boolean" => {}
+            SchemaDataType::primitive(_) => {
+                stats.push(column_type(field.get_name(), field.get_type())?);
+            }
+            SchemaDataType::r#struct(s) => {
+                let nested = value_stats_fields(&s.get_fields().iter().collect::<Vec<_>>())?;
+                if !nested.is_empty() {
+                    stats.push(optional_group(field.get_name(), nested)?);
+                }
+            }
+            SchemaDataType::array(_) | SchemaDataType::map(_) => {}
+        }
+    }
+    Ok(stats)
+}
+
+/// Returns the null count columns of the stats of the given fields, recursing into structs.
+fn null_count_fields(fields: &[&SchemaField]) -> Result<Vec<TypePtr>, ParquetError> {
+    let mut stats = Vec::new();
+    for field in fields {
+        match field.get_type() {
+            SchemaDataType::r#struct(s) => {
+                let nested = null_count_fields(&s.get_fields().iter().collect::<Vec<_>>())?;
+                if !nested.is_empty() {
+                    stats.push(optional_group(field.get_name(), nested)?);
+                }
+            }
+            SchemaDataType::primitive(_) => {
+                stats.push(primitive(field.get_name(), PhysicalType::INT64, None)?)
+            }
+            SchemaDataType::array(_) | SchemaDataType::map(_) => {}
+        }
+    }
+    Ok(stats)
+}
+
+/// Returns the optional parquet column storing values of a Delta data type, the way Spark writes
+/// them.
+fn column_type(name: &str, data_type: &SchemaDataType) -> Result<TypePtr, ParquetError> {
+    lazy_static! {
+        static ref DECIMAL_REGEX: Regex = Regex::new(r"^decimal\((\d{1,2}),(\d{1,2})\)$").unwrap();
+    }
+
+    match data_type {
+        SchemaDataType::primitive(p) => match p.as_str() {
+            "string" => string(name),
+            "long" => primitive(name, PhysicalType::INT64, None),
+            "integer" => primitive(name, PhysicalType::INT32, None),
+            "short" => primitive(name, PhysicalType::INT32, Some(ConvertedType::INT_16)),
+            "byte" => primitive(name, PhysicalType::INT32, Some(ConvertedType::INT_8)),
+            "float" => primitive(name, PhysicalType::FLOAT, None),
+            "double" => primitive(name, PhysicalType::DOUBLE, None),
+            "boolean" => primitive(name, PhysicalType::BOOLEAN, None),
+            "binary" => primitive(name, PhysicalType::BYTE_ARRAY, None),
+            "date" => primitive(name, PhysicalType::INT32, Some(ConvertedType::DATE)),
+            "timestamp" => primitive(
+                name,
+                PhysicalType::INT64,
+                Some(ConvertedType::TIMESTAMP_MICROS),
+            ),
+            decimal => {
+                let captures = DECIMAL_REGEX.captures(decimal).ok_or_else(|| {
+                    ParquetError::General(format!("Invalid data type for parquet: {}", decimal))
+                })?;
+                let precision = captures[1].parse::<i32>().unwrap_or_default();
+                let scale = captures[2].parse::<i32>().unwrap_or_default();
+                decimal_type(name, precision, scale)
+            }
+        },
+        SchemaDataType::r#struct(s) => optional_group(
+            name,
+            s.get_fields()
+                .iter()
+                .map(|field| column_type(field.get_name(), field.get_type()))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        SchemaDataType::array(a) => list(name, column_type("element", a.get_element_type())?),
+        SchemaDataType::map(m) => map(
+            name,
+            column_type("key", m.get_key_type())?,
+            column_type("value", m.get_value_type())?,
+        ),
+    }
+}
+
+/// Returns a decimal column in the smallest physical type holding its precision, as Spark writes
+/// them.
+fn decimal_type(name: &str, precision: i32, scale: i32) -> Result<TypePtr, ParquetError> {
+    let physical_type = if precision <= 9 {
+        PhysicalType::INT32
+    } else if precision <= 18 {
+        PhysicalType::INT64
+    } else {
+        PhysicalType::FIXED_LEN_BYTE_ARRAY
+    };
+    // the number of bytes of a two's complement big-endian unscaled value of the precision
+    let length = (1..=16)
+        .find(|bytes| 2f64.powi(8 * bytes - 1) >= 10f64.powi(precision))
+        .unwrap_or(16);
+    let mut builder = Type::primitive_type_builder(name, physical_type)
+        .with_repetition(Repetition::OPTIONAL)
+        .with_converted_type(ConvertedType::DECIMAL)
+        .with_precision(precision)
+        .with_scale(scale);
+    if physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+        builder = builder.with_length(length);
+    }
+    Ok(Arc::new(builder.build()?))
+}
+
+fn primitive(
+    name: &str,
+    physical_type: PhysicalType,
+    converted_type: Option<ConvertedType>,
+) -> Result<TypePtr, ParquetError> {
+    Ok(Arc::new(
+        Type::primitive_type_builder(name, physical_type)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_converted_type(converted_type.unwrap_or(ConvertedType::NONE))
+            .build()?,
+    ))
+}
+
+fn string(name: &str) -> Result<TypePtr, ParquetError> {
+    primitive(name, PhysicalType::BYTE_ARRAY, Some(ConvertedType::UTF8))
+}
+
+fn string_map(name: &str) -> Result<TypePtr, ParquetError> {
+    map(name, string("key")?, string("value")?)
+}
+
+/// Returns a three-level list group with the given element column.
+fn list(name: &str, element: TypePtr) -> Result<TypePtr, ParquetError> {
+    let repeated = Arc::new(
+        Type::group_type_builder("list")
+            .with_repetition(Repetition::REPEATED)
+            .with_fields(&mut vec![element])
+            .build()?,
+    );
+    Ok(Arc::new(
+        Type::group_type_builder(name)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_converted_type(ConvertedType::LIST)
+            .with_fields(&mut vec![repeated])
+            .build()?,
+    ))
+}
+
+/// Returns a map group of the given key and value columns. Map keys can't be null.
+fn map(name: &str, key: TypePtr, value: TypePtr) -> Result<TypePtr, ParquetError> {
+    let key = Arc::new(required(&key)?);
+    let key_value = Arc::new(
+        Type::group_type_builder("key_value")
+            .with_repetition(Repetition::REPEATED)
+            .with_fields(&mut vec![key, value])
+            .build()?,
+    );
+    Ok(Arc::new(
+        Type::group_type_builder(name)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_converted_type(ConvertedType::MAP)
+            .with_fields(&mut vec![key_value])
+            .build()?,
+    ))
+}
+
+/// Returns a copy of an optional column with a required repetition.
+fn required(column: &Type) -> Result<Type, ParquetError> {
+    let info = column.get_basic_info();
+    match column {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => Type::primitive_type_builder(info.name(), *physical_type)
+            .with_repetition(Repetition::REQUIRED)
+            .with_converted_type(info.converted_type())
+            .with_length(*type_length)
+            .with_precision(*precision)
+            .with_scale(*scale)
+            .build(),
+        Type::GroupType { fields, .. } => Type::group_type_builder(info.name())
+            .with_repetition(Repetition::REQUIRED)
+            .with_converted_type(info.converted_type())
+            .with_fields(&mut fields.clone())
+            .build(),
+    }
+}
+
+fn optional_group(name: &str, fields: Vec<TypePtr>) -> Result<TypePtr, ParquetError> {
+    Ok(Arc::new(group(name, Some(Repetition::OPTIONAL), fields)?))
+}
+
+fn group(
+    name: &str,
+    repetition: Option<Repetition>,
+    mut fields: Vec<TypePtr>,
+) -> Result<Type, ParquetError> {
+    let mut builder = Type::group_type_builder(name).with_fields(&mut fields);
+    if let Some(repetition) = repetition {
+        builder = builder.with_repetition(repetition);
+    }
+    builder.build()
+}
@@ -3,32 +3,47 @@
 // Reference: https://github.com/delta-io/delta/blob/master/PROTOCOL.md
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
 use std::io::{BufRead, BufReader, Cursor};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
 
 use arrow::error::ArrowError;
-use chrono::{DateTime, FixedOffset, Utc};
-use futures::StreamExt;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use flate2::bufread::MultiGzDecoder;
+use futures::{Stream, StreamExt, TryStreamExt};
 use lazy_static::lazy_static;
-use log::debug;
+use log::{debug, warn};
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
 use parquet::errors::ParquetError;
 use parquet::file::{
+    footer::parse_metadata,
+    metadata::ParquetMetaData,
     reader::{FileReader, SerializedFileReader},
     serialized_reader::SliceableCursor,
 };
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::convert::TryFrom;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use super::action;
-use super::action::{Action, DeltaOperation};
-use super::partitions::{DeltaTablePartition, PartitionFilter};
+use super::action::{Action, DeltaOperation, LogAction, UnknownAction};
+use super::partitions::{
+    partition_values_from_path, DeltaTablePartition, PartitionFilter, PartitionValue,
+};
 use super::schema::*;
 use super::storage;
-use super::storage::{StorageBackend, StorageError, UriError};
+use super::storage::bounded::{run_bounded, BoundedStorageBackend};
+use super::storage::{ObjectMeta, StorageBackend, StorageBackendKind, StorageError, UriError};
+use super::table_properties;
+use super::writer::stats::{
+    compare_values, count_stat, fill_missing_stats, stats_from_footer, stats_from_record_batches,
+    value_stat,
+};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Metadata for a checkpoint file
@@ -40,6 +55,38 @@ pub struct CheckPoint {
     parts: Option<u32>, // 10 digits decimals
 }
 
+impl CheckPoint {
+    /// Returns the table version the checkpoint was written for.
+    pub fn version(&self) -> DeltaDataTypeVersion {
+        self.version
+    }
+
+    /// Returns the number of actions in the checkpoint, or 0 when it was found by listing the log
+    /// directory instead of reading `_last_checkpoint`.
+    pub fn size(&self) -> DeltaDataTypeLong {
+        self.size
+    }
+
+    /// Returns the number of files of a multi-part checkpoint, `None` for single file checkpoints.
+    pub fn parts(&self) -> Option<u32> {
+        self.parts
+    }
+}
+
+/// Checkpoints of a DeltaTable, returned by `DeltaTable::get_last_checkpoint_info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointInfo {
+    /// The checkpoint currently recorded in the `_last_checkpoint` file of the table, `None` when
+    /// the file doesn't exist.
+    pub last_checkpoint: Option<CheckPoint>,
+    /// The checkpoint the loaded state was restored from, `None` when it was replayed from the
+    /// first commit.
+    pub loaded_checkpoint: Option<CheckPoint>,
+    /// Number of commits replayed on top of the loaded checkpoint, or from the first commit, to
+    /// reach the loaded version.
+    pub num_commits_since_checkpoint: DeltaDataTypeVersion,
+}
+
 impl PartialEq for CheckPoint {
     fn eq(&self, other: &Self) -> bool {
         self.version == other.version
@@ -149,11 +196,135 @@ pub enum DeltaTableError {
         /// The invalid partition filter used.
         partition_filter: String,
     },
+    /// Error returned when an overwrite restricted by partition filters adds a file outside of the
+    /// partitions it replaces.
+    #[error("File {path} doesn't match the predicate {predicate} of the overwrite")]
+    OverwriteOutsidePredicate {
+        /// The path of the added file.
+        path: String,
+        /// The predicate of the overwrite.
+        predicate: String,
+    },
     /// Error returned when Vacuume retention period is below the safe threshold
     #[error(
         "Invalid retention period, retention for Vacuum must be greater than 1 week (168 hours)"
     )]
     InvalidVacuumRetentionPeriod,
+    /// Error returned when planning a compaction with a target file size that isn't positive.
+    #[error("Invalid compaction target size {0}, it must be greater than 0 bytes")]
+    InvalidTargetSize(DeltaDataTypeLong),
+    /// Error returned when the data files of the table aren't parquet files, or the format has
+    /// options that aren't supported.
+    #[error("Unsupported table format: provider {provider} with options {options:?}")]
+    UnsupportedFormat {
+        /// The format provider of the table.
+        provider: String,
+        /// The options of the format.
+        options: HashMap<String, String>,
+    },
+    /// Error returned when committing metadata with an unknown `delta.*` table property, or a
+    /// property value of the wrong format.
+    #[error("Invalid table property {key}: {reason}")]
+    InvalidTableProperty {
+        /// The key of the property.
+        key: String,
+        /// Why the property is invalid, with the closest known property for unknown ones.
+        reason: String,
+    },
+    /// Error returned when a column name doesn't refer to a column of the table.
+    #[error("Column {column} not found in the table")]
+    ColumnNotFound {
+        /// The column name that wasn't found.
+        column: String,
+    },
+    /// Error returned when a column name refers to several columns of the table when matched
+    /// case-insensitively.
+    #[error("Column {column} is ambiguous, it matches columns {candidates:?}")]
+    AmbiguousColumn {
+        /// The ambiguous column name.
+        column: String,
+        /// The columns of the table matching the name.
+        candidates: Vec<String>,
+    },
+    /// Error returned when vacuuming a table loaded with `skip_tombstones`.
+    #[error("Table was loaded without tombstones, which are required to vacuum it")]
+    TombstonesNotLoaded,
+    /// Error returned when time traveling to a timestamp before the first commit of the table.
+    #[error(
+        "Timestamp is earlier than the table creation, earliest available timestamp: {}",
+        .earliest
+    )]
+    TimestampEarlierThanTableCreation {
+        /// The timestamp of the earliest available version of the table.
+        earliest: DateTime<Utc>,
+    },
+    /// Error returned when loading a version whose commit has been cleaned up from the log, with
+    /// no checkpoint left to restore it from.
+    #[error(
+        "Version {} is no longer retained, earliest available version: {}",
+        .version,
+        .earliest_available
+    )]
+    VersionNotRetained {
+        /// The requested version.
+        version: DeltaDataTypeVersion,
+        /// The earliest version the table can still be loaded at.
+        earliest_available: DeltaDataTypeVersion,
+    },
+    /// Error returned when time traveling to a timestamp before the earliest version retained in
+    /// the log, after the commits of older versions have been cleaned up.
+    #[error(
+        "Timestamp is earlier than the earliest retained version {}, committed at {}",
+        .earliest_available,
+        .earliest
+    )]
+    TimestampNotRetained {
+        /// The timestamp of the earliest available version of the table.
+        earliest: DateTime<Utc>,
+        /// The earliest version the table can still be loaded at.
+        earliest_available: DeltaDataTypeVersion,
+    },
+    /// Error returned when loading a table with `DeltaTableConfig::verify_file_sizes` and active
+    /// files are missing from the storage or have another size than in their add action.
+    #[error(
+        "{} active files don't match their add action, e.g. {}",
+        .mismatches.len(),
+        .mismatches[0]
+    )]
+    FileSizeMismatch {
+        /// The files that don't match their add action.
+        mismatches: Vec<FileSizeMismatch>,
+    },
+    /// Error returned when an operation takes longer than its `OperationTimeouts` duration.
+    #[error("{} timed out after {:?}", .operation, .timeout)]
+    Timeout {
+        /// The operation that timed out, e.g. `load`.
+        operation: String,
+        /// The timeout of the operation.
+        timeout: Duration,
+    },
+    /// Error returned when an operation is aborted by `DeltaTableConfig::cancellation_token`.
+    #[error("{} cancelled", .operation)]
+    Cancelled {
+        /// The cancelled operation, e.g. `load`.
+        operation: String,
+    },
+    /// Error returned when a commit would set the protocol of a table to versions this crate
+    /// doesn't support, or to versions lower than the current ones.
+    #[error(
+        "Invalid protocol upgrade to reader version {} and writer version {}: {}",
+        .min_reader_version,
+        .min_writer_version,
+        .reason
+    )]
+    InvalidProtocolUpgrade {
+        /// The requested minimum reader version.
+        min_reader_version: i32,
+        /// The requested minimum writer version.
+        min_writer_version: i32,
+        /// Why the versions can't be used.
+        reason: String,
+    },
 }
 
 /// Delta table metadata
@@ -187,6 +358,52 @@ impl fmt::Display for DeltaTableMetaData {
     }
 }
 
+impl DeltaTableMetaData {
+    /// Creates the metadata of a new table, with a random id and the current time as its
+    /// creation time. The format defaults to parquet.
+    pub fn new(
+        name: Option<String>,
+        description: Option<String>,
+        format: Option<action::Format>,
+        schema: Schema,
+        partition_columns: Vec<String>,
+        configuration: HashMap<String, String>,
+    ) -> Self {
+        let created_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as DeltaDataTypeTimestamp)
+            .unwrap_or(0);
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            format: format.unwrap_or_else(|| action::Format::new("parquet", None)),
+            schema,
+            partition_columns,
+            created_time,
+            configuration,
+        }
+    }
+}
+
+impl TryFrom<DeltaTableMetaData> for action::MetaData {
+    type Error = serde_json::Error;
+
+    fn try_from(metadata: DeltaTableMetaData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: metadata.id,
+            name: metadata.name,
+            description: metadata.description,
+            format: metadata.format,
+            schemaString: serde_json::to_string(&metadata.schema)?,
+            partitionColumns: metadata.partition_columns,
+            createdTime: metadata.created_time,
+            configuration: metadata.configuration,
+        })
+    }
+}
+
 /// Error related to Delta log application
 #[derive(thiserror::Error, Debug)]
 pub enum ApplyLogError {
@@ -213,6 +430,14 @@ pub enum ApplyLogError {
         #[from]
         source: std::io::Error,
     },
+    /// Error returned when the log holds an action of an unknown type that can't be ignored,
+    /// because of `UnknownActionPolicy::Strict` or because the table requires a reader version
+    /// higher than this crate supports.
+    #[error("Unknown action {} in the log can't be skipped", .action_type)]
+    UnknownAction {
+        /// The type of the unknown action.
+        action_type: String,
+    },
 }
 
 impl From<StorageError> for ApplyLogError {
@@ -254,17 +479,255 @@ impl From<StorageError> for LoadCheckpointError {
     }
 }
 
-#[derive(Default, Debug)]
+/// A file removed from the table that may still be read by concurrent readers of older versions,
+/// and that must not be vacuumed before its retention period has passed.
+///
+/// Only what's needed to vacuum the file is kept in memory; the full `remove` actions can be read
+/// with `DeltaTable::load_extended_tombstones`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tombstone {
+    /// The path of the removed file, relative to the table root.
+    pub path: String,
+    /// The time the file was removed, in milliseconds since the Unix epoch.
+    pub deletion_timestamp: DeltaDataTypeTimestamp,
+}
+
+#[derive(Default, Debug, Clone)]
 struct DeltaTableState {
     // A remove action should remain in the state of the table as a tombstone until it has expired.
     // A tombstone expires when the creation timestamp of the delta file exceeds the expiration
-    tombstones: Vec<action::Remove>,
+    tombstones: Vec<Tombstone>,
     files: Vec<action::Add>,
-    commit_infos: Vec<Value>,
+    // positions of the active files by path, so that adds and removes find the file they replace
+    // or remove without scanning the file list; built on the first add and dropped when a remove
+    // shifts the positions
+    file_positions: Option<HashMap<String, usize>>,
+    commit_infos: Vec<action::CommitInfo>,
+    unknown_actions: Vec<UnknownAction>,
     app_transaction_version: HashMap<String, DeltaDataTypeVersion>,
     min_reader_version: i32,
     min_writer_version: i32,
     current_metadata: Option<DeltaTableMetaData>,
+    // built after loading when enabled by `DeltaTableConfig::partition_index`, and dropped as
+    // soon as a log entry changes the state
+    partition_index: Option<PartitionIndex>,
+}
+
+/// Indices of the active files grouped by their partition values, in the order of the partition
+/// columns, so that partition filters are evaluated once per partition rather than once per file.
+#[derive(Default, Debug, Clone)]
+struct PartitionIndex {
+    files: HashMap<Vec<Option<String>>, Vec<usize>>,
+}
+
+impl PartitionIndex {
+    fn new(files: &[action::Add], partition_columns: &[String]) -> Self {
+        let mut index = Self::default();
+        for (i, add) in files.iter().enumerate() {
+            let values = partition_columns
+                .iter()
+                .map(|column| add.get_partition_value(column).map(str::to_string))
+                .collect();
+            index.files.entry(values).or_insert_with(Vec::new).push(i);
+        }
+
+        index
+    }
+
+    /// Returns the indices of the files matching the filters, in file order. Equality filters
+    /// on all the partition columns are answered with a single lookup.
+    fn matching_files(
+        &self,
+        partition_columns: &[String],
+        filters: &[PartitionFilter<&str>],
+    ) -> Vec<usize> {
+        let matches = |values: &[Option<String>]| {
+            let partitions = partition_columns
+                .iter()
+                .zip(values)
+                .map(|(key, value)| DeltaTablePartition {
+                    key,
+                    value: value.as_deref(),
+                })
+                .collect::<Vec<DeltaTablePartition>>();
+            filters
+                .iter()
+                .all(|filter| filter.match_partitions(&partitions))
+        };
+        let equal_values = partition_columns
+            .iter()
+            .map(|column| {
+                filters.iter().find_map(|filter| match &filter.value {
+                    PartitionValue::Equal(value) if filter.key == column.as_str() => {
+                        Some(Some(value.to_string()))
+                    }
+                    _ => None,
+                })
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let mut indices = match equal_values {
+            Some(values) => self
+                .files
+                .get_key_value(&values)
+                .filter(|(values, _)| matches(values))
+                .map(|(_, indices)| indices.clone())
+                .unwrap_or_default(),
+            None => self
+                .files
+                .iter()
+                .filter(|(values, _)| matches(values))
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect(),
+        };
+        indices.sort_unstable();
+
+        indices
+    }
+}
+
+/// The highest reader protocol version this crate implements.
+pub const MAX_SUPPORTED_READER_VERSION: i32 = 1;
+/// The highest writer protocol version this crate implements, which covers column invariants
+/// and append-only tables.
+pub const MAX_SUPPORTED_WRITER_VERSION: i32 = 2;
+
+/// Options controlling how a DeltaTable is loaded.
+#[derive(Clone, Debug, Default)]
+pub struct DeltaTableConfig {
+    /// Find the latest checkpoint by listing the log directory instead of reading the
+    /// `_last_checkpoint` file. Useful when `_last_checkpoint` is suspected to be wrong.
+    pub ignore_last_checkpoint: bool,
+    /// When scanning the table with DataFusion, prune the files whose add action has no stats
+    /// with the row group statistics in their parquet footers.
+    pub footer_pruning: bool,
+    /// Discard `remove` actions while loading instead of keeping them as tombstones. The removed
+    /// files are still dropped from the file list, but the table can't be vacuumed. Meant for
+    /// read-only consumers of tables with long retention periods.
+    pub skip_tombstones: bool,
+    /// Read the gzip-compressed `.json.gz` sidecar copy of a commit when its `.json` file is
+    /// missing. Meant for archival tooling reading historical logs whose plain commit files have
+    /// been moved to cold storage.
+    pub compressed_log_sidecars: bool,
+    /// How column names in partition filters, predicates and written data are matched with the
+    /// columns of the table.
+    pub column_resolution: ColumnResolution,
+    /// After loading or updating, check that the active files exist in the storage with the size
+    /// recorded in their add actions, and fail with `FileSizeMismatch` otherwise. Catches files
+    /// overwritten out of band before queries return wrong data, at the cost of one HEAD request
+    /// per file.
+    pub verify_file_sizes: bool,
+    /// After uploading a data file, check that the stored object matches the uploaded bytes
+    /// before committing its add action, with `StorageBackend::verify_obj`. Guards against
+    /// uploads silently truncated on the way, at the cost of one HEAD request per file.
+    pub verify_uploads: bool,
+    /// Directory, relative to the table root, where transactions write their log entries before
+    /// renaming them to the next version, e.g. `_delta_log/.tmp`. Defaults to the log directory,
+    /// where other Delta writers put them too, at the cost of temporary `_commit_<uuid>.json`
+    /// files showing up in its listings. Unused by `commit_with` and `commit_version` on storage
+    /// backends supporting `put_obj_atomic`, which write log entries directly.
+    pub temp_commit_dir: Option<String>,
+    /// After loading or updating, index the active files by their partition values, so that
+    /// filtering files by partitions looks up the matching partitions instead of scanning all the
+    /// files. Meant for services filtering the files of large tables many times per version, at
+    /// the cost of building the index once per loaded version.
+    pub partition_index: bool,
+    /// When scanning the table with DataFusion, group the files into at most this many partitions
+    /// of similar total size, instead of scanning each file in its own partition. Bounds the
+    /// number of files read concurrently, and so the number of requests in flight against object
+    /// stores with high per-request latency.
+    pub scan_partitions: Option<usize>,
+    /// When time traveling with `load_with_datetime` to a timestamp before the earliest version
+    /// retained in the log, load that version and log a warning instead of failing with
+    /// `TimestampNotRetained`.
+    pub clamp_time_travel: bool,
+    /// Maximum durations of loads, commits, vacuums and individual storage operations.
+    pub timeouts: OperationTimeouts,
+    /// How actions of types this crate doesn't know are handled when reading the log.
+    pub unknown_actions: UnknownActionPolicy,
+    /// Maximum number of objects requested per page when listing the log to find the latest
+    /// version, on backends reporting `list_after`. Defaults to the page size of the backend, e.g.
+    /// 1000 objects on S3; smaller pages return sooner for tables with few commits since their
+    /// last checkpoint.
+    pub list_page_size: Option<usize>,
+    /// Number of versions probed with concurrent HEAD requests when looking for the latest
+    /// version on backends that can't list after a path, e.g. `Some(16)`. Defaults to one request
+    /// at a time. Probing ahead costs requests for versions past the latest one, but saves a
+    /// round trip per commit on object stores with high latency.
+    pub head_prefetch: Option<usize>,
+    /// Token aborting the loads, commits, vacuums and storage operations of the table in progress
+    /// when cancelled, and failing the following ones with `Cancelled`. Meant for servers
+    /// abandoning the work of requests whose clients went away.
+    pub cancellation_token: Option<CancellationToken>,
+}
+
+/// How actions of types this crate doesn't know, e.g. ones added by later versions of the Delta
+/// protocol, are handled when reading the log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownActionPolicy {
+    /// Skip unknown actions, recording them in the loaded state, as long as the table doesn't
+    /// require a reader version higher than this crate supports. Features that change how
+    /// tables are read raise the reader version, so the actions they add can't be ignored.
+    Tolerant,
+    /// Fail on any unknown action.
+    Strict,
+}
+
+impl Default for UnknownActionPolicy {
+    fn default() -> Self {
+        UnknownActionPolicy::Tolerant
+    }
+}
+
+impl UnknownActionPolicy {
+    /// Fails unless `action` can be skipped in a table with the given reader version.
+    fn check(&self, action: &UnknownAction, min_reader_version: i32) -> Result<(), ApplyLogError> {
+        match self {
+            UnknownActionPolicy::Tolerant if min_reader_version <= MAX_SUPPORTED_READER_VERSION => {
+                Ok(())
+            }
+            _ => Err(ApplyLogError::UnknownAction {
+                action_type: action.action_type.clone(),
+            }),
+        }
+    }
+}
+
+/// Maximum durations of the operations of a table, unbounded when not set. An operation running
+/// longer fails with a `Timeout` error.
+///
+/// Operations are aborted at their next await point, so a timed out load leaves the table in a
+/// partially loaded state that must be reloaded, and a timed out commit may have been committed
+/// already if it timed out while updating the table to the new version.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OperationTimeouts {
+    /// Bounds `load`, `update`, `load_version`, `load_with_datetime`, `load_consistent` and
+    /// `load_from_checkpoint`.
+    pub load: Option<Duration>,
+    /// Bounds the commits of `DeltaTransaction`, including their retries.
+    pub commit: Option<Duration>,
+    /// Bounds `vacuum` and `vacuum_with_inventory`.
+    pub vacuum: Option<Duration>,
+    /// Bounds each request to the storage backend, and each item of its listings.
+    pub storage_op: Option<Duration>,
+}
+
+/// Source of the current time for retention logic, such as vacuum and the cleanup of temporary
+/// commit files, and for the timestamps of commits. Replaceable so that tests and simulations
+/// can control time.
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The default `Clock`, reading the system time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
 }
 
 /// In memory representation of a Delta Table
@@ -274,15 +737,140 @@ pub struct DeltaTable {
     /// The path the DeltaTable was loaded from.
     pub table_path: String,
 
-    state: DeltaTableState,
+    // Shared with the snapshots handed out by `snapshot()`. Updates copy the state on write when
+    // a snapshot still references it.
+    state: Arc<DeltaTableState>,
 
     // metadata
     // application_transactions
-    storage: Box<dyn StorageBackend>,
+    storage: Arc<dyn StorageBackend>,
 
     last_check_point: Option<CheckPoint>,
     log_path: String,
-    version_timestamp: HashMap<DeltaDataTypeVersion, i64>,
+    config: DeltaTableConfig,
+    // Cache of commit timestamps, behind a mutex so read-only methods can populate it through a
+    // shared reference.
+    version_timestamp: Mutex<HashMap<DeltaDataTypeVersion, i64>>,
+    load_metrics: LoadMetrics,
+    clock: Arc<dyn Clock>,
+    event_listener: Option<Arc<dyn TableEventListener>>,
+    post_commit_hooks: Vec<Arc<dyn PostCommitHook>>,
+    // when the state was last loaded or updated, according to `clock`
+    refreshed_at: Option<SystemTime>,
+}
+
+/// Measurements of the most recent load or update of a table, reported by `debug_dump`.
+#[derive(Debug, Default, Clone)]
+struct LoadMetrics {
+    checkpoint_version: Option<DeltaDataTypeVersion>,
+    checkpoint_duration: Duration,
+    num_log_files_replayed: usize,
+    log_replay_duration: Duration,
+}
+
+/// Report of the loaded state of a DeltaTable and of how it was loaded, for attributing slow or
+/// unexpected loads to specific tables. Returned by `DeltaTable::debug_dump`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DeltaTableDebugDump {
+    /// The path the table was loaded from.
+    pub table_uri: String,
+    /// The loaded version.
+    pub version: DeltaDataTypeVersion,
+    /// The version of the checkpoint the most recent load or update started from, if any.
+    pub checkpoint_version: Option<DeltaDataTypeVersion>,
+    /// Time spent reading the checkpoint in the most recent load or update, in milliseconds.
+    pub checkpoint_load_millis: u64,
+    /// Number of log files, including log compactions, replayed by the most recent load or
+    /// update.
+    pub num_log_files_replayed: usize,
+    /// Time spent reading and replaying log files in the most recent load or update, in
+    /// milliseconds.
+    pub log_replay_millis: u64,
+    /// Number of active files.
+    pub num_files: usize,
+    /// Number of tombstones.
+    pub num_tombstones: usize,
+    /// Number of application transactions.
+    pub num_app_transactions: usize,
+    /// Number of commit infos kept in the state.
+    pub num_commit_infos: usize,
+    /// Minimum reader version of the table protocol.
+    pub min_reader_version: i32,
+    /// Minimum writer version of the table protocol.
+    pub min_writer_version: i32,
+}
+
+/// Aggregates of the active files of one partition, returned by `DeltaTable::get_partition_stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionStats {
+    /// The partition values of the files, keyed by partition column. Empty for tables without
+    /// partition columns, which are reported as a single partition.
+    pub partition_values: HashMap<String, Option<String>>,
+    /// Number of active files.
+    pub num_files: usize,
+    /// Total size of the active files, in bytes.
+    pub total_bytes: DeltaDataTypeLong,
+    /// Smallest minimum value of the timestamp column in the file statistics, if any file has
+    /// statistics for it.
+    pub min_timestamp: Option<DateTime<Utc>>,
+    /// Largest maximum value of the timestamp column in the file statistics, if any file has
+    /// statistics for it.
+    pub max_timestamp: Option<DateTime<Utc>>,
+    /// Number of files without statistics for the timestamp column, which aren't accounted for
+    /// in `min_timestamp` and `max_timestamp`.
+    pub num_files_without_timestamp_stats: usize,
+    /// Latest modification time of the files, in milliseconds since the Unix epoch.
+    pub latest_modification_time: DeltaDataTypeTimestamp,
+}
+
+/// Table level statistics of a column, merged from the statistics of the active files, returned
+/// by `DeltaTable::column_stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// Number of active files.
+    pub num_files: usize,
+    /// Number of files without a null count for the column in their statistics, which aren't
+    /// accounted for in the other fields.
+    pub num_files_without_stats: usize,
+    /// Number of records of the files with statistics.
+    pub num_records: DeltaDataTypeLong,
+    /// Smallest minimum value of the column in the file statistics, if any. Strings are compared
+    /// on the prefixes recorded in the statistics.
+    pub min_value: Option<Value>,
+    /// Largest maximum value of the column in the file statistics, if any.
+    pub max_value: Option<Value>,
+    /// Number of null values of the files with statistics.
+    pub null_count: DeltaDataTypeLong,
+    /// Estimate of the number of distinct values: the sum of the `distinctCount` of the files,
+    /// at most their number of non-null values. It's an upper bound, exact when the files don't
+    /// share values, e.g. when partitioned by a related column. `None` when a file with
+    /// statistics has no distinct count for the column.
+    pub distinct_count: Option<DeltaDataTypeLong>,
+}
+
+/// An active file whose size in the storage doesn't match its add action, returned by
+/// `DeltaTable::verify_files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSizeMismatch {
+    /// The path of the file, relative to the table root.
+    pub path: String,
+    /// The size recorded in the add action, in bytes.
+    pub expected_size: DeltaDataTypeLong,
+    /// The size of the file in the storage, in bytes, or `None` when the file is missing.
+    pub actual_size: Option<DeltaDataTypeLong>,
+}
+
+impl fmt::Display for FileSizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.actual_size {
+            Some(size) => write!(
+                f,
+                "{} has {} bytes instead of {}",
+                self.path, size, self.expected_size
+            ),
+            None => write!(f, "{} is missing", self.path),
+        }
+    }
 }
 
 impl DeltaTable {
@@ -291,9 +879,32 @@ impl DeltaTable {
         self.storage.join_path(&self.log_path, &version)
     }
 
+    /// Reads the log entry of the given version, falling back to its `.json.gz` sidecar copy
+    /// when enabled by `DeltaTableConfig::compressed_log_sidecars`.
+    async fn get_log_obj(&self, version: DeltaDataTypeVersion) -> Result<Vec<u8>, StorageError> {
+        let log_path = self.version_to_log_path(version);
+        match self.storage.get_obj(&log_path).await {
+            Err(StorageError::NotFound) if self.config.compressed_log_sidecars => {
+                self.storage.get_obj(&format!("{}.gz", log_path)).await
+            }
+            result => result,
+        }
+    }
+
+    /// The directory prepared commits are written to, see `DeltaTableConfig::temp_commit_dir`.
+    fn tmp_commit_dir(&self) -> String {
+        match &self.config.temp_commit_dir {
+            Some(dir) => self.storage.join_path(
+                &self.table_path,
+                dir.trim_matches(|c| c == '/' || c == '\\'),
+            ),
+            None => self.log_path.clone(),
+        }
+    }
+
     fn tmp_commit_log_path(&self, token: &str) -> String {
         let path = format!("_commit_{}.json", token);
-        self.storage.join_path(&self.log_path, &path)
+        self.storage.join_path(&self.tmp_commit_dir(), &path)
     }
 
     fn get_checkpoint_data_paths(&self, check_point: &CheckPoint) -> Vec<String> {
@@ -322,14 +933,58 @@ impl DeltaTable {
         checkpoint_data_paths
     }
 
+    /// Returns the checkpoint recorded in the `_last_checkpoint` file of the table along with the
+    /// checkpoint the loaded state was restored from. A loaded checkpoint far behind the last one,
+    /// or many commits since the loaded checkpoint, explain slow loads. The `_last_checkpoint`
+    /// file is read even when the table is configured to ignore it.
+    pub async fn get_last_checkpoint_info(&self) -> Result<CheckpointInfo, DeltaTableError> {
+        let last_checkpoint = match self.read_last_checkpoint_file().await {
+            Ok(check_point) => Some(check_point),
+            Err(LoadCheckpointError::NotFound) => None,
+            Err(e) => return Err(DeltaTableError::LoadCheckpoint { source: e }),
+        };
+        let num_commits_since_checkpoint = match self.last_check_point {
+            Some(check_point) => self.version - check_point.version,
+            None => self.version + 1,
+        };
+
+        Ok(CheckpointInfo {
+            last_checkpoint,
+            loaded_checkpoint: self.last_check_point,
+            num_commits_since_checkpoint,
+        })
+    }
+
     async fn get_last_checkpoint(&self) -> Result<CheckPoint, LoadCheckpointError> {
+        if self.config.ignore_last_checkpoint {
+            return match self
+                .find_latest_check_point_for_version(DeltaDataTypeVersion::MAX)
+                .await
+            {
+                Ok(Some(check_point)) => Ok(check_point),
+                Ok(None) => Err(LoadCheckpointError::NotFound),
+                Err(DeltaTableError::StorageError { source }) => {
+                    Err(LoadCheckpointError::from(source))
+                }
+                Err(e) => Err(LoadCheckpointError::Storage {
+                    source: StorageError::Generic(e.to_string()),
+                }),
+            };
+        }
+
+        self.read_last_checkpoint_file().await
+    }
+
+    async fn read_last_checkpoint_file(&self) -> Result<CheckPoint, LoadCheckpointError> {
         let last_checkpoint_path = self.storage.join_path(&self.log_path, "_last_checkpoint");
         let data = self.storage.get_obj(&last_checkpoint_path).await?;
 
         Ok(serde_json::from_slice(&data)?)
     }
 
-    async fn find_latest_check_point_for_version(
+    /// Finds the latest checkpoint at or below the given version by listing the log directory,
+    /// without relying on the `_last_checkpoint` file.
+    pub async fn find_latest_check_point_for_version(
         &self,
         version: DeltaDataTypeVersion,
     ) -> Result<Option<CheckPoint>, DeltaTableError> {
@@ -388,32 +1043,242 @@ impl DeltaTable {
         Ok(cp)
     }
 
+    /// Returns the earliest version the table can be loaded at: version 0 while its commit is in
+    /// the log, otherwise the oldest checkpoint, the commits before it having been cleaned up.
+    pub async fn get_earliest_version(&self) -> Result<DeltaDataTypeVersion, DeltaTableError> {
+        lazy_static! {
+            static ref LOG_FILE_REGEX: Regex = Regex::new(
+                r#"[/\\]_delta_log[/\\](\d{20})\.(json|json\.gz|checkpoint\..*parquet)$"#
+            )
+            .unwrap();
+        }
+
+        let mut earliest_commit: Option<DeltaDataTypeVersion> = None;
+        let mut earliest_checkpoint: Option<DeltaDataTypeVersion> = None;
+        let mut stream = self.storage.list_objs(&self.log_path).await?;
+        while let Some(obj_meta) = stream.next().await {
+            let obj_meta = obj_meta?;
+            if let Some(captures) = LOG_FILE_REGEX.captures(&obj_meta.path) {
+                let version: DeltaDataTypeVersion =
+                    captures.get(1).unwrap().as_str().parse().unwrap();
+                let earliest = if captures.get(2).unwrap().as_str().starts_with("json") {
+                    &mut earliest_commit
+                } else {
+                    &mut earliest_checkpoint
+                };
+                *earliest = Some(earliest.map_or(version, |earliest| earliest.min(version)));
+            }
+        }
+
+        match (earliest_commit, earliest_checkpoint) {
+            (Some(0), _) => Ok(0),
+            (_, Some(checkpoint)) => Ok(checkpoint),
+            (Some(commit), None) => Ok(commit),
+            (None, None) => Err(DeltaTableError::NotATable),
+        }
+    }
+
     fn apply_log_from_bufread<R: BufRead>(
         &mut self,
         reader: BufReader<R>,
     ) -> Result<(), ApplyLogError> {
-        for line in reader.lines() {
-            let action: Action = serde_json::from_str(line?.as_str())?;
-            process_action(&mut self.state, &action)?;
+        let require_tombstones = !self.config.skip_tombstones;
+        let unknown_actions = self.config.unknown_actions;
+        let state = Arc::make_mut(&mut self.state);
+        state.partition_index = None;
+        for line in decompressed_log_reader(reader)?.lines() {
+            let line = line?;
+            // blank lines, e.g. a trailing empty line, hold no action
+            if line.trim().is_empty() {
+                continue;
+            }
+            match LogAction::from_json(&line)? {
+                LogAction::Known(action) => process_action(state, &action, require_tombstones)?,
+                LogAction::Unknown(action) => {
+                    unknown_actions.check(&action, state.min_reader_version)?;
+                    debug!(
+                        "Skipping unknown action {} in the log of table {}",
+                        action.action_type, self.table_path
+                    );
+                    state.unknown_actions.push(action);
+                }
+            }
         }
 
         Ok(())
     }
 
     async fn apply_log(&mut self, version: DeltaDataTypeVersion) -> Result<(), ApplyLogError> {
-        let log_path = self.version_to_log_path(version);
-        let commit_log_bytes = self.storage.get_obj(&log_path).await?;
+        let started_at = Instant::now();
+        let commit_log_bytes = self.get_log_obj(version).await?;
         let reader = BufReader::new(Cursor::new(commit_log_bytes));
 
-        self.apply_log_from_bufread(reader)
+        self.apply_log_from_bufread(reader)?;
+        self.load_metrics.num_log_files_replayed += 1;
+        self.load_metrics.log_replay_duration += started_at.elapsed();
+
+        Ok(())
+    }
+
+    fn compacted_log_path(
+        &self,
+        start_version: DeltaDataTypeVersion,
+        end_version: DeltaDataTypeVersion,
+    ) -> String {
+        let path = format!("{:020}.{:020}.compacted.json", start_version, end_version);
+        self.storage.join_path(&self.log_path, &path)
+    }
+
+    /// Lists the log compaction files ending at or before `max_version`, returning the largest
+    /// end version available for each start version.
+    async fn find_log_compactions(
+        &self,
+        max_version: DeltaDataTypeVersion,
+    ) -> Result<HashMap<DeltaDataTypeVersion, DeltaDataTypeVersion>, DeltaTableError> {
+        lazy_static! {
+            static ref COMPACTION_REGEX: Regex =
+                Regex::new(r#"^*[/\\]_delta_log[/\\](\d{20})\.(\d{20})\.compacted\.json$"#)
+                    .unwrap();
+        }
+
+        let mut compactions = HashMap::new();
+        let mut stream = self.storage.list_objs(&self.log_path).await?;
+
+        while let Some(obj_meta) = stream.next().await {
+            let obj_meta = obj_meta?;
+            if let Some(captures) = COMPACTION_REGEX.captures(&obj_meta.path) {
+                let start: DeltaDataTypeVersion =
+                    captures.get(1).unwrap().as_str().parse().unwrap();
+                let end: DeltaDataTypeVersion = captures.get(2).unwrap().as_str().parse().unwrap();
+                if end < start || end > max_version {
+                    continue;
+                }
+                let entry = compactions.entry(start).or_insert(end);
+                if end > *entry {
+                    *entry = end;
+                }
+            }
+        }
+
+        Ok(compactions)
+    }
+
+    /// Applies the log compactions that continue the log from `next_version` without going past
+    /// `max_version`. Returns the next version to apply.
+    async fn apply_log_compactions(
+        &mut self,
+        mut next_version: DeltaDataTypeVersion,
+        max_version: DeltaDataTypeVersion,
+    ) -> Result<DeltaDataTypeVersion, DeltaTableError> {
+        let compactions = self.find_log_compactions(max_version).await?;
+
+        while let Some(end_version) = compactions.get(&next_version) {
+            let started_at = Instant::now();
+            let compacted_log_path = self.compacted_log_path(next_version, *end_version);
+            let compacted_log_bytes = self.storage.get_obj(&compacted_log_path).await?;
+            let reader = BufReader::new(Cursor::new(compacted_log_bytes));
+            self.apply_log_from_bufread(reader)?;
+            self.load_metrics.num_log_files_replayed += 1;
+            self.load_metrics.log_replay_duration += started_at.elapsed();
+            next_version = end_version + 1;
+        }
+
+        Ok(next_version)
+    }
+
+    /// Writes a log compaction file reconciling the actions committed from `start_version` to
+    /// `end_version` (both inclusive), so later loads can apply it in place of the individual
+    /// commits. Commit infos are dropped, and only the last action for each file, application
+    /// transaction, protocol and metadata is kept. Returns the path of the written file.
+    pub async fn create_log_compaction(
+        &self,
+        start_version: DeltaDataTypeVersion,
+        end_version: DeltaDataTypeVersion,
+    ) -> Result<String, DeltaTableError> {
+        if start_version < 0 || end_version < start_version {
+            return Err(DeltaTableError::InvalidVersion(end_version));
+        }
+
+        let mut protocol = None;
+        let mut metadata = None;
+        let mut txns: HashMap<String, Action> = HashMap::new();
+        // last action for each file path, along with its position in the log
+        let mut file_actions: HashMap<String, (usize, Action)> = HashMap::new();
+        let mut position = 0;
+
+        for version in start_version..=end_version {
+            for action in self.get_commit_actions(version).await? {
+                let path = match &action {
+                    Action::add(add) => add.path.clone(),
+                    Action::remove(remove) => remove.path.clone(),
+                    Action::protocol(_) => {
+                        protocol = Some(action);
+                        continue;
+                    }
+                    Action::metaData(_) => {
+                        metadata = Some(action);
+                        continue;
+                    }
+                    Action::txn(txn) => {
+                        txns.insert(txn.appId.clone(), action);
+                        continue;
+                    }
+                    Action::commitInfo(_) => continue,
+                };
+                file_actions.insert(path, (position, action));
+                position += 1;
+            }
+        }
+
+        let mut actions = Vec::new();
+        actions.extend(protocol);
+        actions.extend(metadata);
+        actions.extend(txns.into_iter().map(|(_, txn)| txn));
+        let mut file_actions = file_actions.into_iter().map(|(_, a)| a).collect::<Vec<_>>();
+        file_actions.sort_by_key(|(position, _)| *position);
+        actions.extend(file_actions.into_iter().map(|(_, action)| action));
+
+        let log_entry = log_entry_from_actions(&actions)?;
+        let compacted_log_path = self.compacted_log_path(start_version, end_version);
+        self.storage
+            .put_obj(&compacted_log_path, log_entry.as_bytes())
+            .await?;
+        self.emit_event(TableEvent::LogCompactionCreated {
+            start_version,
+            end_version,
+        });
+
+        Ok(compacted_log_path)
     }
 
     async fn restore_checkpoint(&mut self, check_point: CheckPoint) -> Result<(), DeltaTableError> {
-        let checkpoint_data_paths = self.get_checkpoint_data_paths(&check_point);
+        let started_at = Instant::now();
         // process actions from checkpoint
-        self.state = DeltaTableState::default();
-        for f in &checkpoint_data_paths {
-            let obj = self.storage.get_obj(&f).await?;
+        let mut state = DeltaTableState::default();
+        let require_tombstones = !self.config.skip_tombstones;
+        self.for_each_checkpoint_action(check_point, |action| {
+            process_action(&mut state, &action, require_tombstones)?;
+            Ok(())
+        })
+        .await?;
+        self.state = Arc::new(state);
+        self.load_metrics.checkpoint_version = Some(check_point.version);
+        self.load_metrics.checkpoint_duration = started_at.elapsed();
+
+        Ok(())
+    }
+
+    async fn for_each_checkpoint_action<F>(
+        &self,
+        check_point: CheckPoint,
+        mut f: F,
+    ) -> Result<(), DeltaTableError>
+    where
+        F: FnMut(Action) -> Result<(), DeltaTableError>,
+    {
+        let checkpoint_data_paths = self.get_checkpoint_data_paths(&check_point);
+        for p in &checkpoint_data_paths {
+            let obj = self.storage.get_obj(&p).await?;
             let preader = SerializedFileReader::new(SliceableCursor::new(obj))?;
             let schema = preader.metadata().file_metadata().schema();
             if !schema.is_group() {
@@ -422,17 +1287,15 @@ impl DeltaTable {
                 )));
             }
             for record in preader.get_row_iter(None)? {
-                process_action(
-                    &mut self.state,
-                    &Action::from_parquet_record(&schema, &record)?,
-                )?;
+                f(Action::from_parquet_record(&schema, &record)?)?;
             }
         }
 
         Ok(())
     }
 
-    async fn get_latest_version(&mut self) -> Result<DeltaDataTypeVersion, DeltaTableError> {
+    /// Returns the latest version committed to the transaction log, without loading it.
+    pub async fn get_latest_version(&self) -> Result<DeltaDataTypeVersion, DeltaTableError> {
         let mut version = match self.get_last_checkpoint().await {
             Ok(last_check_point) => last_check_point.version,
             Err(LoadCheckpointError::NotFound) => {
@@ -444,36 +1307,89 @@ impl DeltaTable {
             }
         };
 
-        // scan logs after checkpoint
-        loop {
-            match self
-                .storage
-                .head_obj(&self.version_to_log_path(version))
-                .await
-            {
+        if self.storage.capabilities().list_after {
+            return self.list_latest_version(version).await;
+        }
+
+        // scan logs after checkpoint, probing the next versions ahead in order
+        let prefetch = self.config.head_prefetch.unwrap_or(1).max(1);
+        let mut probes = futures::stream::iter(version..)
+            .map(|version| async move {
+                let result = self
+                    .storage
+                    .head_obj(&self.version_to_log_path(version))
+                    .await;
+                (version, result)
+            })
+            .buffered(prefetch);
+        while let Some((probed_version, result)) = probes.next().await {
+            match result {
                 Ok(meta) => {
                     // also cache timestamp for version
                     self.version_timestamp
-                        .insert(version, meta.modified.timestamp());
-                    version += 1;
+                        .lock()
+                        .unwrap()
+                        .insert(probed_version, meta.modified.timestamp());
                 }
-                Err(e) => {
-                    match e {
-                        StorageError::NotFound => {
-                            version -= 1;
-                        }
-                        _ => return Err(DeltaTableError::from(e)),
-                    }
+                Err(StorageError::NotFound) => {
+                    version = probed_version - 1;
                     break;
                 }
+                Err(e) => return Err(DeltaTableError::from(e)),
             }
         }
 
         Ok(version)
     }
 
+    /// Finds the latest version by listing the commits from `version` on, which takes a request
+    /// per page of the listing instead of one per commit. Like the requests of
+    /// `get_latest_version`, the versions stop at the first missing commit.
+    async fn list_latest_version(
+        &self,
+        mut version: DeltaDataTypeVersion,
+    ) -> Result<DeltaDataTypeVersion, DeltaTableError> {
+        lazy_static! {
+            static ref COMMIT_FILE_REGEX: Regex =
+                Regex::new(r#"[/\\]_delta_log[/\\](\d{20})\.json$"#).unwrap();
+        }
+
+        // the zero padded version is a prefix of the commit, so it sorts right before it
+        let start_after = self
+            .storage
+            .join_path(&self.log_path, &format!("{:020}", version));
+        let mut stream = self
+            .storage
+            .list_objs_after(&self.log_path, &start_after, self.config.list_page_size)
+            .await?;
+        let mut commit_timestamps = HashMap::new();
+        while let Some(obj_meta) = stream.next().await {
+            let obj_meta = obj_meta?;
+            if let Some(captures) = COMMIT_FILE_REGEX.captures(&obj_meta.path) {
+                let commit_version: DeltaDataTypeVersion =
+                    captures.get(1).unwrap().as_str().parse().unwrap();
+                commit_timestamps.insert(commit_version, obj_meta.modified.timestamp());
+            }
+        }
+
+        let mut version_timestamp = self.version_timestamp.lock().unwrap();
+        while let Some(timestamp) = commit_timestamps.get(&version) {
+            // also cache timestamp for version
+            version_timestamp.insert(version, *timestamp);
+            version += 1;
+        }
+
+        Ok(version - 1)
+    }
+
     /// Load DeltaTable with data from latest checkpoint
     pub async fn load(&mut self) -> Result<(), DeltaTableError> {
+        let (timeout, token) = self.load_bounds();
+        run_operation("load", timeout, token, self.load_unbounded()).await
+    }
+
+    async fn load_unbounded(&mut self) -> Result<(), DeltaTableError> {
+        self.load_metrics = LoadMetrics::default();
         match self.get_last_checkpoint().await {
             Ok(last_check_point) => {
                 self.last_check_point = Some(last_check_point);
@@ -489,19 +1405,35 @@ impl DeltaTable {
             }
         }
 
+        self.version = self
+            .apply_log_compactions(self.version, DeltaDataTypeVersion::MAX)
+            .await?;
         self.apply_logs_after_current_version().await?;
+        self.check_format()?;
+        self.verify_loaded_files().await?;
+        self.index_partitions();
+        self.loaded("Loaded");
 
         Ok(())
     }
 
     /// Updates the DeltaTable to the most recent state committed to the transaction log.
     pub async fn update(&mut self) -> Result<(), DeltaTableError> {
+        let (timeout, token) = self.load_bounds();
+        run_operation("update", timeout, token, self.update_unbounded()).await
+    }
+
+    async fn update_unbounded(&mut self) -> Result<(), DeltaTableError> {
+        self.load_metrics = LoadMetrics::default();
         match self.get_last_checkpoint().await {
             Ok(last_check_point) => {
                 if self.last_check_point != Some(last_check_point) {
                     self.last_check_point = Some(last_check_point);
                     self.restore_checkpoint(last_check_point).await?;
                     self.version = last_check_point.version + 1;
+                } else {
+                    // the loaded version was already applied
+                    self.version += 1;
                 }
             }
             Err(LoadCheckpointError::NotFound) => {
@@ -513,130 +1445,551 @@ impl DeltaTable {
         }
 
         self.apply_logs_after_current_version().await?;
+        self.check_format()?;
+        self.verify_loaded_files().await?;
+        self.index_partitions();
+        self.loaded("Updated");
 
         Ok(())
     }
 
-    async fn apply_logs_after_current_version(&mut self) -> Result<(), DeltaTableError> {
-        // replay logs after checkpoint
-        loop {
-            match self.apply_log(self.version).await {
-                Ok(_) => {
-                    self.version += 1;
-                }
-                Err(e) => {
-                    match e {
-                        ApplyLogError::EndOfLog => {
-                            self.version -= 1;
-                            if self.version == -1 {
-                                // no snapshot found, no 0 version found.  this is not a delta
-                                // table, possibly an empty directroy.
-                                return Err(DeltaTableError::NotATable);
-                            }
-                        }
-                        _ => {
-                            return Err(DeltaTableError::from(e));
-                        }
-                    }
-                    break;
-                }
-            }
+    /// Updates the table with `update` when it was last loaded or updated `max_age` ago or
+    /// earlier, according to the table clock, and returns whether it did. Long-lived handles
+    /// calling this on each request pick up new versions at most `max_age` late without reading
+    /// the log every time.
+    pub async fn refresh_if_stale(&mut self, max_age: Duration) -> Result<bool, DeltaTableError> {
+        if !self.is_stale(max_age) {
+            return Ok(false);
         }
+        self.update().await?;
+        Ok(true)
+    }
+
+    /// Returns whether the table was last loaded or updated `max_age` ago or earlier, or never.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        match self.refreshed_at {
+            // a clock set back makes the age unknown, refresh to be safe
+            Some(refreshed_at) => match self.clock.now().duration_since(refreshed_at) {
+                Ok(age) => age >= max_age,
+                Err(_) => true,
+            },
+            None => true,
+        }
+    }
+
+    /// Returns when the table was last loaded or updated, according to the table clock.
+    pub fn refreshed_at(&self) -> Option<SystemTime> {
+        self.refreshed_at
+    }
+
+    /// Fails when the files of the loaded table are stored in a format that can't be read.
+    fn check_format(&self) -> Result<(), DeltaTableError> {
+        let result = match &self.state.current_metadata {
+            Some(metadata) => check_format(&metadata.format),
+            None => Ok(()),
+        };
+        if let Err(e) = &result {
+            self.emit_event(TableEvent::ProtocolError {
+                version: self.version,
+                error: e.to_string(),
+            });
+        }
+
+        result
+    }
+
+    /// Checks the sizes of all the active files when enabled by
+    /// `DeltaTableConfig::verify_file_sizes`.
+    async fn verify_loaded_files(&self) -> Result<(), DeltaTableError> {
+        if !self.config.verify_file_sizes {
+            return Ok(());
+        }
+        let mismatches = self.verify_files(None).await?;
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(DeltaTableError::FileSizeMismatch { mismatches })
+        }
+    }
+
+    /// Indexes the active files by their partition values when enabled by
+    /// `DeltaTableConfig::partition_index` and the loaded version isn't indexed yet.
+    fn index_partitions(&mut self) {
+        if !self.config.partition_index || self.state.partition_index.is_some() {
+            return;
+        }
+        let partition_columns = match &self.state.current_metadata {
+            Some(metadata) if !metadata.partition_columns.is_empty() => &metadata.partition_columns,
+            _ => return,
+        };
+        let index = PartitionIndex::new(&self.state.files, partition_columns);
+        Arc::make_mut(&mut self.state).partition_index = Some(index);
+    }
+
+    /// Checks that the active files exist in the storage with the size recorded in their add
+    /// actions, and returns the files that don't, sorted by path. When `sample_size` is given,
+    /// only that many files, evenly spread over the file list, are checked. Files whose size
+    /// isn't reported by the storage backend are assumed to match.
+    pub async fn verify_files(
+        &self,
+        sample_size: Option<usize>,
+    ) -> Result<Vec<FileSizeMismatch>, DeltaTableError> {
+        let files = &self.state.files;
+        let indices: Vec<usize> = match sample_size {
+            Some(sample_size) if sample_size < files.len() => (0..sample_size)
+                .map(|i| i * files.len() / sample_size)
+                .collect(),
+            _ => (0..files.len()).collect(),
+        };
+
+        let mut mismatches = futures::stream::iter(indices.into_iter().map(|i| &files[i]))
+            .map(|add| async move {
+                let path = self.storage.join_path(&self.table_path, &add.path);
+                let actual_size = match self.storage.head_obj(&path).await {
+                    Ok(meta) => match meta.size {
+                        Some(size) if size != add.size => Some(size),
+                        _ => return Ok(None),
+                    },
+                    Err(StorageError::NotFound) => None,
+                    Err(e) => return Err(e),
+                };
+                Ok(Some(FileSizeMismatch {
+                    path: add.path.clone(),
+                    expected_size: add.size,
+                    actual_size,
+                }))
+            })
+            .buffer_unordered(VERIFY_FILES_CONCURRENCY)
+            .try_filter_map(|mismatch| futures::future::ready(Ok(mismatch)))
+            .try_collect::<Vec<_>>()
+            .await?;
+        mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(mismatches)
+    }
+
+    /// Reads the footer of the parquet file of the given add action, e.g. to compare the
+    /// statistics of its row groups with the ones recorded in the log. Only the end of the file
+    /// is fetched when the storage backend supports range reads, the whole file otherwise.
+    pub async fn read_parquet_metadata(
+        &self,
+        add: &action::Add,
+    ) -> Result<ParquetMetaData, DeltaTableError> {
+        let path = self.storage.join_path(&self.table_path, &add.path);
+        if !self.storage.capabilities().range_reads {
+            let obj = self.storage.get_obj(&path).await?;
+            return Ok(parse_metadata(&SliceableCursor::new(obj))?);
+        }
+
+        // the footer usually fits in the first read, ending with its length and the magic bytes
+        let file_size = add.size as u64;
+        let tail_size = std::cmp::min(PARQUET_FOOTER_READ_SIZE, file_size);
+        let mut tail = self
+            .storage
+            .get_obj_range(&path, file_size - tail_size..file_size)
+            .await?;
+        if tail.len() >= 8 {
+            let mut metadata_len = [0; 4];
+            metadata_len.copy_from_slice(&tail[tail.len() - 8..tail.len() - 4]);
+            let footer_len = u32::from_le_bytes(metadata_len) as u64 + 8;
+            if footer_len > tail_size && footer_len <= file_size {
+                tail = self
+                    .storage
+                    .get_obj_range(&path, file_size - footer_len..file_size)
+                    .await?;
+            }
+        }
+
+        Ok(parse_metadata(&SliceableCursor::new(tail))?)
+    }
+
+    /// Returns the add actions of the active files matching the partition filters along with
+    /// pre-signed URLs to download them, valid for `expiry`. All the active files are returned
+    /// when there are no filters. Fails with `StorageError::PresignNotSupported` when the storage
+    /// backend of the table can't sign URLs.
+    pub async fn get_presigned_file_urls(
+        &self,
+        filters: &[PartitionFilter<&str>],
+        expiry: Duration,
+    ) -> Result<Vec<(&action::Add, String)>, DeltaTableError> {
+        let files = if filters.is_empty() {
+            self.state.files.iter().collect()
+        } else {
+            actions_by_partitions(&self.state, filters, self.config.column_resolution)?
+        };
+        presigned_file_urls(self.storage.as_ref(), &self.table_path, files, expiry).await
+    }
+
+    /// Records that the table was loaded or updated to the latest version.
+    fn loaded(&mut self, event: &str) {
+        self.refreshed_at = Some(self.clock.now());
+        debug!(
+            "{} table {} at version {}: replayed {} log files in {:?}, checkpoint {:?} read in {:?}",
+            event,
+            self.table_path,
+            self.version,
+            self.load_metrics.num_log_files_replayed,
+            self.load_metrics.log_replay_duration,
+            self.load_metrics.checkpoint_version,
+            self.load_metrics.checkpoint_duration,
+        );
+    }
+
+    async fn apply_logs_after_current_version(&mut self) -> Result<(), DeltaTableError> {
+        // replay logs after checkpoint
+        loop {
+            match self.apply_log(self.version).await {
+                Ok(_) => {
+                    self.version += 1;
+                }
+                Err(e) => {
+                    match e {
+                        ApplyLogError::EndOfLog => {
+                            self.version -= 1;
+                            if self.version == -1 {
+                                // no snapshot found, no 0 version found.  this is not a delta
+                                // table, possibly an empty directroy.
+                                return Err(DeltaTableError::NotATable);
+                            }
+                        }
+                        _ => {
+                            return Err(DeltaTableError::from(e));
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the DeltaTable state from the checkpoint written at the given version only, without
+    /// replaying the commits following it. Fails when there's no checkpoint for that version.
+    pub async fn load_from_checkpoint(
+        &mut self,
+        version: DeltaDataTypeVersion,
+    ) -> Result<(), DeltaTableError> {
+        let (timeout, token) = self.load_bounds();
+        run_operation(
+            "load_from_checkpoint",
+            timeout,
+            token,
+            self.load_from_checkpoint_unbounded(version),
+        )
+        .await
+    }
+
+    async fn load_from_checkpoint_unbounded(
+        &mut self,
+        version: DeltaDataTypeVersion,
+    ) -> Result<(), DeltaTableError> {
+        let check_point = match self.find_latest_check_point_for_version(version).await? {
+            Some(check_point) if check_point.version == version => check_point,
+            _ => {
+                return Err(DeltaTableError::LoadCheckpoint {
+                    source: LoadCheckpointError::NotFound,
+                });
+            }
+        };
+        self.load_metrics = LoadMetrics::default();
+        self.restore_checkpoint(check_point).await?;
+        self.last_check_point = Some(check_point);
+        self.version = version;
+        self.check_format()?;
+        self.verify_loaded_files().await?;
+        self.index_partitions();
+        self.loaded("Loaded");
 
         Ok(())
     }
 
+    /// Loads the latest version of the table as a single consistent version. Unlike `load`, which
+    /// keeps applying commits until it finds no more, the head version is determined first and
+    /// the state is replayed up to that version only, ignoring the commits and checkpoints
+    /// written while loading. On eventually consistent stores, this guarantees that the loaded
+    /// state corresponds to one version of the table, at the cost of missing the commits that
+    /// land during the load. The state is always replayed from the latest checkpoint, even when
+    /// the table was already loaded.
+    pub async fn load_consistent(&mut self) -> Result<(), DeltaTableError> {
+        let (timeout, token) = self.load_bounds();
+        run_operation(
+            "load_consistent",
+            timeout,
+            token,
+            self.load_consistent_unbounded(),
+        )
+        .await
+    }
+
+    async fn load_consistent_unbounded(&mut self) -> Result<(), DeltaTableError> {
+        let version = self.get_latest_version().await?;
+        if version < 0 {
+            return Err(DeltaTableError::NotATable);
+        }
+        // replay from scratch rather than on top of a previously loaded version
+        self.state = Arc::new(DeltaTableState::default());
+        self.load_version_unbounded(version).await
+    }
+
     /// Loads the DeltaTable state for the given version.
     pub async fn load_version(
         &mut self,
         version: DeltaDataTypeVersion,
+    ) -> Result<(), DeltaTableError> {
+        let (timeout, token) = self.load_bounds();
+        run_operation(
+            "load_version",
+            timeout,
+            token,
+            self.load_version_unbounded(version),
+        )
+        .await
+    }
+
+    async fn load_version_unbounded(
+        &mut self,
+        version: DeltaDataTypeVersion,
     ) -> Result<(), DeltaTableError> {
         // check if version is valid
         let log_path = self.version_to_log_path(version);
-        match self.storage.head_obj(&log_path).await {
-            Ok(_) => {}
-            Err(StorageError::NotFound) => {
-                return Err(DeltaTableError::InvalidVersion(version));
+        let head = match self.storage.head_obj(&log_path).await {
+            Err(StorageError::NotFound) if self.config.compressed_log_sidecars => {
+                self.storage.head_obj(&format!("{}.gz", log_path)).await
             }
+            head => head,
+        };
+        match head {
+            Ok(_) => {}
+            Err(StorageError::NotFound) => match self.get_earliest_version().await {
+                // the commit of the earliest version was cleaned up, but not its checkpoint
+                Ok(earliest) if version == earliest => {}
+                Ok(earliest) if (0..earliest).contains(&version) => {
+                    return Err(DeltaTableError::VersionNotRetained {
+                        version,
+                        earliest_available: earliest,
+                    });
+                }
+                Ok(_) | Err(DeltaTableError::NotATable) => {
+                    return Err(DeltaTableError::InvalidVersion(version));
+                }
+                Err(e) => return Err(e),
+            },
             Err(e) => {
                 return Err(DeltaTableError::from(e));
             }
         }
         self.version = version;
+        self.load_metrics = LoadMetrics::default();
 
         let mut next_version;
         // 1. find latest checkpoint below version
         match self.find_latest_check_point_for_version(version).await? {
             Some(check_point) => {
                 self.restore_checkpoint(check_point).await?;
+                self.last_check_point = Some(check_point);
                 next_version = check_point.version + 1;
             }
             None => {
                 // no checkpoint found, start from the beginning
+                self.last_check_point = None;
                 next_version = 0;
             }
         }
 
-        // 2. apply all logs starting from checkpoint
+        // 2. apply all logs starting from checkpoint, using log compactions where available
+        next_version = self
+            .apply_log_compactions(next_version, self.version)
+            .await?;
         while next_version <= self.version {
             self.apply_log(next_version).await?;
             next_version += 1;
         }
+        self.check_format()?;
+        self.verify_loaded_files().await?;
+        self.index_partitions();
+        self.loaded("Loaded");
 
         Ok(())
     }
 
-    async fn get_version_timestamp(
-        &mut self,
+    /// Returns the timestamp, in seconds since the Unix epoch, at which the given version was
+    /// committed.
+    pub async fn get_version_timestamp(
+        &self,
         version: DeltaDataTypeVersion,
     ) -> Result<i64, DeltaTableError> {
-        match self.version_timestamp.get(&version) {
-            Some(ts) => Ok(*ts),
+        let cached = self
+            .version_timestamp
+            .lock()
+            .unwrap()
+            .get(&version)
+            .copied();
+        match cached {
+            Some(ts) => Ok(ts),
             None => {
-                let meta = self
+                let meta = match self
                     .storage
                     .head_obj(&self.version_to_log_path(version))
-                    .await?;
+                    .await
+                {
+                    // the commit was cleaned up, the checkpoint was written right after it
+                    Err(StorageError::NotFound) => {
+                        let checkpoint_path = self.storage.join_path(
+                            &self.log_path,
+                            &format!("{:020}.checkpoint.parquet", version),
+                        );
+                        self.storage.head_obj(&checkpoint_path).await?
+                    }
+                    meta => meta?,
+                };
                 let ts = meta.modified.timestamp();
                 // also cache timestamp for version
-                self.version_timestamp.insert(version, ts);
+                self.version_timestamp.lock().unwrap().insert(version, ts);
 
                 Ok(ts)
             }
         }
     }
 
+    /// Returns a snapshot of the table at the given version without changing the loaded state,
+    /// so several versions of the same table can be served at once. The snapshot shares the
+    /// storage backend of this table.
+    pub async fn checkout(
+        &self,
+        version: DeltaDataTypeVersion,
+    ) -> Result<DeltaTableSnapshot, DeltaTableError> {
+        if version == self.version && self.state.current_metadata.is_some() {
+            return Ok(self.snapshot());
+        }
+
+        let mut table = Self {
+            version: 0,
+            state: Arc::new(DeltaTableState::default()),
+            storage: self.storage.clone(),
+            table_path: self.table_path.clone(),
+            last_check_point: None,
+            log_path: self.log_path.clone(),
+            config: self.config.clone(),
+            version_timestamp: Mutex::new(HashMap::new()),
+            load_metrics: LoadMetrics::default(),
+            clock: self.clock.clone(),
+            event_listener: self.event_listener.clone(),
+            post_commit_hooks: self.post_commit_hooks.clone(),
+            refreshed_at: None,
+        };
+        table.load_version(version).await?;
+
+        Ok(table.snapshot())
+    }
+
+    /// Returns the actions committed in the given version of the transaction log, in the order
+    /// they were written. Returns an `InvalidVersion` error when the log entry doesn't exist.
+    pub async fn get_commit_actions(
+        &self,
+        version: DeltaDataTypeVersion,
+    ) -> Result<Vec<Action>, DeltaTableError> {
+        let commit_log_bytes = match self.get_log_obj(version).await {
+            Ok(bytes) => bytes,
+            Err(StorageError::NotFound) => return Err(DeltaTableError::InvalidVersion(version)),
+            Err(e) => return Err(DeltaTableError::from(e)),
+        };
+
+        let min_reader_version = self.state.min_reader_version;
+        let mut actions = Vec::new();
+        for action in read_log_entry(&commit_log_bytes)? {
+            match action {
+                LogAction::Known(action) => actions.push(action),
+                LogAction::Unknown(action) => self
+                    .config
+                    .unknown_actions
+                    .check(&action, min_reader_version)?,
+            }
+        }
+
+        Ok(actions)
+    }
+
+    /// Returns a stream of the actions committed in each version of the given range, in
+    /// ascending version order. The stream yields an `InvalidVersion` error for versions missing
+    /// from the transaction log.
+    pub fn get_commit_actions_range(
+        &self,
+        versions: RangeInclusive<DeltaDataTypeVersion>,
+    ) -> impl Stream<Item = Result<(DeltaDataTypeVersion, Vec<Action>), DeltaTableError>> + '_ {
+        futures::stream::iter(versions).then(move |version| async move {
+            let actions = self.get_commit_actions(version).await?;
+            Ok((version, actions))
+        })
+    }
+
+    /// Folds the actions committed in the given range of versions into `visitor`, calling its
+    /// callbacks for each action in log order, and returns it. Only one commit is held in memory
+    /// at a time and no table state is built, so aggregates over long histories, e.g. the bytes
+    /// added per day, stay cheap to compute. Returns an `InvalidVersion` error for versions
+    /// missing from the transaction log, e.g. when older entries have been cleaned up.
+    pub async fn replay<V: LogReplayVisitor>(
+        &self,
+        versions: RangeInclusive<DeltaDataTypeVersion>,
+        mut visitor: V,
+    ) -> Result<V, DeltaTableError> {
+        for version in versions {
+            let actions = self.get_commit_actions(version).await?;
+            visitor.on_version(version);
+            for action in &actions {
+                match action {
+                    Action::add(add) => visitor.on_add(version, add),
+                    Action::remove(remove) => visitor.on_remove(version, remove),
+                    Action::metaData(metadata) => visitor.on_metadata(version, metadata),
+                    Action::protocol(protocol) => visitor.on_protocol(version, protocol),
+                    Action::txn(txn) => visitor.on_txn(version, txn),
+                    Action::commitInfo(commit_info) => visitor.on_commit_info(version, commit_info),
+                }
+            }
+        }
+
+        Ok(visitor)
+    }
+
+    /// Returns the `commitInfo` entries of the transaction log, newest first, starting at the
+    /// currently loaded version, with their `version` set. At most `limit` commits are read when
+    /// provided. Commits without a `commitInfo` action are skipped, and reading stops at the first
+    /// missing log entry, e.g. when older entries have been cleaned up.
+    pub async fn history(
+        &self,
+        limit: Option<usize>,
+    ) -> Result<Vec<action::CommitInfo>, DeltaTableError> {
+        let mut commit_infos = Vec::new();
+        let mut version = self.version;
+        let mut remaining = limit.unwrap_or(usize::MAX);
+
+        while version >= 0 && remaining > 0 {
+            let actions = match self.get_commit_actions(version).await {
+                Ok(actions) => actions,
+                Err(DeltaTableError::InvalidVersion(_)) => break,
+                Err(e) => return Err(e),
+            };
+            for action in actions {
+                if let Action::commitInfo(mut info) = action {
+                    info.version.get_or_insert(version);
+                    commit_infos.push(info);
+                }
+            }
+            version -= 1;
+            remaining -= 1;
+        }
+
+        Ok(commit_infos)
+    }
+
     /// Returns the file list tracked in current table state filtered by provided
     /// `PartitionFilter`s.
     pub fn get_files_by_partitions(
         &self,
         filters: &[PartitionFilter<&str>],
     ) -> Result<Vec<String>, DeltaTableError> {
-        let partitions_number = match &self
-            .state
-            .current_metadata
-            .as_ref()
-            .ok_or(DeltaTableError::NoMetadata)?
-            .partition_columns
-        {
-            partitions if !partitions.is_empty() => partitions.len(),
-            _ => return Err(DeltaTableError::LoadPartitions),
-        };
-        let separator = "/";
-        let files = self
-            .state
-            .files
-            .iter()
-            .filter(|add| {
-                let partitions = add
-                    .path
-                    .splitn(partitions_number + 1, separator)
-                    .filter_map(|p: &str| DeltaTablePartition::try_from(p).ok())
-                    .collect::<Vec<DeltaTablePartition>>();
-                filters
-                    .iter()
-                    .all(|filter| filter.match_partitions(&partitions))
-            })
+        let files = actions_by_partitions(&self.state, filters, self.config.column_resolution)?
+            .into_iter()
             .map(|add| add.path.clone())
             .collect();
 
@@ -655,6 +2008,159 @@ impl DeltaTable {
             .collect())
     }
 
+    /// Aggregates the active files per partition from the add actions, without reading any data:
+    /// number of files, total size, latest modification time and, when `timestamp_column` is
+    /// given, the range of that date or timestamp column according to the file statistics.
+    /// Partitions are sorted by their values, in the order of the partition columns.
+    pub fn get_partition_stats(
+        &self,
+        timestamp_column: Option<&str>,
+    ) -> Result<Vec<PartitionStats>, DeltaTableError> {
+        let partition_columns = &self
+            .state
+            .current_metadata
+            .as_ref()
+            .ok_or(DeltaTableError::NoMetadata)?
+            .partition_columns;
+        let timestamp_column = match timestamp_column {
+            Some(column) => {
+                let columns = self
+                    .get_schema()?
+                    .get_fields()
+                    .iter()
+                    .map(|field| field.get_name().to_string())
+                    .collect::<Vec<_>>();
+                Some(
+                    self.config
+                        .column_resolution
+                        .resolve(column, &columns)?
+                        .to_string(),
+                )
+            }
+            None => None,
+        };
+
+        let mut partitions: BTreeMap<Vec<Option<&str>>, PartitionStats> = BTreeMap::new();
+        for add in self.state.files.iter() {
+            let key = partition_columns
+                .iter()
+                .map(|column| add.get_partition_value(column))
+                .collect();
+            let stats = partitions.entry(key).or_insert_with(|| PartitionStats {
+                partition_values: partition_columns
+                    .iter()
+                    .map(|column| {
+                        let value = add.get_partition_value(column).map(str::to_string);
+                        (column.clone(), value)
+                    })
+                    .collect(),
+                num_files: 0,
+                total_bytes: 0,
+                min_timestamp: None,
+                max_timestamp: None,
+                num_files_without_timestamp_stats: 0,
+                latest_modification_time: add.modificationTime,
+            });
+            stats.num_files += 1;
+            stats.total_bytes += add.size;
+            stats.latest_modification_time =
+                stats.latest_modification_time.max(add.modificationTime);
+
+            if let Some(column) = &timestamp_column {
+                match timestamp_range(add, column)? {
+                    Some((min, max)) => {
+                        stats.min_timestamp = Some(stats.min_timestamp.map_or(min, |m| m.min(min)));
+                        stats.max_timestamp = Some(stats.max_timestamp.map_or(max, |m| m.max(max)));
+                    }
+                    None => stats.num_files_without_timestamp_stats += 1,
+                }
+            }
+        }
+
+        Ok(partitions.into_iter().map(|(_, stats)| stats).collect())
+    }
+
+    /// Merges the statistics of the active files for a column into table level statistics,
+    /// without reading any data. Fields of struct columns are addressed by their dot separated
+    /// path, e.g. `a.b`, resolved like `DeltaTableConfig::column_resolution` resolves column
+    /// names. Only the JSON statistics of the add actions are read, and struct columns and
+    /// partition columns have no statistics of their own.
+    pub fn column_stats(&self, column: &str) -> Result<ColumnStats, DeltaTableError> {
+        let path = self.resolve_column_path(column)?;
+        let mut column_stats = ColumnStats {
+            num_files: self.state.files.len(),
+            num_files_without_stats: 0,
+            num_records: 0,
+            min_value: None,
+            max_value: None,
+            null_count: 0,
+            distinct_count: Some(0),
+        };
+        for add in self.state.files.iter() {
+            let stats = match add.get_stats()? {
+                Some(stats) => stats,
+                None => {
+                    column_stats.num_files_without_stats += 1;
+                    continue;
+                }
+            };
+            let null_count = match count_stat(&stats.nullCount, &path) {
+                Some(null_count) => null_count,
+                None => {
+                    column_stats.num_files_without_stats += 1;
+                    continue;
+                }
+            };
+            column_stats.num_records += stats.numRecords;
+            column_stats.null_count += null_count;
+            if let Some(min) = value_stat(&stats.minValues, &path) {
+                match &column_stats.min_value {
+                    Some(current) if compare_values(min, current) != Some(Ordering::Less) => {}
+                    _ => column_stats.min_value = Some(min.clone()),
+                }
+            }
+            if let Some(max) = value_stat(&stats.maxValues, &path) {
+                match &column_stats.max_value {
+                    Some(current) if compare_values(max, current) != Some(Ordering::Greater) => {}
+                    _ => column_stats.max_value = Some(max.clone()),
+                }
+            }
+            column_stats.distinct_count = column_stats
+                .distinct_count
+                .zip(count_stat(&stats.distinctCount, &path))
+                .map(|(total, count)| total + count);
+        }
+        column_stats.distinct_count = column_stats
+            .distinct_count
+            .map(|count| count.min(column_stats.num_records - column_stats.null_count));
+
+        Ok(column_stats)
+    }
+
+    /// Resolves a dot separated column path against the fields of the schema and its structs.
+    fn resolve_column_path(&self, column: &str) -> Result<Vec<String>, DeltaTableError> {
+        let mut fields: &[SchemaField] = self.get_schema()?.get_fields();
+        let mut path = Vec::new();
+        for name in column.split('.') {
+            let names = fields
+                .iter()
+                .map(|field| field.get_name().to_string())
+                .collect::<Vec<_>>();
+            let resolved = self.config.column_resolution.resolve(name, &names)?;
+            let field = fields
+                .iter()
+                .find(|field| field.get_name() == resolved)
+                .unwrap();
+            path.push(resolved.to_string());
+            fields = match field.get_type() {
+                SchemaDataType::r#struct(struct_type) => struct_type.get_fields(),
+                _ => &[],
+            };
+        }
+
+        Ok(path)
+    }
+
     /// Return a refernece to the "add" actions present in the loaded state
     pub fn get_actions(&self) -> &Vec<action::Add> {
         &self.state.files
@@ -672,15 +2178,86 @@ impl DeltaTable {
         self.get_files_iter().collect()
     }
 
+    /// Returns the paths of the files present in the loaded state along with their sizes in bytes,
+    /// as recorded in the add actions.
+    pub fn get_files_with_sizes(&self) -> Vec<(&str, DeltaDataTypeLong)> {
+        files_with_sizes(&self.state)
+    }
+
+    /// Returns the number of rows of the loaded state, summed from the `numRecords` stats of the
+    /// add actions. Files without stats aren't counted, so the result is a lower bound unless all
+    /// the files have stats.
+    pub fn get_row_count_estimate(&self) -> Result<DeltaDataTypeLong, DeltaTableError> {
+        row_count_estimate(&self.state)
+    }
+
+    /// Returns the total size in bytes of the files of the loaded state, as recorded in the add
+    /// actions.
+    pub fn get_table_size_bytes(&self) -> DeltaDataTypeLong {
+        table_size_bytes(&self.state)
+    }
+
     /// Returns a copy of the file paths present in the loaded state.
     pub fn get_file_paths(&self) -> Vec<String> {
         self.state
             .files
             .iter()
-            .map(|add| self.storage.join_path(&self.table_path, &add.path))
+            .map(|add| self.object_path(&add.path))
             .collect()
     }
 
+    /// Returns a report of the loaded state and of how the most recent load or update went.
+    pub fn debug_dump(&self) -> DeltaTableDebugDump {
+        DeltaTableDebugDump {
+            table_uri: self.table_path.clone(),
+            version: self.version,
+            checkpoint_version: self.load_metrics.checkpoint_version,
+            checkpoint_load_millis: self.load_metrics.checkpoint_duration.as_millis() as u64,
+            num_log_files_replayed: self.load_metrics.num_log_files_replayed,
+            log_replay_millis: self.load_metrics.log_replay_duration.as_millis() as u64,
+            num_files: self.state.files.len(),
+            num_tombstones: self.state.tombstones.len(),
+            num_app_transactions: self.state.app_transaction_version.len(),
+            num_commit_infos: self.state.commit_infos.len(),
+            min_reader_version: self.state.min_reader_version,
+            min_writer_version: self.state.min_writer_version,
+        }
+    }
+
+    /// Returns the config the table was created with.
+    pub fn config(&self) -> &DeltaTableConfig {
+        &self.config
+    }
+
+    /// Returns the storage backend of the table, configured with the credentials and storage
+    /// options the table was opened with, and bounded by its `OperationTimeouts::storage_op` and
+    /// cancellation token. Lets applications read the data files of the table, or write side
+    /// artifacts such as manifests next to it, exactly like the table does. Objects are
+    /// addressed by full paths, see `object_path`.
+    pub fn storage_backend(&self) -> Arc<dyn StorageBackend> {
+        self.storage.clone()
+    }
+
+    /// Returns the full path, for the storage backend of the table, of the object at the given
+    /// path relative to the table root, e.g. the `path` of an add action or
+    /// `_symlink_format_manifest/manifest`.
+    pub fn object_path(&self, relative_path: &str) -> String {
+        self.storage.join_path(&self.table_path, relative_path)
+    }
+
+    /// Returns the kind of storage backend the table lives in, from the scheme of its path.
+    pub fn storage_backend_kind(&self) -> Result<StorageBackendKind, DeltaTableError> {
+        Ok(storage::parse_uri(&self.table_path)?.kind())
+    }
+
+    /// Returns the URI of the table in a normalized form, with its scheme and without trailing
+    /// slashes, e.g. `file:///data/table` for the relative local path `./table/` opened from
+    /// `/data`. Storage options given in the query string of the URI the table was opened with
+    /// aren't included.
+    pub fn table_uri(&self) -> Result<String, DeltaTableError> {
+        Ok(storage::parse_uri(&self.table_path)?.to_normalized_string())
+    }
+
     /// Returns the metadata associated with the loaded state.
     pub fn get_metadata(&self) -> Result<&DeltaTableMetaData, DeltaTableError> {
         self.state
@@ -690,10 +2267,67 @@ impl DeltaTable {
     }
 
     /// Returns a vector of tombstones (i.e. `Remove` actions present in the current delta log.
-    pub fn get_tombstones(&self) -> &Vec<action::Remove> {
+    pub fn get_tombstones(&self) -> &Vec<Tombstone> {
         &self.state.tombstones
     }
 
+    /// Returns the full `remove` actions of the tombstones in the loaded state, in the same order
+    /// as `get_tombstones`. The actions are read again from the latest checkpoint at or before the
+    /// loaded version and the log entries after it.
+    pub async fn load_extended_tombstones(&self) -> Result<Vec<action::Remove>, DeltaTableError> {
+        let mut removes = HashMap::new();
+        let mut next_version = 0;
+        if let Some(check_point) = self
+            .find_latest_check_point_for_version(self.version)
+            .await?
+        {
+            self.for_each_checkpoint_action(check_point, |action| {
+                if let Action::remove(remove) = action {
+                    removes.insert(remove.path.clone(), remove);
+                }
+                Ok(())
+            })
+            .await?;
+            next_version = check_point.version + 1;
+        }
+        while next_version <= self.version {
+            for action in self.get_commit_actions(next_version).await? {
+                if let Action::remove(remove) = action {
+                    removes.insert(remove.path.clone(), remove);
+                }
+            }
+            next_version += 1;
+        }
+
+        Ok(self
+            .state
+            .tombstones
+            .iter()
+            .map(|tombstone| {
+                removes
+                    .remove(&tombstone.path)
+                    .unwrap_or_else(|| action::Remove {
+                        path: tombstone.path.clone(),
+                        deletionTimestamp: tombstone.deletion_timestamp,
+                        ..Default::default()
+                    })
+            })
+            .collect())
+    }
+
+    /// Returns the commit infos of the commits replayed when loading the table, in commit order.
+    /// Commits restored from a checkpoint don't have commit infos.
+    pub fn get_commit_infos(&self) -> &Vec<action::CommitInfo> {
+        &self.state.commit_infos
+    }
+
+    /// Returns the actions of unknown types skipped while replaying the commits of the table,
+    /// see `UnknownActionPolicy::Tolerant`. Like commit infos, they aren't restored from
+    /// checkpoints.
+    pub fn get_unknown_actions(&self) -> &Vec<UnknownAction> {
+        &self.state.unknown_actions
+    }
+
     /// Returns the current version of the DeltaTable based on the loaded metadata.
     pub fn get_app_transaction_version(&self) -> &HashMap<String, DeltaDataTypeVersion> {
         &self.state.app_transaction_version
@@ -711,26 +2345,69 @@ impl DeltaTable {
         self.state.min_writer_version
     }
 
-    /// List files no longer referenced by a Delta table and are older than the retention threshold.
-    fn get_stale_files(&self, retention_hours: u64) -> Result<Vec<String>, DeltaTableError> {
+    /// Returns an immutable snapshot of the loaded state. Taking a snapshot doesn't copy the
+    /// state; the next `update` or `load_version` copies it instead, so the snapshot keeps
+    /// returning the files and metadata of the version it was taken at.
+    pub fn snapshot(&self) -> DeltaTableSnapshot {
+        DeltaTableSnapshot {
+            version: self.version,
+            table_path: self.table_path.clone(),
+            state: self.state.clone(),
+            storage: self.storage.clone(),
+            column_resolution: self.config.column_resolution,
+            refreshed_at: self.refreshed_at,
+        }
+    }
+
+    /// Returns the timestamp in milliseconds before which files are old enough to be vacuumed.
+    fn vacuum_cutoff_timestamp(&self, retention_hours: u64) -> Result<i64, DeltaTableError> {
         if retention_hours < 168 {
             return Err(DeltaTableError::InvalidVacuumRetentionPeriod);
         }
-        let before_duration = (SystemTime::now() - Duration::from_secs(3600 * retention_hours))
+        let before_duration = (self.clock.now() - Duration::from_secs(3600 * retention_hours))
             .duration_since(UNIX_EPOCH);
-        let delete_before_timestamp = match before_duration {
-            Ok(duration) => duration.as_millis() as i64,
-            Err(_) => return Err(DeltaTableError::InvalidVacuumRetentionPeriod),
-        };
+        match before_duration {
+            Ok(duration) => Ok(duration.as_millis() as i64),
+            Err(_) => Err(DeltaTableError::InvalidVacuumRetentionPeriod),
+        }
+    }
+
+    /// List files no longer referenced by a Delta table and are older than the retention threshold.
+    fn get_stale_files(&self, retention_hours: u64) -> Result<Vec<String>, DeltaTableError> {
+        let delete_before_timestamp = self.vacuum_cutoff_timestamp(retention_hours)?;
+        if self.config.skip_tombstones {
+            return Err(DeltaTableError::TombstonesNotLoaded);
+        }
 
         Ok(self
             .get_tombstones()
             .iter()
-            .filter(|tombstone| tombstone.deletionTimestamp < delete_before_timestamp)
+            .filter(|tombstone| tombstone.deletion_timestamp < delete_before_timestamp)
             .map(|tombstone| self.storage.join_path(&self.table_path, &tombstone.path))
             .collect::<Vec<String>>())
     }
 
+    /// Whether a path is a change data file, written by writers of tables with the change data
+    /// feed enabled: a file of the `_change_data` directory or a file of a `cdc` action of the
+    /// loaded versions. Change data files are never part of the table state, so vacuum deletes
+    /// them once they are older than the retention period.
+    fn is_change_data_file(&self, path_name: &str, cdc_paths: &HashSet<String>) -> bool {
+        path_name.starts_with(&self.storage.join_path(&self.table_path, "_change_data"))
+            || cdc_paths.contains(path_name)
+    }
+
+    /// Returns the full paths of the files of the `cdc` actions of the loaded versions. Like other
+    /// unknown actions, they aren't restored from checkpoints.
+    fn get_cdc_file_paths(&self) -> HashSet<String> {
+        self.state
+            .unknown_actions
+            .iter()
+            .filter(|action| action.action_type == "cdc")
+            .filter_map(|action| action.value.get("path")?.as_str())
+            .map(|path| self.storage.join_path(&self.table_path, path))
+            .collect()
+    }
+
     /// Whether a path should be hidden for delta-related file operations, such as Vacuum.
     /// Names of the form partitionCol=[value] are partition directories, and should be
     /// deleted even if they'd normally be hidden. The _db_index directory contains (bloom filter)
@@ -752,46 +2429,465 @@ impl DeltaTable {
                     .iter()
                     .any(|partition_column| {
                         path_name.starts_with(
-                            &self.storage.join_path(&self.table_path, partition_column),
+                            &self
+                                .storage
+                                .join_path(&self.table_path, &format!("{}=", partition_column)),
                         )
                     }),
         )
     }
 
-    /// Run the Vacuum command on the Delta Table: delete files no longer referenced by a Delta table and are older than the retention threshold.
-    /// We do not recommend that you set a retention interval shorter than 7 days, because old snapshots and uncommitted files can still be in use by concurrent readers or writers to the table. If vacuum cleans up active files, concurrent readers can fail or, worse, tables can be corrupted when vacuum deletes files that have not yet been committed.
-    pub async fn vacuum(
+    /// Computes the statistics of the active files that were committed without them and commits
+    /// them in `add` actions with `dataChange` unset, so that readers can skip these files.
+    /// Returns the committed version, or `None` when all the files already have statistics.
+    ///
+    /// Statistics are taken from the row group statistics in the footer of each file. Only the
+    /// columns whose minimum and maximum values can't be taken from the footer are read.
+    pub async fn compute_missing_stats(
+        &mut self,
+    ) -> Result<Option<DeltaDataTypeVersion>, DeltaTransactionError> {
+        let mut actions = Vec::new();
+        for add in self.state.files.iter().filter(|add| add.stats.is_none()) {
+            let obj = self
+                .storage
+                .get_obj(&self.storage.join_path(&self.table_path, &add.path))
+                .await
+                .map_err(|source| DeltaTransactionError::Storage { source })?;
+            let file_reader = SerializedFileReader::new(SliceableCursor::new(obj))
+                .map_err(DeltaTableError::from)?;
+            let (mut stats, missing_columns) = stats_from_footer(file_reader.metadata());
+            if !missing_columns.is_empty() {
+                let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+                let schema = Arc::new(
+                    arrow_reader
+                        .get_schema_by_columns(missing_columns.clone(), true)
+                        .map_err(DeltaTableError::from)?,
+                );
+                let batches = arrow_reader
+                    .get_record_reader_by_columns(missing_columns, STATS_BATCH_SIZE)
+                    .map_err(DeltaTableError::from)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(DeltaTableError::from)?;
+                let column_stats =
+                    stats_from_record_batches(schema, &batches).map_err(DeltaTableError::from)?;
+                fill_missing_stats(&mut stats, column_stats);
+            }
+
+            actions.push(Action::add(action::Add {
+                dataChange: false,
+                stats: Some(serde_json::to_string(&stats)?),
+                stats_parsed: None,
+                ..add.clone()
+            }));
+        }
+        if actions.is_empty() {
+            return Ok(None);
+        }
+
+        let version = self
+            .create_transaction(None)
+            .commit_with(&actions, Some(DeltaOperation::ComputeStats))
+            .await?;
+
+        Ok(Some(version))
+    }
+
+    /// Fills in the partition values missing from the add actions of the active files with the
+    /// values of the `key=value` directories of their paths, and commits the corrected add actions
+    /// with `dataChange` unset. Some tools converting existing tables to Delta leave
+    /// `partitionValues` empty, which makes partition filters skip all their files. Returns the
+    /// committed version, or `None` when no file needs repairing.
+    ///
+    /// Files whose paths don't have a directory for each missing partition column are left as
+    /// they are.
+    pub async fn repair_partition_values(
+        &mut self,
+    ) -> Result<Option<DeltaDataTypeVersion>, DeltaTransactionError> {
+        let partition_columns = &self
+            .state
+            .current_metadata
+            .as_ref()
+            .ok_or(DeltaTableError::NoMetadata)?
+            .partition_columns;
+
+        let mut actions = Vec::new();
+        for add in self.state.files.iter() {
+            if partition_columns
+                .iter()
+                .all(|column| add.partitionValues.contains_key(column))
+            {
+                continue;
+            }
+            let path_values = match partition_values_from_path(&add.path, partition_columns) {
+                Some(path_values) => path_values,
+                None => {
+                    warn!(
+                        "Can't infer the partition values of {} from its path",
+                        add.path
+                    );
+                    continue;
+                }
+            };
+            let mut partition_values = add.partitionValues.clone();
+            for (column, value) in path_values {
+                partition_values.entry(column).or_insert(value);
+            }
+            actions.push(Action::add(action::Add {
+                partitionValues: partition_values,
+                dataChange: false,
+                ..add.clone()
+            }));
+        }
+        if actions.is_empty() {
+            return Ok(None);
+        }
+
+        let version = self
+            .create_transaction(None)
+            .commit_with(&actions, Some(DeltaOperation::RepairPartitionValues))
+            .await?;
+
+        Ok(Some(version))
+    }
+
+    /// Raises the protocol versions required to read and write the table by committing a
+    /// `protocol` action, and returns the committed version, or `None` when the table already
+    /// requires these versions. Fails with `InvalidProtocolUpgrade` when a version is higher than
+    /// this crate supports, or lower than the current one since protocols can't be downgraded.
+    ///
+    /// Commits changing the metadata of a table upgrade its protocol automatically when they
+    /// enable features requiring a higher writer version, e.g. column invariants or
+    /// `delta.appendOnly`.
+    pub async fn upgrade_protocol(
+        &mut self,
+        min_reader_version: i32,
+        min_writer_version: i32,
+    ) -> Result<Option<DeltaDataTypeVersion>, DeltaTransactionError> {
+        if self.state.current_metadata.is_none() {
+            return Err(DeltaTableError::NoMetadata.into());
+        }
+        let current_versions = (self.state.min_reader_version, self.state.min_writer_version);
+        if (min_reader_version, min_writer_version) == current_versions {
+            return Ok(None);
+        }
+        check_protocol_upgrade(current_versions, (min_reader_version, min_writer_version))?;
+
+        let protocol = action::Protocol {
+            minReaderVersion: min_reader_version,
+            minWriterVersion: min_writer_version,
+        };
+        let version = self
+            .create_transaction(None)
+            .commit_with(
+                &[Action::protocol(protocol.clone())],
+                Some(DeltaOperation::UpgradeProtocol {
+                    newProtocol: protocol,
+                }),
+            )
+            .await?;
+
+        Ok(Some(version))
+    }
+
+    /// Sets the given properties in the configuration of the table, keeping its other properties,
+    /// by committing its metadata with the new configuration, and returns the committed version.
+    /// Fails with `InvalidTableProperty` when a `delta.*` property is unknown or its value doesn't
+    /// have the expected format, see [`table_properties`](crate::table_properties). Properties
+    /// enabling features, e.g. `delta.appendOnly`, upgrade the protocol of the table as needed.
+    pub async fn set_properties(
+        &mut self,
+        properties: HashMap<String, String>,
+    ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
+        let mut metadata = self.get_metadata()?.clone();
+        metadata.configuration.extend(properties.clone());
+        let metadata = action::MetaData::try_from(metadata).map_err(DeltaTableError::from)?;
+
+        self.create_transaction(None)
+            .commit_with(
+                &[Action::metaData(metadata)],
+                Some(DeltaOperation::SetTableProperties { properties }),
+            )
+            .await
+    }
+
+    /// Creates the table by committing version 0 with the given protocol, metadata and add
+    /// actions in a single log entry, then loads it. Meant for converting existing parquet files
+    /// to a Delta table when the file inventory and its statistics are already known.
+    ///
+    /// Fails with `VersionAlreadyExists` when the table already exists.
+    pub async fn initialize_with_actions(
+        &mut self,
+        metadata: action::MetaData,
+        protocol: action::Protocol,
+        adds: Vec<action::Add>,
+    ) -> Result<(), DeltaTransactionError> {
+        // fail before committing a schema that readers can't parse
+        serde_json::from_str::<Schema>(&metadata.schemaString).map_err(DeltaTableError::from)?;
+
+        let mut actions = Vec::with_capacity(adds.len() + 2);
+        actions.push(Action::protocol(protocol));
+        actions.push(Action::metaData(metadata));
+        actions.extend(adds.into_iter().map(Action::add));
+
+        self.commit_initial_version(&actions, DeltaOperation::Convert)
+            .await
+    }
+
+    /// Creates an empty table by committing version 0 with the given protocol and metadata, then
+    /// loads it. The `commit_info` is committed as the commitInfo action of the log entry; when
+    /// none is given, one is recorded for a `CREATE TABLE` operation. The creation time of the
+    /// metadata is taken from the clock of the table.
+    ///
+    /// Fails with `VersionAlreadyExists` when the table already exists.
+    pub async fn create(
+        &mut self,
+        mut metadata: DeltaTableMetaData,
+        protocol: action::Protocol,
+        commit_info: Option<serde_json::Map<String, Value>>,
+    ) -> Result<(), DeltaTransactionError> {
+        let operation = DeltaOperation::Create {
+            description: metadata.description.clone(),
+            partitionBy: metadata.partition_columns.clone(),
+            properties: metadata.configuration.clone(),
+        };
+        metadata.created_time = self.now_millis();
+        let metadata = action::MetaData::try_from(metadata).map_err(DeltaTableError::from)?;
+
+        let mut actions = Vec::with_capacity(3);
+        if let Some(commit_info) = commit_info {
+            actions.push(Action::commitInfo(action::CommitInfo::from_value(
+                &Value::Object(commit_info),
+            )));
+        }
+        actions.push(Action::protocol(protocol));
+        actions.push(Action::metaData(metadata));
+
+        self.commit_initial_version(&actions, operation).await
+    }
+
+    /// Commits the actions creating the table as version 0, then loads the table.
+    async fn commit_initial_version(
+        &mut self,
+        actions: &[Action],
+        operation: DeltaOperation,
+    ) -> Result<(), DeltaTransactionError> {
+        let mut transaction = self.create_transaction(None);
+        // not finalized, since updating a table that was never loaded would skip version 0
+        if transaction.writes_log_entries_directly() {
+            let log_entry = transaction.log_entry(actions, Some(&operation))?;
+            transaction
+                .try_commit(LogEntrySource::Bytes(log_entry.as_bytes()), 0)
+                .await?;
+        } else {
+            let prepared_commit = transaction.prepare(actions, Some(operation)).await?;
+            if let Err(e) = transaction
+                .try_commit(LogEntrySource::TmpFile(&prepared_commit.tmp_log_path), 0)
+                .await
+            {
+                transaction.discard(prepared_commit).await;
+                return Err(e.into());
+            }
+        }
+
+        self.load().await?;
+
+        Ok(())
+    }
+
+    /// Run the Vacuum command on the Delta Table: delete files no longer referenced by a Delta table and are older than the retention threshold.
+    /// Change data files, in the `_change_data` directory or referenced by `cdc` actions, are never referenced by the table and are deleted once modified before the retention threshold.
+    /// We do not recommend that you set a retention interval shorter than 7 days, because old snapshots and uncommitted files can still be in use by concurrent readers or writers to the table. If vacuum cleans up active files, concurrent readers can fail or, worse, tables can be corrupted when vacuum deletes files that have not yet been committed.
+    /// Files are deleted in batches; before each batch, the commits after the loaded version are read and the files they add back, e.g. when a concurrent writer restores an older version, are kept. They are left out of the returned paths.
+    pub async fn vacuum(
+        &mut self,
+        retention_hours: u64,
+        dry_run: bool,
+    ) -> Result<Vec<String>, DeltaTableError> {
+        let timeout = self.config.timeouts.vacuum;
+        let token = self.config.cancellation_token.clone();
+        // the listing can't borrow the table while vacuum_objects mutates it
+        let storage = self.storage.clone();
+        let table_path = self.table_path.clone();
+        run_operation("vacuum", timeout, token, async {
+            let all_files = storage.list_objs(&table_path).await?;
+            self.vacuum_objects(retention_hours, dry_run, all_files)
+                .await
+        })
+        .await
+    }
+
+    /// Runs the Vacuum command like `vacuum`, on the objects of a pre-computed listing of the
+    /// table directory, e.g. from an S3 Inventory report, instead of listing the storage. This
+    /// makes vacuum feasible on buckets with too many objects to list.
+    ///
+    /// The paths of the objects must be full paths, as returned by `StorageBackend::list_objs`.
+    /// Objects missing from the inventory aren't deleted.
+    pub async fn vacuum_with_inventory(
+        &mut self,
+        files: impl Stream<Item = ObjectMeta>,
+        retention_hours: u64,
+        dry_run: bool,
+    ) -> Result<Vec<String>, DeltaTableError> {
+        let timeout = self.config.timeouts.vacuum;
+        let token = self.config.cancellation_token.clone();
+        run_operation(
+            "vacuum",
+            timeout,
+            token,
+            self.vacuum_objects(retention_hours, dry_run, files.map(Ok)),
+        )
+        .await
+    }
+
+    /// Deletes the temporary commit files left in the log directory, or in the directory set by
+    /// `DeltaTableConfig::temp_commit_dir`, by writers that failed or crashed before committing,
+    /// once they are older than `max_age`. Returns the paths of the deleted files, or of the files
+    /// that would be deleted when `dry_run` is set.
+    pub async fn cleanup_temp_commit_files(
+        &self,
+        max_age: Duration,
+        dry_run: bool,
+    ) -> Result<Vec<String>, DeltaTableError> {
+        lazy_static! {
+            static ref TMP_COMMIT_REGEX: Regex =
+                Regex::new(r#"^_commit_[0-9a-f-]{36}\.json$"#).unwrap();
+        }
+
+        let now = DateTime::<Utc>::from(self.clock.now());
+        let tmp_commit_dir = self.tmp_commit_dir();
+        let mut stale_files = Vec::new();
+        // commits and checkpoints sort before the temporary commit files
+        let start_after = self.storage.join_path(&tmp_commit_dir, "_commit_");
+        let mut stream = match self
+            .storage
+            .list_objs_after(&tmp_commit_dir, &start_after, None)
+            .await
+        {
+            Ok(stream) => stream,
+            // no commit was ever prepared in a separate directory
+            Err(StorageError::NotFound) => return Ok(stale_files),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(obj_meta) = stream.next().await {
+            let obj_meta = obj_meta?;
+            // files modified in the future are kept
+            let is_stale = now
+                .signed_duration_since(obj_meta.modified)
+                .to_std()
+                .map_or(false, |age| age > max_age);
+            // only files directly in the directory, listings of object stores are recursive
+            let is_tmp_commit = obj_meta
+                .path
+                .strip_prefix(&tmp_commit_dir)
+                .map(|name| name.trim_start_matches(|c| c == '/' || c == '\\'))
+                .map_or(false, |name| TMP_COMMIT_REGEX.is_match(name));
+            if is_stale && is_tmp_commit {
+                stale_files.push(obj_meta.path);
+            }
+        }
+
+        if !dry_run {
+            for path in &stale_files {
+                match self.storage.delete_obj(path).await {
+                    Ok(_) | Err(StorageError::NotFound) => {}
+                    Err(source) => return Err(DeltaTableError::StorageError { source }),
+                }
+            }
+        }
+
+        Ok(stale_files)
+    }
+
+    async fn vacuum_objects(
         &mut self,
         retention_hours: u64,
         dry_run: bool,
+        objects: impl Stream<Item = Result<ObjectMeta, StorageError>>,
     ) -> Result<Vec<String>, DeltaTableError> {
-        let tombstones_path = self.get_stale_files(retention_hours)?;
+        let tombstones_path: HashSet<String> =
+            self.get_stale_files(retention_hours)?.into_iter().collect();
+        let file_paths: HashSet<String> = self.get_file_paths().into_iter().collect();
+        let cdc_paths = self.get_cdc_file_paths();
+        let delete_before_timestamp = self.vacuum_cutoff_timestamp(retention_hours)?;
 
         let mut tombstones = vec![];
-        let mut all_files = self.storage.list_objs(&self.table_path).await?;
-        while let Some(obj_meta) = all_files.next().await {
+        let mut objects = Box::pin(objects);
+        while let Some(obj_meta) = objects.next().await {
             let obj_meta = obj_meta?;
-            let is_not_valid_file = !self.get_file_paths().contains(&obj_meta.path);
+            let is_not_valid_file = !file_paths.contains(&obj_meta.path);
             let is_valid_tombstone = tombstones_path.contains(&obj_meta.path);
             let is_not_hidden_directory = !self.is_hidden_directory(&obj_meta.path)?;
-            if is_not_valid_file && is_valid_tombstone && is_not_hidden_directory {
+            let is_expired_change_data = self.is_change_data_file(&obj_meta.path, &cdc_paths)
+                && obj_meta.modified.timestamp_millis() < delete_before_timestamp;
+            if is_not_valid_file
+                && (is_valid_tombstone || is_expired_change_data)
+                && is_not_hidden_directory
+            {
                 tombstones.push(obj_meta.path);
             }
         }
 
+        // temporary commit files older than the retention period belong to failed writers
+        let retention = Duration::from_secs(3600 * retention_hours);
+        let tmp_commit_files = self.cleanup_temp_commit_files(retention, dry_run).await?;
+
         if dry_run {
+            tombstones.extend(tmp_commit_files);
             return Ok(tombstones);
         }
 
-        for tombstone in &tombstones {
-            match self.storage.delete_obj(&tombstone).await {
-                Ok(_) => continue,
-                Err(StorageError::NotFound) => continue,
-                Err(err) => return Err(DeltaTableError::StorageError { source: err }),
+        // files re-added by commits after the planned version, e.g. by a concurrent restore, are
+        // checked for before each batch of deletes and kept
+        let mut checked_version = self.version;
+        let mut added_paths = HashSet::new();
+        let mut deleted = Vec::with_capacity(tombstones.len());
+        for batch in tombstones.chunks(VACUUM_DELETE_BATCH_SIZE) {
+            checked_version = self
+                .collect_paths_added_after(checked_version, &mut added_paths)
+                .await?;
+            for tombstone in batch {
+                if added_paths.contains(tombstone) {
+                    warn!(
+                        "Not vacuuming {}, added back after version {}",
+                        tombstone, self.version
+                    );
+                    continue;
+                }
+                match self.storage.delete_obj(tombstone).await {
+                    Ok(_) | Err(StorageError::NotFound) => deleted.push(tombstone.clone()),
+                    Err(err) => return Err(DeltaTableError::StorageError { source: err }),
+                }
             }
         }
+        self.emit_event(TableEvent::VacuumCompleted {
+            version: self.version,
+            num_deleted_files: deleted.len(),
+            num_deleted_temp_commits: tmp_commit_files.len(),
+        });
+        deleted.extend(tmp_commit_files);
+
+        Ok(deleted)
+    }
 
-        Ok(tombstones)
+    /// Inserts the full paths of the files added by the commits after `version` into `paths`,
+    /// and returns the latest version read.
+    async fn collect_paths_added_after(
+        &self,
+        mut version: DeltaDataTypeVersion,
+        paths: &mut HashSet<String>,
+    ) -> Result<DeltaDataTypeVersion, DeltaTableError> {
+        loop {
+            let actions = match self.get_commit_actions(version + 1).await {
+                Ok(actions) => actions,
+                Err(DeltaTableError::InvalidVersion(_)) => return Ok(version),
+                Err(e) => return Err(e),
+            };
+            paths.extend(actions.iter().filter_map(|action| match action {
+                Action::add(add) => Some(self.object_path(&add.path)),
+                _ => None,
+            }));
+            version += 1;
+        }
     }
 
     /// Return table schema parsed from transaction log. Return None if table hasn't been loaded or
@@ -823,57 +2919,157 @@ impl DeltaTable {
     pub fn new(
         table_path: &str,
         storage_backend: Box<dyn StorageBackend>,
+    ) -> Result<Self, DeltaTableError> {
+        Self::new_with_config(table_path, storage_backend, DeltaTableConfig::default())
+    }
+
+    /// Create a new Delta Table struct with the given config, without loading any data from
+    /// backing storage.
+    ///
+    /// NOTE: This is for advanced users. If you don't know why you need to use this method, please
+    /// call one of the `open_table` helper methods instead.
+    pub fn new_with_config(
+        table_path: &str,
+        storage_backend: Box<dyn StorageBackend>,
+        config: DeltaTableConfig,
     ) -> Result<Self, DeltaTableError> {
         let log_path_normalized = storage_backend.join_path(table_path, "_delta_log");
+        let mut storage: Arc<dyn StorageBackend> = Arc::from(storage_backend);
+        if config.timeouts.storage_op.is_some() || config.cancellation_token.is_some() {
+            storage = Arc::new(BoundedStorageBackend::new(
+                storage,
+                config.timeouts.storage_op,
+                config.cancellation_token.clone(),
+            ));
+        }
         Ok(Self {
             version: 0,
-            state: DeltaTableState::default(),
-            storage: storage_backend,
+            state: Arc::new(DeltaTableState::default()),
+            storage,
             table_path: table_path.to_string(),
             last_check_point: None,
             log_path: log_path_normalized,
-            version_timestamp: HashMap::new(),
+            config,
+            version_timestamp: Mutex::new(HashMap::new()),
+            load_metrics: LoadMetrics::default(),
+            clock: Arc::new(SystemClock),
+            event_listener: None,
+            post_commit_hooks: Vec::new(),
+            refreshed_at: None,
         })
     }
 
+    /// Replaces the clock used for retention logic and commit timestamps, which defaults to the
+    /// system clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Returns the clock of the table, see `set_clock`.
+    pub(crate) fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Returns the current time of the table clock in milliseconds since the Unix epoch, the
+    /// unit of the timestamps of actions.
+    pub(crate) fn now_millis(&self) -> i64 {
+        self.clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+
+    /// Reports the commits, log compactions, vacuums and protocol errors of the table to the
+    /// given listener.
+    pub fn set_event_listener(&mut self, listener: Arc<dyn TableEventListener>) {
+        self.event_listener = Some(listener);
+    }
+
+    fn emit_event(&self, event: TableEvent) {
+        if let Some(listener) = &self.event_listener {
+            listener.on_event(&self.table_path, &event);
+        }
+    }
+
+    /// Runs the given hook after each successful commit of a transaction on the table, after the
+    /// hooks added before it.
+    pub fn add_post_commit_hook(&mut self, hook: Arc<dyn PostCommitHook>) {
+        self.post_commit_hooks.push(hook);
+    }
+
     /// Time travel Delta table to latest version that's created at or before provided `datetime`
     /// argument.
     ///
-    /// Internally, this methods performs a binary search on all Delta transaction logs.
+    /// Internally, this methods performs a binary search on all Delta transaction logs. Returns a
+    /// `TimestampEarlierThanTableCreation` error when `datetime` is before the first commit, and
+    /// loads the latest version when it is after the last commit. When `datetime` is before the
+    /// earliest version retained in the log, returns a `TimestampNotRetained` error, or loads
+    /// that version with `DeltaTableConfig::clamp_time_travel`.
     pub async fn load_with_datetime(
         &mut self,
         datetime: DateTime<Utc>,
     ) -> Result<(), DeltaTableError> {
-        let mut min_version = 0;
-        let mut max_version = self.get_latest_version().await?;
-        let mut version = min_version;
+        let (timeout, token) = self.load_bounds();
+        run_operation(
+            "load_with_datetime",
+            timeout,
+            token,
+            self.load_with_datetime_unbounded(datetime),
+        )
+        .await
+    }
+
+    async fn load_with_datetime_unbounded(
+        &mut self,
+        datetime: DateTime<Utc>,
+    ) -> Result<(), DeltaTableError> {
         let target_ts = datetime.timestamp();
+        let earliest_version = self.get_earliest_version().await?;
+        let earliest_ts = self.get_version_timestamp(earliest_version).await?;
+        if target_ts < earliest_ts {
+            let earliest =
+                DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(earliest_ts, 0), Utc);
+            if earliest_version == 0 {
+                return Err(DeltaTableError::TimestampEarlierThanTableCreation { earliest });
+            }
+            if !self.config.clamp_time_travel {
+                return Err(DeltaTableError::TimestampNotRetained {
+                    earliest,
+                    earliest_available: earliest_version,
+                });
+            }
+            warn!(
+                "Timestamp {} of table {} is earlier than its earliest retained version {}, \
+                 loading that version instead",
+                datetime, self.table_path, earliest_version
+            );
+            return self.load_version_unbounded(earliest_version).await;
+        }
 
-        // binary search
-        while min_version <= max_version {
-            let pivot = (max_version + min_version) / 2;
-            version = pivot;
+        // binary search for the latest version committed at or before the target timestamp,
+        // the earliest version always qualifies at this point
+        let mut min_version = earliest_version;
+        let mut max_version = self.get_latest_version().await?;
+        while min_version < max_version {
+            let pivot = min_version + (max_version - min_version + 1) / 2;
             let pts = self.get_version_timestamp(pivot).await?;
 
-            match pts.cmp(&target_ts) {
-                Ordering::Equal => {
-                    break;
-                }
-                Ordering::Less => {
-                    min_version = pivot + 1;
-                }
-                Ordering::Greater => {
-                    max_version = pivot - 1;
-                    version = max_version
-                }
+            if pts <= target_ts {
+                min_version = pivot;
+            } else {
+                max_version = pivot - 1;
             }
         }
 
-        if version < 0 {
-            version = 0;
-        }
+        self.load_version_unbounded(min_version).await
+    }
 
-        self.load_version(version).await
+    fn load_bounds(&self) -> (Option<Duration>, Option<CancellationToken>) {
+        (
+            self.config.timeouts.load,
+            self.config.cancellation_token.clone(),
+        )
     }
 }
 
@@ -904,6 +3100,269 @@ impl std::fmt::Debug for DeltaTable {
     }
 }
 
+async fn presigned_file_urls<'a>(
+    storage: &dyn StorageBackend,
+    table_path: &str,
+    files: Vec<&'a action::Add>,
+    expiry: Duration,
+) -> Result<Vec<(&'a action::Add, String)>, DeltaTableError> {
+    if !storage.capabilities().presign {
+        return Err(StorageError::PresignNotSupported.into());
+    }
+    let mut urls = Vec::with_capacity(files.len());
+    for add in files {
+        let url = storage
+            .presign(&storage.join_path(table_path, &add.path), expiry)
+            .await?;
+        urls.push((add, url));
+    }
+
+    Ok(urls)
+}
+
+fn files_with_sizes(state: &DeltaTableState) -> Vec<(&str, DeltaDataTypeLong)> {
+    state
+        .files
+        .iter()
+        .map(|add| (add.path.as_str(), add.size))
+        .collect()
+}
+
+fn row_count_estimate(state: &DeltaTableState) -> Result<DeltaDataTypeLong, DeltaTableError> {
+    let mut num_records = 0;
+    for add in state.files.iter() {
+        // checkpoints may only have the parsed stats of a file
+        if let Some(stats) = add.get_stats()? {
+            num_records += stats.numRecords;
+        } else if let Some(stats) = add.get_stats_parsed()? {
+            num_records += stats.numRecords;
+        }
+    }
+    Ok(num_records)
+}
+
+fn table_size_bytes(state: &DeltaTableState) -> DeltaDataTypeLong {
+    state.files.iter().map(|add| add.size).sum()
+}
+
+fn actions_by_partitions<'a>(
+    state: &'a DeltaTableState,
+    filters: &[PartitionFilter<&str>],
+    column_resolution: ColumnResolution,
+) -> Result<Vec<&'a action::Add>, DeltaTableError> {
+    let partition_columns = &state
+        .current_metadata
+        .as_ref()
+        .ok_or(DeltaTableError::NoMetadata)?
+        .partition_columns;
+    if partition_columns.is_empty() {
+        return Err(DeltaTableError::LoadPartitions);
+    }
+    let filters = filters
+        .iter()
+        .map(|filter| {
+            Ok(PartitionFilter {
+                key: column_resolution.resolve(filter.key, partition_columns)?,
+                value: filter.value.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, DeltaTableError>>()?;
+    if let Some(index) = &state.partition_index {
+        return Ok(index
+            .matching_files(partition_columns, &filters)
+            .into_iter()
+            .map(|i| &state.files[i])
+            .collect());
+    }
+    // the partition values are read from the add actions rather than the file paths, since
+    // files don't have to be stored in `key=value` partition directories
+    Ok(state
+        .files
+        .iter()
+        .filter(|add| add_matches_partitions(add, partition_columns, &filters))
+        .collect())
+}
+
+/// Whether the partition values of an add action match all the filters, whose keys must already
+/// be resolved to partition columns.
+fn add_matches_partitions(
+    add: &action::Add,
+    partition_columns: &[String],
+    filters: &[PartitionFilter<&str>],
+) -> bool {
+    let partitions = partition_columns
+        .iter()
+        .map(|key| DeltaTablePartition {
+            key,
+            value: add.get_partition_value(key),
+        })
+        .collect::<Vec<DeltaTablePartition>>();
+    filters
+        .iter()
+        .all(|filter| filter.match_partitions(&partitions))
+}
+
+/// Immutable view of a DeltaTable state at a given version, created by `DeltaTable::snapshot`.
+/// Cloning a snapshot is cheap, and snapshots can be shared between threads while the owning
+/// DeltaTable keeps updating.
+#[derive(Clone)]
+pub struct DeltaTableSnapshot {
+    version: DeltaDataTypeVersion,
+    table_path: String,
+    state: Arc<DeltaTableState>,
+    storage: Arc<dyn StorageBackend>,
+    column_resolution: ColumnResolution,
+    refreshed_at: Option<SystemTime>,
+}
+
+impl DeltaTableSnapshot {
+    /// Returns the version of the table the snapshot was taken at.
+    pub fn version(&self) -> DeltaDataTypeVersion {
+        self.version
+    }
+
+    /// Returns when the table was last loaded or updated before the snapshot was taken.
+    pub fn refreshed_at(&self) -> Option<SystemTime> {
+        self.refreshed_at
+    }
+
+    /// Returns the path of the table the snapshot was taken from.
+    pub fn table_path(&self) -> &str {
+        &self.table_path
+    }
+
+    /// Return a refernece to the "add" actions present in the snapshot
+    pub fn get_actions(&self) -> &Vec<action::Add> {
+        &self.state.files
+    }
+
+    /// Returns an iterator of file names present in the snapshot
+    #[inline]
+    pub fn get_files_iter(&self) -> impl Iterator<Item = &str> {
+        self.state.files.iter().map(|add| add.path.as_str())
+    }
+
+    /// Returns a collection of file names present in the snapshot
+    #[inline]
+    pub fn get_files(&self) -> Vec<&str> {
+        self.get_files_iter().collect()
+    }
+
+    /// Returns the paths of the files present in the snapshot along with their sizes in bytes, as
+    /// recorded in the add actions.
+    pub fn get_files_with_sizes(&self) -> Vec<(&str, DeltaDataTypeLong)> {
+        files_with_sizes(&self.state)
+    }
+
+    /// Returns the number of rows of the snapshot, summed from the `numRecords` stats of the add
+    /// actions. Files without stats aren't counted.
+    pub fn get_row_count_estimate(&self) -> Result<DeltaDataTypeLong, DeltaTableError> {
+        row_count_estimate(&self.state)
+    }
+
+    /// Returns the total size in bytes of the files of the snapshot.
+    pub fn get_table_size_bytes(&self) -> DeltaDataTypeLong {
+        table_size_bytes(&self.state)
+    }
+
+    /// Returns the "add" actions of the snapshot matching all the partition filters. Filter keys
+    /// are resolved with the column resolution of the table the snapshot was taken from.
+    pub fn get_actions_by_partitions(
+        &self,
+        filters: &[PartitionFilter<&str>],
+    ) -> Result<Vec<&action::Add>, DeltaTableError> {
+        actions_by_partitions(&self.state, filters, self.column_resolution)
+    }
+
+    /// Returns the add actions of the files of the snapshot matching the partition filters along
+    /// with pre-signed URLs to download them, see `DeltaTable::get_presigned_file_urls`.
+    pub async fn get_presigned_file_urls(
+        &self,
+        filters: &[PartitionFilter<&str>],
+        expiry: Duration,
+    ) -> Result<Vec<(&action::Add, String)>, DeltaTableError> {
+        let files = if filters.is_empty() {
+            self.state.files.iter().collect()
+        } else {
+            self.get_actions_by_partitions(filters)?
+        };
+        presigned_file_urls(self.storage.as_ref(), &self.table_path, files, expiry).await
+    }
+
+    /// Returns the storage backend of the table the snapshot was taken from, see
+    /// `DeltaTable::storage_backend`.
+    pub fn storage_backend(&self) -> Arc<dyn StorageBackend> {
+        self.storage.clone()
+    }
+
+    /// Returns a copy of the file paths present in the snapshot.
+    pub fn get_file_paths(&self) -> Vec<String> {
+        self.state
+            .files
+            .iter()
+            .map(|add| self.storage.join_path(&self.table_path, &add.path))
+            .collect()
+    }
+
+    /// Returns the metadata associated with the snapshot.
+    pub fn get_metadata(&self) -> Result<&DeltaTableMetaData, DeltaTableError> {
+        self.state
+            .current_metadata
+            .as_ref()
+            .ok_or(DeltaTableError::NoMetadata)
+    }
+
+    /// Return table schema of the snapshot. Return `DeltaTableError` if no metadata was found in
+    /// the log.
+    pub fn get_schema(&self) -> Result<&Schema, DeltaTableError> {
+        self.state
+            .current_metadata
+            .as_ref()
+            .map(|m| &m.schema)
+            .ok_or(DeltaTableError::NoSchema)
+    }
+
+    /// Returns a vector of tombstones (i.e. `Remove` actions) present in the snapshot.
+    pub fn get_tombstones(&self) -> &Vec<Tombstone> {
+        &self.state.tombstones
+    }
+
+    /// Returns the commit infos of the commits replayed to load the snapshot, in commit order.
+    pub fn get_commit_infos(&self) -> &Vec<action::CommitInfo> {
+        &self.state.commit_infos
+    }
+
+    /// Returns the actions of unknown types skipped while replaying the commits of the snapshot.
+    pub fn get_unknown_actions(&self) -> &Vec<UnknownAction> {
+        &self.state.unknown_actions
+    }
+
+    /// Returns the application transaction versions present in the snapshot.
+    pub fn get_app_transaction_version(&self) -> &HashMap<String, DeltaDataTypeVersion> {
+        &self.state.app_transaction_version
+    }
+
+    /// Returns the minimum reader version supported by the snapshot.
+    pub fn get_min_reader_version(&self) -> i32 {
+        self.state.min_reader_version
+    }
+
+    /// Returns the minimum writer version supported by the snapshot.
+    pub fn get_min_writer_version(&self) -> i32 {
+        self.state.min_writer_version
+    }
+}
+
+impl std::fmt::Debug for DeltaTableSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "DeltaTableSnapshot <{}> version {}",
+            self.table_path, self.version
+        )
+    }
+}
+
 /// Error returned by the DeltaTransaction struct
 #[derive(thiserror::Error, Debug)]
 pub enum DeltaTransactionError {
@@ -923,6 +3382,29 @@ pub enum DeltaTransactionError {
         inner: TransactionCommitAttemptError,
     },
 
+    /// Error that indicates a concurrent commit changed the table metadata or protocol since the
+    /// version the transaction was based on. The actions of the transaction may no longer match
+    /// the table schema, so the caller must validate them again before retrying.
+    #[error("Metadata changed by concurrent commit at version {version}")]
+    MetadataChanged {
+        /// The version of the concurrent commit that changed the metadata or protocol.
+        version: DeltaDataTypeVersion,
+    },
+
+    /// Error that indicates a concurrent commit added or removed a file read by the transaction
+    /// since the version the transaction was based on, e.g. a file it replaces. Committing the
+    /// actions anyway could lose the changes of the concurrent commit, so the caller must read
+    /// the table again before retrying.
+    #[error(
+        "File {path} read by the transaction changed by concurrent commit at version {version}"
+    )]
+    ReadFilesChanged {
+        /// The version of the concurrent commit that changed the file.
+        version: DeltaDataTypeVersion,
+        /// The path of the changed file.
+        path: String,
+    },
+
     /// Error that indicates the record batch is missing a partition column required by the Delta
     /// schema.
     #[error("RecordBatch is missing partition column in Delta schema.")]
@@ -945,6 +3427,16 @@ pub enum DeltaTransactionError {
         source: DeltaTableError,
     },
 
+    /// Error that indicates the log entry of the transaction is larger than the maximum commit
+    /// size set with `DeltaTransactionOptions::with_max_commit_size`.
+    #[error("Log entry of {size} bytes exceeds the maximum commit size of {max_size} bytes")]
+    CommitTooLarge {
+        /// The size of the log entry in bytes.
+        size: usize,
+        /// The maximum commit size in bytes.
+        max_size: usize,
+    },
+
     /// Error caused by a problem while using serde_json to serialize an action.
     #[error("Action serialization failed: {source}")]
     ActionSerializationFailed {
@@ -957,14 +3449,37 @@ pub enum DeltaTransactionError {
 /// Error that occurs when a single transaction commit attempt fails
 #[derive(thiserror::Error, Debug)]
 pub enum TransactionCommitAttemptError {
-    // NOTE: it would be nice to add a `num_retries` prop to this error so we can identify how frequently we hit optimistic concurrency retries and look for optimization paths
     /// Error indicating the transaction commit attempt failed because the Delta table version has already been committed.
     /// This is expected in the case of multiple writers to the same table and retried within the
     /// optimistic concurrency loop.
-    #[error("Version already exists: {source}")]
+    #[error("Version already exists after {num_retries} retries: {source}")]
     VersionExists {
         /// The wrapped StorageError.
         source: StorageError,
+        /// The number of times the commit was retried before giving up.
+        num_retries: u32,
+    },
+
+    /// Error indicating the transaction commit attempt failed because a concurrent commit changed
+    /// the table metadata or protocol. Retrying could commit actions that don't match the new
+    /// schema, so this is not retried within the optimistic concurrency loop.
+    #[error("Metadata changed by concurrent commit at version {version}")]
+    MetadataChanged {
+        /// The version of the concurrent commit that changed the metadata or protocol.
+        version: DeltaDataTypeVersion,
+    },
+
+    /// Error indicating the transaction commit attempt failed because a concurrent commit added or
+    /// removed a file read by the transaction. This is not retried within the optimistic
+    /// concurrency loop, since the actions were prepared from the old files.
+    #[error(
+        "File {path} read by the transaction changed by concurrent commit at version {version}"
+    )]
+    ReadFilesChanged {
+        /// The version of the concurrent commit that changed the file.
+        version: DeltaDataTypeVersion,
+        /// The path of the changed file.
+        path: String,
     },
 
     /// Error indicating a general DeltaTable error occurred during a transaction commit attempt.
@@ -989,6 +3504,12 @@ impl From<TransactionCommitAttemptError> for DeltaTransactionError {
             TransactionCommitAttemptError::VersionExists { .. } => {
                 DeltaTransactionError::VersionAlreadyExists { inner: error }
             }
+            TransactionCommitAttemptError::MetadataChanged { version } => {
+                DeltaTransactionError::MetadataChanged { version }
+            }
+            TransactionCommitAttemptError::ReadFilesChanged { version, path } => {
+                DeltaTransactionError::ReadFilesChanged { version, path }
+            }
             _ => DeltaTransactionError::TransactionCommitAttempt { inner: error },
         }
     }
@@ -996,9 +3517,10 @@ impl From<TransactionCommitAttemptError> for DeltaTransactionError {
 impl From<StorageError> for TransactionCommitAttemptError {
     fn from(error: StorageError) -> Self {
         match error {
-            StorageError::AlreadyExists(_) => {
-                TransactionCommitAttemptError::VersionExists { source: error }
-            }
+            StorageError::AlreadyExists(_) => TransactionCommitAttemptError::VersionExists {
+                source: error,
+                num_retries: 0,
+            },
             _ => TransactionCommitAttemptError::Storage { source: error },
         }
     }
@@ -1006,11 +3528,173 @@ impl From<StorageError> for TransactionCommitAttemptError {
 
 const DEFAULT_DELTA_MAX_RETRY_COMMIT_ATTEMPTS: u32 = 10_000_000;
 
+/// Size of a log entry above which the `CommitSizePolicy` of a transaction applies, 100MiB.
+const DEFAULT_MAX_COMMIT_SIZE: usize = 100 * 1024 * 1024;
+
+/// Number of rows read at once when computing the statistics of data files.
+const STATS_BATCH_SIZE: usize = 8192;
+
+/// Maximum number of concurrent HEAD requests sent by `DeltaTable::verify_files`.
+const VERIFY_FILES_CONCURRENCY: usize = 16;
+
+/// Number of bytes read from the end of a parquet file by `DeltaTable::read_parquet_metadata`,
+/// enough for the footers of most files.
+const PARQUET_FOOTER_READ_SIZE: u64 = 64 * 1024;
+
+/// Number of files `DeltaTable::vacuum` deletes between checks of the log for commits adding back
+/// the files it planned to delete.
+const VACUUM_DELETE_BATCH_SIZE: usize = 1000;
+
+/// Metrics of the last commit of a `DeltaTransaction`, successful or not.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommitMetrics {
+    /// Number of attempts to write the log entry, including the last one.
+    pub num_attempts: u32,
+    /// Number of attempts that failed because a concurrent writer committed the version first.
+    pub num_conflicts: u32,
+    /// Time spent committing, from the first attempt until the commit succeeded or failed.
+    pub commit_duration: Duration,
+}
+
+/// Receives the metrics of transaction commits, e.g. to alert on contention between writers.
+pub trait CommitMetricsRecorder: Send + Sync + fmt::Debug {
+    /// Called at the end of each commit of a transaction on the table at `table_path`.
+    fn record_commit(&self, table_path: &str, metrics: &CommitMetrics);
+}
+
+/// An operation on a table or a problem with it, reported to a `TableEventListener`, e.g. to alert
+/// on table health from an observability pipeline. Events serialize to JSON objects with an
+/// `event` field holding the snake case name of the variant, e.g. `commit_succeeded`, next to the
+/// fields of the variant. These names are stable.
+///
+/// Checkpoints aren't written by this crate yet, so there is no event for their creation.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TableEvent {
+    /// A transaction committed a new version.
+    CommitSucceeded {
+        /// The committed version.
+        version: DeltaDataTypeVersion,
+        /// The name of the committed operation, as recorded in the commit info.
+        operation: Option<String>,
+        /// Number of attempts to write the log entry.
+        num_attempts: u32,
+        /// Number of attempts that failed because a concurrent writer committed first.
+        num_conflicts: u32,
+        /// Time spent committing, in milliseconds.
+        duration_ms: u64,
+    },
+    /// A transaction failed to commit its log entry.
+    CommitFailed {
+        /// The name of the operation that failed to commit.
+        operation: Option<String>,
+        /// Number of attempts to write the log entry.
+        num_attempts: u32,
+        /// Number of attempts that failed because a concurrent writer committed first.
+        num_conflicts: u32,
+        /// The error the commit failed with.
+        error: String,
+    },
+    /// A log compaction file was written by `DeltaTable::create_log_compaction`.
+    LogCompactionCreated {
+        /// The first version reconciled by the log compaction.
+        start_version: DeltaDataTypeVersion,
+        /// The last version reconciled by the log compaction.
+        end_version: DeltaDataTypeVersion,
+    },
+    /// A vacuum that wasn't a dry run deleted the stale files of the table.
+    VacuumCompleted {
+        /// The version of the table that was vacuumed.
+        version: DeltaDataTypeVersion,
+        /// Number of deleted data files.
+        num_deleted_files: usize,
+        /// Number of deleted temporary commit files.
+        num_deleted_temp_commits: usize,
+    },
+    /// The loaded version of the table uses a protocol feature this crate doesn't support.
+    ProtocolError {
+        /// The loaded version.
+        version: DeltaDataTypeVersion,
+        /// The error the load failed with.
+        error: String,
+    },
+}
+
+/// Receives the events of a table, see `TableEvent`.
+pub trait TableEventListener: Send + Sync + fmt::Debug {
+    /// Called after each event of the table at `table_path`.
+    fn on_event(&self, table_path: &str, event: &TableEvent);
+}
+
+/// A version committed by a transaction, passed to the `PostCommitHook`s of the table.
+#[derive(Debug)]
+pub struct CommittedVersion<'a> {
+    /// The committed version.
+    pub version: DeltaDataTypeVersion,
+    /// The name of the committed operation, as recorded in the commit info.
+    pub operation: Option<&'a str>,
+    /// The `operationMetrics` recorded in the commit info, see
+    /// `DeltaTransaction::with_operation_metrics`.
+    pub operation_metrics: Option<&'a serde_json::Map<String, Value>>,
+    /// The metrics of the commit.
+    pub metrics: &'a CommitMetrics,
+}
+
+/// Runs after each successful commit of a transaction on a table, e.g. to report lineage or to
+/// notify downstream consumers. Hooks can't fail the commit, which already happened; they're
+/// expected to handle their own errors, e.g. by logging them.
+pub trait PostCommitHook: Send + Sync + fmt::Debug {
+    /// Called once `table` was updated after committing `commit`. The table may be at a later
+    /// version when concurrent writers committed since.
+    fn after_commit(&self, table: &DeltaTable, commit: &CommittedVersion<'_>);
+}
+
+/// Callbacks receiving the actions of the commits replayed by `DeltaTable::replay`. All callbacks
+/// do nothing by default, so visitors only implement the ones for the actions they aggregate.
+pub trait LogReplayVisitor {
+    /// Called at the start of each replayed commit, before its actions.
+    fn on_version(&mut self, _version: DeltaDataTypeVersion) {}
+    /// Called for each `add` action.
+    fn on_add(&mut self, _version: DeltaDataTypeVersion, _add: &action::Add) {}
+    /// Called for each `remove` action.
+    fn on_remove(&mut self, _version: DeltaDataTypeVersion, _remove: &action::Remove) {}
+    /// Called for each `metaData` action.
+    fn on_metadata(&mut self, _version: DeltaDataTypeVersion, _metadata: &action::MetaData) {}
+    /// Called for each `protocol` action.
+    fn on_protocol(&mut self, _version: DeltaDataTypeVersion, _protocol: &action::Protocol) {}
+    /// Called for each `txn` action.
+    fn on_txn(&mut self, _version: DeltaDataTypeVersion, _txn: &action::Txn) {}
+    /// Called for each `commitInfo` action.
+    fn on_commit_info(
+        &mut self,
+        _version: DeltaDataTypeVersion,
+        _commit_info: &action::CommitInfo,
+    ) {
+    }
+}
+
+/// What a `DeltaTransaction` does with log entries larger than its maximum commit size. Other
+/// engines may fail to parse such entries, so large sets of actions should rather be split with
+/// `split_actions` and committed over several transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitSizePolicy {
+    /// Logs a warning and commits the log entry anyway.
+    Warn,
+    /// Fails with `DeltaTransactionError::CommitTooLarge` without writing the log entry.
+    Reject,
+}
+
 /// Options for customizing behavior of a `DeltaTransaction`
 #[derive(Debug)]
 pub struct DeltaTransactionOptions {
     /// number of retry attempts allowed when committing a transaction
     max_retry_commit_attempts: u32,
+    /// receives the metrics of each commit
+    metrics_recorder: Option<Arc<dyn CommitMetricsRecorder>>,
+    /// size in bytes of the log entries above which `commit_size_policy` applies
+    max_commit_size: usize,
+    /// what to do with log entries larger than `max_commit_size`
+    commit_size_policy: CommitSizePolicy,
 }
 
 impl DeltaTransactionOptions {
@@ -1018,18 +3702,116 @@ impl DeltaTransactionOptions {
     pub fn new(max_retry_commit_attempts: u32) -> Self {
         Self {
             max_retry_commit_attempts,
+            ..Default::default()
         }
     }
+
+    /// Reports the metrics of each commit to the given recorder.
+    pub fn with_metrics_recorder(mut self, recorder: Arc<dyn CommitMetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(recorder);
+        self
+    }
+
+    /// Applies `policy` to log entries larger than `max_commit_size` bytes. By default, a warning
+    /// is logged for log entries larger than 100MiB.
+    pub fn with_max_commit_size(
+        mut self,
+        max_commit_size: usize,
+        policy: CommitSizePolicy,
+    ) -> Self {
+        self.max_commit_size = max_commit_size;
+        self.commit_size_policy = policy;
+        self
+    }
 }
 
 impl Default for DeltaTransactionOptions {
     fn default() -> Self {
         Self {
             max_retry_commit_attempts: DEFAULT_DELTA_MAX_RETRY_COMMIT_ATTEMPTS,
+            metrics_recorder: None,
+            max_commit_size: DEFAULT_MAX_COMMIT_SIZE,
+            commit_size_policy: CommitSizePolicy::Warn,
+        }
+    }
+}
+
+/// A log entry written to a temporary file by `DeltaTransaction::prepare`, waiting to be
+/// committed with `DeltaTransaction::finalize` or discarded with `DeltaTransaction::abort`.
+#[derive(Debug)]
+pub struct PreparedCommit {
+    tmp_log_path: String,
+    operation: Option<String>,
+}
+
+impl PreparedCommit {
+    /// The path of the temporary file holding the log entry.
+    pub fn tmp_log_path(&self) -> &str {
+        &self.tmp_log_path
+    }
+}
+
+/// Where the log entry committed by a transaction attempt comes from.
+#[derive(Clone, Copy, Debug)]
+enum LogEntrySource<'a> {
+    /// A temporary file renamed to the log entry of the version.
+    TmpFile(&'a str),
+    /// The log entry itself, written directly with `StorageBackend::put_obj_atomic`.
+    Bytes(&'a [u8]),
+}
+
+/// The files of the loaded version read by a transaction to prepare its actions. The commits of
+/// concurrent writers since that version must not change them, otherwise the transaction fails
+/// with `ReadFilesChanged` instead of retrying.
+#[derive(Debug, Default)]
+struct ReadFiles {
+    /// The paths of the read files, which concurrent commits must not remove.
+    paths: HashSet<String>,
+    /// The partitions of the read files, in which concurrent commits must not add data. `None`
+    /// when the transaction doesn't depend on the files added concurrently.
+    partitions: Option<ReadPartitions>,
+}
+
+/// The partitions read by a transaction, see `ReadFiles`.
+#[derive(Debug)]
+struct ReadPartitions {
+    partition_columns: Vec<String>,
+    /// The partition filters, with keys resolved to partition columns. Without filters, all the
+    /// partitions are read.
+    filters: Vec<(String, PartitionValue<String>)>,
+}
+
+impl ReadFiles {
+    /// Returns the path of the read file changed by the action of a concurrent commit, if any.
+    /// Like Spark, re-adds that don't change the data, e.g. of recomputed stats, are ignored.
+    fn changed_path<'a>(&self, action: &'a Action) -> Option<&'a str> {
+        match action {
+            Action::remove(remove) if self.paths.contains(&remove.path) => Some(&remove.path),
+            Action::add(add) if add.dataChange => {
+                let partitions = self.partitions.as_ref()?;
+                let filters = partitions
+                    .filters
+                    .iter()
+                    .map(|(key, value)| PartitionFilter {
+                        key: key.as_str(),
+                        value: value.map(String::as_str),
+                    })
+                    .collect::<Vec<_>>();
+                if add_matches_partitions(add, &partitions.partition_columns, &filters) {
+                    Some(&add.path)
+                } else {
+                    None
+                }
+            }
+            _ => None,
         }
     }
 }
 
+fn operation_name(operation: Option<&DeltaOperation>) -> Option<String> {
+    operation.map(|operation| operation.name().to_string())
+}
+
 /// Object representing a delta transaction.
 /// Clients that do not need to mutate action content in case a transaction conflict is encountered
 /// may use the `commit_with` method and rely on optimistic concurrency to determine the
@@ -1042,6 +3824,9 @@ impl Default for DeltaTransactionOptions {
 pub struct DeltaTransaction<'a> {
     delta_table: &'a mut DeltaTable,
     options: DeltaTransactionOptions,
+    metrics: CommitMetrics,
+    operation_metrics: Option<serde_json::Map<String, Value>>,
+    read_files: ReadFiles,
 }
 
 impl<'a> DeltaTransaction<'a> {
@@ -1052,19 +3837,63 @@ impl<'a> DeltaTransaction<'a> {
         DeltaTransaction {
             delta_table,
             options: options.unwrap_or_else(DeltaTransactionOptions::default),
+            metrics: CommitMetrics::default(),
+            operation_metrics: None,
+            read_files: ReadFiles::default(),
         }
     }
 
+    /// Sets the `operationMetrics` recorded in the commitInfo of the commits of the transaction,
+    /// e.g. the number of written rows. Like Spark, metric values should be strings.
+    pub fn with_operation_metrics(mut self, metrics: serde_json::Map<String, Value>) -> Self {
+        self.operation_metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the paths of the files of the loaded version read to prepare the actions of the
+    /// transaction, e.g. the files rewritten by a delete. `commit_with` fails with
+    /// `DeltaTransactionError::ReadFilesChanged` instead of retrying when a concurrent commit
+    /// removed one of them, since the actions would apply to stale data.
+    pub fn with_read_paths(mut self, paths: impl IntoIterator<Item = String>) -> Self {
+        self.read_files.paths = paths.into_iter().collect();
+        self
+    }
+
+    /// Returns the metrics of the last commit of the transaction, successful or not.
+    pub fn metrics(&self) -> &CommitMetrics {
+        &self.metrics
+    }
+
     /// Commits the given actions to the delta log.
     /// This method will retry the transaction commit based on the value of `max_retry_commit_attempts` set in `DeltaTransactionOptions`.
+    /// Fails with `DeltaTransactionError::MetadataChanged` instead of retrying when a concurrent
+    /// commit changed the table metadata or protocol after the currently loaded version, and with
+    /// `ReadFilesChanged` when it changed the files read by the transaction. Transactions on
+    /// tables without loaded metadata aren't checked for conflicts.
+    ///
+    /// Bounded by the commit timeout and the cancellation token of the table. A commit aborted
+    /// that way may leave its temporary log entry behind, see
+    /// `DeltaTable::cleanup_temp_commit_files`.
     pub async fn commit_with(
         &mut self,
         additional_actions: &[Action],
-        _operation: Option<DeltaOperation>,
+        operation: Option<DeltaOperation>,
     ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
-        // TODO: stubbing `operation` parameter (which will be necessary for writing the CommitInfo action), but leaving it unused for now.
-        // `CommitInfo` is a fairly dynamic data structure so we should work out the data structure approach separately.
+        let (timeout, token) = self.commit_bounds();
+        run_operation(
+            "commit",
+            timeout,
+            token,
+            self.commit_with_unbounded(additional_actions, operation),
+        )
+        .await
+    }
 
+    async fn commit_with_unbounded(
+        &mut self,
+        additional_actions: &[Action],
+        operation: Option<DeltaOperation>,
+    ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
         // TODO: calculate isolation level to use when checking for conflicts.
         // Leaving conflict checking unimplemented for now to get the "single writer" implementation off the ground.
         // Leaving some commmented code in place as a guidepost for the future.
@@ -1080,51 +3909,428 @@ impl<'a> DeltaTransaction<'a> {
         //     IsolationLevel::Serializable
         // };
 
-        // TODO: create a CommitInfo action and prepend it to actions.
+        // the version the actions were prepared against, -1 when the table doesn't exist yet
+        let read_version = if self.delta_table.state.current_metadata.is_some() {
+            self.delta_table.version
+        } else {
+            -1
+        };
 
-        // Serialize all actions that are part of this log entry.
-        let log_entry = log_entry_from_actions(additional_actions)?;
+        // backends with conditional puts get the log entry written directly at each attempted
+        // version, saving the round trips of the temporary file and its rename
+        let prepared_commit = if self.writes_log_entries_directly() {
+            None
+        } else {
+            Some(self.prepare(additional_actions, operation.clone()).await?)
+        };
+        let log_entry;
+        let source = match &prepared_commit {
+            Some(prepared_commit) => LogEntrySource::TmpFile(&prepared_commit.tmp_log_path),
+            None => {
+                log_entry = self.log_entry(additional_actions, operation.as_ref())?;
+                LogEntrySource::Bytes(log_entry.as_bytes())
+            }
+        };
 
         // try to commit in a loop in case other writers write the next version first
-        let version = self.try_commit_loop(log_entry.as_bytes()).await?;
+        let started_at = Instant::now();
+        self.metrics = CommitMetrics::default();
+        let result = self.try_commit_loop(source, read_version).await;
+        self.record_commit_metrics(started_at);
+        self.emit_commit_event(operation_name(operation.as_ref()), &result);
+        let version = match result {
+            Ok(version) => version,
+            Err(e) => {
+                if let Some(prepared_commit) = prepared_commit {
+                    self.discard(prepared_commit).await;
+                }
+                return Err(e.into());
+            }
+        };
 
         // NOTE: since we have the log entry in memory already,
         // we could optimize this further by merging the log entry instead of updating from storage.
         self.delta_table.update().await?;
+        self.run_post_commit_hooks(version, operation_name(operation.as_ref()).as_deref());
 
         Ok(version)
     }
 
     /// Commits the delta transaction at the specified version.
     /// Propagates version conflict errors back to the caller immediately.
+    /// Bounded like `commit_with`.
     pub async fn commit_version(
         &mut self,
         version: DeltaDataTypeVersion,
         additional_actions: &[Action],
-        _operation: Option<DeltaOperation>,
+        operation: Option<DeltaOperation>,
     ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
-        // TODO: create a CommitInfo action and prepend it to actions.
+        let (timeout, token) = self.commit_bounds();
+        run_operation("commit", timeout, token, async {
+            if self.writes_log_entries_directly() {
+                let log_entry = self.log_entry(additional_actions, operation.as_ref())?;
+                let source = LogEntrySource::Bytes(log_entry.as_bytes());
+                return self
+                    .commit_at(source, operation_name(operation.as_ref()), version)
+                    .await;
+            }
+            let prepared_commit = self.prepare(additional_actions, operation).await?;
+            let result = self.finalize(&prepared_commit, version).await;
+            if result.is_err() {
+                self.discard(prepared_commit).await;
+            }
+
+            result
+        })
+        .await
+    }
+
+    /// Whether log entries are written directly at the committed version with
+    /// `StorageBackend::put_obj_atomic`, instead of through a temporary file renamed to it.
+    fn writes_log_entries_directly(&self) -> bool {
+        self.delta_table.storage.capabilities().conditional_put
+    }
+
+    fn commit_bounds(&self) -> (Option<Duration>, Option<CancellationToken>) {
+        let config = &self.delta_table.config;
+        (config.timeouts.commit, config.cancellation_token.clone())
+    }
 
-        let log_entry = log_entry_from_actions(additional_actions)?;
+    /// Writes the log entry of the given actions to a temporary file in the log directory without
+    /// committing it. The returned handle must be either committed with `finalize` or discarded
+    /// with `abort`, which lets external orchestrators coordinate the commit with other systems,
+    /// e.g. a metastore update.
+    pub async fn prepare(
+        &mut self,
+        additional_actions: &[Action],
+        operation: Option<DeltaOperation>,
+    ) -> Result<PreparedCommit, DeltaTransactionError> {
+        let log_entry = self.log_entry(additional_actions, operation.as_ref())?;
         let tmp_log_path = self.prepare_commit(log_entry.as_bytes()).await?;
-        let version = self.try_commit(&tmp_log_path, version).await?;
+
+        Ok(PreparedCommit {
+            tmp_log_path,
+            operation: operation_name(operation.as_ref()),
+        })
+    }
+
+    /// Builds the log entry committing the given actions, with the commitInfo and protocol
+    /// upgrade they require.
+    fn log_entry(
+        &self,
+        additional_actions: &[Action],
+        operation: Option<&DeltaOperation>,
+    ) -> Result<String, DeltaTransactionError> {
+        check_actions_metadata(
+            additional_actions,
+            self.delta_table.state.current_metadata.as_ref(),
+        )?;
+
+        let mut actions = self.actions_with_commit_info(additional_actions, operation)?;
+        if let Some(protocol) = self.protocol_upgrade(additional_actions)? {
+            actions.push(Action::protocol(protocol));
+        }
+        let log_entry = log_entry_from_actions(&actions)?;
+        self.check_commit_size(log_entry.len())?;
+
+        Ok(log_entry)
+    }
+
+    /// Commits the given actions over several transactions, each with a log entry of at most about
+    /// `max_batch_size` bytes, and returns the committed versions. The batches aren't committed
+    /// atomically: when a commit fails, the batches before it stay committed. So this is only
+    /// meant for actions that don't depend on each other, e.g. the adds of a large append.
+    pub async fn commit_in_batches(
+        &mut self,
+        actions: &[Action],
+        operation: Option<DeltaOperation>,
+        max_batch_size: usize,
+    ) -> Result<Vec<DeltaDataTypeVersion>, DeltaTransactionError> {
+        let mut versions = Vec::new();
+        for batch in split_actions(actions, max_batch_size)? {
+            versions.push(self.commit_with(&batch, operation.clone()).await?);
+        }
+
+        Ok(versions)
+    }
+
+    /// Commits the add actions of new data files replacing the data of the table, like the
+    /// overwrite mode of Spark writes: the active files matching the partition filters, or all the
+    /// active files when there are none, are removed in the same commit. Filtering like
+    /// `replaceWhere` only replaces some partitions, so the added files must match the filters
+    /// too, otherwise nothing is committed and `OverwriteOutsidePredicate` is returned.
+    ///
+    /// The removed files are the ones active in the loaded version. Fails with `ReadFilesChanged`
+    /// when a concurrent commit removed one of them, or added data matching the partition filters,
+    /// since the overwrite would otherwise keep the concurrently added files or remove files
+    /// twice.
+    pub async fn overwrite_with(
+        &mut self,
+        adds: Vec<action::Add>,
+        partition_filters: &[PartitionFilter<'_, &str>],
+    ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
+        let table = &self.delta_table;
+        // without filters, concurrent commits must not add data anywhere
+        let mut read_partitions = ReadPartitions {
+            partition_columns: Vec::new(),
+            filters: Vec::new(),
+        };
+        let predicate = if partition_filters.is_empty() {
+            None
+        } else {
+            let predicate = partition_filters
+                .iter()
+                .map(|filter| filter.to_string())
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let partition_columns = &table.get_metadata()?.partition_columns;
+            let filters = partition_filters
+                .iter()
+                .map(|filter| {
+                    Ok(PartitionFilter {
+                        key: table
+                            .config
+                            .column_resolution
+                            .resolve(filter.key, partition_columns)?,
+                        value: filter.value.clone(),
+                    })
+                })
+                .collect::<Result<Vec<_>, DeltaTableError>>()?;
+            if let Some(add) = adds
+                .iter()
+                .find(|add| !add_matches_partitions(add, partition_columns, &filters))
+            {
+                return Err(DeltaTableError::OverwriteOutsidePredicate {
+                    path: add.path.clone(),
+                    predicate,
+                }
+                .into());
+            }
+            read_partitions = ReadPartitions {
+                partition_columns: partition_columns.clone(),
+                filters: filters
+                    .into_iter()
+                    .map(|filter| (filter.key.to_string(), filter.value.map(|v| v.to_string())))
+                    .collect(),
+            };
+            Some(predicate)
+        };
+
+        let replaced_files = if partition_filters.is_empty() {
+            table.get_actions().iter().collect()
+        } else {
+            actions_by_partitions(
+                &table.state,
+                partition_filters,
+                table.config.column_resolution,
+            )?
+        };
+        let deletion_timestamp = table.now_millis();
+        let read_files = ReadFiles {
+            paths: replaced_files.iter().map(|add| add.path.clone()).collect(),
+            partitions: Some(read_partitions),
+        };
+        let mut actions = replaced_files
+            .into_iter()
+            .map(|add| Action::remove(add.to_remove(deletion_timestamp, true)))
+            .collect::<Vec<_>>();
+        actions.extend(adds.into_iter().map(Action::add));
+
+        let operation = DeltaOperation::Write {
+            mode: action::SaveMode::Overwrite,
+            partitionBy: None,
+            predicate,
+        };
+        self.read_files = read_files;
+        self.commit_with(&actions, Some(operation)).await
+    }
+
+    /// Commits a prepared log entry at the specified version.
+    /// Propagates version conflict errors back to the caller immediately, in which case the
+    /// prepared log entry can still be committed at another version or aborted.
+    pub async fn finalize(
+        &mut self,
+        prepared_commit: &PreparedCommit,
+        version: DeltaDataTypeVersion,
+    ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
+        self.commit_at(
+            LogEntrySource::TmpFile(&prepared_commit.tmp_log_path),
+            prepared_commit.operation.clone(),
+            version,
+        )
+        .await
+    }
+
+    /// Commits a log entry at the specified version, without retrying on conflicts.
+    async fn commit_at(
+        &mut self,
+        source: LogEntrySource<'_>,
+        operation: Option<String>,
+        version: DeltaDataTypeVersion,
+    ) -> Result<DeltaDataTypeVersion, DeltaTransactionError> {
+        let started_at = Instant::now();
+        self.metrics = CommitMetrics {
+            num_attempts: 1,
+            ..Default::default()
+        };
+        let result = self.try_commit(source, version).await;
+        if let Err(TransactionCommitAttemptError::VersionExists { .. }) = result {
+            self.metrics.num_conflicts = 1;
+        }
+        self.record_commit_metrics(started_at);
+        self.emit_commit_event(operation.clone(), &result);
+        let version = result?;
 
         self.delta_table.update().await?;
+        self.run_post_commit_hooks(version, operation.as_deref());
 
         Ok(version)
     }
 
+    /// Deletes the temporary file of a prepared log entry that won't be committed.
+    pub async fn abort(
+        &mut self,
+        prepared_commit: PreparedCommit,
+    ) -> Result<(), DeltaTransactionError> {
+        match self
+            .delta_table
+            .storage
+            .delete_obj(&prepared_commit.tmp_log_path)
+            .await
+        {
+            Ok(_) | Err(StorageError::NotFound) => Ok(()),
+            Err(source) => Err(DeltaTransactionError::Storage { source }),
+        }
+    }
+
+    /// Aborts a prepared commit after a terminal failure, so that its temporary file isn't left
+    /// behind. Failing to delete it is only logged, the file is then deleted by vacuum.
+    async fn discard(&mut self, prepared_commit: PreparedCommit) {
+        let tmp_log_path = prepared_commit.tmp_log_path.clone();
+        if let Err(e) = self.abort(prepared_commit).await {
+            debug!(
+                "Failed to delete temporary commit file {} of table {}: {}",
+                tmp_log_path, self.delta_table.table_path, e
+            );
+        }
+    }
+
+    fn check_commit_size(&self, size: usize) -> Result<(), DeltaTransactionError> {
+        let max_size = self.options.max_commit_size;
+        if size <= max_size {
+            return Ok(());
+        }
+        match self.options.commit_size_policy {
+            CommitSizePolicy::Warn => {
+                warn!(
+                    "Log entry of {} bytes for table {} exceeds the maximum commit size of {} bytes",
+                    size, self.delta_table.table_path, max_size
+                );
+                Ok(())
+            }
+            CommitSizePolicy::Reject => {
+                Err(DeltaTransactionError::CommitTooLarge { size, max_size })
+            }
+        }
+    }
+
+    /// Returns the protocol the table must be upgraded to for the metadata set by the actions,
+    /// when the actions don't set a protocol themselves. Only tables that were already loaded
+    /// are upgraded, new tables are created with the protocol they are committed with.
+    fn protocol_upgrade(
+        &self,
+        actions: &[Action],
+    ) -> Result<Option<action::Protocol>, DeltaTableError> {
+        let state = &self.delta_table.state;
+        if state.current_metadata.is_none()
+            || actions.iter().any(|a| matches!(a, Action::protocol(_)))
+        {
+            return Ok(None);
+        }
+        let current_versions = (state.min_reader_version, state.min_writer_version);
+        let mut versions = current_versions;
+        for action in actions {
+            if let Action::metaData(metadata) = action {
+                let (min_reader_version, min_writer_version) = required_protocol(metadata)?;
+                versions.0 = versions.0.max(min_reader_version);
+                versions.1 = versions.1.max(min_writer_version);
+            }
+        }
+        if versions == current_versions {
+            return Ok(None);
+        }
+        check_protocol_upgrade(current_versions, versions)?;
+
+        Ok(Some(action::Protocol {
+            minReaderVersion: versions.0,
+            minWriterVersion: versions.1,
+        }))
+    }
+
+    /// Prepends a commitInfo action to the given actions, unless they already contain one.
+    fn actions_with_commit_info(
+        &self,
+        actions: &[Action],
+        operation: Option<&DeltaOperation>,
+    ) -> Result<Vec<Action>, serde_json::Error> {
+        if actions.iter().any(|a| matches!(a, Action::commitInfo(_))) {
+            return Ok(actions.to_vec());
+        }
+
+        let mut info = serde_json::Map::new();
+        // the version the transaction was based on, unset when creating a new table
+        if self.delta_table.state.current_metadata.is_some() {
+            info.insert(
+                "readVersion".to_string(),
+                Value::from(self.delta_table.version),
+            );
+        }
+        let client_version = format!("delta-rs/{}", crate_version());
+        info.insert(
+            "clientVersion".to_string(),
+            Value::String(client_version.clone()),
+        );
+        let commit_info = action::CommitInfo {
+            timestamp: Some(self.delta_table.now_millis()),
+            operation: operation.map(|operation| operation.name().to_string()),
+            operationParameters: operation
+                .map(|operation| operation.operation_parameters())
+                .transpose()?,
+            operationMetrics: self.operation_metrics.clone(),
+            engineInfo: Some(client_version),
+            info,
+            ..Default::default()
+        };
+
+        let mut actions_with_commit_info = Vec::with_capacity(actions.len() + 1);
+        actions_with_commit_info.push(Action::commitInfo(commit_info));
+        actions_with_commit_info.extend_from_slice(actions);
+
+        Ok(actions_with_commit_info)
+    }
+
     async fn try_commit_loop(
         &mut self,
-        log_entry: &[u8],
+        source: LogEntrySource<'_>,
+        read_version: DeltaDataTypeVersion,
     ) -> Result<DeltaDataTypeVersion, TransactionCommitAttemptError> {
         let mut attempt_number: u32 = 0;
+        // the latest version known not to change the metadata
+        let mut checked_version = read_version;
 
-        let tmp_log_path = self.prepare_commit(log_entry).await?;
         loop {
             let version = self.next_attempt_version().await?;
 
-            let commit_result = self.try_commit(&tmp_log_path, version).await;
+            // transactions on tables that weren't loaded have no snapshot to conflict with
+            if read_version >= 0 {
+                for winning_version in checked_version + 1..version {
+                    self.check_winning_commit(winning_version).await?;
+                }
+            }
+            checked_version = version - 1;
+
+            self.metrics.num_attempts += 1;
+            let commit_result = self.try_commit(source, version).await;
 
             match commit_result {
                 Ok(v) => {
@@ -1132,15 +4338,20 @@ impl<'a> DeltaTransaction<'a> {
                 }
                 Err(e) => {
                     match e {
-                        TransactionCommitAttemptError::VersionExists { .. }
+                        TransactionCommitAttemptError::VersionExists { source, .. }
                             if attempt_number > self.options.max_retry_commit_attempts + 1 =>
                         {
-                            debug!("Transaction attempt failed. Attempts exhausted beyond max_retry_commit_attempts of {} so failing.", self.options.max_retry_commit_attempts);
-                            return Err(e);
+                            self.metrics.num_conflicts += 1;
+                            debug!("Transaction attempt on table {} at version {} failed. Attempts exhausted beyond max_retry_commit_attempts of {} so failing.", self.delta_table.table_path, version, self.options.max_retry_commit_attempts);
+                            return Err(TransactionCommitAttemptError::VersionExists {
+                                source,
+                                num_retries: attempt_number,
+                            });
                         }
                         TransactionCommitAttemptError::VersionExists { .. } => {
+                            self.metrics.num_conflicts += 1;
                             attempt_number += 1;
-                            debug!("Transaction attempt failed. Incrementing attempt number to {} and retrying.", attempt_number);
+                            debug!("Transaction attempt on table {} at version {} failed. Incrementing attempt number to {} and retrying.", self.delta_table.table_path, version, attempt_number);
                         }
                         // NOTE: Add other retryable errors as needed here
                         _ => {
@@ -1152,10 +4363,64 @@ impl<'a> DeltaTransaction<'a> {
         }
     }
 
+    fn record_commit_metrics(&mut self, started_at: Instant) {
+        self.metrics.commit_duration = started_at.elapsed();
+        debug!(
+            "Commit to table {} took {} attempts with {} conflicts in {:?}.",
+            self.delta_table.table_path,
+            self.metrics.num_attempts,
+            self.metrics.num_conflicts,
+            self.metrics.commit_duration
+        );
+        if let Some(recorder) = &self.options.metrics_recorder {
+            recorder.record_commit(&self.delta_table.table_path, &self.metrics);
+        }
+    }
+
+    fn run_post_commit_hooks(&self, version: DeltaDataTypeVersion, operation: Option<&str>) {
+        let commit = CommittedVersion {
+            version,
+            operation,
+            operation_metrics: self.operation_metrics.as_ref(),
+            metrics: &self.metrics,
+        };
+        for hook in &self.delta_table.post_commit_hooks {
+            hook.after_commit(&*self.delta_table, &commit);
+        }
+    }
+
+    fn emit_commit_event(
+        &self,
+        operation: Option<String>,
+        result: &Result<DeltaDataTypeVersion, TransactionCommitAttemptError>,
+    ) {
+        let num_attempts = self.metrics.num_attempts;
+        let num_conflicts = self.metrics.num_conflicts;
+        self.delta_table.emit_event(match result {
+            Ok(version) => TableEvent::CommitSucceeded {
+                version: *version,
+                operation,
+                num_attempts,
+                num_conflicts,
+                duration_ms: self.metrics.commit_duration.as_millis() as u64,
+            },
+            Err(e) => TableEvent::CommitFailed {
+                operation,
+                num_attempts,
+                num_conflicts,
+                error: e.to_string(),
+            },
+        });
+    }
+
     async fn prepare_commit(
         &mut self,
         log_entry: &[u8],
     ) -> Result<String, TransactionCommitAttemptError> {
+        // a rename replacing the log entry of a concurrent writer would silently drop its commit
+        if !self.delta_table.storage.capabilities().atomic_rename {
+            return Err(StorageError::AtomicCommitNotSupported.into());
+        }
         let token = Uuid::new_v4().to_string();
         let tmp_log_path = self.delta_table.tmp_commit_log_path(&token);
 
@@ -1169,21 +4434,65 @@ impl<'a> DeltaTransaction<'a> {
 
     async fn try_commit(
         &mut self,
-        tmp_log_path: &str,
+        source: LogEntrySource<'_>,
         version: DeltaDataTypeVersion,
     ) -> Result<DeltaDataTypeVersion, TransactionCommitAttemptError> {
         let log_path = self.delta_table.version_to_log_path(version);
 
-        // move temporary commit file to delta log directory
-        // rely on storage to fail if the file already exists -
-        self.delta_table
-            .storage
-            .rename_obj(tmp_log_path, &log_path)
-            .await?;
+        // rely on storage to fail if the file already exists
+        match source {
+            // move temporary commit file to delta log directory
+            LogEntrySource::TmpFile(tmp_log_path) => {
+                self.delta_table
+                    .storage
+                    .rename_obj(tmp_log_path, &log_path)
+                    .await?
+            }
+            LogEntrySource::Bytes(log_entry) => {
+                self.delta_table
+                    .storage
+                    .put_obj_atomic(&log_path, log_entry)
+                    .await?
+            }
+        }
 
         Ok(version)
     }
 
+    /// Fails when the commit at `version` contains a metaData or protocol action, or changes the
+    /// files read by the transaction.
+    async fn check_winning_commit(
+        &self,
+        version: DeltaDataTypeVersion,
+    ) -> Result<(), TransactionCommitAttemptError> {
+        let actions = self.delta_table.get_commit_actions(version).await?;
+        if actions
+            .iter()
+            .any(|a| matches!(a, Action::metaData(_) | Action::protocol(_)))
+        {
+            debug!(
+                "Concurrent commit to table {} at version {} changed the table metadata.",
+                self.delta_table.table_path, version
+            );
+            return Err(TransactionCommitAttemptError::MetadataChanged { version });
+        }
+        if let Some(path) = actions
+            .iter()
+            .find_map(|action| self.read_files.changed_path(action))
+        {
+            debug!(
+                "Concurrent commit to table {} at version {} changed the read file {}.",
+                self.delta_table.table_path, version, path
+            );
+            return Err(TransactionCommitAttemptError::ReadFilesChanged {
+                version,
+                path: path.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
     async fn next_attempt_version(
         &mut self,
     ) -> Result<DeltaDataTypeVersion, TransactionCommitAttemptError> {
@@ -1192,28 +4501,315 @@ impl<'a> DeltaTransaction<'a> {
     }
 }
 
+/// Runs the operation `future` bounded by `timeout` and `cancellation_token`, failing with
+/// `Timeout` or `Cancelled` errors naming `operation`.
+async fn run_operation<T, E: From<DeltaTableError>>(
+    operation: &str,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+    future: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    run_bounded(
+        future,
+        timeout,
+        cancellation_token.as_ref(),
+        || {
+            DeltaTableError::Timeout {
+                operation: operation.to_string(),
+                timeout: timeout.unwrap_or_default(),
+            }
+            .into()
+        },
+        || {
+            DeltaTableError::Cancelled {
+                operation: operation.to_string(),
+            }
+            .into()
+        },
+    )
+    .await
+}
+
+/// Fails unless the format is parquet without options, the only format this crate reads and
+/// writes.
+fn check_format(format: &action::Format) -> Result<(), DeltaTableError> {
+    let options = format.get_options().cloned().unwrap_or_default();
+    if !format.get_provider().eq_ignore_ascii_case("parquet") || !options.is_empty() {
+        return Err(DeltaTableError::UnsupportedFormat {
+            provider: format.get_provider().to_string(),
+            options,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the reader and writer protocol versions required by the features the metadata
+/// enables, following the Delta protocol.
+fn required_protocol(metadata: &action::MetaData) -> Result<(i32, i32), DeltaTableError> {
+    let enabled = |key: &str| {
+        metadata
+            .configuration
+            .get(key)
+            .map_or(false, |value| value.eq_ignore_ascii_case("true"))
+    };
+    let writer_version = if enabled("delta.enableChangeDataFeed") {
+        4
+    } else if metadata
+        .configuration
+        .keys()
+        .any(|key| key.starts_with("delta.constraints."))
+    {
+        3
+    } else if enabled("delta.appendOnly")
+        || !serde_json::from_str::<Schema>(&metadata.schemaString)?
+            .get_invariants()?
+            .is_empty()
+    {
+        2
+    } else {
+        1
+    };
+
+    Ok((1, writer_version))
+}
+
+/// Fails when the protocol of a table can't be changed from the current versions to the
+/// requested ones.
+fn check_protocol_upgrade(
+    (current_reader_version, current_writer_version): (i32, i32),
+    (min_reader_version, min_writer_version): (i32, i32),
+) -> Result<(), DeltaTableError> {
+    let reason = if min_reader_version > MAX_SUPPORTED_READER_VERSION
+        || min_writer_version > MAX_SUPPORTED_WRITER_VERSION
+    {
+        format!(
+            "the highest supported versions are reader version {} and writer version {}",
+            MAX_SUPPORTED_READER_VERSION, MAX_SUPPORTED_WRITER_VERSION
+        )
+    } else if min_reader_version < current_reader_version
+        || min_writer_version < current_writer_version
+    {
+        format!(
+            "the table already requires reader version {} and writer version {}",
+            current_reader_version, current_writer_version
+        )
+    } else {
+        return Ok(());
+    };
+
+    Err(DeltaTableError::InvalidProtocolUpgrade {
+        min_reader_version,
+        min_writer_version,
+        reason,
+    })
+}
+
+/// Fails when one of the actions sets table metadata with an unsupported format, or sets table
+/// properties that are invalid. Properties unchanged from the current metadata aren't checked
+/// again, so tables committed with invalid properties by other writers can still be updated.
+fn check_actions_metadata(
+    actions: &[Action],
+    current_metadata: Option<&DeltaTableMetaData>,
+) -> Result<(), DeltaTableError> {
+    for action in actions {
+        if let Action::metaData(metadata) = action {
+            check_format(&metadata.format)?;
+            for (key, value) in &metadata.configuration {
+                let is_unchanged = current_metadata
+                    .and_then(|current| current.configuration.get(key))
+                    .map_or(false, |current_value| current_value == value);
+                if !is_unchanged {
+                    table_properties::validate_table_property(key, value)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the range of a date or timestamp column in the statistics of a file, from its JSON
+/// stats or, for files read from checkpoints, its parsed stats.
+fn timestamp_range(
+    add: &action::Add,
+    column: &str,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>, DeltaTableError> {
+    if let Some(stats) = add.get_stats()? {
+        let value = |values: &HashMap<String, action::ColumnValueStat>| {
+            values
+                .get(column)
+                .and_then(|stat| stat.as_value())
+                .and_then(|value| value.as_str())
+                .and_then(parse_timestamp_stat)
+        };
+        return Ok(value(&stats.minValues).zip(value(&stats.maxValues)));
+    }
+    if let Some(stats) = add.get_stats_parsed()? {
+        let value = |values: &HashMap<String, parquet::record::Field>| {
+            values.get(column).and_then(timestamp_from_field)
+        };
+        return Ok(value(&stats.minValues).zip(value(&stats.maxValues)));
+    }
+
+    Ok(None)
+}
+
+/// Parses a date or timestamp stat, formatted as `2021-03-06` or `2021-03-06T15:16:07.000Z`.
+fn parse_timestamp_stat(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|date| DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+}
+
+fn timestamp_from_field(field: &parquet::record::Field) -> Option<DateTime<Utc>> {
+    let millis = match field {
+        parquet::record::Field::TimestampMillis(millis) => *millis as i64,
+        parquet::record::Field::TimestampMicros(micros) => (*micros / 1000) as i64,
+        parquet::record::Field::Date(days) => *days as i64 * 24 * 60 * 60 * 1000,
+        _ => return None,
+    };
+
+    Some(Utc.timestamp_millis(millis))
+}
+
+/// Magic bytes starting a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Wraps the reader of a log object with a gzip decoder when the object is gzip-compressed, as
+/// detected by its magic bytes.
+pub(crate) fn decompressed_log_reader<'a, R: BufRead + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn BufRead + 'a>, std::io::Error> {
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Parses the actions of a log entry, e.g. a commit file or an archived copy of one. Gzip
+/// compressed entries are decompressed transparently.
+/// Fails with `UnknownAction` on actions of unknown types.
+pub fn read_log_actions(log_bytes: &[u8]) -> Result<Vec<Action>, DeltaTableError> {
+    read_log_entry(log_bytes)?
+        .into_iter()
+        .map(|action| match action {
+            LogAction::Known(action) => Ok(action),
+            LogAction::Unknown(action) => {
+                Err(DeltaTableError::from(ApplyLogError::UnknownAction {
+                    action_type: action.action_type,
+                }))
+            }
+        })
+        .collect()
+}
+
+/// Parses the actions of a log entry like `read_log_actions`, keeping actions of unknown types.
+pub fn read_log_entry(log_bytes: &[u8]) -> Result<Vec<LogAction>, DeltaTableError> {
+    let reader = decompressed_log_reader(BufReader::new(log_bytes))
+        .map_err(|e| DeltaTableError::from(ApplyLogError::from(e)))?;
+
+    let mut actions = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| DeltaTableError::from(ApplyLogError::from(e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        actions.push(LogAction::from_json(line.as_str())?);
+    }
+
+    Ok(actions)
+}
+
+/// Splits actions into batches whose log entries are at most `max_batch_size` bytes, not counting
+/// the commitInfo added when committing them, while keeping the actions in order. An action
+/// larger than `max_batch_size` is put in a batch of its own.
+pub fn split_actions(
+    actions: &[Action],
+    max_batch_size: usize,
+) -> Result<Vec<Vec<Action>>, serde_json::Error> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_size = 0;
+    for action in actions {
+        // each action takes a line of the log entry
+        let size = serde_json::to_string(action)?.len() + 1;
+        if !batch.is_empty() && batch_size + size > max_batch_size {
+            batches.push(std::mem::take(&mut batch));
+            batch_size = 0;
+        }
+        batch.push(action.clone());
+        batch_size += size;
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    Ok(batches)
+}
+
+/// Serializes actions to a log entry of newline-delimited JSON, with one action per line and a
+/// trailing newline, like the log entries of other engines.
 fn log_entry_from_actions(actions: &[Action]) -> Result<String, serde_json::Error> {
-    let mut jsons = Vec::<String>::new();
+    let mut log_entry = String::new();
 
     for action in actions {
         let json = serde_json::to_string(action)?;
-        jsons.push(json);
+        // readers split log entries into actions by lines
+        if json.contains(|c| c == '\n' || c == '\r') {
+            return Err(<serde_json::Error as serde::ser::Error>::custom(format!(
+                "Action doesn't fit on a single line: {}",
+                json
+            )));
+        }
+        log_entry.push_str(&json);
+        log_entry.push('\n');
     }
 
-    Ok(jsons.join("\n"))
+    Ok(log_entry)
 }
 
 fn process_action(
     state: &mut DeltaTableState,
     action: &Action,
+    require_tombstones: bool,
 ) -> Result<(), serde_json::error::Error> {
     match action {
         Action::add(v) => {
-            state.files.push(v.clone());
+            // an add of an existing path replaces the file, e.g. when only its stats change
+            let files = &mut state.files;
+            let positions = state
+                .file_positions
+                .get_or_insert_with(|| file_positions(files));
+            match positions.get(&v.path) {
+                Some(&position) => files[position] = v.clone(),
+                None => {
+                    positions.insert(v.path.clone(), files.len());
+                    files.push(v.clone());
+                }
+            }
         }
         Action::remove(v) => {
-            state.files.retain(|a| *a.path != v.path);
-            state.tombstones.push(v.clone());
+            // removes of files that aren't active, e.g. the tombstones of checkpoints, leave the
+            // file list and its positions untouched
+            let is_active = state
+                .file_positions
+                .as_ref()
+                .map_or(true, |positions| positions.contains_key(&v.path));
+            if is_active {
+                state.files.retain(|a| *a.path != v.path);
+                state.file_positions = None;
+            }
+            if require_tombstones {
+                state.tombstones.push(Tombstone {
+                    path: v.path.clone(),
+                    deletion_timestamp: v.deletionTimestamp,
+                });
+            }
         }
         Action::protocol(v) => {
             state.min_reader_version = v.minReaderVersion;
@@ -1245,14 +4841,169 @@ fn process_action(
     Ok(())
 }
 
+/// Indexes the positions of the files by path.
+fn file_positions(files: &[action::Add]) -> HashMap<String, usize> {
+    files
+        .iter()
+        .enumerate()
+        .map(|(position, add)| (add.path.clone(), position))
+        .collect()
+}
+
+/// The version a [`DeltaTableBuilder`] loads the table at.
+#[derive(Debug, Clone, PartialEq)]
+enum DeltaVersion {
+    Newest,
+    Version(DeltaDataTypeVersion),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Builder for opening a DeltaTable, combining the options that control where the table is read
+/// from and how it's loaded.
+///
+/// ```rust
+/// async {
+///   let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+///       .with_version(2)
+///       .without_tombstones()
+///       .load()
+///       .await
+///       .unwrap();
+///   assert_eq!(table.version, 2);
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeltaTableBuilder {
+    table_uri: String,
+    storage_options: HashMap<String, String>,
+    version: DeltaVersion,
+    config: DeltaTableConfig,
+    clock: Option<Arc<dyn Clock>>,
+    event_listener: Option<Arc<dyn TableEventListener>>,
+    post_commit_hooks: Vec<Arc<dyn PostCommitHook>>,
+}
+
+impl DeltaTableBuilder {
+    /// Creates a builder for the table at the given URI. Storage options can be given in the
+    /// query string of the URI, e.g. `s3://bucket/table?region=us-west-2`.
+    pub fn from_uri(table_uri: &str) -> Self {
+        Self {
+            table_uri: table_uri.to_string(),
+            storage_options: HashMap::new(),
+            version: DeltaVersion::Newest,
+            config: DeltaTableConfig::default(),
+            clock: None,
+            event_listener: None,
+            post_commit_hooks: Vec::new(),
+        }
+    }
+
+    /// Loads the table at the given version instead of the latest one.
+    pub fn with_version(mut self, version: DeltaDataTypeVersion) -> Self {
+        self.version = DeltaVersion::Version(version);
+        self
+    }
+
+    /// Loads the latest version of the table committed at or before `datetime`.
+    pub fn with_datetime(mut self, datetime: DateTime<Utc>) -> Self {
+        self.version = DeltaVersion::Timestamp(datetime);
+        self
+    }
+
+    /// Adds storage options used to configure the storage backend. These take precedence over
+    /// the options in the query string of the table URI.
+    pub fn with_storage_options(mut self, storage_options: HashMap<String, String>) -> Self {
+        self.storage_options.extend(storage_options);
+        self
+    }
+
+    /// Sets the config controlling how the table is loaded.
+    pub fn with_config(mut self, config: DeltaTableConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Discards `remove` actions while loading, see `DeltaTableConfig::skip_tombstones`.
+    pub fn without_tombstones(mut self) -> Self {
+        self.config.skip_tombstones = true;
+        self
+    }
+
+    /// Bounds the durations of the operations of the table, see `OperationTimeouts`.
+    pub fn with_timeouts(mut self, timeouts: OperationTimeouts) -> Self {
+        self.config.timeouts = timeouts;
+        self
+    }
+
+    /// Aborts the operations of the table once `token` is cancelled, see
+    /// `DeltaTableConfig::cancellation_token`.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.config.cancellation_token = Some(token);
+        self
+    }
+
+    /// Uses the given clock for retention logic and commit timestamps instead of the system
+    /// clock, see `DeltaTable::set_clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Reports the events of the table to the given listener, including the protocol errors of
+    /// the initial load, see `DeltaTable::set_event_listener`.
+    pub fn with_event_listener(mut self, listener: Arc<dyn TableEventListener>) -> Self {
+        self.event_listener = Some(listener);
+        self
+    }
+
+    /// Runs the given hook after each successful commit to the table, see
+    /// `DeltaTable::add_post_commit_hook`.
+    pub fn with_post_commit_hook(mut self, hook: Arc<dyn PostCommitHook>) -> Self {
+        self.post_commit_hooks.push(hook);
+        self
+    }
+
+    /// Creates the DeltaTable without loading any data from the backing storage.
+    pub fn build(self) -> Result<DeltaTable, DeltaTableError> {
+        let mut table_uri = storage::TableUri::parse(&self.table_uri)?;
+        table_uri.options.extend(self.storage_options);
+        let storage_backend = storage::get_backend_for_table_uri(&table_uri)?;
+
+        let mut table =
+            DeltaTable::new_with_config(&table_uri.location, storage_backend, self.config)?;
+        if let Some(clock) = self.clock {
+            table.set_clock(clock);
+        }
+        if let Some(listener) = self.event_listener {
+            table.set_event_listener(listener);
+        }
+        for hook in self.post_commit_hooks {
+            table.add_post_commit_hook(hook);
+        }
+
+        Ok(table)
+    }
+
+    /// Creates the DeltaTable and loads it at the requested version.
+    pub async fn load(self) -> Result<DeltaTable, DeltaTableError> {
+        let version = self.version.clone();
+        let mut table = self.build()?;
+        match version {
+            DeltaVersion::Newest => table.load().await?,
+            DeltaVersion::Version(version) => table.load_version(version).await?,
+            DeltaVersion::Timestamp(datetime) => table.load_with_datetime(datetime).await?,
+        }
+
+        Ok(table)
+    }
+}
+
 /// Creates and loads a DeltaTable from the given path with current metadata.
-/// Infers the storage backend to use from the scheme in the given table path.
+/// Infers the storage backend to use from the scheme in the given table path, and configures it
+/// with the storage options in the query string of the path, e.g.
+/// `s3://bucket/table?region=us-west-2`.
 pub async fn open_table(table_path: &str) -> Result<DeltaTable, DeltaTableError> {
-    let storage_backend = storage::get_backend_for_uri(table_path)?;
-    let mut table = DeltaTable::new(table_path, storage_backend)?;
-    table.load().await?;
-
-    Ok(table)
+    DeltaTableBuilder::from_uri(table_path).load().await
 }
 
 /// Creates a DeltaTable from the given path and loads it with the metadata from the given version.
@@ -1261,11 +5012,10 @@ pub async fn open_table_with_version(
     table_path: &str,
     version: DeltaDataTypeVersion,
 ) -> Result<DeltaTable, DeltaTableError> {
-    let storage_backend = storage::get_backend_for_uri(table_path)?;
-    let mut table = DeltaTable::new(table_path, storage_backend)?;
-    table.load_version(version).await?;
-
-    Ok(table)
+    DeltaTableBuilder::from_uri(table_path)
+        .with_version(version)
+        .load()
+        .await
 }
 
 /// Creates a DeltaTable from the given path.
@@ -1273,11 +5023,10 @@ pub async fn open_table_with_version(
 /// Infers the storage backend to use from the scheme in the given table path.
 pub async fn open_table_with_ds(table_path: &str, ds: &str) -> Result<DeltaTable, DeltaTableError> {
     let datetime = DateTime::<Utc>::from(DateTime::<FixedOffset>::parse_from_rfc3339(ds)?);
-    let storage_backend = storage::get_backend_for_uri(table_path)?;
-    let mut table = DeltaTable::new(table_path, storage_backend)?;
-    table.load_with_datetime(datetime).await?;
-
-    Ok(table)
+    DeltaTableBuilder::from_uri(table_path)
+        .with_datetime(datetime)
+        .load()
+        .await
 }
 
 /// Returns rust create version, can be use used in language bindings to expose Rust core version
@@ -1300,6 +5049,7 @@ mod tests {
 
         let mut state = DeltaTableState {
             files: vec![],
+            file_positions: None,
             commit_infos: vec![],
             tombstones: vec![],
             current_metadata: None,
@@ -1314,7 +5064,7 @@ mod tests {
             lastUpdated: 0,
         });
 
-        let _ = process_action(&mut state, &txn_action).unwrap();
+        let _ = process_action(&mut state, &txn_action, true).unwrap();
 
         assert_eq!(2, *state.app_transaction_version.get("abc").unwrap());
         assert_eq!(1, *state.app_transaction_version.get("xyz").unwrap());
@@ -19,23 +19,64 @@
 //!       .await.unwrap();
 //! };
 //! ```
+//!
+//! Filters on partition columns and on columns with statistics in the add actions are pushed
+//! down into the scan, which skips the files that can't contain matching rows. Each scanned file
+//! is read in its own partition, unless `DeltaTableConfig::scan_partitions` bounds their number.
+//!
+//! A whole directory of tables can be exposed to a context with a [`DeltaSchemaProvider`].
+//!
+//! Rows matching a predicate are deleted with `DeltaTable::delete`, or updated with
+//! `DeltaTable::update`, which only rewrite the files containing matching rows.
+//!
+//! Query results are appended to a table with [`insert_into`]:
+//!
+//! ```rust,no_run
+//! use datafusion::execution::context::ExecutionContext;
+//!
+//! async {
+//!   let mut ctx = ExecutionContext::new();
+//!   let mut table = deltalake::open_table("./tests/data/simple_table")
+//!       .await
+//!       .unwrap();
+//!   let df = ctx.sql("SELECT * FROM source").unwrap();
+//!   let plan = ctx.create_physical_plan(&df.to_logical_plan()).unwrap();
+//!   deltalake::delta_datafusion::insert_into(&mut table, plan).await.unwrap();
+//! };
+//! ```
 
 use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use arrow::datatypes::Schema as ArrowSchema;
-use datafusion::datasource::datasource::Statistics;
-use datafusion::datasource::TableProvider;
-use datafusion::logical_plan::{combine_filters, Expr};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, NaiveDate};
+use datafusion::catalog::schema::SchemaProvider;
+use datafusion::dataframe::DataFrame;
+use datafusion::datasource::datasource::{Statistics, TableProviderFilterPushDown};
+use datafusion::datasource::{MemTable, TableProvider};
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::ExecutionContext;
+use datafusion::logical_plan::{combine_filters, Expr, Operator};
 use datafusion::physical_plan::parquet::{ParquetExec, ParquetPartition, RowGroupPredicateBuilder};
-use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_plan::{collect, ExecutionPlan};
+use datafusion::scalar::ScalarValue;
 use parquet::arrow::ParquetFileArrowReader;
 use parquet::file::reader::SerializedFileReader;
+use serde_json::Value;
 
+use crate::action::{self, Action, ColumnValueStat, DeltaOperation, SaveMode, Stats};
 use crate::delta;
+use crate::partitions;
+use crate::scan::{self, DeltaScanTask};
 use crate::schema;
+use crate::storage::StorageError;
+use crate::writer;
 
 impl TableProvider for delta::DeltaTable {
     fn schema(&self) -> Arc<ArrowSchema> {
@@ -57,37 +98,99 @@ impl TableProvider for delta::DeltaTable {
         let schema = <ArrowSchema as TryFrom<&schema::Schema>>::try_from(
             delta::DeltaTable::schema(&self).unwrap(),
         )?;
-        let filenames = self.get_file_paths();
-
-        let partitions = filenames
-            .into_iter()
-            .map(|fname| {
-                let mut num_rows = 0;
-                let mut total_byte_size = 0;
-
-                let file = File::open(&fname)?;
-                let file_reader = Arc::new(SerializedFileReader::new(file)?);
-                let mut arrow_reader = ParquetFileArrowReader::new(file_reader);
-                let meta_data = arrow_reader.get_metadata();
-                // collect all the unique schemas in this data set
-                for i in 0..meta_data.num_row_groups() {
-                    let row_group_meta = meta_data.row_group(i);
-                    num_rows += row_group_meta.num_rows();
-                    total_byte_size += row_group_meta.total_byte_size();
-                }
-                let statistics = Statistics {
-                    num_rows: Some(num_rows as usize),
-                    total_byte_size: Some(total_byte_size as usize),
-                    column_statistics: None,
-                };
-
-                Ok(ParquetPartition::new(vec![fname], statistics))
-            })
-            .collect::<datafusion::error::Result<_>>()?;
-
+        let partition_columns = self
+            .get_metadata()
+            .map(|metadata| metadata.partition_columns.clone())
+            .unwrap_or_default();
         let predicate_builder = combine_filters(filters).and_then(|predicate_expr| {
             RowGroupPredicateBuilder::try_new(&predicate_expr, schema.clone()).ok()
         });
+        let footer_pruning = self.config().footer_pruning;
+        let column_resolution = self.config().column_resolution;
+        let files = self
+            .get_actions()
+            .iter()
+            .zip(self.get_file_paths())
+            .filter(|(add, _)| {
+                let file = FilePruningContext::new(add, &partition_columns, column_resolution);
+                filters.iter().all(|filter| file.may_match(filter))
+            });
+
+        let mut scanned_files = Vec::new();
+        for (add, fname) in files {
+            let mut num_rows = 0;
+            let mut total_byte_size = 0;
+
+            let file = File::open(&fname)?;
+            let file_reader = Arc::new(SerializedFileReader::new(file)?);
+            let mut arrow_reader = ParquetFileArrowReader::new(file_reader);
+            let meta_data = arrow_reader.get_metadata();
+            // the footer is read anyway, so files without stats in their add action can still be
+            // skipped when none of their row groups match
+            if let (true, None, Some(predicate_builder)) =
+                (footer_pruning, &add.stats, &predicate_builder)
+            {
+                let row_groups = meta_data.row_groups();
+                let row_group_predicate = predicate_builder.build_row_group_predicate(row_groups);
+                if !row_groups
+                    .iter()
+                    .enumerate()
+                    .any(|(i, row_group)| row_group_predicate(row_group, i))
+                {
+                    continue;
+                }
+            }
+            // collect all the unique schemas in this data set
+            for i in 0..meta_data.num_row_groups() {
+                let row_group_meta = meta_data.row_group(i);
+                num_rows += row_group_meta.num_rows();
+                total_byte_size += row_group_meta.total_byte_size();
+            }
+            let statistics = Statistics {
+                num_rows: Some(num_rows as usize),
+                total_byte_size: Some(total_byte_size as usize),
+                column_statistics: None,
+            };
+
+            scanned_files.push((fname, statistics, add.size));
+        }
+
+        let partitions = match self.config().scan_partitions {
+            Some(target_partitions) => {
+                let sizes = scanned_files
+                    .iter()
+                    .map(|(_, _, size)| *size)
+                    .collect::<Vec<_>>();
+                group_files_by_size(&sizes, target_partitions)
+                    .into_iter()
+                    .map(|group| {
+                        let mut fnames = Vec::with_capacity(group.len());
+                        let mut statistics = Statistics {
+                            num_rows: Some(0),
+                            total_byte_size: Some(0),
+                            column_statistics: None,
+                        };
+                        for i in group {
+                            let (fname, file_statistics, _) = &scanned_files[i];
+                            fnames.push(fname.clone());
+                            statistics.num_rows = statistics
+                                .num_rows
+                                .zip(file_statistics.num_rows)
+                                .map(|(a, b)| a + b);
+                            statistics.total_byte_size = statistics
+                                .total_byte_size
+                                .zip(file_statistics.total_byte_size)
+                                .map(|(a, b)| a + b);
+                        }
+                        ParquetPartition::new(fnames, statistics)
+                    })
+                    .collect()
+            }
+            None => scanned_files
+                .into_iter()
+                .map(|(fname, statistics, _)| ParquetPartition::new(vec![fname], statistics))
+                .collect(),
+        };
 
         Ok(Arc::new(ParquetExec::new(
             partitions,
@@ -103,9 +206,773 @@ impl TableProvider for delta::DeltaTable {
         self
     }
 
+    fn supports_filter_pushdown(
+        &self,
+        _filter: &Expr,
+    ) -> datafusion::error::Result<TableProviderFilterPushDown> {
+        // files are pruned with partition values and stats, and row groups with parquet stats,
+        // so the filters must still be applied to the scanned rows
+        Ok(TableProviderFilterPushDown::Inexact)
+    }
+
     fn statistics(&self) -> Statistics {
         // TODO: proxy delta table stats after https://github.com/delta-io/delta.rs/issues/45 has
         // been completed
         Statistics::default()
     }
 }
+
+/// Groups files with the given sizes into at most `target_partitions` partitions of similar total
+/// size, for scanning them with as many DataFusion partitions. Returns the indices of the files of
+/// each non-empty partition, in their original order. The largest files are assigned first, each to
+/// the partition with the smallest total size so far.
+///
+/// Files are the unit of parallelism: DataFusion scans whole parquet files, so a file larger than
+/// the others still ends up in a partition of its own.
+pub fn group_files_by_size(sizes: &[i64], target_partitions: usize) -> Vec<Vec<usize>> {
+    let mut groups: Vec<(i64, Vec<usize>)> = vec![(0, Vec::new()); target_partitions.max(1)];
+    let mut by_size = (0..sizes.len()).collect::<Vec<_>>();
+    by_size.sort_by_key(|i| std::cmp::Reverse(sizes[*i]));
+    for i in by_size {
+        // the first of the smallest groups, so that ties fill the groups in order
+        let group = groups
+            .iter_mut()
+            .min_by_key(|(total_size, _)| *total_size)
+            .unwrap();
+        group.0 += sizes[i];
+        group.1.push(i);
+    }
+    groups
+        .into_iter()
+        .filter(|(_, files)| !files.is_empty())
+        .map(|(_, mut files)| {
+            files.sort_unstable();
+            files
+        })
+        .collect()
+}
+
+/// Executes the plan and appends its output to the table in new data files, committed as a
+/// `WRITE` operation. Returns the committed version.
+///
+/// The output schema of the plan must match the table schema, except for the column types that
+/// `writer::can_normalize` converts to the table types, like dictionary arrays or large strings,
+/// and for the columns with a default value, see `SchemaField::get_default_value`, which can be
+/// omitted. The omitted columns are filled with their default value and listed in the
+/// `defaultedColumns` operation metric of the commit. The rows of partitioned tables are written
+/// to a file per partition, see [`DeltaWriter`](writer::DeltaWriter).
+pub async fn insert_into(
+    table: &mut delta::DeltaTable,
+    plan: Arc<dyn ExecutionPlan>,
+) -> datafusion::error::Result<schema::DeltaDataTypeVersion> {
+    let table_schema = TableProvider::schema(table);
+    let default_values = table
+        .get_schema()
+        .map_err(|e| DataFusionError::Execution(e.to_string()))?
+        .get_fields()
+        .iter()
+        .map(|field| field.get_default_value().map(str::to_string))
+        .collect::<Vec<_>>();
+    let column_resolution = table.config().column_resolution;
+    let plan_schema = plan.schema();
+    // the plan column written to each table column, `None` for omitted columns with a default
+    let mut plan_columns = Vec::with_capacity(table_schema.fields().len());
+    let mut plan_fields = plan_schema.fields().iter().enumerate().peekable();
+    for (field, default_value) in table_schema.fields().iter().zip(&default_values) {
+        match plan_fields.peek() {
+            Some((i, plan_field))
+                if column_resolution.matches(plan_field.name(), field.name())
+                    && writer::can_normalize(plan_field.data_type(), field.data_type()) =>
+            {
+                plan_columns.push(Some(*i));
+                plan_fields.next();
+            }
+            _ if default_value.is_some() => plan_columns.push(None),
+            _ => break,
+        }
+    }
+    if plan_columns.len() != table_schema.fields().len() || plan_fields.peek().is_some() {
+        return Err(DataFusionError::Plan(format!(
+            "Output schema {:?} doesn't match the table schema {:?}",
+            plan_schema, table_schema
+        )));
+    }
+    let defaulted_columns = plan_columns
+        .iter()
+        .zip(table_schema.fields())
+        .filter(|(column, _)| column.is_none())
+        .map(|(_, field)| field.name().as_str())
+        .collect::<Vec<_>>();
+
+    let batches = collect(plan)
+        .await?
+        .iter()
+        .map(|batch| {
+            let columns = plan_columns
+                .iter()
+                .zip(table_schema.fields())
+                .zip(&default_values)
+                .map(|((column, field), default_value)| match column {
+                    Some(i) => writer::normalize_array(batch.column(*i), field.data_type()),
+                    None => writer::default_value_array(
+                        default_value.as_deref().unwrap_or_default(),
+                        field.data_type(),
+                        batch.num_rows(),
+                    ),
+                })
+                .collect::<arrow::error::Result<Vec<_>>>()?;
+            RecordBatch::try_new(table_schema.clone(), columns)
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+    let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    let adds = write_batches(table, &batches)
+        .await
+        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+    let num_output_bytes: i64 = adds.iter().map(|add| add.size).sum();
+    let actions = adds.into_iter().map(Action::add).collect::<Vec<_>>();
+
+    let mut operation_metrics = serde_json::Map::new();
+    operation_metrics.insert("numFiles".to_string(), actions.len().to_string().into());
+    operation_metrics.insert("numOutputRows".to_string(), num_rows.to_string().into());
+    operation_metrics.insert(
+        "numOutputBytes".to_string(),
+        num_output_bytes.to_string().into(),
+    );
+    if !defaulted_columns.is_empty() {
+        operation_metrics.insert(
+            "defaultedColumns".to_string(),
+            serde_json::to_string(&defaulted_columns)
+                .map_err(|e| DataFusionError::Execution(e.to_string()))?
+                .into(),
+        );
+    }
+
+    let operation = DeltaOperation::Write {
+        mode: SaveMode::Append,
+        partitionBy: None,
+        predicate: None,
+    };
+    table
+        .create_transaction(None)
+        .with_operation_metrics(operation_metrics)
+        .commit_with(&actions, Some(operation))
+        .await
+        .map_err(|e| DataFusionError::Execution(e.to_string()))
+}
+
+/// Number of rows per batch read from the files rewritten by `DeltaTable::delete` and
+/// `DeltaTable::update`.
+const REWRITE_BATCH_SIZE: usize = 8192;
+
+impl delta::DeltaTable {
+    /// Deletes the rows matching the predicate and returns the committed version, or `None` when
+    /// no row matches. The files that may contain matching rows according to their partition
+    /// values and stats are read, and those that do are removed, along with the add action of a
+    /// file of their other rows when there are some, in a single `DELETE` commit. Like in SQL,
+    /// rows for which the predicate is null are kept.
+    ///
+    /// The `operationMetrics` of the commit record the number of removed and added files, and of
+    /// deleted and copied rows.
+    ///
+    /// Fails instead of retrying when a concurrent commit removed one of the read files, since
+    /// its rows may have been deleted or changed already.
+    pub async fn delete(
+        &mut self,
+        predicate: Expr,
+    ) -> datafusion::error::Result<Option<schema::DeltaDataTypeVersion>> {
+        // rows are kept unless the predicate is true for them
+        let keep = Expr::BinaryExpr {
+            left: Box::new(Expr::Not(Box::new(predicate.clone()))),
+            op: Operator::Or,
+            right: Box::new(Expr::IsNull(Box::new(predicate.clone()))),
+        };
+        let operation = DeltaOperation::Delete {
+            predicate: Some(format!("{:?}", predicate)),
+        };
+
+        self.rewrite_matching_files(
+            &predicate,
+            |frame| frame.filter(keep.clone()),
+            operation,
+            "numDeletedRows",
+        )
+        .await
+    }
+
+    /// Sets the columns of the rows matching the predicate to the values of the assignments, by
+    /// column name, and returns the committed version, or `None` when no row matches. The
+    /// assigned expressions are evaluated against the rows before the update and cast to the
+    /// types of their columns. Like `delete`, only the files containing matching rows are
+    /// rewritten, and their remove actions are committed along with the add actions of the
+    /// rewritten files in a single `UPDATE` commit. Rows assigned other partition values are
+    /// written to the files of their new partitions.
+    ///
+    /// The `operationMetrics` of the commit record the number of removed and added files, and of
+    /// updated and copied rows. Like `delete`, fails instead of retrying when a concurrent commit
+    /// removed one of the read files.
+    pub async fn update(
+        &mut self,
+        predicate: Expr,
+        assignments: HashMap<String, Expr>,
+    ) -> datafusion::error::Result<Option<schema::DeltaDataTypeVersion>> {
+        let table_schema = <ArrowSchema as TryFrom<&schema::Schema>>::try_from(
+            self.get_schema()
+                .map_err(|e| DataFusionError::Execution(e.to_string()))?,
+        )?;
+        if let Some(column) = assignments
+            .keys()
+            .find(|column| table_schema.field_with_name(column).is_err())
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Column {} of the assignments isn't in the table schema {:?}",
+                column, table_schema
+            )));
+        }
+        let projection = table_schema
+            .fields()
+            .iter()
+            .map(|field| match assignments.get(field.name()) {
+                Some(value) => Expr::Case {
+                    expr: None,
+                    when_then_expr: vec![(
+                        Box::new(predicate.clone()),
+                        Box::new(Expr::Cast {
+                            expr: Box::new(value.clone()),
+                            data_type: field.data_type().clone(),
+                        }),
+                    )],
+                    else_expr: Some(Box::new(Expr::Column(field.name().clone()))),
+                }
+                .alias(field.name()),
+                None => Expr::Column(field.name().clone()),
+            })
+            .collect::<Vec<_>>();
+        let operation = DeltaOperation::Update {
+            predicate: Some(format!("{:?}", predicate)),
+        };
+
+        self.rewrite_matching_files(
+            &predicate,
+            |frame| frame.select(projection.clone()),
+            operation,
+            "numUpdatedRows",
+        )
+        .await
+    }
+
+    /// Rewrites the files containing rows matching the predicate, among the files that may
+    /// contain some according to their partition values and stats, with the rows `rewrite`
+    /// returns for the rows of each file. Commits the remove actions of the files along with the
+    /// add actions of the rewritten ones as `operation`, and returns the committed version, or
+    /// `None` when no row matches. The number of matching rows is recorded in the operation
+    /// metrics under `matched_rows_metric`.
+    async fn rewrite_matching_files<F>(
+        &mut self,
+        predicate: &Expr,
+        rewrite: F,
+        operation: DeltaOperation,
+        matched_rows_metric: &str,
+    ) -> datafusion::error::Result<Option<schema::DeltaDataTypeVersion>>
+    where
+        F: Fn(Arc<dyn DataFrame>) -> datafusion::error::Result<Arc<dyn DataFrame>>,
+    {
+        let table_schema = self
+            .get_schema()
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?
+            .clone();
+        let partition_columns = self
+            .get_metadata()
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?
+            .partition_columns
+            .clone();
+        let column_resolution = self.config().column_resolution;
+        let candidates = self
+            .get_actions()
+            .iter()
+            .filter(|add| {
+                FilePruningContext::new(add, &partition_columns, column_resolution)
+                    .may_match(predicate)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let read_paths = candidates
+            .iter()
+            .map(|add| add.path.clone())
+            .collect::<Vec<_>>();
+
+        let storage = self.storage_backend();
+        let mut delta_writer = writer::DeltaWriter::for_table(self)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+        let deletion_timestamp = self.now_millis();
+        let mut actions = Vec::new();
+        let mut num_matched_rows = 0;
+        let mut num_copied_rows = 0;
+        for add in candidates {
+            let task = DeltaScanTask {
+                path: storage.join_path(&self.table_path, &add.path),
+                ranges: vec![],
+                partition_values: add.partitionValues.clone(),
+                schema: table_schema.clone(),
+            };
+            let batches = scan::read_scan_task(&task, storage.as_ref(), REWRITE_BATCH_SIZE)
+                .await
+                .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+            let num_rows = count_rows(&batches);
+            if num_rows == 0 {
+                continue;
+            }
+
+            let provider = MemTable::try_new(batches[0].schema(), vec![batches])?;
+            let frame = ExecutionContext::new().read_table(Arc::new(provider))?;
+            let num_file_matched_rows =
+                count_rows(&frame.filter(predicate.clone())?.collect().await?);
+            if num_file_matched_rows == 0 {
+                continue;
+            }
+            for batch in rewrite(frame)?.collect().await? {
+                delta_writer
+                    .write(&batch)
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+            }
+            num_matched_rows += num_file_matched_rows;
+            num_copied_rows += num_rows - num_file_matched_rows;
+            actions.push(Action::remove(add.to_remove(deletion_timestamp, true)));
+        }
+        if actions.is_empty() {
+            return Ok(None);
+        }
+
+        let num_removed_files = actions.len();
+        let adds = delta_writer
+            .flush()
+            .await
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+        let mut operation_metrics = serde_json::Map::new();
+        operation_metrics.insert(
+            "numRemovedFiles".to_string(),
+            num_removed_files.to_string().into(),
+        );
+        operation_metrics.insert("numAddedFiles".to_string(), adds.len().to_string().into());
+        operation_metrics.insert(
+            matched_rows_metric.to_string(),
+            num_matched_rows.to_string().into(),
+        );
+        operation_metrics.insert(
+            "numCopiedRows".to_string(),
+            num_copied_rows.to_string().into(),
+        );
+        actions.extend(adds.into_iter().map(Action::add));
+
+        // the candidates without matching rows were read too, a concurrent commit removing them
+        // could have moved their rows to files this operation doesn't rewrite
+        let version = self
+            .create_transaction(None)
+            .with_read_paths(read_paths)
+            .with_operation_metrics(operation_metrics)
+            .commit_with(&actions, Some(operation))
+            .await
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+        Ok(Some(version))
+    }
+}
+
+fn count_rows(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(|batch| batch.num_rows()).sum()
+}
+
+/// Writes the batches, with the table schema, to new data files and returns their add actions.
+async fn write_batches(
+    table: &delta::DeltaTable,
+    batches: &[RecordBatch],
+) -> Result<Vec<action::Add>, delta::DeltaTableError> {
+    let mut delta_writer = writer::DeltaWriter::for_table(table)?;
+    for batch in batches {
+        delta_writer.write(batch)?;
+    }
+    delta_writer.flush().await
+}
+
+/// Resolves the table names of a [`DeltaSchemaProvider`] to table uris, e.g. by listing a
+/// directory or by looking them up in an external catalog.
+pub trait DeltaTableResolver: Send + Sync {
+    /// Returns the names of all the tables that can be resolved.
+    fn table_names(&self) -> Vec<String>;
+
+    /// Returns the uri of the table with the given name, if any.
+    fn table_uri(&self, name: &str) -> Option<String>;
+}
+
+impl DeltaTableResolver for HashMap<String, String> {
+    fn table_names(&self) -> Vec<String> {
+        self.keys().cloned().collect()
+    }
+
+    fn table_uri(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// Resolves the subdirectories of a local directory that contain a `_delta_log` directory as
+/// tables named after the subdirectory.
+pub struct DirectoryTableResolver {
+    root: PathBuf,
+}
+
+impl DirectoryTableResolver {
+    /// Creates a resolver for the tables in the `root` directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl DeltaTableResolver for DirectoryTableResolver {
+    fn table_names(&self) -> Vec<String> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("_delta_log").is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect()
+    }
+
+    fn table_uri(&self, name: &str) -> Option<String> {
+        let path = self.root.join(name);
+        if path.join("_delta_log").is_dir() {
+            path.to_str().map(|p| p.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// DataFusion schema that exposes the tables resolved by a [`DeltaTableResolver`] as relations.
+///
+/// Tables are opened on first access and cached in the provider, so later queries read the
+/// version loaded first.
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use datafusion::catalog::catalog::MemoryCatalogProvider;
+/// use datafusion::execution::context::ExecutionContext;
+/// use deltalake::delta_datafusion::{DeltaSchemaProvider, DirectoryTableResolver};
+///
+/// let mut ctx = ExecutionContext::new();
+/// let catalog = MemoryCatalogProvider::new();
+/// let schema = DeltaSchemaProvider::new(Arc::new(DirectoryTableResolver::new("./tests/data")));
+/// catalog.register_schema("tables", Arc::new(schema));
+/// ctx.register_catalog("delta", Arc::new(catalog));
+/// let df = ctx.sql("SELECT id FROM delta.tables.simple_table").unwrap();
+/// ```
+pub struct DeltaSchemaProvider {
+    resolver: Arc<dyn DeltaTableResolver>,
+    tables: Mutex<HashMap<String, Arc<delta::DeltaTable>>>,
+}
+
+impl DeltaSchemaProvider {
+    /// Creates a schema provider resolving table names with `resolver`.
+    pub fn new(resolver: Arc<dyn DeltaTableResolver>) -> Self {
+        Self {
+            resolver,
+            tables: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drops the cached table with the given name, so that it's opened again on next access.
+    pub fn invalidate(&self, name: &str) {
+        self.tables.lock().unwrap().remove(name);
+    }
+}
+
+impl SchemaProvider for DeltaSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.resolver.table_names()
+    }
+
+    fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
+        if let Some(table) = self.tables.lock().unwrap().get(name) {
+            return Some(table.clone());
+        }
+
+        let table_uri = self.resolver.table_uri(name)?;
+        let table = match open_table_blocking(table_uri.clone()) {
+            Ok(table) => Arc::new(table),
+            Err(e) => {
+                log::error!(
+                    "Failed to open Delta table {} at {}: {}",
+                    name,
+                    table_uri,
+                    e
+                );
+                return None;
+            }
+        };
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), table.clone());
+
+        Some(table)
+    }
+}
+
+fn open_table_blocking(table_uri: String) -> Result<delta::DeltaTable, delta::DeltaTableError> {
+    // `SchemaProvider::table` isn't async and is usually called from within a runtime, so the
+    // table is opened on a separate thread with a runtime of its own
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|source| StorageError::Io { source })?;
+        runtime.block_on(delta::open_table(&table_uri))
+    })
+    .join()
+    .unwrap_or_else(|_| {
+        Err(StorageError::Generic("Thread opening the table panicked".to_string()).into())
+    })
+}
+
+/// Value of a literal in a pushed down filter, or of a partition value or stat it is compared
+/// with.
+#[derive(Debug, Clone, PartialEq)]
+enum PruningValue {
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    // days since the epoch
+    Date(i64),
+    // microseconds since the epoch
+    Timestamp(i64),
+}
+
+impl PruningValue {
+    fn from_literal(literal: &ScalarValue) -> Option<Self> {
+        match literal {
+            ScalarValue::Int8(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::Int16(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::Int32(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::Int64(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::UInt8(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::UInt16(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::UInt32(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::UInt64(v) => v.map(|v| PruningValue::Int(v.into())),
+            ScalarValue::Float32(v) => v.map(|v| PruningValue::Float(v.into())),
+            ScalarValue::Float64(v) => v.map(PruningValue::Float),
+            ScalarValue::Utf8(v) | ScalarValue::LargeUtf8(v) => v.clone().map(PruningValue::Str),
+            ScalarValue::Boolean(v) => v.map(PruningValue::Bool),
+            ScalarValue::Date32(v) => v.map(|v| PruningValue::Date(v.into())),
+            ScalarValue::TimestampMicrosecond(v) => v.map(PruningValue::Timestamp),
+            // only whole microseconds compare exactly with partition values and stats
+            ScalarValue::TimestampNanosecond(v) => v
+                .filter(|v| v % 1000 == 0)
+                .map(|v| PruningValue::Timestamp(v / 1000)),
+            _ => None,
+        }
+    }
+
+    /// Parses a partition value, or a string stat, as a value of the same kind as `self`.
+    fn parse_like(&self, value: &str) -> Option<Self> {
+        match self {
+            PruningValue::Int(_) => value.parse().ok().map(PruningValue::Int),
+            PruningValue::Float(_) => value.parse().ok().map(PruningValue::Float),
+            PruningValue::Str(_) => Some(PruningValue::Str(value.to_string())),
+            PruningValue::Bool(_) => value.parse().ok().map(PruningValue::Bool),
+            PruningValue::Date(_) => partitions::parse_date_partition_value(value).map(|date| {
+                PruningValue::Date(
+                    date.signed_duration_since(NaiveDate::from_ymd(1970, 1, 1))
+                        .num_days(),
+                )
+            }),
+            // partition values are formatted like Spark, and stats in RFC 3339
+            PruningValue::Timestamp(_) => partitions::parse_timestamp_partition_value(value)
+                .or_else(|| {
+                    DateTime::parse_from_rfc3339(value)
+                        .ok()
+                        .map(|timestamp| timestamp.naive_utc())
+                })
+                .map(|timestamp| {
+                    PruningValue::Timestamp(
+                        timestamp.timestamp() * 1_000_000
+                            + i64::from(timestamp.timestamp_subsec_micros()),
+                    )
+                }),
+        }
+    }
+
+    /// Converts a stat value to a value of the same kind as `self`.
+    fn stat_like(&self, value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) => self.parse_like(value),
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => Some(PruningValue::Int(n.into())),
+                None => n.as_f64().map(PruningValue::Float),
+            },
+            Value::Bool(b) => Some(PruningValue::Bool(*b)),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (PruningValue::Int(a), PruningValue::Int(b)) => a.partial_cmp(b),
+            (PruningValue::Float(a), PruningValue::Float(b)) => a.partial_cmp(b),
+            (PruningValue::Int(a), PruningValue::Float(b)) => (*a as f64).partial_cmp(b),
+            (PruningValue::Float(a), PruningValue::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (PruningValue::Str(a), PruningValue::Str(b)) => a.partial_cmp(b),
+            (PruningValue::Bool(a), PruningValue::Bool(b)) => a.partial_cmp(b),
+            (PruningValue::Date(a), PruningValue::Date(b)) => a.partial_cmp(b),
+            (PruningValue::Timestamp(a), PruningValue::Timestamp(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+/// Decides whether a data file may contain rows matching a filter, from its partition values
+/// and stats. Files are only pruned when that is certain, unsupported filters match all files.
+struct FilePruningContext<'a> {
+    add: &'a action::Add,
+    partition_columns: &'a [String],
+    column_resolution: schema::ColumnResolution,
+    stats: Option<Stats>,
+}
+
+impl<'a> FilePruningContext<'a> {
+    fn new(
+        add: &'a action::Add,
+        partition_columns: &'a [String],
+        column_resolution: schema::ColumnResolution,
+    ) -> Self {
+        Self {
+            add,
+            partition_columns,
+            column_resolution,
+            stats: add.get_stats().ok().flatten(),
+        }
+    }
+
+    /// Returns the minimum and maximum value of the column in the file, as values of the same
+    /// kind as `literal`.
+    fn column_bounds(
+        &self,
+        column: &str,
+        literal: &PruningValue,
+    ) -> Option<(PruningValue, PruningValue)> {
+        if let Ok(column) = self
+            .column_resolution
+            .resolve(column, self.partition_columns)
+        {
+            // null partition values don't prune the file
+            let value = literal.parse_like(self.add.get_partition_value(column)?)?;
+            return Some((value.clone(), value));
+        }
+
+        let stats = self.stats.as_ref()?;
+        let stat_value = |values: &HashMap<String, ColumnValueStat>| {
+            values
+                .get(column)
+                .or_else(|| {
+                    values
+                        .iter()
+                        .find(|(name, _)| self.column_resolution.matches(column, name))
+                        .map(|(_, stat)| stat)
+                })
+                .and_then(ColumnValueStat::as_value)
+                .and_then(|value| literal.stat_like(value))
+        };
+        Some((stat_value(&stats.minValues)?, stat_value(&stats.maxValues)?))
+    }
+
+    fn may_match(&self, filter: &Expr) -> bool {
+        match filter {
+            Expr::BinaryExpr { left, op, right } => match op {
+                Operator::And => self.may_match(left) && self.may_match(right),
+                Operator::Or => self.may_match(left) || self.may_match(right),
+                _ => match (column_name(left), literal_value(right)) {
+                    (Some(column), Some(literal)) => {
+                        self.comparison_may_match(column, op, &literal)
+                    }
+                    _ => match (literal_value(left), column_name(right), flip_operator(op)) {
+                        (Some(literal), Some(column), Some(op)) => {
+                            self.comparison_may_match(column, &op, &literal)
+                        }
+                        _ => true,
+                    },
+                },
+            },
+            Expr::Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            } => match (column_name(expr), literal_value(low), literal_value(high)) {
+                (Some(column), Some(low), Some(high)) => {
+                    self.comparison_may_match(column, &Operator::GtEq, &low)
+                        && self.comparison_may_match(column, &Operator::LtEq, &high)
+                }
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    fn comparison_may_match(&self, column: &str, op: &Operator, literal: &PruningValue) -> bool {
+        let (min, max) = match self.column_bounds(column, literal) {
+            Some(bounds) => bounds,
+            None => return true,
+        };
+        let min_cmp = min.compare(literal);
+        let max_cmp = max.compare(literal);
+        match op {
+            Operator::Eq => {
+                !matches!(min_cmp, Some(Ordering::Greater))
+                    && !matches!(max_cmp, Some(Ordering::Less))
+            }
+            Operator::NotEq => {
+                !(matches!(min_cmp, Some(Ordering::Equal))
+                    && matches!(max_cmp, Some(Ordering::Equal)))
+            }
+            Operator::Lt => !matches!(min_cmp, Some(Ordering::Greater) | Some(Ordering::Equal)),
+            Operator::LtEq => !matches!(min_cmp, Some(Ordering::Greater)),
+            Operator::Gt => !matches!(max_cmp, Some(Ordering::Less) | Some(Ordering::Equal)),
+            Operator::GtEq => !matches!(max_cmp, Some(Ordering::Less)),
+            _ => true,
+        }
+    }
+}
+
+fn column_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Column(name) => Some(name),
+        _ => None,
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<PruningValue> {
+    match expr {
+        Expr::Literal(literal) => PruningValue::from_literal(literal),
+        // literals cast to the column type, e.g. a string compared with a date column
+        Expr::Cast { expr, .. } => literal_value(expr),
+        _ => None,
+    }
+}
+
+/// Returns the operator comparing the operands in swapped order, e.g. `a < b` is `b > a`.
+fn flip_operator(op: &Operator) -> Option<Operator> {
+    match op {
+        Operator::Eq => Some(Operator::Eq),
+        Operator::NotEq => Some(Operator::NotEq),
+        Operator::Lt => Some(Operator::Gt),
+        Operator::LtEq => Some(Operator::GtEq),
+        Operator::Gt => Some(Operator::Lt),
+        Operator::GtEq => Some(Operator::LtEq),
+        _ => None,
+    }
+}
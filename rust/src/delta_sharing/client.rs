@@ -0,0 +1,499 @@
+//! Client for reading tables shared through a Delta Sharing server.
+//!
+//! ```rust
+//! async {
+//!   let table = deltalake::delta_sharing::open_shared_table(
+//!       "./open-datasets.share#delta_sharing.default.COVID_19_NYT",
+//!   ).await.unwrap();
+//!   let files = table.get_files();
+//! };
+//! ```
+
+use std::ops::Range;
+use std::pin::Pin;
+
+use futures::Stream;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    DeltaSharingError, ListResponse, Share, SharedFile, SharedSchema, SharedTable,
+    TABLE_VERSION_HEADER,
+};
+use crate::action;
+use crate::delta::DeltaTableMetaData;
+use crate::scan::DeltaScanTask;
+use crate::schema::{DeltaDataTypeInt, DeltaDataTypeTimestamp, DeltaDataTypeVersion, Schema};
+use crate::storage::{ObjectMeta, StorageBackend, StorageCapabilities, StorageError};
+
+/// Credentials used to access a Delta Sharing server, usually stored in a `.share` profile file
+/// handed out by the data provider.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaSharingProfile {
+    /// The file format version of the profile file.
+    pub share_credentials_version: i32,
+    /// The url of the sharing server.
+    pub endpoint: String,
+    /// The bearer token used to access the sharing server.
+    pub bearer_token: String,
+    /// The expiration time of the bearer token in ISO 8601 format, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<String>,
+}
+
+impl DeltaSharingProfile {
+    /// Reads a profile from a local profile file.
+    pub async fn from_file(path: &str) -> Result<Self, DeltaSharingError> {
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| DeltaSharingError::Storage {
+                source: StorageError::from(e),
+            })?;
+        let profile: Self = serde_json::from_slice(&data)?;
+        if profile.share_credentials_version > 1 {
+            return Err(DeltaSharingError::InvalidProfile(format!(
+                "unsupported shareCredentialsVersion {}",
+                profile.share_credentials_version
+            )));
+        }
+
+        Ok(profile)
+    }
+}
+
+/// Client for the Delta Sharing REST protocol.
+#[derive(Debug, Clone)]
+pub struct DeltaSharingClient {
+    profile: DeltaSharingProfile,
+    http_client: reqwest::Client,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct QueryTableRequest {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    predicate_hints: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_hint: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SharedProtocol {
+    min_reader_version: DeltaDataTypeInt,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SharedMetaData {
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    format: action::Format,
+    schema_string: String,
+    #[serde(default)]
+    partition_columns: Vec<String>,
+    #[serde(default)]
+    created_time: Option<DeltaDataTypeTimestamp>,
+    #[serde(default)]
+    configuration: std::collections::HashMap<String, String>,
+}
+
+impl DeltaSharingClient {
+    /// Creates a new client for the sharing server described by the given profile.
+    pub fn new(profile: DeltaSharingProfile) -> Self {
+        Self {
+            profile,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Returns the profile used by the client.
+    pub fn profile(&self) -> &DeltaSharingProfile {
+        &self.profile
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.profile.endpoint.trim_end_matches('/'), path)
+    }
+
+    async fn check_status(
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, DeltaSharingError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        Err(DeltaSharingError::UnexpectedStatus {
+            status: response.status().as_u16(),
+            message: response.text().await.unwrap_or_default(),
+        })
+    }
+
+    async fn list_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, DeltaSharingError> {
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .http_client
+                .get(&self.url(path))
+                .bearer_auth(&self.profile.bearer_token);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let response = Self::check_status(request.send().await?).await?;
+            let page: ListResponse<T> = serde_json::from_slice(&response.bytes().await?)?;
+            items.extend(page.items);
+
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Lists the shares accessible to the recipient.
+    pub async fn list_shares(&self) -> Result<Vec<Share>, DeltaSharingError> {
+        self.list_all("shares").await
+    }
+
+    /// Lists the schemas in the given share.
+    pub async fn list_schemas(&self, share: &str) -> Result<Vec<SharedSchema>, DeltaSharingError> {
+        self.list_all(&format!("shares/{}/schemas", share)).await
+    }
+
+    /// Lists the tables in the given schema.
+    pub async fn list_tables(
+        &self,
+        share: &str,
+        schema: &str,
+    ) -> Result<Vec<SharedTable>, DeltaSharingError> {
+        self.list_all(&format!("shares/{}/schemas/{}/tables", share, schema))
+            .await
+    }
+
+    fn table_path(table: &SharedTable) -> String {
+        format!(
+            "shares/{}/schemas/{}/tables/{}",
+            table.share, table.schema, table.name
+        )
+    }
+
+    fn parse_table_version(
+        response: &reqwest::Response,
+    ) -> Result<DeltaDataTypeVersion, DeltaSharingError> {
+        response
+            .headers()
+            .get(TABLE_VERSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| {
+                DeltaSharingError::InvalidResponse(format!(
+                    "missing or invalid {} header",
+                    TABLE_VERSION_HEADER
+                ))
+            })
+    }
+
+    /// Returns the current version of the shared table.
+    pub async fn get_table_version(
+        &self,
+        table: &SharedTable,
+    ) -> Result<DeltaDataTypeVersion, DeltaSharingError> {
+        let response = self
+            .http_client
+            .head(&self.url(&Self::table_path(table)))
+            .bearer_auth(&self.profile.bearer_token)
+            .send()
+            .await?;
+        let response = Self::check_status(response).await?;
+
+        Self::parse_table_version(&response)
+    }
+
+    /// Queries the latest snapshot of the shared table. `predicate_hints` are SQL boolean
+    /// expressions the server may use to skip files, `limit_hint` is the number of rows the
+    /// recipient intends to read. Both are best effort, the server is allowed to ignore them.
+    pub async fn query_table(
+        &self,
+        table: &SharedTable,
+        predicate_hints: &[String],
+        limit_hint: Option<i64>,
+    ) -> Result<DeltaSharingTable, DeltaSharingError> {
+        let body = serde_json::to_string(&QueryTableRequest {
+            predicate_hints: predicate_hints.to_vec(),
+            limit_hint,
+        })?;
+        let response = self
+            .http_client
+            .post(&self.url(&format!("{}/query", Self::table_path(table))))
+            .bearer_auth(&self.profile.bearer_token)
+            .header("Content-Type", "application/json; charset=utf-8")
+            .body(body)
+            .send()
+            .await?;
+        let response = Self::check_status(response).await?;
+        let version = Self::parse_table_version(&response)?;
+        let bytes = response.bytes().await?;
+
+        let mut min_reader_version = None;
+        let mut metadata = None;
+        let mut files = Vec::new();
+        for line in bytes.split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+            let mut line: serde_json::Map<String, Value> = serde_json::from_slice(line)?;
+            if let Some(protocol) = line.remove("protocol") {
+                let protocol: SharedProtocol = serde_json::from_value(protocol)?;
+                min_reader_version = Some(protocol.min_reader_version);
+            } else if let Some(meta) = line.remove("metaData") {
+                let meta: SharedMetaData = serde_json::from_value(meta)?;
+                let schema: Schema = serde_json::from_str(&meta.schema_string)?;
+                metadata = Some(DeltaTableMetaData {
+                    id: meta.id,
+                    name: meta.name,
+                    description: meta.description,
+                    format: meta.format,
+                    schema,
+                    partition_columns: meta.partition_columns,
+                    created_time: meta.created_time.unwrap_or_default(),
+                    configuration: meta.configuration,
+                });
+            } else if let Some(file) = line.remove("file") {
+                files.push(serde_json::from_value(file)?);
+            } else {
+                debug!("Ignoring unexpected line in query response: {:?}", line);
+            }
+        }
+
+        Ok(DeltaSharingTable {
+            table: table.clone(),
+            version,
+            min_reader_version: min_reader_version.ok_or_else(|| {
+                DeltaSharingError::InvalidResponse("missing protocol in query response".into())
+            })?,
+            metadata: metadata.ok_or_else(|| {
+                DeltaSharingError::InvalidResponse("missing metaData in query response".into())
+            })?,
+            files,
+            backend: PreSignedUrlBackend {
+                http_client: self.http_client.clone(),
+            },
+        })
+    }
+}
+
+/// Read-only handle to a snapshot of a shared table. Data files are referenced by pre-signed
+/// urls and can be read through [`DeltaSharingTable::storage_backend`].
+#[derive(Debug)]
+pub struct DeltaSharingTable {
+    /// The shared table this snapshot was queried from.
+    pub table: SharedTable,
+    /// The version of the shared table.
+    pub version: DeltaDataTypeVersion,
+    min_reader_version: DeltaDataTypeInt,
+    metadata: DeltaTableMetaData,
+    files: Vec<SharedFile>,
+    backend: PreSignedUrlBackend,
+}
+
+impl DeltaSharingTable {
+    /// Returns the pre-signed urls of the data files in the snapshot.
+    pub fn get_files(&self) -> Vec<&str> {
+        self.files.iter().map(|f| f.url.as_str()).collect()
+    }
+
+    /// Returns the data files in the snapshot, including partition values and stats.
+    pub fn get_shared_files(&self) -> &Vec<SharedFile> {
+        &self.files
+    }
+
+    /// Returns the metadata of the shared table.
+    pub fn get_metadata(&self) -> &DeltaTableMetaData {
+        &self.metadata
+    }
+
+    /// Returns the schema of the shared table.
+    pub fn get_schema(&self) -> &Schema {
+        &self.metadata.schema
+    }
+
+    /// Returns the minimum reader version required to read the shared table.
+    pub fn get_min_reader_version(&self) -> DeltaDataTypeInt {
+        self.min_reader_version
+    }
+
+    /// Returns a storage backend able to read the pre-signed urls of the snapshot.
+    pub fn storage_backend(&self) -> &dyn StorageBackend {
+        &self.backend
+    }
+
+    /// Splits the data files of the snapshot into scan tasks, see [`crate::scan`].
+    pub fn get_scan_tasks(&self) -> Vec<DeltaScanTask> {
+        self.files
+            .iter()
+            .map(|f| DeltaScanTask {
+                path: f.url.clone(),
+                ranges: vec![],
+                partition_values: f.partition_values.clone(),
+                schema: self.metadata.schema.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Storage backend reading objects through pre-signed urls. Only `get_obj` and `get_obj_range`
+/// are supported.
+#[derive(Debug, Clone)]
+pub struct PreSignedUrlBackend {
+    http_client: reqwest::Client,
+}
+
+impl PreSignedUrlBackend {
+    fn unsupported(op: &str) -> StorageError {
+        StorageError::Generic(format!("{} is not supported for pre-signed urls", op))
+    }
+
+    /// Fetches the object at the url, or the bytes of the given HTTP `range` of it.
+    async fn fetch(&self, path: &str, range: Option<String>) -> Result<Vec<u8>, StorageError> {
+        let mut request = self.http_client.get(path);
+        if let Some(range) = range {
+            request = request.header(reqwest::header::RANGE, range);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| StorageError::Generic(e.to_string()))?;
+        match response.status().as_u16() {
+            404 => Err(StorageError::NotFound),
+            // the range starts past the end of the object
+            416 => Ok(Vec::new()),
+            s if (200..300).contains(&s) => Ok(response
+                .bytes()
+                .await
+                .map_err(|e| StorageError::Generic(e.to_string()))?
+                .to_vec()),
+            s => Err(StorageError::Generic(format!(
+                "Failed to fetch pre-signed url, status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for PreSignedUrlBackend {
+    async fn head_obj(&self, _path: &str) -> Result<ObjectMeta, StorageError> {
+        Err(Self::unsupported("head_obj"))
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        debug!("fetching pre-signed url...");
+        self.fetch(path, None).await
+    }
+
+    async fn get_obj_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        debug!(
+            "fetching bytes {}..{} of pre-signed url...",
+            range.start, range.end
+        );
+        if range.start >= range.end {
+            return Ok(Vec::new());
+        }
+        self.fetch(
+            path,
+            Some(format!("bytes={}-{}", range.start, range.end - 1)),
+        )
+        .await
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        _path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        Err(Self::unsupported("list_objs"))
+    }
+
+    async fn put_obj(&self, _path: &str, _obj_bytes: &[u8]) -> Result<(), StorageError> {
+        Err(Self::unsupported("put_obj"))
+    }
+
+    async fn rename_obj(&self, _src: &str, _dst: &str) -> Result<(), StorageError> {
+        Err(Self::unsupported("rename_obj"))
+    }
+
+    async fn delete_obj(&self, _path: &str) -> Result<(), StorageError> {
+        Err(Self::unsupported("delete_obj"))
+    }
+
+    fn capabilities(&self) -> StorageCapabilities {
+        StorageCapabilities {
+            atomic_rename: false,
+            range_reads: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parses a shared table url of the form `<profile-file-path>#<share>.<schema>.<table>`.
+pub fn parse_shared_table_url(url: &str) -> Result<(&str, SharedTable), DeltaSharingError> {
+    let invalid = || DeltaSharingError::InvalidTableUrl(url.to_string());
+    let (profile_path, coordinates) = match url.rfind('#') {
+        Some(idx) => (&url[..idx], &url[idx + 1..]),
+        None => return Err(invalid()),
+    };
+    let parts: Vec<&str> = coordinates.split('.').collect();
+    if profile_path.is_empty() || parts.len() != 3 || parts.iter().any(|p| p.is_empty()) {
+        return Err(invalid());
+    }
+
+    Ok((
+        profile_path,
+        SharedTable {
+            name: parts[2].to_string(),
+            schema: parts[1].to_string(),
+            share: parts[0].to_string(),
+            share_id: None,
+            id: None,
+        },
+    ))
+}
+
+/// Opens the latest snapshot of a shared table identified by
+/// `<profile-file-path>#<share>.<schema>.<table>`.
+pub async fn open_shared_table(url: &str) -> Result<DeltaSharingTable, DeltaSharingError> {
+    let (profile_path, table) = parse_shared_table_url(url)?;
+    let profile = DeltaSharingProfile::from_file(profile_path).await?;
+    let client = DeltaSharingClient::new(profile);
+
+    client.query_table(&table, &[], None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_shared_table_url() {
+        let (profile, table) =
+            parse_shared_table_url("/tmp/config.share#share.schema.table").unwrap();
+        assert_eq!(profile, "/tmp/config.share");
+        assert_eq!(table.share, "share");
+        assert_eq!(table.schema, "schema");
+        assert_eq!(table.name, "table");
+    }
+
+    #[test]
+    fn parse_invalid_shared_table_url() {
+        assert!(parse_shared_table_url("/tmp/config.share").is_err());
+        assert!(parse_shared_table_url("/tmp/config.share#share.table").is_err());
+        assert!(parse_shared_table_url("#share.schema.table").is_err());
+    }
+}
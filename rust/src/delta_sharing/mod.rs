@@ -0,0 +1,139 @@
+//! Delta Sharing protocol support.
+//!
+//! [Delta Sharing](https://github.com/delta-io/delta-sharing/blob/main/PROTOCOL.md) is a REST
+//! protocol for sharing Delta tables with recipients that don't have direct access to the
+//! underlying storage. Data files are handed out as pre-signed URLs.
+//!
+//! This module is gated behind the `delta-sharing` feature, and its [`server`] behind the
+//! `delta-sharing-server` feature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::StorageError;
+use crate::DeltaTableError;
+
+pub mod client;
+#[cfg(feature = "delta-sharing-server")]
+pub mod server;
+
+pub use self::client::{open_shared_table, DeltaSharingClient, DeltaSharingTable};
+
+/// Header holding the version of the table in the responses of a sharing server.
+const TABLE_VERSION_HEADER: &str = "delta-table-version";
+
+/// Error returned when interacting with a Delta Sharing server fails.
+#[derive(thiserror::Error, Debug)]
+pub enum DeltaSharingError {
+    /// Error returned when the HTTP request to the sharing server failed.
+    #[error("Delta Sharing request failed: {source}")]
+    Http {
+        /// The underlying HTTP client error.
+        #[from]
+        source: reqwest::Error,
+    },
+    /// Error returned when the sharing server responded with an unsuccessful status code.
+    #[error("Delta Sharing server responded with status {status}: {message}")]
+    UnexpectedStatus {
+        /// The HTTP status code of the response.
+        status: u16,
+        /// The error message returned by the server.
+        message: String,
+    },
+    /// Error returned when a response or profile file contains invalid JSON.
+    #[error("Invalid JSON in Delta Sharing response: {source}")]
+    InvalidJson {
+        /// The underlying JSON error.
+        #[from]
+        source: serde_json::Error,
+    },
+    /// Error returned when the response is missing a required part.
+    #[error("Invalid Delta Sharing response: {0}")]
+    InvalidResponse(String),
+    /// Error returned when the profile file is invalid.
+    #[error("Invalid Delta Sharing profile: {0}")]
+    InvalidProfile(String),
+    /// Error returned when the shared table url is not of the form
+    /// `<profile-file-path>#<share>.<schema>.<table>`.
+    #[error("Invalid shared table url: {0}")]
+    InvalidTableUrl(String),
+    /// Error returned when reading a profile file or a data file failed.
+    #[error("Storage interaction failed: {source}")]
+    Storage {
+        /// The underlying storage error.
+        #[from]
+        source: StorageError,
+    },
+    /// Error returned when decoding the shared table failed.
+    #[error("DeltaTable interaction failed: {source}")]
+    DeltaTable {
+        /// The underlying DeltaTable error.
+        #[from]
+        source: DeltaTableError,
+    },
+}
+
+/// A share is a logical grouping of schemas exposed to recipients.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Share {
+    /// The name of the share.
+    pub name: String,
+    /// The unique identifier of the share, if the server exposes one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// A schema is a logical grouping of tables within a share.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SharedSchema {
+    /// The name of the schema.
+    pub name: String,
+    /// The share the schema belongs to.
+    pub share: String,
+}
+
+/// A table shared through a Delta Sharing server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedTable {
+    /// The name of the table.
+    pub name: String,
+    /// The schema the table belongs to.
+    pub schema: String,
+    /// The share the table belongs to.
+    pub share: String,
+    /// The unique identifier of the share, if the server exposes one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_id: Option<String>,
+    /// The unique identifier of the table, if the server exposes one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// A data file of a shared table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFile {
+    /// A pre-signed url to read the data file.
+    pub url: String,
+    /// Unique identifier of the data file within the table.
+    pub id: String,
+    /// A map from partition column to value for this file.
+    pub partition_values: std::collections::HashMap<String, Option<String>>,
+    /// The size of this file in bytes.
+    pub size: i64,
+    /// Statistics about the data in this file, serialized as JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<String>,
+}
+
+/// A page of items returned by the list endpoints of a Delta Sharing server.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResponse<T> {
+    /// The items in this page.
+    #[serde(default = "Vec::new")]
+    pub items: Vec<T>,
+    /// Token used to retrieve the next page, absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
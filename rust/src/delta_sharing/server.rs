@@ -0,0 +1,591 @@
+//! Server side of the Delta Sharing protocol, sharing tables loaded through this crate.
+//!
+//! Tables are organized in shares and schemas as described by a [`SharingServerConfig`], and
+//! recipients authenticate with the bearer tokens of their [`RecipientConfig`]. Data files are
+//! handed out as pre-signed urls, see `StorageBackend::presign`, so only tables in storage
+//! backends able to sign urls can be queried.
+//!
+//! ```rust,no_run
+//! async {
+//!   let config: deltalake::delta_sharing::server::SharingServerConfig =
+//!       serde_json::from_slice(&std::fs::read("./sharing-server.json").unwrap()).unwrap();
+//!   let server = deltalake::delta_sharing::server::DeltaSharingServer::new(config);
+//!   server.serve(([127, 0, 0, 1], 8080).into()).await.unwrap();
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{ListResponse, Share, SharedFile, SharedSchema, SharedTable, TABLE_VERSION_HEADER};
+use crate::action;
+use crate::delta::{DeltaTableError, DeltaTableSnapshot};
+use crate::partitions::{PartitionFilter, PartitionValue};
+use crate::storage::{percent_decode, StorageError};
+use crate::table_cache::DeltaTableLoader;
+
+/// Time a pre-signed url handed out by the server stays valid, unless configured otherwise.
+const DEFAULT_URL_EXPIRY: Duration = Duration::from_secs(15 * 60);
+
+/// Time a loaded table is served before it is refreshed, unless configured otherwise.
+const DEFAULT_SNAPSHOT_TTL: Duration = Duration::from_secs(60);
+
+/// Shares, schemas and tables exposed by a [`DeltaSharingServer`], and the recipients allowed to
+/// read them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SharingServerConfig {
+    /// The shares exposed by the server.
+    pub shares: Vec<ShareConfig>,
+    /// The recipients allowed to access the shares.
+    pub recipients: Vec<RecipientConfig>,
+}
+
+/// A share exposed by a [`DeltaSharingServer`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShareConfig {
+    /// The name of the share.
+    pub name: String,
+    /// The schemas of the share.
+    pub schemas: Vec<SchemaConfig>,
+}
+
+/// A schema of a share exposed by a [`DeltaSharingServer`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SchemaConfig {
+    /// The name of the schema.
+    pub name: String,
+    /// The tables of the schema.
+    pub tables: Vec<TableConfig>,
+}
+
+/// A table of a schema exposed by a [`DeltaSharingServer`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TableConfig {
+    /// The name the table is shared as.
+    pub name: String,
+    /// The uri the table is loaded from.
+    pub location: String,
+    /// The unique identifier the table is shared with, if any.
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// A recipient of the shares of a [`DeltaSharingServer`].
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientConfig {
+    /// The bearer token the recipient authenticates with.
+    pub bearer_token: String,
+    /// The names of the shares the recipient can access.
+    pub shares: Vec<String>,
+}
+
+impl fmt::Debug for RecipientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print secrets
+        f.debug_struct("RecipientConfig")
+            .field("bearer_token", &"***")
+            .field("shares", &self.shares)
+            .finish()
+    }
+}
+
+/// Body of the requests to the query endpoint.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct QueryTableRequest {
+    #[serde(default)]
+    predicate_hints: Vec<String>,
+    #[serde(default)]
+    limit_hint: Option<i64>,
+}
+
+/// Error answered to a request, with the error codes of the Delta Sharing protocol.
+struct ErrorResponse {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ErrorResponse {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "RESOURCE_DOES_NOT_EXIST", message)
+    }
+
+    fn invalid_parameter(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "INVALID_PARAMETER_VALUE", message)
+    }
+}
+
+impl From<DeltaTableError> for ErrorResponse {
+    fn from(err: DeltaTableError) -> Self {
+        match &err {
+            DeltaTableError::NotATable
+            | DeltaTableError::StorageError {
+                source: StorageError::NotFound,
+            } => Self::not_found(err.to_string()),
+            _ => Self::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                err.to_string(),
+            ),
+        }
+    }
+}
+
+type HandlerResult = Result<Response<Body>, ErrorResponse>;
+
+/// Serves the tables of a [`SharingServerConfig`] over the Delta Sharing REST protocol.
+pub struct DeltaSharingServer {
+    config: SharingServerConfig,
+    loader: DeltaTableLoader,
+    url_expiry: Duration,
+}
+
+impl DeltaSharingServer {
+    /// Creates a server for the shares and recipients of the given config.
+    pub fn new(config: SharingServerConfig) -> Self {
+        Self {
+            config,
+            loader: DeltaTableLoader::new(DEFAULT_SNAPSHOT_TTL),
+            url_expiry: DEFAULT_URL_EXPIRY,
+        }
+    }
+
+    /// Sets how long the pre-signed urls of the data files stay valid, 15 minutes by default.
+    pub fn with_url_expiry(mut self, url_expiry: Duration) -> Self {
+        self.url_expiry = url_expiry;
+        self
+    }
+
+    /// Sets how long a loaded table is served before it is refreshed, 60 seconds by default.
+    pub fn with_snapshot_ttl(mut self, ttl: Duration) -> Self {
+        self.loader = DeltaTableLoader::new(ttl);
+        self
+    }
+
+    /// Listens on `addr` and serves requests until the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), hyper::Error> {
+        let server = Arc::new(self);
+        let make_svc = make_service_fn(move |_conn| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(req).await) }
+                }))
+            }
+        });
+
+        Server::bind(&addr).serve(make_svc).await
+    }
+
+    /// Answers a request to the sharing server. Paths are relative to the server endpoint, e.g.
+    /// `/shares/{share}/schemas`.
+    pub async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        match self.route(req).await {
+            Ok(response) => response,
+            Err(err) => {
+                debug!(
+                    "Answering sharing request with {}: {}",
+                    err.code, err.message
+                );
+                Response::builder()
+                    .status(err.status)
+                    .header(CONTENT_TYPE, "application/json; charset=utf-8")
+                    .body(Body::from(
+                        json!({ "errorCode": err.code, "message": err.message }).to_string(),
+                    ))
+                    .unwrap()
+            }
+        }
+    }
+
+    async fn route(&self, req: Request<Body>) -> HandlerResult {
+        let recipient = self.authenticate(&req)?;
+        let segments = req
+            .uri()
+            .path()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(percent_decode)
+            .collect::<Vec<_>>();
+        let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+        let query = parse_query(req.uri().query());
+        let method = req.method().as_str().to_string();
+
+        if let Some(share) = segments.get(1) {
+            if !recipient.shares.iter().any(|name| name == share) {
+                return Err(ErrorResponse::not_found(format!(
+                    "Share {} doesn't exist",
+                    share
+                )));
+            }
+        }
+
+        match (method.as_str(), segments.as_slice()) {
+            ("GET", ["shares"]) => {
+                let shares = self
+                    .config
+                    .shares
+                    .iter()
+                    .filter(|share| recipient.shares.contains(&share.name))
+                    .map(|share| Share {
+                        name: share.name.clone(),
+                        id: None,
+                    })
+                    .collect();
+                list_response(shares, &query)
+            }
+            ("GET", ["shares", share]) => {
+                let share = self.share(share)?;
+                json_response(json!({ "share": { "name": share.name } }))
+            }
+            ("GET", ["shares", share, "schemas"]) => {
+                let share = self.share(share)?;
+                let schemas = share
+                    .schemas
+                    .iter()
+                    .map(|schema| SharedSchema {
+                        name: schema.name.clone(),
+                        share: share.name.clone(),
+                    })
+                    .collect();
+                list_response(schemas, &query)
+            }
+            ("GET", ["shares", share, "schemas", schema, "tables"]) => {
+                let share = self.share(share)?;
+                let schema = self.schema(share, schema)?;
+                list_response(shared_tables(share, schema).collect(), &query)
+            }
+            ("GET", ["shares", share, "all-tables"]) => {
+                let share = self.share(share)?;
+                let tables = share
+                    .schemas
+                    .iter()
+                    .flat_map(|schema| shared_tables(share, schema))
+                    .collect();
+                list_response(tables, &query)
+            }
+            ("HEAD", ["shares", share, "schemas", schema, "tables", table])
+            | ("GET", ["shares", share, "schemas", schema, "tables", table])
+            | ("GET", ["shares", share, "schemas", schema, "tables", table, "version"]) => {
+                let snapshot = self.load(share, schema, table).await?;
+                Ok(Response::builder()
+                    .header(TABLE_VERSION_HEADER, snapshot.version())
+                    .body(Body::empty())
+                    .unwrap())
+            }
+            ("GET", ["shares", share, "schemas", schema, "tables", table, "metadata"]) => {
+                let snapshot = self.load(share, schema, table).await?;
+                let lines = vec![protocol_line(&snapshot), metadata_line(&snapshot)?];
+                ndjson_response(&snapshot, lines)
+            }
+            ("POST", ["shares", share, "schemas", schema, "tables", table, "query"]) => {
+                let snapshot = self.load(share, schema, table).await?;
+                let body = hyper::body::to_bytes(req.into_body())
+                    .await
+                    .map_err(|e| ErrorResponse::invalid_parameter(e.to_string()))?;
+                let request: QueryTableRequest = if body.is_empty() {
+                    QueryTableRequest::default()
+                } else {
+                    serde_json::from_slice(&body)
+                        .map_err(|e| ErrorResponse::invalid_parameter(e.to_string()))?
+                };
+                self.query(&snapshot, &request).await
+            }
+            _ => Err(ErrorResponse::not_found(format!(
+                "No endpoint for {} {}",
+                method,
+                req.uri().path()
+            ))),
+        }
+    }
+
+    fn authenticate(&self, req: &Request<Body>) -> Result<&RecipientConfig, ErrorResponse> {
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        token
+            .and_then(|token| {
+                self.config
+                    .recipients
+                    .iter()
+                    .find(|recipient| recipient.bearer_token == token)
+            })
+            .ok_or_else(|| {
+                ErrorResponse::new(
+                    StatusCode::UNAUTHORIZED,
+                    "UNAUTHENTICATED",
+                    "Missing or invalid bearer token",
+                )
+            })
+    }
+
+    fn share(&self, name: &str) -> Result<&ShareConfig, ErrorResponse> {
+        self.config
+            .shares
+            .iter()
+            .find(|share| share.name == name)
+            .ok_or_else(|| ErrorResponse::not_found(format!("Share {} doesn't exist", name)))
+    }
+
+    fn schema<'a>(
+        &self,
+        share: &'a ShareConfig,
+        name: &str,
+    ) -> Result<&'a SchemaConfig, ErrorResponse> {
+        share
+            .schemas
+            .iter()
+            .find(|schema| schema.name == name)
+            .ok_or_else(|| ErrorResponse::not_found(format!("Schema {} doesn't exist", name)))
+    }
+
+    async fn load(
+        &self,
+        share: &str,
+        schema: &str,
+        table: &str,
+    ) -> Result<DeltaTableSnapshot, ErrorResponse> {
+        let share = self.share(share)?;
+        let schema = self.schema(share, schema)?;
+        let table = schema
+            .tables
+            .iter()
+            .find(|t| t.name == table)
+            .ok_or_else(|| ErrorResponse::not_found(format!("Table {} doesn't exist", table)))?;
+
+        Ok(self.loader.load(&table.location).await?)
+    }
+
+    async fn query(
+        &self,
+        snapshot: &DeltaTableSnapshot,
+        request: &QueryTableRequest,
+    ) -> HandlerResult {
+        let partition_columns = &snapshot.get_metadata()?.partition_columns;
+        let filters = request
+            .predicate_hints
+            .iter()
+            .filter_map(|hint| partition_filter_from_hint(hint, partition_columns))
+            .collect::<Vec<_>>();
+        let files = snapshot
+            .get_presigned_file_urls(&filters, self.url_expiry)
+            .await?;
+
+        let mut lines = vec![protocol_line(snapshot), metadata_line(snapshot)?];
+        let mut num_records = 0;
+        for (add, url) in files {
+            // the limit is a hint, files are only skipped once enough rows are known to be
+            // returned
+            if matches!(request.limit_hint, Some(limit) if num_records >= limit) {
+                break;
+            }
+            if let Ok(Some(stats)) = add.get_stats() {
+                num_records += stats.numRecords;
+            }
+            lines.push(json!({ "file": shared_file(add, url) }));
+        }
+
+        ndjson_response(snapshot, lines)
+    }
+}
+
+impl fmt::Debug for DeltaSharingServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DeltaSharingServer <{} shares>",
+            self.config.shares.len()
+        )
+    }
+}
+
+fn shared_tables<'a>(
+    share: &'a ShareConfig,
+    schema: &'a SchemaConfig,
+) -> impl Iterator<Item = SharedTable> + 'a {
+    schema.tables.iter().map(move |table| SharedTable {
+        name: table.name.clone(),
+        schema: schema.name.clone(),
+        share: share.name.clone(),
+        share_id: None,
+        id: table.id.clone(),
+    })
+}
+
+fn shared_file(add: &action::Add, url: String) -> SharedFile {
+    SharedFile {
+        url,
+        // paths are unique among the active files of a table
+        id: add.path.clone(),
+        partition_values: add.partitionValues.clone(),
+        size: add.size,
+        stats: add.stats.clone(),
+    }
+}
+
+fn protocol_line(snapshot: &DeltaTableSnapshot) -> Value {
+    json!({ "protocol": { "minReaderVersion": snapshot.get_min_reader_version() } })
+}
+
+fn metadata_line(snapshot: &DeltaTableSnapshot) -> Result<Value, ErrorResponse> {
+    let metadata = snapshot.get_metadata()?;
+    let schema_string = serde_json::to_string(&metadata.schema).map_err(|e| {
+        ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            e.to_string(),
+        )
+    })?;
+
+    Ok(json!({
+        "metaData": {
+            "id": metadata.id,
+            "name": metadata.name,
+            "description": metadata.description,
+            "format": metadata.format,
+            "schemaString": schema_string,
+            "partitionColumns": metadata.partition_columns,
+            "configuration": metadata.configuration,
+        }
+    }))
+}
+
+/// Turns a predicate hint on a partition column into a partition filter. Only comparisons of a
+/// column with a literal, `column = 'value'` and `column != 'value'`, and null checks,
+/// `column IS [NOT] NULL`, are understood. Hints are best effort, so the others are ignored and
+/// the files they would have skipped are returned.
+fn partition_filter_from_hint<'a>(
+    hint: &'a str,
+    partition_columns: &'a [String],
+) -> Option<PartitionFilter<'a, &'a str>> {
+    let hint = hint.trim();
+    let upper = hint.to_ascii_uppercase();
+    let (column, value) = if let Some(column) = upper.strip_suffix(" IS NOT NULL") {
+        (&hint[..column.len()], PartitionValue::IsNotNull)
+    } else if let Some(column) = upper.strip_suffix(" IS NULL") {
+        (&hint[..column.len()], PartitionValue::IsNull)
+    } else {
+        let (idx, op) = ["!=", "<>", "="]
+            .iter()
+            .find_map(|op| hint.find(op).map(|idx| (idx, *op)))?;
+        let literal = hint[idx + op.len()..].trim();
+        let literal = match literal.strip_prefix('\'') {
+            Some(quoted) => quoted.strip_suffix('\'')?,
+            None => literal,
+        };
+        // escaped quotes would have to be unescaped
+        if literal.contains('\'') {
+            return None;
+        }
+        let value = match op {
+            "=" => PartitionValue::Equal(literal),
+            _ => PartitionValue::NotEqual(literal),
+        };
+        (&hint[..idx], value)
+    };
+
+    let column = column.trim();
+    let key = partition_columns
+        .iter()
+        .find(|partition_column| partition_column.eq_ignore_ascii_case(column))?;
+    Some(PartitionFilter {
+        key: key.as_str(),
+        value,
+    })
+}
+
+fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    query
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = percent_decode(parts.next().unwrap_or(""));
+            let value = percent_decode(parts.next().unwrap_or(""));
+            (key, value)
+        })
+        .collect()
+}
+
+/// Answers a page of `items`, starting at the offset given by the `pageToken` query parameter
+/// and holding at most `maxResults` items.
+fn list_response<T: Serialize>(items: Vec<T>, query: &HashMap<String, String>) -> HandlerResult {
+    let offset = match query.get("pageToken") {
+        Some(token) => token
+            .parse::<usize>()
+            .map_err(|_| ErrorResponse::invalid_parameter("Invalid pageToken"))?,
+        None => 0,
+    };
+    let max_results = match query.get("maxResults") {
+        Some(max) => match max.parse::<usize>() {
+            Ok(max) if max > 0 => max,
+            _ => return Err(ErrorResponse::invalid_parameter("Invalid maxResults")),
+        },
+        None => usize::MAX,
+    };
+    let end = offset.saturating_add(max_results).min(items.len());
+    let next_page_token = if end < items.len() {
+        Some(end.to_string())
+    } else {
+        None
+    };
+    let items = items
+        .into_iter()
+        .skip(offset)
+        .take(max_results)
+        .collect::<Vec<_>>();
+
+    json_response(
+        serde_json::to_value(ListResponse {
+            items,
+            next_page_token,
+        })
+        .unwrap(),
+    )
+}
+
+fn json_response(body: Value) -> HandlerResult {
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Body::from(body.to_string()))
+        .unwrap())
+}
+
+fn ndjson_response(snapshot: &DeltaTableSnapshot, lines: Vec<Value>) -> HandlerResult {
+    let mut body = String::new();
+    for line in lines {
+        body.push_str(&line.to_string());
+        body.push('\n');
+    }
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/x-ndjson; charset=utf-8")
+        .header(TABLE_VERSION_HEADER, snapshot.version())
+        .body(Body::from(body))
+        .unwrap())
+}
@@ -0,0 +1,252 @@
+//! Helpers for integration testing Delta pipelines against a local S3 compatible store, such as
+//! localstack or MinIO, enabled with the `integration-testing` feature.
+//!
+//! An [`S3TestContext`] points the S3 storage backend at the store through the AWS environment
+//! variables, creates buckets, uploads local tables and cleans up the prefixes written by tests.
+//! With the `dynamodb` feature, it also provisions the DynamoDB table of the S3 lock. The store
+//! itself must already be running, e.g. with the `docker-compose.yml` of this repository.
+//!
+//! ```rust,no_run
+//! use deltalake::integration_testing::S3TestContext;
+//!
+//! async {
+//!   let context = S3TestContext::localstack();
+//!   context.create_bucket("deltars").await.unwrap();
+//!   context
+//!       .upload_dir("./tests/data/simple_table", "s3://deltars/simple")
+//!       .await
+//!       .unwrap();
+//!   let table = deltalake::open_table("s3://deltars/simple").await.unwrap();
+//!   context.delete_prefix("s3://deltars/simple").await.unwrap();
+//! };
+//! ```
+
+use std::path::Path;
+
+use rusoto_core::{Region, RusotoError};
+use rusoto_s3::{
+    CreateBucketError, CreateBucketRequest, DeleteObjectRequest, ListObjectsV2Request,
+    PutObjectRequest, S3Client, S3,
+};
+
+use crate::storage::{parse_uri, StorageError};
+
+/// Endpoint of the S3 and DynamoDB services of localstack with its default port.
+pub const LOCALSTACK_ENDPOINT: &str = "http://localhost:4566";
+
+/// Access to a local S3 compatible store for integration tests.
+pub struct S3TestContext {
+    endpoint: String,
+    client: S3Client,
+}
+
+impl S3TestContext {
+    /// Creates a context for the store at `endpoint`, and sets the AWS environment variables so
+    /// that the tables opened by the process use it, with the `test` credentials of localstack.
+    /// The environment is global to the process, so tests using different stores must not run
+    /// concurrently.
+    pub fn new(endpoint: &str) -> Self {
+        std::env::set_var("AWS_REGION", "us-east-2");
+        std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+        std::env::set_var("AWS_ENDPOINT_URL", endpoint);
+        Self {
+            endpoint: endpoint.to_string(),
+            client: S3Client::new(region(endpoint)),
+        }
+    }
+
+    /// Creates a context for localstack at [`LOCALSTACK_ENDPOINT`].
+    pub fn localstack() -> Self {
+        Self::new(LOCALSTACK_ENDPOINT)
+    }
+
+    /// The endpoint of the store.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The region to create rusoto clients for the services of the store with.
+    pub fn region(&self) -> Region {
+        region(&self.endpoint)
+    }
+
+    /// Creates a bucket, succeeding when it already exists.
+    pub async fn create_bucket(&self, bucket: &str) -> Result<(), StorageError> {
+        let result = self
+            .client
+            .create_bucket(CreateBucketRequest {
+                bucket: bucket.to_string(),
+                ..Default::default()
+            })
+            .await;
+        match result {
+            Ok(_)
+            | Err(RusotoError::Service(CreateBucketError::BucketAlreadyOwnedByYou(_)))
+            | Err(RusotoError::Service(CreateBucketError::BucketAlreadyExists(_))) => Ok(()),
+            Err(e) => Err(StorageError::Generic(format!(
+                "Failed to create bucket {}: {}",
+                bucket, e
+            ))),
+        }
+    }
+
+    /// Uploads the files of a local directory and its subdirectories under an S3 URI, e.g. a
+    /// table of test data, keeping their relative paths.
+    pub async fn upload_dir(&self, local_dir: &str, uri: &str) -> Result<(), StorageError> {
+        let object = parse_uri(uri)?.into_s3object()?;
+        let mut files = Vec::new();
+        list_local_files(Path::new(local_dir), "", &mut files)?;
+        for (relative_path, path) in files {
+            let body = std::fs::read(&path).map_err(|source| StorageError::Io { source })?;
+            self.client
+                .put_object(PutObjectRequest {
+                    bucket: object.bucket.to_string(),
+                    key: join_key(object.key, &relative_path),
+                    body: Some(body.into()),
+                    ..Default::default()
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes all the objects under an S3 URI.
+    pub async fn delete_prefix(&self, uri: &str) -> Result<(), StorageError> {
+        self.cleanup_dir_except(uri, &[]).await
+    }
+
+    /// Deletes the objects under an S3 URI, except the ones whose file name is in `keep` or
+    /// starts with a dot. Meant to reset a table to its initial commits between test runs.
+    pub async fn cleanup_dir_except(&self, uri: &str, keep: &[&str]) -> Result<(), StorageError> {
+        let object = parse_uri(uri)?.into_s3object()?;
+        for key in self.list_keys(object.bucket, object.key).await? {
+            let name = key.rsplit('/').next().unwrap_or_default();
+            if keep.contains(&name) || name.starts_with('.') {
+                continue;
+            }
+            self.client
+                .delete_object(DeleteObjectRequest {
+                    bucket: object.bucket.to_string(),
+                    key,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|source| StorageError::S3Delete { source })?;
+        }
+        Ok(())
+    }
+
+    /// Sets the environment variables making the S3 backend lock commits with the DynamoDB table
+    /// `table_name`, under the partition key value `key`. Short refresh periods keep tests of
+    /// concurrent writers fast.
+    #[cfg(feature = "dynamodb")]
+    pub fn use_dynamodb_lock(&self, table_name: &str, key: &str) {
+        std::env::set_var("AWS_S3_LOCKING_PROVIDER", "dynamodb");
+        std::env::set_var("DYNAMO_LOCK_TABLE_NAME", table_name);
+        std::env::set_var("DYNAMO_LOCK_PARTITION_KEY_VALUE", key);
+        std::env::set_var("DYNAMO_LOCK_REFRESH_PERIOD_MILLIS", "100");
+        std::env::set_var("DYNAMO_LOCK_ADDITIONAL_TIME_TO_WAIT_MILLIS", "100");
+    }
+
+    /// Creates the DynamoDB table of the S3 lock, succeeding when it already exists.
+    #[cfg(feature = "dynamodb")]
+    pub async fn create_lock_table(&self, table_name: &str) -> Result<(), StorageError> {
+        use crate::storage::s3::dynamodb_lock::PARTITION_KEY_NAME;
+        use rusoto_dynamodb::{
+            AttributeDefinition, CreateTableError, CreateTableInput, DynamoDb, DynamoDbClient,
+            KeySchemaElement, ProvisionedThroughput,
+        };
+
+        let client = DynamoDbClient::new(self.region());
+        let result = client
+            .create_table(CreateTableInput {
+                table_name: table_name.to_string(),
+                attribute_definitions: vec![AttributeDefinition {
+                    attribute_name: PARTITION_KEY_NAME.to_string(),
+                    attribute_type: "S".to_string(),
+                }],
+                key_schema: vec![KeySchemaElement {
+                    attribute_name: PARTITION_KEY_NAME.to_string(),
+                    key_type: "HASH".to_string(),
+                }],
+                provisioned_throughput: Some(ProvisionedThroughput {
+                    read_capacity_units: 10,
+                    write_capacity_units: 10,
+                }),
+                ..Default::default()
+            })
+            .await;
+        match result {
+            Ok(_) | Err(RusotoError::Service(CreateTableError::ResourceInUse(_))) => Ok(()),
+            Err(e) => Err(StorageError::Generic(format!(
+                "Failed to create DynamoDB table {}: {}",
+                table_name, e
+            ))),
+        }
+    }
+
+    async fn list_keys(&self, bucket: &str, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let result = self
+                .client
+                .list_objects_v2(ListObjectsV2Request {
+                    bucket: bucket.to_string(),
+                    prefix: Some(prefix.to_string()),
+                    continuation_token,
+                    ..Default::default()
+                })
+                .await?;
+            keys.extend(
+                result
+                    .contents
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|obj| obj.key),
+            );
+            continuation_token = result.next_continuation_token;
+            if continuation_token.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+fn region(endpoint: &str) -> Region {
+    Region::Custom {
+        name: "custom".to_string(),
+        endpoint: endpoint.to_string(),
+    }
+}
+
+fn join_key(prefix: &str, relative_path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        relative_path.to_string()
+    } else {
+        format!("{}/{}", prefix, relative_path)
+    }
+}
+
+/// Collects the files under `dir` with their paths relative to it, `/` separated.
+fn list_local_files(
+    dir: &Path,
+    relative_dir: &str,
+    files: &mut Vec<(String, std::path::PathBuf)>,
+) -> Result<(), StorageError> {
+    let entries = std::fs::read_dir(dir).map_err(|source| StorageError::Io { source })?;
+    for entry in entries {
+        let entry = entry.map_err(|source| StorageError::Io { source })?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative_path = join_key(relative_dir, &name);
+        let path = entry.path();
+        if path.is_dir() {
+            list_local_files(&path, &relative_path, files)?;
+        } else {
+            files.push((relative_path, path));
+        }
+    }
+    Ok(())
+}
@@ -40,6 +40,11 @@
 //! - `s3` - enable the S3 storage backend to work with Delta Tables in AWS S3.
 //! - `azure` - enable the Azure storage backend to work with Delta Tables in Azure Data Lake Storage Gen2 accounts.
 //! - `datafusion-ext` - enable the `datafusion::datasource::TableProvider` trait implementation for Delta Tables, allowing them to be queried using [DataFusion](https://github.com/apache/arrow/tree/master/rust/datafusion).
+//! - `delta-sharing` - enable the client for reading tables shared through a [Delta Sharing](https://github.com/delta-io/delta-sharing) server.
+//! - `delta-sharing-server` - enable the Delta Sharing server, sharing tables loaded through this crate.
+//! - `server` - build the `delta-server` binary, which serves read-only table metadata over HTTP.
+//! - `maintenance-server` - enable the gRPC maintenance service and build the `delta-maintenanced` binary serving it.
+//! - `bench-utils` - enable the generators of synthetic tables used by the benchmarks.
 
 #![deny(warnings)]
 #![deny(missing_docs)]
@@ -56,11 +61,20 @@ extern crate serde_json;
 extern crate thiserror;
 
 pub mod action;
+pub mod checkpoints;
 mod delta;
 pub mod delta_arrow;
+pub mod lineage;
+pub mod log_export;
+pub mod optimize;
 pub mod partitions;
+pub mod scan;
 mod schema;
 pub mod storage;
+pub mod streaming;
+pub mod table_cache;
+pub mod table_properties;
+pub mod writer;
 
 #[cfg(feature = "datafusion-ext")]
 pub mod delta_datafusion;
@@ -68,9 +82,22 @@ pub mod delta_datafusion;
 #[cfg(feature = "rust-dataframe-ext")]
 mod delta_dataframe;
 
+#[cfg(feature = "delta-sharing")]
+pub mod delta_sharing;
+
+#[cfg(feature = "maintenance-server")]
+pub mod maintenance;
+
+#[cfg(feature = "bench-utils")]
+pub mod bench_utils;
+
+#[cfg(feature = "integration-testing")]
+pub mod integration_testing;
+
 pub use self::delta::*;
 pub use self::partitions::*;
 pub use self::schema::*;
 pub use self::storage::{
-    get_backend_for_uri, parse_uri, StorageBackend, StorageError, Uri, UriError,
+    get_backend_for_table_uri, get_backend_for_uri, parse_uri, StorageBackend, StorageBackendKind,
+    StorageCapabilities, StorageError, TableUri, Uri, UriError,
 };
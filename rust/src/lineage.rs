@@ -0,0 +1,306 @@
+//! [OpenLineage](https://openlineage.io) run events for the commits of Delta Tables, so writes
+//! from this crate show up in lineage systems, e.g. Marquez, next to the ones of Spark jobs.
+//!
+//! [`OpenLineageHook`] is a [`PostCommitHook`] building a `COMPLETE` run event for each commit to
+//! a table, with the table as output dataset. The dataset carries the schema and version facets
+//! and, when the commit recorded Spark's `numOutputRows` and `numOutputBytes` operation metrics,
+//! the output statistics facet. The run carries a `deltaCommit` facet with the version, operation
+//! and metrics of the commit. Events are handed to a [`LineageEmitter`], which sends them to the
+//! lineage backend, e.g. by posting them to its HTTP API.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct PrintEmitter;
+//!
+//! impl deltalake::lineage::LineageEmitter for PrintEmitter {
+//!     fn emit(&self, event: &deltalake::lineage::RunEvent) {
+//!         println!("{}", serde_json::to_string(event).unwrap());
+//!     }
+//! }
+//!
+//! async {
+//!   let hook = deltalake::lineage::OpenLineageHook::new("ingest", "events", Arc::new(PrintEmitter));
+//!   let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+//!       .with_post_commit_hook(Arc::new(hook))
+//!       .load()
+//!       .await
+//!       .unwrap();
+//! };
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::delta::{CommittedVersion, DeltaTable, PostCommitHook};
+use crate::schema::SchemaDataType;
+
+/// The producer of the events, as required by the OpenLineage spec.
+pub const PRODUCER: &str = "https://github.com/delta-io/delta.rs";
+
+const RUN_EVENT_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/1-0-2/OpenLineage.json#/definitions/RunEvent";
+const SCHEMA_FACET_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/facets/1-0-0/SchemaDatasetFacet.json#/$defs/SchemaDatasetFacet";
+const VERSION_FACET_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/facets/1-0-0/DatasetVersionDatasetFacet.json#/$defs/DatasetVersionDatasetFacet";
+const OUTPUT_STATISTICS_FACET_SCHEMA_URL: &str =
+    "https://openlineage.io/spec/facets/1-0-0/OutputStatisticsOutputDatasetFacet.json#/$defs/OutputStatisticsOutputDatasetFacet";
+const DELTA_COMMIT_FACET_SCHEMA_URL: &str =
+    "https://github.com/delta-io/delta.rs#DeltaCommitRunFacet";
+
+/// An OpenLineage run event, serializing to the JSON expected by OpenLineage backends.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    /// The type of the event, `COMPLETE` for commits.
+    pub event_type: String,
+    /// When the event happened, in RFC 3339 format.
+    pub event_time: String,
+    /// The run the event belongs to.
+    pub run: Run,
+    /// The job of the run.
+    pub job: Job,
+    /// The datasets read by the run.
+    pub inputs: Vec<Dataset>,
+    /// The datasets written by the run.
+    pub outputs: Vec<Dataset>,
+    /// The producer of the event.
+    pub producer: String,
+    /// The URL of the schema of the event.
+    #[serde(rename = "schemaURL")]
+    pub schema_url: String,
+}
+
+/// A run of a job, one per commit.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    /// The unique id of the run.
+    pub run_id: String,
+    /// The facets of the run, by name.
+    pub facets: Map<String, Value>,
+}
+
+/// The job committing to the tables.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Job {
+    /// The namespace of the job.
+    pub namespace: String,
+    /// The name of the job, unique in its namespace.
+    pub name: String,
+}
+
+/// A dataset read or written by a run.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Dataset {
+    /// The namespace of the dataset, e.g. `s3://bucket` or `file`.
+    pub namespace: String,
+    /// The name of the dataset, unique in its namespace, e.g. the path of the table.
+    pub name: String,
+    /// The facets of the dataset, by name.
+    pub facets: Map<String, Value>,
+    /// The facets of the dataset only meaningful for outputs, by name.
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub output_facets: Map<String, Value>,
+}
+
+/// Sends run events to a lineage backend. Emitters are called in the commit path, so they
+/// shouldn't block for long, e.g. by queuing the events for a background task to send them.
+pub trait LineageEmitter: Send + Sync + fmt::Debug {
+    /// Sends `event`, handling its own errors.
+    fn emit(&self, event: &RunEvent);
+}
+
+/// A post commit hook emitting an OpenLineage run event for each commit, see the module docs.
+#[derive(Debug)]
+pub struct OpenLineageHook {
+    job_namespace: String,
+    job_name: String,
+    emitter: Arc<dyn LineageEmitter>,
+}
+
+impl OpenLineageHook {
+    /// Creates a hook emitting the events of the given job to `emitter`.
+    pub fn new(
+        job_namespace: impl Into<String>,
+        job_name: impl Into<String>,
+        emitter: Arc<dyn LineageEmitter>,
+    ) -> Self {
+        Self {
+            job_namespace: job_namespace.into(),
+            job_name: job_name.into(),
+            emitter,
+        }
+    }
+
+    /// Builds the run event of the commit to `table`.
+    pub fn run_event(&self, table: &DeltaTable, commit: &CommittedVersion<'_>) -> RunEvent {
+        let mut run_facets = Map::new();
+        run_facets.insert(
+            "deltaCommit".to_string(),
+            json!({
+                "_producer": PRODUCER,
+                "_schemaURL": DELTA_COMMIT_FACET_SCHEMA_URL,
+                "version": commit.version,
+                "operation": commit.operation,
+                "operationMetrics": commit.operation_metrics,
+                "numAttempts": commit.metrics.num_attempts,
+                "numConflicts": commit.metrics.num_conflicts,
+                "durationMs": commit.metrics.commit_duration.as_millis() as u64,
+            }),
+        );
+
+        RunEvent {
+            event_type: "COMPLETE".to_string(),
+            event_time: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            run: Run {
+                run_id: Uuid::new_v4().to_string(),
+                facets: run_facets,
+            },
+            job: Job {
+                namespace: self.job_namespace.clone(),
+                name: self.job_name.clone(),
+            },
+            inputs: Vec::new(),
+            outputs: vec![output_dataset(table, commit)],
+            producer: PRODUCER.to_string(),
+            schema_url: RUN_EVENT_SCHEMA_URL.to_string(),
+        }
+    }
+}
+
+impl PostCommitHook for OpenLineageHook {
+    fn after_commit(&self, table: &DeltaTable, commit: &CommittedVersion<'_>) {
+        self.emitter.emit(&self.run_event(table, commit));
+    }
+}
+
+fn output_dataset(table: &DeltaTable, commit: &CommittedVersion<'_>) -> Dataset {
+    let (namespace, name) = dataset_name(&table.table_path);
+
+    let mut facets = Map::new();
+    if let Ok(schema) = table.get_schema() {
+        let fields = schema
+            .get_fields()
+            .iter()
+            .map(|field| json!({"name": field.get_name(), "type": type_name(field.get_type())}))
+            .collect::<Vec<_>>();
+        facets.insert(
+            "schema".to_string(),
+            json!({
+                "_producer": PRODUCER,
+                "_schemaURL": SCHEMA_FACET_SCHEMA_URL,
+                "fields": fields,
+            }),
+        );
+    }
+    facets.insert(
+        "version".to_string(),
+        json!({
+            "_producer": PRODUCER,
+            "_schemaURL": VERSION_FACET_SCHEMA_URL,
+            "datasetVersion": commit.version.to_string(),
+        }),
+    );
+
+    let mut output_facets = Map::new();
+    let metric = |name: &str| {
+        commit
+            .operation_metrics
+            .and_then(|m| metric_value(m.get(name)?))
+    };
+    if let Some(row_count) = metric("numOutputRows") {
+        let mut statistics = json!({
+            "_producer": PRODUCER,
+            "_schemaURL": OUTPUT_STATISTICS_FACET_SCHEMA_URL,
+            "rowCount": row_count,
+        });
+        if let Some(size) = metric("numOutputBytes") {
+            statistics["size"] = json!(size);
+        }
+        output_facets.insert("outputStatistics".to_string(), statistics);
+    }
+
+    Dataset {
+        namespace,
+        name,
+        facets,
+        output_facets,
+    }
+}
+
+/// Splits a table location into the namespace and name of its dataset, following the naming
+/// conventions of OpenLineage: `s3://bucket/path` is named `path` in the `s3://bucket`
+/// namespace, and local paths are named by their absolute path in the `file` namespace.
+fn dataset_name(table_path: &str) -> (String, String) {
+    let table_path = table_path.trim_end_matches('/');
+    if let Some(scheme_end) = table_path.find("://") {
+        let authority_start = scheme_end + 3;
+        return match table_path[authority_start..].find('/') {
+            Some(i) => (
+                table_path[..authority_start + i].to_string(),
+                table_path[authority_start + i + 1..].to_string(),
+            ),
+            None => (table_path.to_string(), String::new()),
+        };
+    }
+
+    let path = std::path::Path::new(table_path);
+    let path = match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    ("file".to_string(), path.to_string_lossy().into_owned())
+}
+
+fn type_name(data_type: &SchemaDataType) -> &str {
+    match data_type {
+        SchemaDataType::primitive(name) => name,
+        SchemaDataType::r#struct(_) => "struct",
+        SchemaDataType::array(_) => "array",
+        SchemaDataType::map(_) => "map",
+    }
+}
+
+/// Reads a numeric operation metric, which Spark records as a string.
+fn metric_value(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        value => value.as_u64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_name() {
+        assert_eq!(
+            dataset_name("s3://bucket/path/to/table/"),
+            ("s3://bucket".to_string(), "path/to/table".to_string())
+        );
+        assert_eq!(
+            dataset_name("/data/table"),
+            ("file".to_string(), "/data/table".to_string())
+        );
+        let (namespace, name) = dataset_name("./table");
+        assert_eq!(namespace, "file");
+        assert!(std::path::Path::new(&name).is_absolute());
+    }
+
+    #[test]
+    fn test_metric_value() {
+        assert_eq!(metric_value(&json!("42")), Some(42));
+        assert_eq!(metric_value(&json!(42)), Some(42));
+        assert_eq!(metric_value(&json!("many")), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Export of the transaction log of a Delta Table to a local directory, e.g. to attach it to a bug
+//! report or to analyze the history of a table offline.
+//!
+//! [`export_log`] copies the commits, checkpoints and `_last_checkpoint` file of a table into the
+//! `_delta_log` directory of a local output directory. With redaction, file paths and partition
+//! values are replaced with placeholders, column stats are reduced to record and null counts, and
+//! commit infos are stripped of user, notebook and operation details. Parquet checkpoints can't be
+//! redacted, so they are left out of redacted exports, which can then only be replayed when the log
+//! still has all its commits. [`open_table_from_log_dir`] loads the state of a table from an
+//! exported log, without needing its data files.
+//!
+//! ```rust
+//! async {
+//!   let summary = deltalake::log_export::export_log(
+//!       "./tests/data/simple_table",
+//!       "/tmp/simple_table_log",
+//!       true,
+//!   )
+//!   .await
+//!   .unwrap();
+//!   let table = deltalake::log_export::open_table_from_log_dir("/tmp/simple_table_log")
+//!       .await
+//!       .unwrap();
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::delta::{
+    decompressed_log_reader, open_table, ApplyLogError, DeltaTable, DeltaTableError,
+};
+use crate::storage::{self, file::FileStorageBackend, StorageBackend};
+
+/// Keys of the commit infos kept in redacted exports.
+const COMMIT_INFO_KEYS: &[&str] = &[
+    "version",
+    "timestamp",
+    "operation",
+    "readVersion",
+    "isolationLevel",
+    "isBlindAppend",
+    "operationMetrics",
+    "engineInfo",
+];
+
+/// Files written to the output directory by an export.
+#[derive(Debug, Default)]
+pub struct LogExportSummary {
+    /// Names of the exported log files, sorted.
+    pub exported_files: Vec<String>,
+    /// Names of the log files left out of a redacted export because they can't be redacted.
+    pub skipped_files: Vec<String>,
+}
+
+/// Copies the transaction log of the table at `table_uri` into `<output_dir>/_delta_log`, redacting
+/// it when `redact` is true. Fails without overwriting anything when a log file already exists in
+/// the output directory.
+pub async fn export_log(
+    table_uri: &str,
+    output_dir: &str,
+    redact: bool,
+) -> Result<LogExportSummary, DeltaTableError> {
+    lazy_static! {
+        static ref LOG_FILE_REGEX: Regex =
+            Regex::new(r#"^(\d{20}\.[^/\\]+|_last_checkpoint)$"#).unwrap();
+    }
+
+    let storage = storage::get_backend_for_uri(table_uri)?;
+    let log_path = storage.join_path(table_uri, "_delta_log");
+    let mut names = Vec::new();
+    let mut stream = storage.list_objs(&log_path).await?;
+    while let Some(obj_meta) = stream.next().await {
+        let obj_meta = obj_meta?;
+        // only files directly in the log directory, listings of object stores are recursive
+        let name = obj_meta
+            .path
+            .strip_prefix(&log_path)
+            .map(|name| name.trim_start_matches(|c| c == '/' || c == '\\'))
+            .unwrap_or_default();
+        if LOG_FILE_REGEX.is_match(name) {
+            names.push(name.to_string());
+        }
+    }
+    // in version order, so redacted placeholders are numbered in the order they appear in the log
+    names.sort();
+
+    let output = FileStorageBackend::new(output_dir);
+    let output_log_path = output.join_path(output_dir, "_delta_log");
+    let mut redactor = Redactor::default();
+    let mut summary = LogExportSummary::default();
+    for name in names {
+        let is_commit = name.ends_with(".json") || name.ends_with(".json.gz");
+        if redact && !is_commit {
+            summary.skipped_files.push(name);
+            continue;
+        }
+        let mut obj = storage
+            .get_obj(&storage.join_path(&log_path, &name))
+            .await?;
+        if redact {
+            obj = redactor.redact_entry(&obj)?;
+        }
+        output
+            .put_obj(&output.join_path(&output_log_path, &name), &obj)
+            .await?;
+        summary.exported_files.push(name);
+    }
+
+    Ok(summary)
+}
+
+/// Loads the latest version of a table from an exported log, given either the output directory of
+/// [`export_log`] or the `_delta_log` directory in it. Only the log is read, so the data files
+/// don't need to exist.
+pub async fn open_table_from_log_dir(log_dir: &str) -> Result<DeltaTable, DeltaTableError> {
+    let log_dir = log_dir.trim_end_matches(|c| c == '/' || c == '\\');
+    let path = Path::new(log_dir);
+    let table_path = match path.file_name() {
+        Some(name) if name == "_delta_log" => path
+            .parent()
+            .and_then(Path::to_str)
+            .filter(|parent| !parent.is_empty())
+            .unwrap_or("."),
+        _ => log_dir,
+    };
+    open_table(table_path).await
+}
+
+/// Replaces the identifying parts of the actions of log entries. A path or partition value gets
+/// the same placeholder in all the entries of an export, so the redacted log replays to a table
+/// with the same layout.
+#[derive(Default)]
+struct Redactor {
+    paths: HashMap<String, String>,
+    values: HashMap<String, String>,
+}
+
+impl Redactor {
+    /// Redacts the actions of a log entry, decompressing it if needed. The redacted entry is
+    /// written uncompressed, which readers handle even under a `.json.gz` name.
+    fn redact_entry(&mut self, log_bytes: &[u8]) -> Result<Vec<u8>, DeltaTableError> {
+        let reader = decompressed_log_reader(BufReader::new(log_bytes))
+            .map_err(|e| DeltaTableError::from(ApplyLogError::from(e)))?;
+
+        let mut redacted = Vec::new();
+        for line in reader.lines() {
+            let line = line.map_err(|e| DeltaTableError::from(ApplyLogError::from(e)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut action: Value = serde_json::from_str(&line)?;
+            if let Value::Object(action) = &mut action {
+                self.redact_action(action);
+            }
+            serde_json::to_writer(&mut redacted, &action)?;
+            redacted.push(b'\n');
+        }
+
+        Ok(redacted)
+    }
+
+    fn redact_action(&mut self, action: &mut Map<String, Value>) {
+        for key in &["add", "remove", "cdc"] {
+            if let Some(Value::Object(file)) = action.get_mut(*key) {
+                self.redact_file(file);
+            }
+        }
+        if let Some(Value::Object(metadata)) = action.get_mut("metaData") {
+            metadata.remove("name");
+            metadata.remove("description");
+        }
+        if let Some(Value::Object(commit_info)) = action.get_mut("commitInfo") {
+            let keys = commit_info
+                .keys()
+                .filter(|key| !COMMIT_INFO_KEYS.contains(&key.as_str()))
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                commit_info.remove(&key);
+            }
+        }
+    }
+
+    fn redact_file(&mut self, file: &mut Map<String, Value>) {
+        if let Some(Value::String(path)) = file.get_mut("path") {
+            let next_id = self.paths.len();
+            *path = self
+                .paths
+                .entry(path.clone())
+                .or_insert_with(|| match Path::new(path.as_str()).extension() {
+                    Some(extension) => {
+                        format!("redacted-{:05}.{}", next_id, extension.to_string_lossy())
+                    }
+                    None => format!("redacted-{:05}", next_id),
+                })
+                .clone();
+        }
+        if let Some(Value::Object(partition_values)) = file.get_mut("partitionValues") {
+            for value in partition_values.values_mut() {
+                if let Value::String(value) = value {
+                    let next_id = self.values.len();
+                    *value = self
+                        .values
+                        .entry(value.clone())
+                        .or_insert_with(|| format!("value-{}", next_id))
+                        .clone();
+                }
+            }
+        }
+        if let Some(stats) = file.get_mut("stats") {
+            *stats = redact_stats(stats);
+        }
+        file.remove("tags");
+    }
+}
+
+/// Keeps the record and null counts of the JSON-encoded stats of a file, emptying the min and max
+/// values of its columns.
+fn redact_stats(stats: &Value) -> Value {
+    let stats = match stats
+        .as_str()
+        .and_then(|stats| serde_json::from_str::<Map<String, Value>>(stats).ok())
+    {
+        Some(stats) => stats,
+        None => return Value::Null,
+    };
+    let redacted = stats
+        .into_iter()
+        .filter_map(|(key, value)| match key.as_str() {
+            "numRecords" | "nullCount" => Some((key, value)),
+            "minValues" | "maxValues" => Some((key, Value::Object(Map::new()))),
+            _ => None,
+        })
+        .collect::<Map<String, Value>>();
+    Value::String(Value::Object(redacted).to_string())
+}
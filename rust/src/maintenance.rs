@@ -0,0 +1,370 @@
+//! gRPC service running maintenance operations on a set of configured tables, so operators can
+//! schedule them centrally instead of embedding them in every pipeline.
+//!
+//! The `Maintenance` service of `proto/maintenance.proto` is implemented by
+//! [`MaintenanceService`] for the tables of a [`MaintenanceServerConfig`], which requests refer
+//! to by name. Each call opens the table at its latest version. Calls are checked by a
+//! [`MaintenanceAuthorizer`]; servers configured with bearer tokens only accept calls with an
+//! `authorization: Bearer <token>` metadata entry holding one of them.
+//!
+//! Optimize only plans the compaction of the table, see the [`optimize`](crate::optimize) module,
+//! and Checkpoint is answered with `UNIMPLEMENTED` until this crate writes checkpoints.
+//!
+//! ```rust,no_run
+//! async {
+//!   let config: deltalake::maintenance::MaintenanceServerConfig =
+//!       serde_json::from_slice(&std::fs::read("./maintenance-server.json").unwrap()).unwrap();
+//!   let service = deltalake::maintenance::MaintenanceService::new(config);
+//!   service.serve(([127, 0, 0, 1], 50051).into()).await.unwrap();
+//! };
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status};
+
+use crate::delta::{open_table, DeltaTable, DeltaTableError};
+use crate::optimize::CompactionPlan;
+use crate::partitions::PartitionFilter;
+use crate::storage::StorageError;
+
+use self::proto::maintenance_server::{Maintenance, MaintenanceServer};
+use self::proto::*;
+
+/// Messages and service traits generated from `proto/maintenance.proto`.
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("deltalake.maintenance.v1");
+}
+
+/// Retention period of vacuums that don't set one, the shortest one `DeltaTable::vacuum` allows.
+const DEFAULT_RETENTION_HOURS: u64 = 168;
+
+/// Age of the temporary commit files deleted by metadata cleanups that don't set one.
+const DEFAULT_TEMP_COMMIT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Tables exposed by a [`MaintenanceService`], and the bearer tokens of its callers.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceServerConfig {
+    /// The tables maintenance can be run on.
+    pub tables: Vec<MaintenanceTableConfig>,
+    /// The bearer tokens accepted by the server. Calls aren't authenticated when there are none,
+    /// unless an authorizer is set with `MaintenanceService::with_authorizer`.
+    #[serde(default)]
+    pub bearer_tokens: Vec<String>,
+}
+
+impl fmt::Debug for MaintenanceServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print secrets
+        f.debug_struct("MaintenanceServerConfig")
+            .field("tables", &self.tables)
+            .field(
+                "bearer_tokens",
+                &format!("<{} tokens>", self.bearer_tokens.len()),
+            )
+            .finish()
+    }
+}
+
+/// A table exposed by a [`MaintenanceService`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaintenanceTableConfig {
+    /// The name requests refer to the table by.
+    pub name: String,
+    /// The uri the table is loaded from.
+    pub location: String,
+}
+
+/// The methods of the maintenance service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaintenanceMethod {
+    /// Deletes stale data files.
+    Vacuum,
+    /// Writes a checkpoint.
+    Checkpoint,
+    /// Plans the compaction of small files.
+    Optimize,
+    /// Deletes leftover temporary commit files.
+    CleanupMetadata,
+    /// Checks the active files against the storage.
+    Verify,
+}
+
+/// Decides whether a call to the maintenance service is allowed, e.g. from the identity found in
+/// its metadata.
+pub trait MaintenanceAuthorizer: Send + Sync + fmt::Debug {
+    /// Returns the status to answer instead of running `method` on the table named `table`,
+    /// usually `Status::unauthenticated` or `Status::permission_denied`.
+    fn authorize(
+        &self,
+        method: MaintenanceMethod,
+        table: &str,
+        metadata: &MetadataMap,
+    ) -> Result<(), Status>;
+}
+
+/// Allows the calls with an `authorization: Bearer <token>` metadata entry holding one of the
+/// given tokens, on all tables.
+pub struct BearerTokenAuthorizer {
+    tokens: Vec<String>,
+}
+
+impl BearerTokenAuthorizer {
+    /// Creates an authorizer accepting the given tokens.
+    pub fn new(tokens: Vec<String>) -> Self {
+        Self { tokens }
+    }
+}
+
+impl MaintenanceAuthorizer for BearerTokenAuthorizer {
+    fn authorize(
+        &self,
+        _method: MaintenanceMethod,
+        _table: &str,
+        metadata: &MetadataMap,
+    ) -> Result<(), Status> {
+        let token = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match token {
+            Some(token) if self.tokens.iter().any(|t| t == token) => Ok(()),
+            _ => Err(Status::unauthenticated("Missing or invalid bearer token")),
+        }
+    }
+}
+
+impl fmt::Debug for BearerTokenAuthorizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BearerTokenAuthorizer <{} tokens>", self.tokens.len())
+    }
+}
+
+/// Serves the maintenance operations of the tables of a [`MaintenanceServerConfig`].
+#[derive(Debug)]
+pub struct MaintenanceService {
+    tables: Vec<MaintenanceTableConfig>,
+    authorizer: Option<Arc<dyn MaintenanceAuthorizer>>,
+}
+
+impl MaintenanceService {
+    /// Creates a service for the tables of the given config, accepting its bearer tokens.
+    pub fn new(config: MaintenanceServerConfig) -> Self {
+        let mut service = Self {
+            tables: config.tables,
+            authorizer: None,
+        };
+        if !config.bearer_tokens.is_empty() {
+            let authorizer = BearerTokenAuthorizer::new(config.bearer_tokens);
+            service.authorizer = Some(Arc::new(authorizer));
+        }
+
+        service
+    }
+
+    /// Checks the calls with the given authorizer instead of the bearer tokens of the config.
+    pub fn with_authorizer(mut self, authorizer: Arc<dyn MaintenanceAuthorizer>) -> Self {
+        self.authorizer = Some(authorizer);
+        self
+    }
+
+    /// Listens on `addr` and serves requests until the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+        tonic::transport::Server::builder()
+            .add_service(MaintenanceServer::new(self))
+            .serve(addr)
+            .await
+    }
+
+    /// Authorizes the call and opens the table it targets at its latest version.
+    async fn open(
+        &self,
+        method: MaintenanceMethod,
+        table: &str,
+        metadata: &MetadataMap,
+    ) -> Result<DeltaTable, Status> {
+        if let Some(authorizer) = &self.authorizer {
+            authorizer.authorize(method, table, metadata)?;
+        }
+        let config = self
+            .tables
+            .iter()
+            .find(|config| config.name == table)
+            .ok_or_else(|| Status::not_found(format!("Table {} doesn't exist", table)))?;
+        info!("Running {:?} on table {}", method, table);
+
+        open_table(&config.location).await.map_err(to_status)
+    }
+}
+
+#[tonic::async_trait]
+impl Maintenance for MaintenanceService {
+    async fn vacuum(
+        &self,
+        request: Request<VacuumRequest>,
+    ) -> Result<Response<VacuumResponse>, Status> {
+        let mut table = self
+            .open(
+                MaintenanceMethod::Vacuum,
+                &request.get_ref().table,
+                request.metadata(),
+            )
+            .await?;
+        let request = request.into_inner();
+        let retention_hours = match request.retention_hours {
+            0 => DEFAULT_RETENTION_HOURS,
+            retention_hours => retention_hours,
+        };
+        let deleted_files = table
+            .vacuum(retention_hours, request.dry_run)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(VacuumResponse {
+            version: table.version,
+            deleted_files,
+        }))
+    }
+
+    async fn checkpoint(
+        &self,
+        request: Request<CheckpointRequest>,
+    ) -> Result<Response<CheckpointResponse>, Status> {
+        self.open(
+            MaintenanceMethod::Checkpoint,
+            &request.get_ref().table,
+            request.metadata(),
+        )
+        .await?;
+
+        Err(Status::unimplemented("Writing checkpoints isn't supported"))
+    }
+
+    async fn optimize(
+        &self,
+        request: Request<OptimizeRequest>,
+    ) -> Result<Response<OptimizeResponse>, Status> {
+        let table = self
+            .open(
+                MaintenanceMethod::Optimize,
+                &request.get_ref().table,
+                request.metadata(),
+            )
+            .await?;
+        let request = request.into_inner();
+        let partition_filters = request
+            .partition_filters
+            .iter()
+            .map(|filter| {
+                let (key, op) = (filter.key.as_str(), filter.op.as_str());
+                match op {
+                    "in" | "not in" => {
+                        PartitionFilter::try_from((key, op, filter.value.split(',').collect()))
+                    }
+                    _ => PartitionFilter::try_from((key, op, filter.value.as_str())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(to_status)?;
+        let plan =
+            CompactionPlan::from_state(&table.snapshot(), request.target_size, &partition_filters)
+                .map_err(to_status)?;
+
+        Ok(Response::new(OptimizeResponse {
+            read_version: plan.read_version,
+            groups: plan
+                .groups
+                .into_iter()
+                .map(|group| CompactionGroup {
+                    partition_values: group
+                        .partition_values
+                        .into_iter()
+                        .filter_map(|(column, value)| Some((column, value?)))
+                        .collect(),
+                    files: group.files.into_iter().map(|add| add.path).collect(),
+                    total_size: group.total_size,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn cleanup_metadata(
+        &self,
+        request: Request<CleanupMetadataRequest>,
+    ) -> Result<Response<CleanupMetadataResponse>, Status> {
+        let table = self
+            .open(
+                MaintenanceMethod::CleanupMetadata,
+                &request.get_ref().table,
+                request.metadata(),
+            )
+            .await?;
+        let request = request.into_inner();
+        let max_age = match request.max_age_seconds {
+            0 => DEFAULT_TEMP_COMMIT_MAX_AGE,
+            max_age_seconds => Duration::from_secs(max_age_seconds),
+        };
+        let deleted_files = table
+            .cleanup_temp_commit_files(max_age, request.dry_run)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(CleanupMetadataResponse { deleted_files }))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let table = self
+            .open(
+                MaintenanceMethod::Verify,
+                &request.get_ref().table,
+                request.metadata(),
+            )
+            .await?;
+        let sample_size = match request.get_ref().sample_size {
+            0 => None,
+            sample_size => Some(sample_size as usize),
+        };
+        let mismatches = table.verify_files(sample_size).await.map_err(to_status)?;
+
+        Ok(Response::new(VerifyResponse {
+            version: table.version,
+            mismatches: mismatches
+                .into_iter()
+                .map(|mismatch| FileSizeMismatch {
+                    path: mismatch.path,
+                    expected_size: mismatch.expected_size,
+                    actual_size: mismatch.actual_size.unwrap_or(-1),
+                })
+                .collect(),
+        }))
+    }
+}
+
+fn to_status(err: DeltaTableError) -> Status {
+    match &err {
+        DeltaTableError::NotATable
+        | DeltaTableError::StorageError {
+            source: StorageError::NotFound,
+        } => Status::not_found(err.to_string()),
+        DeltaTableError::InvalidVacuumRetentionPeriod
+        | DeltaTableError::InvalidTargetSize(_)
+        | DeltaTableError::InvalidPartitionFilter { .. } => {
+            Status::invalid_argument(err.to_string())
+        }
+        DeltaTableError::Timeout { .. } => Status::deadline_exceeded(err.to_string()),
+        DeltaTableError::Cancelled { .. } => Status::cancelled(err.to_string()),
+        _ => Status::internal(err.to_string()),
+    }
+}
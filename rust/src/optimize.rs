@@ -0,0 +1,152 @@
+//! Planning of OPTIMIZE jobs, which compact the small files of a Delta Table into larger ones.
+//!
+//! [`CompactionPlan::from_state`] bin-packs the small files of each partition of a snapshot into
+//! [`CompactionGroup`]s of about the target size. The plan is serializable, so an external
+//! scheduler can compute it once and hand the groups out to workers, which rewrite each group into
+//! a single file and commit it on its own, with the remove actions of
+//! [`CompactionGroup::remove_actions`] and an `Optimize` operation. Groups can be executed over
+//! several commits; [`CompactionGroup::is_applicable`] tells whether a group can still be
+//! committed against a later snapshot.
+//!
+//! ```rust
+//! async {
+//!   let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned").await.unwrap();
+//!   let plan = deltalake::optimize::CompactionPlan::from_state(
+//!       &table.snapshot(),
+//!       128 * 1024 * 1024,
+//!       &[deltalake::PartitionFilter {
+//!           key: "year",
+//!           value: deltalake::PartitionValue::Equal("2021"),
+//!       }],
+//!   )
+//!   .unwrap();
+//!   for group in plan.groups.iter() {
+//!       let files = group.files.iter().map(|add| add.path.as_str()).collect::<Vec<_>>();
+//!   }
+//! };
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::{self, Action};
+use crate::delta::{DeltaTableError, DeltaTableSnapshot};
+use crate::partitions::PartitionFilter;
+use crate::schema::{DeltaDataTypeLong, DeltaDataTypeTimestamp, DeltaDataTypeVersion};
+
+/// Files of a single partition to rewrite together into one file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompactionGroup {
+    /// The partition values shared by the files of the group, `None` for null values.
+    pub partition_values: HashMap<String, Option<String>>,
+    /// The add actions of the files to rewrite, largest first.
+    pub files: Vec<action::Add>,
+    /// The sum of the sizes of the files, in bytes.
+    pub total_size: DeltaDataTypeLong,
+}
+
+impl CompactionGroup {
+    /// Returns the remove actions to commit along with the add action of the rewritten file.
+    /// Since compaction doesn't change the data, the actions are recorded with `dataChange`
+    /// false, so streaming readers can skip them.
+    pub fn remove_actions(&self, deletion_timestamp: DeltaDataTypeTimestamp) -> Vec<Action> {
+        self.files
+            .iter()
+            .map(|add| Action::remove(add.to_remove(deletion_timestamp, false)))
+            .collect()
+    }
+
+    /// Whether all the files of the group are still active in the snapshot, i.e. no commit since
+    /// the plan was made removed any of them, so the group can still be committed.
+    pub fn is_applicable(&self, snapshot: &DeltaTableSnapshot) -> bool {
+        let active_files = snapshot.get_files_iter().collect::<HashSet<&str>>();
+        self.files
+            .iter()
+            .all(|add| active_files.contains(add.path.as_str()))
+    }
+}
+
+/// Groups of small files to compact, planned from a snapshot of a table.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompactionPlan {
+    /// The version of the snapshot the plan was made from.
+    pub read_version: DeltaDataTypeVersion,
+    /// The size in bytes the rewritten files should have.
+    pub target_size: DeltaDataTypeLong,
+    /// The groups of files to rewrite, sorted by partition values.
+    pub groups: Vec<CompactionGroup>,
+}
+
+impl CompactionPlan {
+    /// Plans the compaction of the files of the snapshot matching the partition filters. Files
+    /// that are at least `target_size` bytes large are left alone. The others are bin-packed per
+    /// partition with a first-fit decreasing heuristic into groups of at most `target_size`
+    /// bytes; groups of a single file would rewrite it as is, so they are left out of the plan.
+    pub fn from_state(
+        snapshot: &DeltaTableSnapshot,
+        target_size: DeltaDataTypeLong,
+        partition_filters: &[PartitionFilter<&str>],
+    ) -> Result<Self, DeltaTableError> {
+        if target_size <= 0 {
+            return Err(DeltaTableError::InvalidTargetSize(target_size));
+        }
+        let partition_columns = &snapshot.get_metadata()?.partition_columns;
+        let files = if partition_filters.is_empty() {
+            snapshot.get_actions().iter().collect()
+        } else {
+            snapshot.get_actions_by_partitions(partition_filters)?
+        };
+
+        let mut partitions: BTreeMap<Vec<Option<&str>>, Vec<&action::Add>> = BTreeMap::new();
+        for add in files.into_iter().filter(|add| add.size < target_size) {
+            let key = partition_columns
+                .iter()
+                .map(|column| add.get_partition_value(column))
+                .collect();
+            partitions.entry(key).or_insert_with(Vec::new).push(add);
+        }
+
+        let mut groups = Vec::new();
+        for (values, mut files) in partitions {
+            files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+            let mut bins: Vec<(DeltaDataTypeLong, Vec<&action::Add>)> = Vec::new();
+            for add in files {
+                match bins
+                    .iter_mut()
+                    .find(|(size, _)| size + add.size <= target_size)
+                {
+                    Some((size, bin)) => {
+                        *size += add.size;
+                        bin.push(add);
+                    }
+                    None => bins.push((add.size, vec![add])),
+                }
+            }
+
+            let partition_values = partition_columns
+                .iter()
+                .cloned()
+                .zip(values.iter().map(|value| value.map(str::to_string)))
+                .collect::<HashMap<_, _>>();
+            groups.extend(bins.into_iter().filter(|(_, bin)| bin.len() > 1).map(
+                |(total_size, bin)| CompactionGroup {
+                    partition_values: partition_values.clone(),
+                    files: bin.into_iter().cloned().collect(),
+                    total_size,
+                },
+            ));
+        }
+
+        Ok(Self {
+            read_version: snapshot.version(),
+            target_size,
+            groups,
+        })
+    }
+
+    /// Returns the number of files the plan rewrites.
+    pub fn num_files(&self) -> usize {
+        self.groups.iter().map(|group| group.files.len()).sum()
+    }
+}
@@ -1,9 +1,20 @@
 //! Delta Table partition handling logic.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime};
 
 use crate::DeltaTableError;
 
+/// Name given to the partition directories of null partition values, as done by Hive and Spark.
+pub const NULL_PARTITION_VALUE_DATA_PATH: &str = "__HIVE_DEFAULT_PARTITION__";
+
+const DATE_PARTITION_VALUE_FORMAT: &str = "%Y-%m-%d";
+// fractional seconds are appended separately, since Spark leaves out their trailing zeros
+const TIMESTAMP_PARTITION_VALUE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
 /// A Enum used for selecting the partition value operation when filtering a DeltaTable partition.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PartitionValue<T> {
@@ -15,6 +26,24 @@ pub enum PartitionValue<T> {
     In(Vec<T>),
     /// The partition values with the not in operator
     NotIn(Vec<T>),
+    /// Matches null partition values
+    IsNull,
+    /// Matches non null partition values
+    IsNotNull,
+}
+
+impl<T> PartitionValue<T> {
+    /// Converts the values of the operator with `f`, e.g. to own borrowed values.
+    pub(crate) fn map<'a, U>(&'a self, f: impl Fn(&'a T) -> U) -> PartitionValue<U> {
+        match self {
+            PartitionValue::Equal(value) => PartitionValue::Equal(f(value)),
+            PartitionValue::NotEqual(value) => PartitionValue::NotEqual(f(value)),
+            PartitionValue::In(values) => PartitionValue::In(values.iter().map(f).collect()),
+            PartitionValue::NotIn(values) => PartitionValue::NotIn(values.iter().map(f).collect()),
+            PartitionValue::IsNull => PartitionValue::IsNull,
+            PartitionValue::IsNotNull => PartitionValue::IsNotNull,
+        }
+    }
 }
 
 /// A Struct used for filtering a DeltaTable partition by key and value.
@@ -29,16 +58,22 @@ pub struct PartitionFilter<'a, T> {
 /// Partition filters methods for filtering the DeltaTable partitions.
 impl<'a> PartitionFilter<'a, &str> {
     /// Indicates if a DeltaTable partition matches with the partition filter by key and value.
+    /// Like in SQL, null partition values only match `IsNull` filters.
     pub fn match_partition(&self, partition: &DeltaTablePartition<'a>) -> bool {
         if self.key != partition.key {
             return false;
         }
 
-        match &self.value {
-            PartitionValue::Equal(value) => value == &partition.value,
-            PartitionValue::NotEqual(value) => value != &partition.value,
-            PartitionValue::In(value) => value.contains(&partition.value),
-            PartitionValue::NotIn(value) => !value.contains(&partition.value),
+        match (&self.value, &partition.value) {
+            (PartitionValue::IsNull, value) => value.is_none(),
+            (PartitionValue::IsNotNull, value) => value.is_some(),
+            (_, None) => false,
+            (PartitionValue::Equal(value), Some(partition_value)) => value == partition_value,
+            (PartitionValue::NotEqual(value), Some(partition_value)) => value != partition_value,
+            (PartitionValue::In(value), Some(partition_value)) => value.contains(partition_value),
+            (PartitionValue::NotIn(value), Some(partition_value)) => {
+                !value.contains(partition_value)
+            }
         }
     }
 
@@ -51,6 +86,30 @@ impl<'a> PartitionFilter<'a, &str> {
     }
 }
 
+/// Formats the filter as a SQL predicate, e.g. `year = '2021'` or `month IN ('1', '2')`, as
+/// recorded in the `predicate` of operations.
+impl fmt::Display for PartitionFilter<'_, &str> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quote = |value: &str| format!("'{}'", value.replace('\'', "''"));
+        let list = |values: &[&str]| {
+            values
+                .iter()
+                .copied()
+                .map(quote)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match &self.value {
+            PartitionValue::Equal(value) => write!(f, "{} = {}", self.key, quote(value)),
+            PartitionValue::NotEqual(value) => write!(f, "{} != {}", self.key, quote(value)),
+            PartitionValue::In(values) => write!(f, "{} IN ({})", self.key, list(values)),
+            PartitionValue::NotIn(values) => write!(f, "{} NOT IN ({})", self.key, list(values)),
+            PartitionValue::IsNull => write!(f, "{} IS NULL", self.key),
+            PartitionValue::IsNotNull => write!(f, "{} IS NOT NULL", self.key),
+        }
+    }
+}
+
 /// Create a PartitionFilter from a filter Tuple with the structure (key, operation, value).
 impl<'a, T: std::fmt::Debug> TryFrom<(&'a str, &str, T)> for PartitionFilter<'a, T> {
     type Error = DeltaTableError;
@@ -102,12 +161,13 @@ impl<'a, T: std::fmt::Debug> TryFrom<(&'a str, &str, Vec<T>)> for PartitionFilte
 pub struct DeltaTablePartition<'a> {
     /// The key of the DeltaTable partition.
     pub key: &'a str,
-    /// The value of the DeltaTable partition.
-    pub value: &'a str,
+    /// The value of the DeltaTable partition, `None` for null partition values.
+    pub value: Option<&'a str>,
 }
 
 /// Create a DeltaTable partition from a HivePartition string.
-/// A HivePartition string is represented by a "key=value" format.
+/// A HivePartition string is represented by a "key=value" format, where the value of null
+/// partitions is `__HIVE_DEFAULT_PARTITION__`.
 impl<'a> TryFrom<&'a str> for DeltaTablePartition<'a> {
     type Error = DeltaTableError;
 
@@ -118,7 +178,8 @@ impl<'a> TryFrom<&'a str> for DeltaTablePartition<'a> {
         match partition_splitted {
             partition_splitted if partition_splitted.len() == 2 => Ok(DeltaTablePartition {
                 key: &partition_splitted[0],
-                value: &partition_splitted[1],
+                value: Some(partition_splitted[1])
+                    .filter(|value| *value != NULL_PARTITION_VALUE_DATA_PATH),
             }),
             _ => Err(DeltaTableError::PartitionError {
                 partition: partition.to_string(),
@@ -126,3 +187,158 @@ impl<'a> TryFrom<&'a str> for DeltaTablePartition<'a> {
         }
     }
 }
+
+fn needs_escaping(c: char) -> bool {
+    matches!(
+        c,
+        '\u{01}'
+            ..='\u{1F}'
+                | '"'
+                | '#'
+                | '%'
+                | '\''
+                | '*'
+                | '/'
+                | ':'
+                | '='
+                | '?'
+                | '\\'
+                | '\u{7F}'
+                | '{'
+                | '['
+                | ']'
+                | '^'
+    )
+}
+
+/// Escapes a partition column name or value for use in a `key=value` partition directory name,
+/// the same way Spark does: control characters and `"#%'*/:=?\{[]^` are replaced by `%` and
+/// their uppercase hex code, e.g. `a/b` becomes `a%2Fb`. Other characters, including spaces and
+/// non-ASCII characters, are kept as they are.
+///
+/// Only directory names are escaped, the `partitionValues` of add actions record the unescaped
+/// values.
+pub fn escape_partition_path_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if needs_escaping(c) {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Returns the relative directory of the data files of a partition, with one `key=value`
+/// directory per partition column, e.g. `year=2021/month=__HIVE_DEFAULT_PARTITION__`. Names and
+/// values are escaped with [`escape_partition_path_name`], and null or missing values are
+/// replaced by [`NULL_PARTITION_VALUE_DATA_PATH`].
+pub fn partition_path(
+    partition_columns: &[String],
+    partition_values: &HashMap<String, Option<String>>,
+) -> String {
+    partition_columns
+        .iter()
+        .map(|column| {
+            let value = match partition_values.get(column).and_then(|v| v.as_deref()) {
+                Some(value) if !value.is_empty() => escape_partition_path_name(value),
+                _ => NULL_PARTITION_VALUE_DATA_PATH.to_string(),
+            };
+            format!("{}={}", escape_partition_path_name(column), value)
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats the partition value of a date column the way Spark does, e.g. `2021-01-01`.
+pub fn format_date_partition_value(date: NaiveDate) -> String {
+    date.format(DATE_PARTITION_VALUE_FORMAT).to_string()
+}
+
+/// Formats the partition value of a timestamp column the way Spark does, e.g.
+/// `2021-01-01 00:00:00` or `2021-01-01 12:30:00.25`, with microsecond precision and without
+/// trailing zeros in the fractional seconds. Timestamps are formatted in UTC, so tables written
+/// by Spark must use a UTC session time zone for the values to match. In partition directories,
+/// the colons are escaped like any other value, e.g. `ts=2021-01-01 00%3A00%3A00`.
+pub fn format_timestamp_partition_value(timestamp: NaiveDateTime) -> String {
+    let mut value = timestamp
+        .format(TIMESTAMP_PARTITION_VALUE_FORMAT)
+        .to_string();
+    let micros = timestamp.timestamp_subsec_micros();
+    if micros > 0 {
+        value.push('.');
+        value.push_str(format!("{:06}", micros).trim_end_matches('0'));
+    }
+    value
+}
+
+/// Parses a date partition value formatted by [`format_date_partition_value`].
+pub fn parse_date_partition_value(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_PARTITION_VALUE_FORMAT).ok()
+}
+
+/// Parses a timestamp partition value formatted by [`format_timestamp_partition_value`], with or
+/// without fractional seconds.
+pub fn parse_timestamp_partition_value(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, &format!("{}%.f", TIMESTAMP_PARTITION_VALUE_FORMAT)).ok()
+}
+
+/// Reverts [`escape_partition_path_name`]. Invalid escape sequences are kept as they are.
+pub fn unescape_partition_path_name(name: &str) -> String {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(idx) = rest.find('%') {
+        unescaped.push_str(&rest[..idx]);
+        let code = rest
+            .get(idx + 1..idx + 3)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match code {
+            Some(code) => {
+                unescaped.push(char::from(code));
+                rest = &rest[idx + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Infers the partition values of a data file from the `key=value` directories of its relative
+/// path, e.g. `year=2021/month=__HIVE_DEFAULT_PARTITION__/part-00000.parquet`. Names and values
+/// are unescaped with [`unescape_partition_path_name`], and [`NULL_PARTITION_VALUE_DATA_PATH`] is
+/// read as a null value. Returns `None` when the path has no directory for one of the partition
+/// columns.
+pub fn partition_values_from_path(
+    path: &str,
+    partition_columns: &[String],
+) -> Option<HashMap<String, Option<String>>> {
+    let mut directories = path.split('/').collect::<Vec<_>>();
+    directories.pop();
+    let path_values = directories
+        .into_iter()
+        .filter_map(|directory| {
+            let mut parts = directory.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => Some((unescape_partition_path_name(key), value)),
+                _ => None,
+            }
+        })
+        .collect::<HashMap<_, _>>();
+
+    partition_columns
+        .iter()
+        .map(|column| {
+            let value = path_values.get(column)?;
+            let value = Some(*value)
+                .filter(|value| *value != NULL_PARTITION_VALUE_DATA_PATH)
+                .map(unescape_partition_path_name);
+            Some((column.clone(), value))
+        })
+        .collect()
+}
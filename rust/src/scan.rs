@@ -0,0 +1,227 @@
+//! Serializable scan tasks for reading a Delta Table snapshot from distributed workers.
+//!
+//! A coordinator loads the table once and splits the data files of the loaded state into
+//! [`DeltaScanTask`]s. Tasks can be serialized and shipped to remote executors (e.g. Ballista),
+//! which decode them into Arrow `RecordBatch`es with [`read_scan_task`] without loading the
+//! transaction log again. Partition columns aren't stored in the data files, so they are decoded
+//! from the partition values of the tasks and appended to the batches.
+//!
+//! ```rust
+//! async {
+//!   let table = deltalake::open_table("./tests/data/simple_table").await.unwrap();
+//!   let tasks = table.get_scan_tasks(None).unwrap();
+//!   let backend = deltalake::get_backend_for_uri(&table.table_path).unwrap();
+//!   for task in tasks.iter() {
+//!       let batches = deltalake::scan::read_scan_task(task, backend.as_ref(), 1024)
+//!           .await
+//!           .unwrap();
+//!   }
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, ArrayRef, BooleanArray, Date32Array, Int64Array};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::reader::SerializedFileReader;
+use parquet::file::serialized_reader::SliceableCursor;
+use serde::{Deserialize, Serialize};
+
+use crate::delta::{DeltaTable, DeltaTableError};
+use crate::partitions::{parse_date_partition_value, parse_timestamp_partition_value};
+use crate::schema::{DeltaDataTypeLong, Schema};
+use crate::storage::StorageBackend;
+use crate::writer::default_value_array;
+
+/// Byte range of a data file covered by a scan task. A row group belongs to the range that
+/// contains its first byte, so splitting a file into adjacent ranges never reads a row group
+/// twice.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileRange {
+    /// Offset of the first byte covered by the range (inclusive).
+    pub start: DeltaDataTypeLong,
+    /// Offset of the last byte covered by the range (exclusive).
+    pub end: DeltaDataTypeLong,
+}
+
+/// A unit of work for reading part of a Delta Table snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeltaScanTask {
+    /// Full path of the data file to read.
+    pub path: String,
+    /// Byte ranges of the data file to read. An empty list means the whole file.
+    pub ranges: Vec<FileRange>,
+    /// The partition values of the data file, taken from its add action.
+    pub partition_values: HashMap<String, Option<String>>,
+    /// Schema of the table the data file belongs to.
+    pub schema: Schema,
+}
+
+impl DeltaTable {
+    /// Splits the files in the loaded state into serializable scan tasks. When `split_size` is
+    /// provided, files larger than `split_size` bytes are split into multiple tasks covering
+    /// adjacent byte ranges.
+    pub fn get_scan_tasks(
+        &self,
+        split_size: Option<DeltaDataTypeLong>,
+    ) -> Result<Vec<DeltaScanTask>, DeltaTableError> {
+        let schema = self.get_schema()?;
+
+        Ok(self
+            .get_actions()
+            .iter()
+            .zip(self.get_file_paths())
+            .flat_map(|(add, path)| {
+                let ranges = match split_size {
+                    Some(split_size) if split_size > 0 && add.size > split_size => {
+                        let mut ranges = Vec::new();
+                        let mut start = 0;
+                        while start < add.size {
+                            let end = std::cmp::min(start + split_size, add.size);
+                            ranges.push(vec![FileRange { start, end }]);
+                            start = end;
+                        }
+                        ranges
+                    }
+                    _ => vec![vec![]],
+                };
+
+                ranges.into_iter().map(move |ranges| DeltaScanTask {
+                    path: path.clone(),
+                    ranges,
+                    partition_values: add.partitionValues.clone(),
+                    schema: schema.clone(),
+                })
+            })
+            .collect())
+    }
+}
+
+fn row_group_offset(row_group: &RowGroupMetaData) -> DeltaDataTypeLong {
+    let column = row_group.column(0);
+    column
+        .dictionary_page_offset()
+        .unwrap_or_else(|| column.data_page_offset())
+}
+
+/// Reads the data covered by a scan task into Arrow record batches. The partition columns of the
+/// table are appended to the columns read from the data file, see [`append_partition_columns`].
+pub async fn read_scan_task(
+    task: &DeltaScanTask,
+    storage: &dyn StorageBackend,
+    batch_size: usize,
+) -> Result<Vec<RecordBatch>, DeltaTableError> {
+    let obj = storage.get_obj(&task.path).await?;
+    let mut file_reader = SerializedFileReader::new(SliceableCursor::new(obj))?;
+
+    if !task.ranges.is_empty() {
+        let ranges = task.ranges.clone();
+        file_reader.filter_row_groups(&|row_group: &RowGroupMetaData, _| {
+            let offset = row_group_offset(row_group);
+            ranges
+                .iter()
+                .any(|range| offset >= range.start && offset < range.end)
+        });
+    }
+
+    let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+    let batches = arrow_reader
+        .get_record_reader(batch_size)?
+        .collect::<Result<Vec<RecordBatch>, _>>()?;
+    if task.partition_values.is_empty() {
+        return Ok(batches);
+    }
+
+    let schema = ArrowSchema::try_from(&task.schema)?;
+    Ok(batches
+        .iter()
+        .map(|batch| append_partition_columns(batch, &task.partition_values, &schema))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Appends a column for each partition value to a batch read from a data file, in the order of
+/// the columns of the table schema, with the values decoded by [`partition_value_array`]. Columns
+/// the data file already holds are kept as they are read.
+pub fn append_partition_columns(
+    batch: &RecordBatch,
+    partition_values: &HashMap<String, Option<String>>,
+    table_schema: &ArrowSchema,
+) -> Result<RecordBatch, ArrowError> {
+    let batch_schema = batch.schema();
+    let mut fields = batch_schema.fields().clone();
+    let mut columns = batch.columns().to_vec();
+    for field in table_schema.fields() {
+        let value = match partition_values.get(field.name()) {
+            Some(value) if batch_schema.field_with_name(field.name()).is_err() => value,
+            _ => continue,
+        };
+        columns.push(partition_value_array(
+            value.as_deref(),
+            field.data_type(),
+            batch.num_rows(),
+        )?);
+        // null partition values are allowed even when the column isn't nullable in the schema
+        fields.push(Field::new(field.name(), field.data_type().clone(), true));
+    }
+
+    RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), columns)
+}
+
+/// Returns an array of `num_rows` copies of a partition value, decoded from its string form in
+/// `partitionValues` into the type of the partition column. Dates and timestamps are parsed with
+/// [`parse_date_partition_value`] and [`parse_timestamp_partition_value`]. Missing values are
+/// nulls, and so are empty values of columns that aren't strings, like Spark reads them.
+pub fn partition_value_array(
+    value: Option<&str>,
+    data_type: &DataType,
+    num_rows: usize,
+) -> Result<ArrayRef, ArrowError> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(new_null_array(data_type, num_rows)),
+    };
+    let invalid_value = || {
+        ArrowError::CastError(format!(
+            "Can't parse partition value {:?} as {:?}",
+            value, data_type
+        ))
+    };
+
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 => default_value_array(value, data_type, num_rows),
+        _ if value.is_empty() => Ok(new_null_array(data_type, num_rows)),
+        DataType::Boolean => {
+            let value = match value.to_lowercase().as_str() {
+                "true" => true,
+                "false" => false,
+                _ => return Err(invalid_value()),
+            };
+            Ok(Arc::new(BooleanArray::from(vec![value; num_rows])))
+        }
+        DataType::Date32 => {
+            let date = parse_date_partition_value(value).ok_or_else(invalid_value)?;
+            let days = (date - NaiveDate::from_ymd(1970, 1, 1)).num_days() as i32;
+            Ok(Arc::new(Date32Array::from(vec![days; num_rows])))
+        }
+        DataType::Timestamp(unit, _) => {
+            let timestamp = parse_timestamp_partition_value(value).ok_or_else(invalid_value)?;
+            let value = match unit {
+                TimeUnit::Second => timestamp.timestamp(),
+                TimeUnit::Millisecond => timestamp.timestamp_millis(),
+                TimeUnit::Microsecond => timestamp.timestamp_nanos() / 1000,
+                TimeUnit::Nanosecond => timestamp.timestamp_nanos(),
+            };
+            let values: ArrayRef = Arc::new(Int64Array::from(vec![value; num_rows]));
+            cast(&values, data_type)
+        }
+        _ => default_value_array(value, data_type, num_rows),
+    }
+}
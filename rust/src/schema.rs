@@ -1,9 +1,12 @@
 #![allow(non_snake_case, non_camel_case_types)]
 
 use std::collections::HashMap;
+use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::DeltaTableError;
+
 /// Type alias for a string expected to match a GUID/UUID format
 pub type Guid = String;
 /// Type alias for i64/Delta long
@@ -17,7 +20,7 @@ pub type DeltaDataTypeInt = i32;
 
 /// Represents a struct field defined in the Delta table schema.
 // https://github.com/delta-io/delta/blob/master/PROTOCOL.md#Schema-Serialization-Format
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default, Clone)]
 pub struct SchemaTypeStruct {
     // type field is always the string "struct", so we are ignoring it here
     r#type: String,
@@ -32,7 +35,7 @@ impl SchemaTypeStruct {
 }
 
 /// Describes a specific field of the Delta table schema.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct SchemaField {
     // Name of this (possibly nested) column
     name: String,
@@ -64,10 +67,16 @@ impl SchemaField {
     pub fn get_metadata(&self) -> &HashMap<String, String> {
         &self.metadata
     }
+
+    /// The value written to the column when written data omits it, from the `delta.defaultValue`
+    /// metadata of the field. The value is given as a string, parsed into the type of the column.
+    pub fn get_default_value(&self) -> Option<&str> {
+        self.metadata.get("delta.defaultValue").map(String::as_str)
+    }
 }
 
 /// Schema definition for array type fields.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct SchemaTypeArray {
     // type field is always the string "array", so we are ignoring it here
     r#type: String,
@@ -91,7 +100,7 @@ impl SchemaTypeArray {
 }
 
 /// Schema definition for map type fields.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct SchemaTypeMap {
     r#type: String,
     keyType: Box<SchemaDataType>,
@@ -133,7 +142,7 @@ impl SchemaTypeMap {
  *   timestamp: Microsecond precision timestamp without a timezone
  */
 /// Enum with variants for each top level schema data type.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum SchemaDataType {
     /// Variant representing non-array, non-map, non-struct fields. Wrapped value will contain the
@@ -147,8 +156,69 @@ pub enum SchemaDataType {
     map(SchemaTypeMap),
 }
 
+/// A constraint on the values of a column, given as a SQL boolean expression in the
+/// `delta.invariants` metadata of the field. Writers must reject rows for which the expression
+/// doesn't hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invariant {
+    /// Dot separated path of the constrained column.
+    pub field_name: String,
+    /// The SQL expression the values of the column must satisfy.
+    pub invariant_sql: String,
+}
+
+/// How column names given by users, e.g. in partition filters or in the schema of written data,
+/// are matched with the columns of the table. Spark matches them case-insensitively by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnResolution {
+    /// Column names must match exactly.
+    CaseSensitive,
+    /// Column names match regardless of ASCII case. The case of the table columns is preserved.
+    CaseInsensitive,
+}
+
+impl Default for ColumnResolution {
+    fn default() -> Self {
+        ColumnResolution::CaseSensitive
+    }
+}
+
+impl ColumnResolution {
+    /// Whether `name` refers to `column`.
+    pub fn matches(&self, name: &str, column: &str) -> bool {
+        match self {
+            ColumnResolution::CaseSensitive => name == column,
+            ColumnResolution::CaseInsensitive => name.eq_ignore_ascii_case(column),
+        }
+    }
+
+    /// Returns the column among `columns` that `name` refers to. An exact match takes precedence
+    /// over case-insensitive ones, which are ambiguous when several columns match.
+    pub fn resolve<'a>(
+        &self,
+        name: &str,
+        columns: &'a [String],
+    ) -> Result<&'a str, DeltaTableError> {
+        if let Some(column) = columns.iter().find(|c| *c == name) {
+            return Ok(column);
+        }
+
+        let candidates: Vec<&String> = columns.iter().filter(|c| self.matches(name, c)).collect();
+        match candidates.as_slice() {
+            [column] => Ok(column),
+            [] => Err(DeltaTableError::ColumnNotFound {
+                column: name.to_string(),
+            }),
+            _ => Err(DeltaTableError::AmbiguousColumn {
+                column: name.to_string(),
+                candidates: candidates.into_iter().cloned().collect(),
+            }),
+        }
+    }
+}
+
 /// Represents the schema of the delta table.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Schema {
     r#type: String,
     fields: Vec<SchemaField>,
@@ -159,4 +229,361 @@ impl Schema {
     pub fn get_fields(&self) -> &Vec<SchemaField> {
         &self.fields
     }
+
+    /// Returns the invariants declared in the metadata of the fields of the schema, including
+    /// nested struct fields. Invariants aren't enforced when reading; this lets engines decide
+    /// how to enforce them when writing.
+    pub fn get_invariants(&self) -> Result<Vec<Invariant>, serde_json::Error> {
+        let mut invariants = Vec::new();
+        collect_invariants(&self.fields, "", &mut invariants)?;
+        Ok(invariants)
+    }
+}
+
+/// A difference between two versions of a table schema, found by `diff`. Nested columns are
+/// named by their dot separated path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    /// A column only in the new schema.
+    Added {
+        /// Path of the column.
+        column: String,
+        /// Type of the column.
+        data_type: SchemaDataType,
+        /// Whether the column is nullable.
+        nullable: bool,
+    },
+    /// A column only in the old schema.
+    Removed {
+        /// Path of the column.
+        column: String,
+    },
+    /// A column whose type changed. Struct columns aren't reported as retyped, the changes of
+    /// their fields are reported instead.
+    Retyped {
+        /// Path of the column.
+        column: String,
+        /// Type of the column in the old schema.
+        old_type: SchemaDataType,
+        /// Type of the column in the new schema.
+        new_type: SchemaDataType,
+    },
+    /// A column that became nullable, or not nullable.
+    NullabilityChanged {
+        /// Path of the column.
+        column: String,
+        /// Whether the column is nullable in the new schema.
+        nullable: bool,
+    },
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaChange::Added {
+                column,
+                data_type,
+                nullable,
+            } => write!(
+                f,
+                "added {} column `{}` of type {}",
+                nullability(*nullable),
+                column,
+                type_name(data_type)
+            ),
+            SchemaChange::Removed { column } => write!(f, "removed column `{}`", column),
+            SchemaChange::Retyped {
+                column,
+                old_type,
+                new_type,
+            } => write!(
+                f,
+                "changed the type of column `{}` from {} to {}",
+                column,
+                type_name(old_type),
+                type_name(new_type)
+            ),
+            SchemaChange::NullabilityChanged { column, nullable } => {
+                write!(f, "made column `{}` {}", column, nullability(*nullable))
+            }
+        }
+    }
+}
+
+/// The changes between two versions of a table schema, returned by `diff`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    /// The changes, in the order of the columns of the old schema followed by the columns added
+    /// by the new schema.
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Whether the schemas are the same, ignoring field metadata.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether data written with the old schema can be read with the new one: the new schema
+    /// only adds nullable columns or makes columns nullable.
+    pub fn is_additive(&self) -> bool {
+        self.changes.iter().all(|change| match change {
+            SchemaChange::Added { nullable, .. } => *nullable,
+            SchemaChange::NullabilityChanged { nullable, .. } => *nullable,
+            _ => false,
+        })
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "no changes");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes how the `new` schema differs from the `old` one, matching columns by name. Field
+/// metadata isn't compared.
+pub fn diff(old: &Schema, new: &Schema) -> SchemaDiff {
+    let mut changes = Vec::new();
+    diff_fields(&old.fields, &new.fields, "", &mut changes);
+    SchemaDiff { changes }
+}
+
+fn diff_fields(
+    old: &[SchemaField],
+    new: &[SchemaField],
+    prefix: &str,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for old_field in old {
+        let column = format!("{}{}", prefix, old_field.name);
+        let new_field = match new.iter().find(|f| f.name == old_field.name) {
+            Some(field) => field,
+            None => {
+                changes.push(SchemaChange::Removed { column });
+                continue;
+            }
+        };
+        if old_field.nullable != new_field.nullable {
+            changes.push(SchemaChange::NullabilityChanged {
+                column: column.clone(),
+                nullable: new_field.nullable,
+            });
+        }
+        match (&old_field.r#type, &new_field.r#type) {
+            (SchemaDataType::r#struct(old_struct), SchemaDataType::r#struct(new_struct)) => {
+                diff_fields(
+                    &old_struct.fields,
+                    &new_struct.fields,
+                    &format!("{}.", column),
+                    changes,
+                );
+            }
+            (old_type, new_type) if old_type != new_type => {
+                changes.push(SchemaChange::Retyped {
+                    column,
+                    old_type: old_type.clone(),
+                    new_type: new_type.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    for new_field in new {
+        if !old.iter().any(|f| f.name == new_field.name) {
+            changes.push(SchemaChange::Added {
+                column: format!("{}{}", prefix, new_field.name),
+                data_type: new_field.r#type.clone(),
+                nullable: new_field.nullable,
+            });
+        }
+    }
+}
+
+fn nullability(nullable: bool) -> &'static str {
+    if nullable {
+        "nullable"
+    } else {
+        "non-nullable"
+    }
+}
+
+fn type_name(data_type: &SchemaDataType) -> String {
+    match data_type {
+        SchemaDataType::primitive(name) => name.clone(),
+        SchemaDataType::r#struct(_) => "struct".to_string(),
+        SchemaDataType::array(array) => format!("array<{}>", type_name(&array.elementType)),
+        SchemaDataType::map(map) => format!(
+            "map<{}, {}>",
+            type_name(&map.keyType),
+            type_name(&map.valueType)
+        ),
+    }
+}
+
+fn collect_invariants(
+    fields: &[SchemaField],
+    prefix: &str,
+    invariants: &mut Vec<Invariant>,
+) -> Result<(), serde_json::Error> {
+    for field in fields {
+        let field_name = format!("{}{}", prefix, field.name);
+        if let Some(invariant) = field.metadata.get("delta.invariants") {
+            // e.g. {"expression":{"expression":"value < 3"}}
+            let invariant: serde_json::Value = serde_json::from_str(invariant)?;
+            if let Some(sql) = invariant["expression"]["expression"].as_str() {
+                invariants.push(Invariant {
+                    field_name: field_name.clone(),
+                    invariant_sql: sql.to_string(),
+                });
+            }
+        }
+        if let SchemaDataType::r#struct(inner) = &field.r#type {
+            collect_invariants(&inner.fields, &format!("{}.", field_name), invariants)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_resolution() {
+        let columns = vec!["Date".to_string(), "id".to_string(), "ID".to_string()];
+
+        let sensitive = ColumnResolution::CaseSensitive;
+        assert_eq!(sensitive.resolve("Date", &columns).unwrap(), "Date");
+        assert!(matches!(
+            sensitive.resolve("date", &columns),
+            Err(DeltaTableError::ColumnNotFound { .. })
+        ));
+
+        let insensitive = ColumnResolution::CaseInsensitive;
+        assert_eq!(insensitive.resolve("date", &columns).unwrap(), "Date");
+        assert_eq!(insensitive.resolve("ID", &columns).unwrap(), "ID");
+        assert!(matches!(
+            insensitive.resolve("Id", &columns),
+            Err(DeltaTableError::AmbiguousColumn { .. })
+        ));
+        assert!(matches!(
+            insensitive.resolve("value", &columns),
+            Err(DeltaTableError::ColumnNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn schema_invariants() {
+        let schema: Schema = serde_json::from_str(
+            r#"{"type":"struct","fields":[
+                {"name":"value","type":"integer","nullable":true,
+                 "metadata":{"delta.invariants":"{\"expression\":{\"expression\":\"value < 3\"}}"}},
+                {"name":"nested","type":{"type":"struct","fields":[
+                    {"name":"count","type":"long","nullable":true,
+                     "metadata":{"delta.invariants":"{\"expression\":{\"expression\":\"nested.count > 0\"}}"}}
+                ]},"nullable":true,"metadata":{}},
+                {"name":"other","type":"string","nullable":true,"metadata":{}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema.get_invariants().unwrap(),
+            vec![
+                Invariant {
+                    field_name: "value".to_string(),
+                    invariant_sql: "value < 3".to_string(),
+                },
+                Invariant {
+                    field_name: "nested.count".to_string(),
+                    invariant_sql: "nested.count > 0".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_diff() {
+        let old: Schema = serde_json::from_str(
+            r#"{"type":"struct","fields":[
+                {"name":"id","type":"long","nullable":false,"metadata":{}},
+                {"name":"value","type":"integer","nullable":true,"metadata":{}},
+                {"name":"nested","type":{"type":"struct","fields":[
+                    {"name":"count","type":"long","nullable":true,"metadata":{}}
+                ]},"nullable":true,"metadata":{}},
+                {"name":"dropped","type":"string","nullable":true,"metadata":{}}
+            ]}"#,
+        )
+        .unwrap();
+        let new: Schema = serde_json::from_str(
+            r#"{"type":"struct","fields":[
+                {"name":"id","type":"long","nullable":true,"metadata":{}},
+                {"name":"value","type":"string","nullable":true,"metadata":{"comment":"ignored"}},
+                {"name":"nested","type":{"type":"struct","fields":[
+                    {"name":"count","type":"long","nullable":true,"metadata":{}},
+                    {"name":"label","type":"string","nullable":true,"metadata":{}}
+                ]},"nullable":true,"metadata":{}},
+                {"name":"added","type":"date","nullable":false,"metadata":{}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert!(diff(&old, &old).is_empty());
+
+        let schema_diff = diff(&old, &new);
+        assert_eq!(
+            schema_diff.changes,
+            vec![
+                SchemaChange::NullabilityChanged {
+                    column: "id".to_string(),
+                    nullable: true,
+                },
+                SchemaChange::Retyped {
+                    column: "value".to_string(),
+                    old_type: SchemaDataType::primitive("integer".to_string()),
+                    new_type: SchemaDataType::primitive("string".to_string()),
+                },
+                SchemaChange::Added {
+                    column: "nested.label".to_string(),
+                    data_type: SchemaDataType::primitive("string".to_string()),
+                    nullable: true,
+                },
+                SchemaChange::Removed {
+                    column: "dropped".to_string(),
+                },
+                SchemaChange::Added {
+                    column: "added".to_string(),
+                    data_type: SchemaDataType::primitive("date".to_string()),
+                    nullable: false,
+                },
+            ]
+        );
+        assert!(!schema_diff.is_additive());
+        assert_eq!(
+            schema_diff.to_string(),
+            "made column `id` nullable, changed the type of column `value` from integer to \
+             string, added nullable column `nested.label` of type string, removed column \
+             `dropped`, added non-nullable column `added` of type date"
+        );
+
+        let additive = SchemaDiff {
+            changes: schema_diff.changes[..1]
+                .iter()
+                .chain(&schema_diff.changes[2..3])
+                .cloned()
+                .collect(),
+        };
+        assert!(additive.is_additive());
+    }
 }
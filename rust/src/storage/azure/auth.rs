@@ -0,0 +1,217 @@
+//! Azure Active Directory token providers for the Azure backend.
+//!
+//! Service principals and managed identities authenticate with OAuth bearer tokens, which expire
+//! after about an hour and are refreshed through the [`CredentialsProvider`] interface.
+
+use std::env;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::super::credentials::{CredentialsProvider, StorageCredentials};
+use super::super::StorageError;
+
+/// The resource, or scope, of tokens granting access to Azure Storage.
+const STORAGE_RESOURCE: &str = "https://storage.azure.com/";
+/// Endpoint of the Azure Instance Metadata Service (IMDS).
+const IMDS_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    // both are strings in some API versions and numbers in others
+    #[serde(default)]
+    expires_in: Option<Value>,
+    #[serde(default)]
+    expires_on: Option<Value>,
+}
+
+fn parse_seconds(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl TokenResponse {
+    fn expires_at(&self) -> Option<DateTime<Utc>> {
+        if let Some(expires_on) = self.expires_on.as_ref().and_then(parse_seconds) {
+            return Some(Utc.timestamp(expires_on, 0));
+        }
+        self.expires_in
+            .as_ref()
+            .and_then(parse_seconds)
+            .map(|expires_in| Utc::now() + Duration::seconds(expires_in))
+    }
+
+    fn into_credentials(self, account_name: &str) -> StorageCredentials {
+        StorageCredentials::AzureBearerToken {
+            account_name: account_name.to_string(),
+            expires_at: self.expires_at(),
+            token: self.access_token,
+        }
+    }
+}
+
+async fn send_token_request(
+    request: reqwest::RequestBuilder,
+) -> Result<TokenResponse, StorageError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| StorageError::AzureAuth(e.to_string()))?;
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| StorageError::AzureAuth(e.to_string()))?;
+    if !status.is_success() {
+        return Err(StorageError::AzureAuth(format!(
+            "Token request failed with status {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        )));
+    }
+    serde_json::from_slice(&body).map_err(|e| StorageError::AzureAuth(e.to_string()))
+}
+
+/// Requests tokens for a service principal authenticating with a client secret.
+pub struct ClientSecretCredentialsProvider {
+    account_name: String,
+    tenant_id: String,
+    client_id: String,
+    client_secret: String,
+    http_client: reqwest::Client,
+}
+
+impl ClientSecretCredentialsProvider {
+    /// Creates a provider of tokens for the given storage account, issued to the service
+    /// principal `client_id` of the Azure AD tenant `tenant_id`.
+    pub fn new(account_name: &str, tenant_id: &str, client_id: &str, client_secret: &str) -> Self {
+        Self {
+            account_name: account_name.to_string(),
+            tenant_id: tenant_id.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ClientSecretCredentialsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ClientSecretCredentialsProvider <{}@{}>",
+            self.client_id, self.tenant_id
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl CredentialsProvider for ClientSecretCredentialsProvider {
+    async fn get_credentials(&self) -> Result<StorageCredentials, StorageError> {
+        let url = format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+            self.tenant_id
+        );
+        let scope = format!("{}.default", STORAGE_RESOURCE);
+        let request = self.http_client.post(&url).form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("scope", scope.as_str()),
+        ]);
+        Ok(send_token_request(request)
+            .await?
+            .into_credentials(&self.account_name))
+    }
+}
+
+/// Requests tokens for the managed identity of the Azure resource the process runs on.
+///
+/// On App Service and Azure Functions, tokens are requested from the endpoint advertised by the
+/// `IDENTITY_ENDPOINT` and `IDENTITY_HEADER` environment variables. Everywhere else, e.g. on
+/// virtual machines or AKS nodes, they are requested from the Instance Metadata Service.
+#[derive(Debug)]
+pub struct ManagedIdentityCredentialsProvider {
+    account_name: String,
+    client_id: Option<String>,
+    http_client: reqwest::Client,
+}
+
+impl ManagedIdentityCredentialsProvider {
+    /// Creates a provider of tokens for the given storage account. `client_id` selects a user
+    /// assigned identity, the system assigned identity is used when it is `None`.
+    pub fn new(account_name: &str, client_id: Option<&str>) -> Self {
+        Self {
+            account_name: account_name.to_string(),
+            client_id: client_id.map(str::to_string),
+            http_client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CredentialsProvider for ManagedIdentityCredentialsProvider {
+    async fn get_credentials(&self) -> Result<StorageCredentials, StorageError> {
+        let mut query = vec![("resource", STORAGE_RESOURCE)];
+        if let Some(client_id) = &self.client_id {
+            query.push(("client_id", client_id.as_str()));
+        }
+
+        let request = match (env::var("IDENTITY_ENDPOINT"), env::var("IDENTITY_HEADER")) {
+            (Ok(endpoint), Ok(header)) => {
+                query.push(("api-version", "2019-08-01"));
+                self.http_client
+                    .get(&endpoint)
+                    .header("X-IDENTITY-HEADER", header)
+                    .query(&query)
+            }
+            _ => {
+                query.push(("api-version", "2018-02-01"));
+                self.http_client
+                    .get(IMDS_ENDPOINT)
+                    .header("Metadata", "true")
+                    .query(&query)
+            }
+        };
+        Ok(send_token_request(request)
+            .await?
+            .into_credentials(&self.account_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_response_expiry() {
+        let response: TokenResponse = serde_json::from_str(
+            r#"{"access_token": "token", "expires_in": "3599", "expires_on": "1600000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.expires_at(), Some(Utc.timestamp(1600000000, 0)));
+
+        let response: TokenResponse =
+            serde_json::from_str(r#"{"access_token": "token", "expires_in": 3599}"#).unwrap();
+        let expires_at = response.expires_at().unwrap();
+        assert!(expires_at > Utc::now() + Duration::minutes(59));
+        assert!(expires_at <= Utc::now() + Duration::minutes(60));
+
+        match response.into_credentials("account") {
+            StorageCredentials::AzureBearerToken {
+                account_name,
+                token,
+                ..
+            } => {
+                assert_eq!(account_name, "account");
+                assert_eq!(token, "token");
+            }
+            _ => panic!("Expected bearer token credentials"),
+        }
+    }
+}
@@ -0,0 +1,517 @@
+//! The Azure Data Lake Storage Gen2 storage backend. It currently only supports read operations.
+//!
+//! This module is gated behind the "azure" feature. The backend authenticates with a SAS token,
+//! the storage account key, a service principal or a managed identity, configured through the
+//! options of the table URI or environment variables, see [`AzureStorageOptions`]. Other
+//! authentication schemes can be plugged in with a [`CredentialsProvider`].
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, RwLock};
+use std::{env, fmt, pin::Pin};
+
+use azure_core::errors::AzureError;
+use azure_core::prelude::*;
+use azure_storage::clients::{
+    AsBlobClient, AsContainerClient, AsStorageClient, ContainerClient, StorageAccountClient,
+};
+use chrono::Duration;
+use futures::stream::{Stream, StreamExt};
+use log::debug;
+
+use super::credentials::{CredentialsProvider, StaticCredentialsProvider, StorageCredentials};
+use super::{parse_uri, ObjectMeta, StorageBackend, StorageCapabilities, StorageError, UriError};
+
+pub mod auth;
+
+/// An object on an Azure Data Lake Storage Gen2 account.
+#[derive(Debug, PartialEq)]
+pub struct AdlsGen2Object<'a> {
+    /// The storage account name.
+    pub account_name: &'a str,
+    /// The container, or filesystem, of the object.
+    pub file_system: &'a str,
+    /// The path of the object on the filesystem.
+    pub path: &'a str,
+}
+
+impl<'a> fmt::Display for AdlsGen2Object<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // This URI syntax is documented at
+        // https://docs.microsoft.com/en-us/azure/storage/blobs/data-lake-storage-introduction-abfs-uri
+        write!(
+            f,
+            "abfss://{}@{}.dfs.core.windows.net/{}",
+            self.file_system, self.account_name, self.path
+        )
+    }
+}
+
+/// Options used to authenticate the AdlsGen2Backend, the first configured mode is used:
+///
+/// 1. a SAS token, `sas_token`
+/// 2. the storage account key, `account_key`
+/// 3. a service principal with a client secret, `tenant_id`, `client_id` and `client_secret`
+/// 4. a managed identity, `use_managed_identity`, with `client_id` selecting a user assigned
+///    identity
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AzureStorageOptions {
+    /// Shared access signature of the storage account.
+    pub sas_token: Option<String>,
+    /// Shared key of the storage account.
+    pub account_key: Option<String>,
+    /// Azure AD tenant of the service principal.
+    pub tenant_id: Option<String>,
+    /// Client id of the service principal or of the user assigned managed identity.
+    pub client_id: Option<String>,
+    /// Client secret of the service principal.
+    pub client_secret: Option<String>,
+    /// Whether to authenticate as the managed identity of the Azure resource, e.g. on AKS or
+    /// Azure Functions.
+    pub use_managed_identity: bool,
+}
+
+impl AzureStorageOptions {
+    /// Query string options recognized in Azure table URIs.
+    pub const URI_OPTIONS: &'static [&'static str] = &[
+        "sas_token",
+        "account_key",
+        "tenant_id",
+        "client_id",
+        "client_secret",
+        "use_managed_identity",
+    ];
+
+    /// Creates options from the query string options of an Azure table URI, e.g.
+    /// `abfss://container@account.dfs.core.windows.net/table?use_managed_identity=true`.
+    pub fn from_uri_options(options: &HashMap<String, String>) -> Result<Self, UriError> {
+        if let Some(key) = options
+            .keys()
+            .find(|key| !Self::URI_OPTIONS.contains(&key.as_str()))
+        {
+            return Err(UriError::UnknownOption(key.clone()));
+        }
+        let use_managed_identity = match options.get("use_managed_identity") {
+            Some(value) => value.parse().map_err(|_| UriError::InvalidOption {
+                key: "use_managed_identity".to_string(),
+                value: value.clone(),
+            })?,
+            None => false,
+        };
+
+        Ok(Self {
+            sas_token: options.get("sas_token").cloned(),
+            account_key: options.get("account_key").cloned(),
+            tenant_id: options.get("tenant_id").cloned(),
+            client_id: options.get("client_id").cloned(),
+            client_secret: options.get("client_secret").cloned(),
+            use_managed_identity,
+        })
+    }
+
+    /// Creates options from the `AZURE_STORAGE_SAS`, `AZURE_STORAGE_KEY`, `AZURE_TENANT_ID`,
+    /// `AZURE_CLIENT_ID`, `AZURE_CLIENT_SECRET` and `AZURE_USE_MANAGED_IDENTITY` environment
+    /// variables.
+    pub fn from_env() -> Self {
+        Self {
+            sas_token: env::var("AZURE_STORAGE_SAS").ok(),
+            account_key: env::var("AZURE_STORAGE_KEY").ok(),
+            tenant_id: env::var("AZURE_TENANT_ID").ok(),
+            client_id: env::var("AZURE_CLIENT_ID").ok(),
+            client_secret: env::var("AZURE_CLIENT_SECRET").ok(),
+            use_managed_identity: env::var("AZURE_USE_MANAGED_IDENTITY")
+                .map(|value| value == "true")
+                .unwrap_or(false),
+        }
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.sas_token.is_some()
+            || self.account_key.is_some()
+            || self.client_secret.is_some()
+            || self.use_managed_identity
+    }
+
+    /// Returns the provider of credentials for the given storage account selected by the
+    /// options.
+    pub fn credentials_provider(
+        &self,
+        account_name: &str,
+    ) -> Result<Arc<dyn CredentialsProvider>, StorageError> {
+        let account_name = account_name.to_string();
+        if let Some(sas_token) = &self.sas_token {
+            debug!("Authenticating to Azure using SAS token");
+            Ok(Arc::new(StaticCredentialsProvider::new(
+                StorageCredentials::AzureSasToken {
+                    account_name,
+                    sas_token: sas_token.clone(),
+                    expires_at: None,
+                },
+            )))
+        } else if let Some(access_key) = &self.account_key {
+            debug!("Authenticating to Azure using access key");
+            Ok(Arc::new(StaticCredentialsProvider::new(
+                StorageCredentials::AzureAccessKey {
+                    account_name,
+                    access_key: access_key.clone(),
+                },
+            )))
+        } else if let Some(client_secret) = &self.client_secret {
+            debug!("Authenticating to Azure using service principal");
+            match (&self.tenant_id, &self.client_id) {
+                (Some(tenant_id), Some(client_id)) => {
+                    Ok(Arc::new(auth::ClientSecretCredentialsProvider::new(
+                        &account_name,
+                        tenant_id,
+                        client_id,
+                        client_secret,
+                    )))
+                }
+                _ => Err(StorageError::AzureConfig(
+                    "tenant_id and client_id must be set to authenticate with a client secret"
+                        .to_string(),
+                )),
+            }
+        } else if self.use_managed_identity {
+            debug!("Authenticating to Azure using managed identity");
+            Ok(Arc::new(auth::ManagedIdentityCredentialsProvider::new(
+                &account_name,
+                self.client_id.as_deref(),
+            )))
+        } else {
+            Err(StorageError::AzureConfig(
+                "Either a SAS token, an account key, a client secret or managed identity \
+                 authentication must be configured"
+                    .to_string(),
+            ))
+        }
+    }
+}
+
+impl fmt::Debug for AzureStorageOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print secrets
+        f.debug_struct("AzureStorageOptions")
+            .field("sas_token", &self.sas_token.as_ref().map(|_| "***"))
+            .field("account_key", &self.account_key.as_ref().map(|_| "***"))
+            .field("tenant_id", &self.tenant_id)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret.as_ref().map(|_| "***"))
+            .field("use_managed_identity", &self.use_managed_identity)
+            .finish()
+    }
+}
+
+/// A storage backend backed by an Azure Data Lake Storage Gen2 account.
+///
+/// This uses the `dfs.core.windows.net` endpoint.
+pub struct AdlsGen2Backend {
+    account: String,
+    container: String,
+    http_client: Arc<Box<dyn HttpClient>>,
+    credentials_provider: Arc<dyn CredentialsProvider>,
+    // client built from the credentials most recently returned by the provider
+    client: RwLock<Option<(StorageCredentials, Arc<ContainerClient>)>>,
+}
+
+impl AdlsGen2Backend {
+    /// Create a new [`AdlsGen2Backend`].
+    ///
+    /// This currently requires the `AZURE_STORAGE_ACCOUNT` environment variable to be set to the
+    /// storage account name, and the credentials to be configured through the environment
+    /// variables described in [`AzureStorageOptions::from_env`]. This also implies that the
+    /// backend is only valid for a single Storage Account.
+    pub fn new(container: &str) -> Result<Self, StorageError> {
+        let account_name = env::var("AZURE_STORAGE_ACCOUNT").map_err(|_| {
+            StorageError::AzureConfig("AZURE_STORAGE_ACCOUNT must be set".to_string())
+        })?;
+
+        Self::new_with_options(container, &account_name, AzureStorageOptions::from_env())
+    }
+
+    /// Create a new [`AdlsGen2Backend`] for a container of the given storage account,
+    /// authenticating as configured by `options`. The environment variables described in
+    /// [`AzureStorageOptions::from_env`] are used when `options` don't configure credentials.
+    pub fn new_with_options(
+        container: &str,
+        account_name: &str,
+        options: AzureStorageOptions,
+    ) -> Result<Self, StorageError> {
+        let options = if options.has_credentials() {
+            options
+        } else {
+            AzureStorageOptions::from_env()
+        };
+        let credentials_provider = options.credentials_provider(account_name)?;
+
+        Ok(Self::new_with_credentials_provider(
+            container,
+            account_name,
+            credentials_provider,
+        ))
+    }
+
+    /// Create a new [`AdlsGen2Backend`] that requests credentials from the given provider,
+    /// whenever the previous ones are about to expire, instead of reading them from the
+    /// environment. The provider must return Azure credentials for `account_name`.
+    ///
+    /// Credentials are first requested on the first request to the storage account.
+    pub fn new_with_credentials_provider(
+        container: &str,
+        account_name: &str,
+        credentials_provider: Arc<dyn CredentialsProvider>,
+    ) -> Self {
+        Self {
+            account: account_name.to_string(),
+            container: container.to_string(),
+            http_client: Arc::new(Box::new(reqwest::Client::new())),
+            credentials_provider,
+            client: RwLock::new(None),
+        }
+    }
+
+    /// Returns the container client, creating it with new credentials from the provider when
+    /// there are none yet or the current ones are about to expire.
+    async fn container_client(&self) -> Result<Arc<ContainerClient>, StorageError> {
+        {
+            let client = self.client.read().unwrap();
+            if let Some((credentials, container_client)) = &*client {
+                if !credentials.expires_within(Duration::minutes(5)) {
+                    return Ok(container_client.clone());
+                }
+            }
+        }
+
+        debug!("Requesting Azure credentials");
+        let credentials = self.credentials_provider.get_credentials().await?;
+        let (account, container_client) =
+            create_container_client(&self.http_client, &credentials, &self.container)?;
+        if account != self.account {
+            return Err(StorageError::AzureConfig(format!(
+                "Credentials provider returned credentials for account {}, expected {}",
+                account, self.account
+            )));
+        }
+        *self.client.write().unwrap() = Some((credentials, container_client.clone()));
+
+        Ok(container_client)
+    }
+
+    fn validate_container<'a>(&self, obj: &AdlsGen2Object<'a>) -> Result<(), StorageError> {
+        if obj.file_system != self.container {
+            Err(StorageError::Uri {
+                source: UriError::ContainerMismatch {
+                    expected: self.container.clone(),
+                    got: obj.file_system.to_string(),
+                },
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Debug for AdlsGen2Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AdlsGen2Backend <{}@{}>", self.container, self.account)
+    }
+}
+
+/// Creates a container client from the given credentials, returning it along with the storage
+/// account name of the credentials.
+fn create_container_client(
+    http_client: &Arc<Box<dyn HttpClient>>,
+    credentials: &StorageCredentials,
+    container: &str,
+) -> Result<(String, Arc<ContainerClient>), StorageError> {
+    let (account_name, storage_account_client) = match credentials {
+        StorageCredentials::AzureSasToken {
+            account_name,
+            sas_token,
+            ..
+        } => (
+            account_name,
+            StorageAccountClient::new_sas_token(http_client.clone(), account_name, sas_token),
+        ),
+        StorageCredentials::AzureAccessKey {
+            account_name,
+            access_key,
+        } => (
+            account_name,
+            StorageAccountClient::new_access_key(http_client.clone(), account_name, access_key),
+        ),
+        StorageCredentials::AzureBearerToken {
+            account_name,
+            token,
+            ..
+        } => (
+            account_name,
+            StorageAccountClient::new_bearer_token(http_client.clone(), account_name, token),
+        ),
+        _ => {
+            return Err(StorageError::AzureConfig(format!(
+                "Expected Azure credentials, got: {:?}",
+                credentials
+            )))
+        }
+    };
+
+    Ok((
+        account_name.clone(),
+        storage_account_client
+            .as_storage_client()
+            .as_container_client(container),
+    ))
+}
+
+fn to_storage_err(err: Box<dyn Error + Sync + std::marker::Send>) -> StorageError {
+    match err.downcast_ref::<AzureError>() {
+        Some(AzureError::UnexpectedHTTPResult(e)) if e.status_code().as_u16() == 404 => {
+            StorageError::NotFound
+        }
+        _ => StorageError::AzureGeneric { source: err },
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for AdlsGen2Backend {
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        debug!("Getting properties for {}", path);
+        let obj = parse_uri(path)?.into_adlsgen2_object()?;
+        self.validate_container(&obj)?;
+
+        let properties = self
+            .container_client()
+            .await?
+            .as_blob_client(obj.path)
+            .get_properties()
+            .execute()
+            .await
+            .map_err(to_storage_err)?;
+        let modified = properties
+            .blob
+            .last_modified
+            .expect("Last-Modified should never be None for committed blobs");
+        Ok(ObjectMeta {
+            path: path.to_string(),
+            modified,
+            size: Some(properties.blob.content_length as i64),
+        })
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        debug!("Loading {}", path);
+        let obj = parse_uri(path)?.into_adlsgen2_object()?;
+        self.validate_container(&obj)?;
+
+        Ok(self
+            .container_client()
+            .await?
+            .as_blob_client(obj.path)
+            .get()
+            .execute()
+            .await
+            .map_err(to_storage_err)?
+            .data)
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        debug!("Listing objects under {}", path);
+        let obj = parse_uri(path)?.into_adlsgen2_object()?;
+        self.validate_container(&obj)?;
+
+        // the listing is collected upfront, so it doesn't borrow a container client that may be
+        // replaced when credentials are refreshed
+        let container_client = self.container_client().await?;
+        let mut pages = Box::pin(container_client.list_blobs().prefix(obj.path).stream());
+        let mut objects = Vec::new();
+        while let Some(response) = pages.next().await {
+            let response = response.map_err(to_storage_err)?;
+            for blob in response.incomplete_vector.vector {
+                let object = AdlsGen2Object {
+                    account_name: &self.account,
+                    file_system: &blob.container_name,
+                    path: &blob.name,
+                };
+                objects.push(Ok(ObjectMeta {
+                    path: object.to_string(),
+                    modified: blob
+                        .last_modified
+                        .expect("Last-Modified should never be None for committed blobs"),
+                    size: Some(blob.content_length as i64),
+                }));
+            }
+        }
+
+        Ok(Box::pin(futures::stream::iter(objects)))
+    }
+
+    async fn put_obj(&self, _path: &str, _obj_bytes: &[u8]) -> Result<(), StorageError> {
+        unimplemented!("put_obj not implemented for azure");
+    }
+
+    async fn rename_obj(&self, _src: &str, _dst: &str) -> Result<(), StorageError> {
+        unimplemented!("rename_obj not implemented for azure");
+    }
+
+    async fn delete_obj(&self, _path: &str) -> Result<(), StorageError> {
+        unimplemented!("delete_obj not implemented for azure");
+    }
+
+    fn capabilities(&self) -> StorageCapabilities {
+        // the backend is read only, so transactions fail before writing anything
+        StorageCapabilities {
+            atomic_rename: false,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn azure_options_from_uri_options() {
+        let mut options = HashMap::new();
+        options.insert("use_managed_identity".to_string(), "true".to_string());
+        options.insert("client_id".to_string(), "identity".to_string());
+        let azure_options = AzureStorageOptions::from_uri_options(&options).unwrap();
+        assert_eq!(
+            azure_options,
+            AzureStorageOptions {
+                client_id: Some("identity".to_string()),
+                use_managed_identity: true,
+                ..Default::default()
+            }
+        );
+        assert!(azure_options.credentials_provider("account").is_ok());
+
+        options.insert("use_managed_identity".to_string(), "yes".to_string());
+        assert_eq!(
+            AzureStorageOptions::from_uri_options(&options),
+            Err(UriError::InvalidOption {
+                key: "use_managed_identity".to_string(),
+                value: "yes".to_string(),
+            })
+        );
+
+        let mut options = HashMap::new();
+        options.insert("client_secret".to_string(), "secret".to_string());
+        let azure_options = AzureStorageOptions::from_uri_options(&options).unwrap();
+        assert!(!format!("{:?}", azure_options).contains("\"secret\""));
+        assert!(azure_options.credentials_provider("account").is_err());
+
+        options.insert("container".to_string(), "other".to_string());
+        assert_eq!(
+            AzureStorageOptions::from_uri_options(&options),
+            Err(UriError::UnknownOption("container".to_string()))
+        );
+    }
+}
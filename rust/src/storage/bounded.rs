@@ -0,0 +1,194 @@
+//! Storage backend wrapper bounding the duration of storage operations.
+
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+use super::{ObjectMeta, StorageBackend, StorageCapabilities, StorageError};
+
+/// Runs `future` until it completes, `timeout` elapses or `cancellation_token` is cancelled,
+/// returning `timed_out` or `cancelled` in the latter cases. Dropping the future at its next
+/// await point is the only way to stop it, so work it already handed off, e.g. a request sent to
+/// an object store, may still complete.
+pub(crate) async fn run_bounded<T, E>(
+    future: impl Future<Output = Result<T, E>>,
+    timeout: Option<Duration>,
+    cancellation_token: Option<&CancellationToken>,
+    timed_out: impl FnOnce() -> E,
+    cancelled: impl FnOnce() -> E,
+) -> Result<T, E> {
+    let future = async {
+        match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, future).await {
+                Ok(result) => result,
+                Err(_) => Err(timed_out()),
+            },
+            None => future.await,
+        }
+    };
+    match cancellation_token {
+        Some(token) => {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(cancelled()),
+                result = future => result,
+            }
+        }
+        None => future.await,
+    }
+}
+
+/// A storage backend failing the operations of the wrapped backend that take longer than a
+/// timeout with `StorageError::Timeout`, and all of them once a cancellation token is cancelled
+/// with `StorageError::Cancelled`. Listings are bounded per listed object.
+#[derive(Debug)]
+pub struct BoundedStorageBackend {
+    inner: Arc<dyn StorageBackend>,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl BoundedStorageBackend {
+    /// Bounds the operations of `inner` by `timeout` and `cancellation_token`, when provided.
+    pub fn new(
+        inner: Arc<dyn StorageBackend>,
+        timeout: Option<Duration>,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Self {
+        Self {
+            inner,
+            timeout,
+            cancellation_token,
+        }
+    }
+
+    async fn bounded<T>(
+        &self,
+        future: impl Future<Output = Result<T, StorageError>>,
+    ) -> Result<T, StorageError> {
+        bounded_op(future, self.timeout, self.cancellation_token.as_ref()).await
+    }
+
+    /// Bounds the wait for each object of a listing. The listing ends after the first error.
+    fn bounded_stream<'a>(&'a self, stream: ObjectStream<'a>) -> ObjectStream<'a> {
+        let timeout = self.timeout;
+        let cancellation_token = self.cancellation_token.as_ref();
+        Box::pin(futures::stream::unfold(
+            Some(stream),
+            move |stream| async move {
+                let mut stream = stream?;
+                let next = bounded_op(
+                    async { Ok(stream.next().await) },
+                    timeout,
+                    cancellation_token,
+                )
+                .await;
+                match next {
+                    Ok(Some(Ok(obj_meta))) => Some((Ok(obj_meta), Some(stream))),
+                    Ok(Some(Err(e))) | Err(e) => Some((Err(e), None)),
+                    Ok(None) => None,
+                }
+            },
+        ))
+    }
+}
+
+type ObjectStream<'a> = Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>;
+
+async fn bounded_op<T>(
+    future: impl Future<Output = Result<T, StorageError>>,
+    timeout: Option<Duration>,
+    cancellation_token: Option<&CancellationToken>,
+) -> Result<T, StorageError> {
+    run_bounded(
+        future,
+        timeout,
+        cancellation_token,
+        || StorageError::Timeout(timeout.unwrap_or_default()),
+        || StorageError::Cancelled,
+    )
+    .await
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for BoundedStorageBackend {
+    fn join_path(&self, path: &str, path_to_join: &str) -> String {
+        self.inner.join_path(path, path_to_join)
+    }
+
+    fn join_paths(&self, paths: &[&str]) -> String {
+        self.inner.join_paths(paths)
+    }
+
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        self.bounded(self.inner.head_obj(path)).await
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        self.bounded(self.inner.get_obj(path)).await
+    }
+
+    async fn get_obj_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        self.bounded(self.inner.get_obj_range(path, range)).await
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        let stream = self.bounded(self.inner.list_objs(path)).await?;
+        Ok(self.bounded_stream(stream))
+    }
+
+    async fn list_objs_after<'a>(
+        &'a self,
+        path: &'a str,
+        start_after: &'a str,
+        page_size: Option<usize>,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        let stream = self
+            .bounded(self.inner.list_objs_after(path, start_after, page_size))
+            .await?;
+        Ok(self.bounded_stream(stream))
+    }
+
+    async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.bounded(self.inner.put_obj(path, obj_bytes)).await
+    }
+
+    async fn rename_obj(&self, src: &str, dst: &str) -> Result<(), StorageError> {
+        self.bounded(self.inner.rename_obj(src, dst)).await
+    }
+
+    async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
+        self.bounded(self.inner.delete_obj(path)).await
+    }
+
+    fn capabilities(&self) -> StorageCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn put_obj_atomic(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.bounded(self.inner.put_obj_atomic(path, obj_bytes))
+            .await
+    }
+
+    async fn presign(&self, path: &str, expiry: Duration) -> Result<String, StorageError> {
+        self.bounded(self.inner.presign(path, expiry)).await
+    }
+
+    async fn verify_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.bounded(self.inner.verify_obj(path, obj_bytes)).await
+    }
+}
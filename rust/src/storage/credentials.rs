@@ -0,0 +1,164 @@
+//! Credentials used by the cloud storage backends.
+//!
+//! Backends created with a [`CredentialsProvider`] request new credentials from it whenever the
+//! previously returned ones are about to expire, so rotating secrets can be supplied by external
+//! token services instead of being read once for the life of the process.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::StorageError;
+
+/// Credentials handed to a storage backend by a [`CredentialsProvider`].
+#[derive(Clone, PartialEq, Eq)]
+pub enum StorageCredentials {
+    /// AWS credentials used by the S3 backend.
+    Aws {
+        /// The AWS access key id.
+        access_key_id: String,
+        /// The AWS secret access key.
+        secret_access_key: String,
+        /// The session token of temporary credentials.
+        session_token: Option<String>,
+        /// When the credentials expire, `None` if they don't.
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// Shared key of an Azure storage account, used by the Azure backend.
+    AzureAccessKey {
+        /// The storage account name.
+        account_name: String,
+        /// The storage account key.
+        access_key: String,
+    },
+    /// Shared access signature for an Azure storage account, used by the Azure backend.
+    AzureSasToken {
+        /// The storage account name.
+        account_name: String,
+        /// The SAS token.
+        sas_token: String,
+        /// When the token expires, `None` if it doesn't.
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// Azure Active Directory OAuth token, used by the Azure backend for service principals and
+    /// managed identities.
+    AzureBearerToken {
+        /// The storage account name.
+        account_name: String,
+        /// The bearer token.
+        token: String,
+        /// When the token expires, `None` if it doesn't.
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl StorageCredentials {
+    /// Returns when the credentials expire, `None` if they don't.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            StorageCredentials::Aws { expires_at, .. } => *expires_at,
+            StorageCredentials::AzureAccessKey { .. } => None,
+            StorageCredentials::AzureSasToken { expires_at, .. } => *expires_at,
+            StorageCredentials::AzureBearerToken { expires_at, .. } => *expires_at,
+        }
+    }
+
+    /// Returns true when the credentials expire within the given duration and should be
+    /// refreshed.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        match self.expires_at() {
+            Some(expires_at) => expires_at <= Utc::now() + duration,
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for StorageCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print secrets
+        match self {
+            StorageCredentials::Aws {
+                access_key_id,
+                expires_at,
+                ..
+            } => write!(
+                f,
+                "StorageCredentials::Aws <{}> expires at {:?}",
+                access_key_id, expires_at
+            ),
+            StorageCredentials::AzureAccessKey { account_name, .. } => {
+                write!(f, "StorageCredentials::AzureAccessKey <{}>", account_name)
+            }
+            StorageCredentials::AzureSasToken {
+                account_name,
+                expires_at,
+                ..
+            } => write!(
+                f,
+                "StorageCredentials::AzureSasToken <{}> expires at {:?}",
+                account_name, expires_at
+            ),
+            StorageCredentials::AzureBearerToken {
+                account_name,
+                expires_at,
+                ..
+            } => write!(
+                f,
+                "StorageCredentials::AzureBearerToken <{}> expires at {:?}",
+                account_name, expires_at
+            ),
+        }
+    }
+}
+
+/// Source of credentials for the cloud storage backends. Implement this trait to plug in
+/// infrastructure specific token services or rotating secrets.
+#[async_trait::async_trait]
+pub trait CredentialsProvider: Send + Sync + fmt::Debug {
+    /// Returns the current credentials. Backends call this again once the returned credentials
+    /// are about to expire.
+    async fn get_credentials(&self) -> Result<StorageCredentials, StorageError>;
+}
+
+/// A provider that always returns the same credentials.
+#[derive(Debug, Clone)]
+pub struct StaticCredentialsProvider {
+    credentials: StorageCredentials,
+}
+
+impl StaticCredentialsProvider {
+    /// Creates a provider returning the given credentials.
+    pub fn new(credentials: StorageCredentials) -> Self {
+        Self { credentials }
+    }
+}
+
+#[async_trait::async_trait]
+impl CredentialsProvider for StaticCredentialsProvider {
+    async fn get_credentials(&self) -> Result<StorageCredentials, StorageError> {
+        Ok(self.credentials.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_expiry() {
+        let credentials = StorageCredentials::AzureSasToken {
+            account_name: "account".to_string(),
+            sas_token: "secret-token".to_string(),
+            expires_at: Some(Utc::now() + Duration::minutes(2)),
+        };
+        assert!(credentials.expires_within(Duration::minutes(5)));
+        assert!(!credentials.expires_within(Duration::minutes(1)));
+        assert!(!format!("{:?}", credentials).contains("secret-token"));
+
+        let credentials = StorageCredentials::AzureAccessKey {
+            account_name: "account".to_string(),
+            access_key: "secret-key".to_string(),
+        };
+        assert!(!credentials.expires_within(Duration::days(365)));
+    }
+}
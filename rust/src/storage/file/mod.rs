@@ -2,16 +2,20 @@
 //!
 //! The local file storage backend is multi-writer safe.
 
+use std::io::SeekFrom;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 
 use chrono::DateTime;
 use futures::{Stream, TryStreamExt};
+use log::debug;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio_stream::wrappers::ReadDirStream;
+use uuid::Uuid;
 
-use super::{ObjectMeta, StorageBackend, StorageError};
+use super::{ObjectMeta, StorageBackend, StorageCapabilities, StorageError};
 
 mod rename;
 
@@ -66,6 +70,7 @@ impl StorageBackend for FileStorageBackend {
         Ok(ObjectMeta {
             path: path.to_string(),
             modified: DateTime::from(attr.modified().unwrap()),
+            size: Some(attr.len() as i64),
         })
     }
 
@@ -73,6 +78,18 @@ impl StorageBackend for FileStorageBackend {
         fs::read(path).await.map_err(StorageError::from)
     }
 
+    async fn get_obj_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        let mut f = fs::File::open(path).await?;
+        let len = f.metadata().await?.len();
+        let end = std::cmp::min(range.end, len);
+        let start = std::cmp::min(range.start, end);
+        let mut buf = vec![0; (end - start) as usize];
+        f.seek(SeekFrom::Start(start)).await?;
+        f.read_exact(&mut buf).await?;
+
+        Ok(buf)
+    }
+
     async fn list_objs<'a>(
         &'a self,
         path: &'a str,
@@ -83,9 +100,11 @@ impl StorageBackend for FileStorageBackend {
         let readdir = ReadDirStream::new(fs::read_dir(path).await?);
 
         Ok(Box::pin(readdir.err_into().and_then(|entry| async move {
+            let metadata = entry.metadata().await.unwrap();
             Ok(ObjectMeta {
                 path: String::from(entry.path().to_str().unwrap()),
-                modified: DateTime::from(entry.metadata().await.unwrap().modified().unwrap()),
+                modified: DateTime::from(metadata.modified().unwrap()),
+                size: Some(metadata.len() as i64),
             })
         })))
     }
@@ -100,7 +119,7 @@ impl StorageBackend for FileStorageBackend {
             .open(path)
             .await?;
 
-        f.write(obj_bytes).await?;
+        f.write_all(obj_bytes).await?;
 
         Ok(())
     }
@@ -109,6 +128,42 @@ impl StorageBackend for FileStorageBackend {
         rename::atomic_rename(src, dst)
     }
 
+    fn capabilities(&self) -> StorageCapabilities {
+        StorageCapabilities {
+            atomic_rename: true,
+            conditional_put: true,
+            // directory listings aren't sorted
+            list_after: false,
+            range_reads: true,
+            presign: false,
+        }
+    }
+
+    async fn put_obj_atomic(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        // write to a hidden sibling first, and hard link it at the path, which fails when the
+        // path exists, so that readers never see the object partially written
+        let path_buf = Path::new(path);
+        let file_name = path_buf
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| StorageError::Generic(format!("Invalid object path: {}", path)))?;
+        let tmp_path = path_buf.with_file_name(format!(".{}.{}.tmp", file_name, Uuid::new_v4()));
+        let tmp_path = tmp_path.to_str().unwrap();
+        self.put_obj(tmp_path, obj_bytes).await?;
+
+        let result = fs::hard_link(tmp_path, path).await;
+        if let Err(e) = fs::remove_file(tmp_path).await {
+            debug!("Failed to delete temporary file {}: {}", tmp_path, e);
+        }
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(StorageError::AlreadyExists(path.to_string()))
+            }
+            Err(e) => Err(StorageError::from(e)),
+        }
+    }
+
     async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
         fs::remove_file(path).await.map_err(StorageError::from)
     }
@@ -143,6 +198,68 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn put_obj_atomic() {
+        let tmp_dir = tempdir::TempDir::new("put_atomic_test").unwrap();
+        let backend = FileStorageBackend::new(tmp_dir.path().to_str().unwrap());
+        let path_buf = tmp_dir
+            .path()
+            .join("_delta_log")
+            .join("00000000000000000000.json");
+        let path = path_buf.to_str().unwrap();
+
+        assert!(backend.capabilities().conditional_put);
+        backend.put_obj_atomic(path, b"hello").await.unwrap();
+        assert!(matches!(
+            backend.put_obj_atomic(path, b"world").await,
+            Err(StorageError::AlreadyExists(s)) if s == path,
+        ));
+        assert_eq!(backend.get_obj(path).await.unwrap(), b"hello");
+        // the temporary files are gone
+        let names = std::fs::read_dir(tmp_dir.path().join("_delta_log"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["00000000000000000000.json"]);
+    }
+
+    #[tokio::test]
+    async fn get_obj_range_and_list_after() {
+        let tmp_dir = tempdir::TempDir::new("range_test").unwrap();
+        let backend = FileStorageBackend::new(tmp_dir.path().to_str().unwrap());
+        let dir = tmp_dir.path().to_str().unwrap();
+        for name in &["a", "b", "c"] {
+            let path = backend.join_path(dir, name);
+            backend.put_obj(&path, b"hello world").await.unwrap();
+        }
+
+        let path = backend.join_path(dir, "a");
+        assert_eq!(backend.get_obj_range(&path, 6..11).await.unwrap(), b"world");
+        assert_eq!(
+            backend.get_obj_range(&path, 6..100).await.unwrap(),
+            b"world"
+        );
+        assert!(backend
+            .get_obj_range(&path, 20..30)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut paths = backend
+            .list_objs_after(dir, &path, None)
+            .await
+            .unwrap()
+            .map_ok(|obj_meta| obj_meta.path)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![backend.join_path(dir, "b"), backend.join_path(dir, "c")]
+        );
+    }
+
     #[tokio::test]
     async fn delete_obj() {
         let tmp_dir = tempdir::TempDir::new("delete_test").unwrap();
@@ -1,10 +1,12 @@
 //! Object storage backend abstraction layer for Delta Table transaction logs and data
 
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::Range;
 use std::pin::Pin;
 
 use chrono::{DateTime, Utc};
-use futures::Stream;
+use futures::{Stream, StreamExt};
 
 #[cfg(feature = "azure")]
 use azure_core::errors::AzureError;
@@ -13,6 +15,8 @@ use std::error::Error;
 
 #[cfg(feature = "azure")]
 pub mod azure;
+pub mod bounded;
+pub mod credentials;
 pub mod file;
 #[cfg(feature = "s3")]
 pub mod s3;
@@ -26,6 +30,18 @@ pub enum UriError {
     /// Error returned when a local file system path is expected, but the URI is not a local file system path.
     #[error("Expected local path URI, found: {0}")]
     ExpectedSLocalPathUri(String),
+    /// Error returned when the query string of a table URI contains an option that isn't
+    /// recognized by the storage backend.
+    #[error("Unknown storage option in URI: {0}")]
+    UnknownOption(String),
+    /// Error returned when an option in the query string of a table URI has an invalid value.
+    #[error("Invalid value for storage option {key}: {value}")]
+    InvalidOption {
+        /// The option key.
+        key: String,
+        /// The invalid value.
+        value: String,
+    },
 
     /// Error returned when the URI is expected to be an S3 path, but does not include a bucket part.
     #[cfg(feature = "s3")]
@@ -39,6 +55,10 @@ pub enum UriError {
     #[cfg(feature = "s3")]
     #[error("Expected S3 URI, found: {0}")]
     ExpectedS3Uri(String),
+    /// Error returned when the bucket of an S3 URI is an ARN, but not a valid access point ARN.
+    #[cfg(feature = "s3")]
+    #[error("Invalid S3 access point ARN: {0}")]
+    InvalidAccessPointArn(String),
 
     /// Error returned when an Azure URI is expected, but the URI is not an Azure file system
     /// (abfs\[s\]) URI.
@@ -73,6 +93,17 @@ pub enum UriError {
     },
 }
 
+/// The kind of storage a table lives in, as determined by the scheme of its URI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StorageBackendKind {
+    /// Local file system, for paths without a scheme or with the `file` scheme.
+    Local,
+    /// AWS S3, for `s3` URIs.
+    S3,
+    /// Azure Data Lake Storage Gen2, for `abfss` URIs.
+    Azure,
+}
+
 /// Enum with variants representing each supported storage backend.
 #[derive(Debug)]
 pub enum Uri<'a> {
@@ -87,6 +118,42 @@ pub enum Uri<'a> {
 }
 
 impl<'a> Uri<'a> {
+    /// Returns the kind of storage backend serving the URI.
+    pub fn kind(&self) -> StorageBackendKind {
+        match self {
+            Uri::LocalPath(_) => StorageBackendKind::Local,
+            #[cfg(feature = "s3")]
+            Uri::S3Object(_) => StorageBackendKind::S3,
+            #[cfg(feature = "azure")]
+            Uri::AdlsGen2Object(_) => StorageBackendKind::Azure,
+        }
+    }
+
+    /// Returns the URI with its scheme and without trailing slashes. Local paths are made
+    /// absolute, resolving symbolic links when the path exists, and prefixed with `file://`.
+    pub fn to_normalized_string(&self) -> String {
+        let uri = match self {
+            Uri::LocalPath(path) => {
+                let path = std::path::Path::new(path);
+                let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| {
+                    std::env::current_dir()
+                        .map(|dir| dir.join(path))
+                        .unwrap_or_else(|_| path.to_path_buf())
+                });
+                format!("file://{}", absolute.display())
+            }
+            #[cfg(feature = "s3")]
+            Uri::S3Object(x) => x.to_string(),
+            #[cfg(feature = "azure")]
+            Uri::AdlsGen2Object(x) => x.to_string(),
+        };
+        match uri.trim_end_matches('/') {
+            // keep the root of the local file system
+            "file:" => "file:///".to_string(),
+            trimmed => trimmed.to_string(),
+        }
+    }
+
     /// Converts the URI to an S3Object. Returns UriError if the URI is not valid for the S3
     /// backend.
     #[cfg(feature = "s3")]
@@ -137,14 +204,12 @@ pub fn parse_uri<'a>(path: &'a str) -> Result<Uri<'a>, UriError> {
         "s3" => {
             cfg_if::cfg_if! {
                 if #[cfg(feature = "s3")] {
-                    let mut path_parts = parts[1].splitn(2, '/');
-                    let bucket = match path_parts.next() {
-                        Some(x) => x,
-                        None => {
-                            return Err(UriError::MissingObjectBucket);
-                        }
-                    };
-                    let key = match path_parts.next() {
+                    let bucket_len = s3::bucket_len(parts[1])?;
+                    let bucket = &parts[1][..bucket_len];
+                    if bucket.is_empty() {
+                        return Err(UriError::MissingObjectBucket);
+                    }
+                    let key = match parts[1][bucket_len..].strip_prefix('/') {
                         Some(x) => x,
                         None => {
                             return Err(UriError::MissingObjectKey);
@@ -179,6 +244,76 @@ pub fn parse_uri<'a>(path: &'a str) -> Result<Uri<'a>, UriError> {
     }
 }
 
+/// A table location along with the storage options given in the query string of its URI, e.g.
+/// `s3://bucket/table?region=us-west-2&profile=prod`. Query strings are only recognized on URIs
+/// with a scheme, so local paths are used as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableUri {
+    /// The table location, without the query string.
+    pub location: String,
+    /// Storage options parsed from the query string.
+    pub options: HashMap<String, String>,
+}
+
+impl TableUri {
+    /// Splits the query string off the given URI and parses it into storage options.
+    pub fn parse(uri: &str) -> Result<Self, UriError> {
+        let mut options = HashMap::new();
+        let location = match uri.find('?') {
+            Some(idx) if uri.contains("://") => {
+                for pair in uri[idx + 1..].split('&').filter(|pair| !pair.is_empty()) {
+                    let mut parts = pair.splitn(2, '=');
+                    let key = percent_decode(parts.next().unwrap_or(""));
+                    let value = percent_decode(parts.next().unwrap_or(""));
+                    if key.is_empty() {
+                        return Err(UriError::UnknownOption(pair.to_string()));
+                    }
+                    options.insert(key, value);
+                }
+                &uri[..idx]
+            }
+            _ => uri,
+        };
+
+        Ok(Self {
+            location: location.to_string(),
+            options,
+        })
+    }
+
+    /// Returns an `UnknownOption` error for the first option not included in `known_options`.
+    pub fn check_options(&self, known_options: &[&str]) -> Result<(), UriError> {
+        match self
+            .options
+            .keys()
+            .find(|key| !known_options.contains(&key.as_str()))
+        {
+            Some(key) => Err(UriError::UnknownOption(key.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Decodes the percent-encoded bytes of a URI component.
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 /// Error enum returned when storage backend interaction fails.
 #[derive(thiserror::Error, Debug)]
 pub enum StorageError {
@@ -200,6 +335,31 @@ pub enum StorageError {
     /// The file system represented by the scheme is not known.
     #[error("File system not supported")]
     FileSystemNotSupported,
+    /// The storage backend can't generate pre-signed URLs.
+    #[error("Pre-signed URLs aren't supported by the storage backend")]
+    PresignNotSupported,
+    /// The storage backend can't create objects atomically, see `StorageBackend::put_obj_atomic`.
+    #[error("Atomic puts aren't supported by the storage backend")]
+    AtomicPutNotSupported,
+    /// The storage backend can neither rename nor put objects atomically, so concurrent writers
+    /// could overwrite each other's log entries, see `StorageCapabilities`.
+    #[error("The storage backend can't commit log entries atomically")]
+    AtomicCommitNotSupported,
+    /// The object stored by an upload doesn't match the uploaded bytes, e.g. because it was
+    /// truncated on the way.
+    #[error("Object {path} doesn't match the uploaded bytes: {reason}")]
+    UploadMismatch {
+        /// The path of the uploaded object.
+        path: String,
+        /// How the stored object differs from the uploaded bytes.
+        reason: String,
+    },
+    /// A storage operation didn't complete within the timeout of `BoundedStorageBackend`.
+    #[error("Storage operation timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    /// A storage operation was abandoned because its cancellation token was cancelled.
+    #[error("Storage operation cancelled")]
+    Cancelled,
     /// Wraps a generic storage backend error. The wrapped string contains the details.
     #[error("Generic error: {0}")]
     Generic(String),
@@ -283,6 +443,10 @@ pub enum StorageError {
     #[cfg(feature = "azure")]
     #[error("Azure config error: {0}")]
     AzureConfig(String),
+    /// Error returned when an Azure Active Directory token can't be acquired
+    #[cfg(feature = "azure")]
+    #[error("Azure authentication error: {0}")]
+    AzureAuth(String),
 
     /// Error returned when the URI is invalid.
     /// The wrapped UriError contains additional details.
@@ -332,6 +496,41 @@ pub struct ObjectMeta {
     // The timestamp of a commit comes from the remote storage `lastModifiedTime`, and can be
     // adjusted for clock skew.
     pub modified: DateTime<Utc>,
+    /// The size of the object in bytes, when reported by the storage backend.
+    pub size: Option<i64>,
+}
+
+/// What a storage backend can do, returned by `StorageBackend::capabilities`, so that the table,
+/// its transactions and readers pick a strategy for the backend instead of assuming the behavior
+/// of specific ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageCapabilities {
+    /// `rename_obj` fails with [StorageError::AlreadyExists] instead of replacing the
+    /// destination, so transactions can commit by renaming temporary files.
+    pub atomic_rename: bool,
+    /// `put_obj_atomic` is implemented, so transactions can write their log entries directly.
+    pub conditional_put: bool,
+    /// `list_objs_after` starts listing after the given path instead of filtering a full
+    /// listing, and lists in lexicographic order.
+    pub list_after: bool,
+    /// `get_obj_range` reads only the requested bytes instead of the whole object.
+    pub range_reads: bool,
+    /// `presign` signs URLs.
+    pub presign: bool,
+}
+
+impl Default for StorageCapabilities {
+    /// The capabilities of a backend only implementing the required methods, with a `rename_obj`
+    /// honoring its contract.
+    fn default() -> Self {
+        Self {
+            atomic_rename: true,
+            conditional_put: false,
+            list_after: false,
+            range_reads: false,
+            presign: false,
+        }
+    }
 }
 
 /// Abstractions for underlying blob storages hosting the Delta table. To add support for new cloud
@@ -360,6 +559,16 @@ pub trait StorageBackend: Send + Sync + Debug {
     /// Fetch object content
     async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError>;
 
+    /// Fetches the bytes of the object in `range`, or up to its end when the range goes past it.
+    /// The default implementation fetches the whole object; backends able to read ranges
+    /// override it and report `range_reads` in their capabilities.
+    async fn get_obj_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        let obj = self.get_obj(path).await?;
+        let end = std::cmp::min(range.end, obj.len() as u64) as usize;
+        let start = std::cmp::min(range.start as usize, end);
+        Ok(obj[start..end].to_vec())
+    }
+
     /// Return a list of objects by `path` prefix in an async stream.
     async fn list_objs<'a>(
         &'a self,
@@ -369,6 +578,29 @@ pub trait StorageBackend: Send + Sync + Debug {
         StorageError,
     >;
 
+    /// Returns the objects listed by `list_objs(path)` whose path sorts after `start_after`. The
+    /// default implementation filters the full listing; backends able to start listing after a
+    /// path override it and report `list_after` in their capabilities. Backends listing in pages
+    /// request at most `page_size` objects per page when given, instead of their default.
+    async fn list_objs_after<'a>(
+        &'a self,
+        path: &'a str,
+        start_after: &'a str,
+        _page_size: Option<usize>,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        let stream = self.list_objs(path).await?;
+        Ok(Box::pin(stream.filter(move |obj_meta| {
+            let keep = match obj_meta {
+                Ok(obj_meta) => obj_meta.path.as_str() > start_after,
+                Err(_) => true,
+            };
+            futures::future::ready(keep)
+        })))
+    }
+
     /// Create new object with `obj_bytes` as content.
     async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError>;
 
@@ -383,16 +615,80 @@ pub trait StorageBackend: Send + Sync + Debug {
 
     /// Deletes object by `path`.
     async fn delete_obj(&self, path: &str) -> Result<(), StorageError>;
+
+    /// Returns what the backend can do. Wrappers of other backends should return the
+    /// capabilities of the wrapped backend.
+    fn capabilities(&self) -> StorageCapabilities {
+        StorageCapabilities::default()
+    }
+
+    /// Creates the object at `path` with `obj_bytes` as content, unless an object exists there
+    /// already, in which case it returns a [StorageError::AlreadyExists] error, like `rename_obj`.
+    /// Readers must never see a partially written object. Backends able to do so, e.g. with
+    /// conditional requests, override this method and report `conditional_put` in their
+    /// capabilities; others return [StorageError::AtomicPutNotSupported]. Transactions on backends
+    /// with conditional puts write their log entries directly at the committed version, instead
+    /// of writing them to a temporary file renamed to that version, which halves the requests of
+    /// a commit.
+    async fn put_obj_atomic(&self, _path: &str, _obj_bytes: &[u8]) -> Result<(), StorageError> {
+        Err(StorageError::AtomicPutNotSupported)
+    }
+
+    /// Returns a URL granting read access to the object at `path` without credentials until
+    /// `expiry` has elapsed. Backends able to sign URLs report `presign` in their capabilities;
+    /// others, like the local file system, return [StorageError::PresignNotSupported].
+    async fn presign(
+        &self,
+        _path: &str,
+        _expiry: std::time::Duration,
+    ) -> Result<String, StorageError> {
+        Err(StorageError::PresignNotSupported)
+    }
+
+    /// Checks that the object at `path` holds `obj_bytes`, e.g. right after uploading them,
+    /// without reading it back, and returns [StorageError::UploadMismatch] otherwise. The default
+    /// implementation compares the size of the object, when reported; backends exposing a
+    /// checksum of the content compare it as well.
+    async fn verify_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        match self.head_obj(path).await?.size {
+            Some(size) if size != obj_bytes.len() as i64 => Err(StorageError::UploadMismatch {
+                path: path.to_string(),
+                reason: format!("expected {} bytes, found {}", obj_bytes.len(), size),
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Dynamically construct a Storage backend trait object based on scheme for provided URI
 pub fn get_backend_for_uri(uri: &str) -> Result<Box<dyn StorageBackend>, StorageError> {
-    match parse_uri(uri)? {
-        Uri::LocalPath(root) => Ok(Box::new(file::FileStorageBackend::new(root))),
+    get_backend_for_table_uri(&TableUri::parse(uri)?)
+}
+
+/// Dynamically construct a Storage backend trait object for the location of the given table URI,
+/// configured with the storage options of its query string.
+pub fn get_backend_for_table_uri(
+    table_uri: &TableUri,
+) -> Result<Box<dyn StorageBackend>, StorageError> {
+    match parse_uri(&table_uri.location)? {
+        Uri::LocalPath(root) => {
+            table_uri.check_options(&[])?;
+            Ok(Box::new(file::FileStorageBackend::new(root)))
+        }
         #[cfg(feature = "s3")]
-        Uri::S3Object(_) => Ok(Box::new(s3::S3StorageBackend::new()?)),
+        Uri::S3Object(_) => {
+            let options = s3::S3StorageOptions::from_uri_options(&table_uri.options)?;
+            Ok(Box::new(s3::S3StorageBackend::new_with_options(options)?))
+        }
         #[cfg(feature = "azure")]
-        Uri::AdlsGen2Object(obj) => Ok(Box::new(azure::AdlsGen2Backend::new(obj.file_system)?)),
+        Uri::AdlsGen2Object(obj) => {
+            let options = azure::AzureStorageOptions::from_uri_options(&table_uri.options)?;
+            Ok(Box::new(azure::AdlsGen2Backend::new_with_options(
+                obj.file_system,
+                obj.account_name,
+                options,
+            )?))
+        }
     }
 }
 
@@ -409,6 +705,41 @@ mod tests {
         assert_eq!(uri2.into_localpath().unwrap(), "/foo/bar");
     }
 
+    #[test]
+    fn test_normalize_local_uri() {
+        let uri = parse_uri("./tests/data/simple_table/").unwrap();
+        assert_eq!(uri.kind(), StorageBackendKind::Local);
+        let normalized = uri.to_normalized_string();
+        assert!(normalized.starts_with("file:///"));
+        assert!(normalized.ends_with("/tests/data/simple_table"));
+        assert!(!normalized.contains("/./"));
+        assert_eq!(
+            parse_uri("file:///tmp/missing_table/")
+                .unwrap()
+                .to_normalized_string(),
+            "file:///tmp/missing_table"
+        );
+    }
+
+    #[test]
+    fn test_parse_table_uri() {
+        let uri =
+            TableUri::parse("s3://bucket/table?region=us-west-2&profile=prod%2Fread").unwrap();
+        assert_eq!(uri.location, "s3://bucket/table");
+        assert_eq!(uri.options.len(), 2);
+        assert_eq!(uri.options["region"], "us-west-2");
+        assert_eq!(uri.options["profile"], "prod/read");
+        assert!(uri.check_options(&["region", "profile"]).is_ok());
+        assert_eq!(
+            uri.check_options(&["region"]),
+            Err(UriError::UnknownOption("profile".to_string()))
+        );
+
+        let uri = TableUri::parse("./tests/data/table?").unwrap();
+        assert_eq!(uri.location, "./tests/data/table?");
+        assert!(uri.options.is_empty());
+    }
+
     #[cfg(feature = "s3")]
     #[test]
     fn test_parse_s3_object_uri() {
@@ -420,6 +751,46 @@ mod tests {
                 key: "bar",
             }
         );
+        let uri = parse_uri("s3://foo/bar/").unwrap();
+        assert_eq!(uri.kind(), StorageBackendKind::S3);
+        assert_eq!(uri.to_normalized_string(), "s3://foo/bar");
+
+        let uri =
+            parse_uri("s3://arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap/foo/bar").unwrap();
+        let object = uri.into_s3object().unwrap();
+        assert_eq!(
+            object,
+            s3::S3Object {
+                bucket: "arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap",
+                key: "foo/bar",
+            }
+        );
+        assert_eq!(
+            object.access_point(),
+            Some(s3::S3AccessPoint {
+                region: "us-west-2",
+                account_id: "123456789012",
+                name: "my-ap",
+            })
+        );
+        assert_eq!(
+            parse_uri("s3://arn:aws:s3::123456789012:accesspoint/mfzwi23gnjvgw.mrap/foo")
+                .unwrap()
+                .into_s3object()
+                .unwrap()
+                .access_point()
+                .unwrap()
+                .region,
+            ""
+        );
+        assert!(matches!(
+            parse_uri("s3://arn:aws:s3:us-west-2:123456789012:bucket/foo"),
+            Err(UriError::InvalidAccessPointArn(_))
+        ));
+        assert!(matches!(
+            parse_uri("s3://foo"),
+            Err(UriError::MissingObjectKey)
+        ));
     }
 
     #[cfg(feature = "azure")]
@@ -1,15 +1,24 @@
 //! AWS S3 storage backend. It only supports a single writer and is not multi-writer safe.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Debug;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::{fmt, pin::Pin};
 
 use chrono::{DateTime, FixedOffset, Utc};
 use futures::Stream;
 use log::debug;
-use rusoto_core::credential::ChainProvider;
-use rusoto_core::{HttpClient, Region, RusotoError};
-use rusoto_credential::AutoRefreshingProvider;
+use rusoto_core::credential::{ChainProvider, ProfileProvider};
+use rusoto_core::request::{DispatchSignedRequest, DispatchSignedRequestFuture, HttpDispatchError};
+use rusoto_core::signature::SignedRequest;
+use rusoto_core::{Client, HttpClient, Region, RusotoError};
+use rusoto_credential::{
+    AutoRefreshingProvider, AwsCredentials, CredentialsError, ProvideAwsCredentials,
+};
+use rusoto_s3::util::{PreSignedRequest, PreSignedRequestOption};
 use rusoto_s3::{
     CopyObjectRequest, DeleteObjectRequest, GetObjectRequest, HeadObjectRequest,
     ListObjectsV2Request, PutObjectRequest, S3Client, S3,
@@ -17,7 +26,8 @@ use rusoto_s3::{
 use rusoto_sts::WebIdentityProvider;
 use tokio::io::AsyncReadExt;
 
-use super::{parse_uri, ObjectMeta, StorageBackend, StorageError};
+use super::credentials::{CredentialsProvider, StorageCredentials};
+use super::{parse_uri, ObjectMeta, StorageBackend, StorageCapabilities, StorageError, UriError};
 
 #[cfg(feature = "dynamodb")]
 pub mod dynamodb_lock;
@@ -62,11 +72,325 @@ impl From<RusotoError<rusoto_s3::ListObjectsV2Error>> for StorageError {
     }
 }
 
-fn create_s3_client(region: Region) -> Result<S3Client, StorageError> {
-    let dispatcher = HttpClient::new()
-        .map_err(|_| StorageError::S3Generic("Failed to create request dispatcher".to_string()))?;
+/// The object store behind the S3 API, which determines the defaults of the S3StorageBackend.
+///
+/// Requests are always signed with AWS Signature Version 4 and, unless the provider only serves
+/// virtual-hosted style requests (`{bucket}.{endpoint}/{key}`), address buckets in the path of
+/// the url (`{endpoint}/{bucket}/{key}`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum S3Provider {
+    /// AWS S3, the endpoint is derived from the region. Buckets can also be given as the ARNs
+    /// of single-region access points, which are sent virtual-hosted style requests.
+    Aws,
+    /// Cloudflare R2. The endpoint, `https://{account_id}.r2.cloudflarestorage.com`, must be
+    /// set and the signing region defaults to `auto`.
+    CloudflareR2,
+    /// Alibaba Cloud OSS, which only serves virtual-hosted style requests. The endpoint of the
+    /// region, e.g. `https://oss-cn-hangzhou.aliyuncs.com`, must be set and the signing region
+    /// defaults to its first label, e.g. `oss-cn-hangzhou`. Pre-signed URLs are virtual-hosted
+    /// style too.
+    AlibabaOss,
+    /// Any other S3 compatible store, e.g. MinIO or Ceph. The endpoint must be set and the
+    /// signing region defaults to `us-east-1`.
+    Compatible,
+}
+
+impl Default for S3Provider {
+    fn default() -> Self {
+        S3Provider::Aws
+    }
+}
+
+impl S3Provider {
+    /// Whether the bucket is addressed in the host name of the requests rather than their path.
+    fn virtual_hosted(self) -> bool {
+        self == S3Provider::AlibabaOss
+    }
+
+    /// Whether buckets can be given as access point ARNs.
+    fn access_points(self) -> bool {
+        self == S3Provider::Aws
+    }
+}
+
+impl FromStr for S3Provider {
+    type Err = UriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aws" => Ok(S3Provider::Aws),
+            "r2" => Ok(S3Provider::CloudflareR2),
+            "oss" => Ok(S3Provider::AlibabaOss),
+            "s3-compatible" => Ok(S3Provider::Compatible),
+            _ => Err(UriError::InvalidOption {
+                key: "provider".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Options used to configure the S3StorageBackend. Unset options fall back to the AWS
+/// environment variables and config files.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct S3StorageOptions {
+    /// The AWS region of the bucket.
+    pub region: Option<String>,
+    /// Endpoint url of an S3 compatible service, overriding `AWS_ENDPOINT_URL`.
+    pub endpoint_url: Option<String>,
+    /// The profile of the AWS config files to read credentials from.
+    pub profile: Option<String>,
+    /// The object store behind the S3 API.
+    pub provider: S3Provider,
+}
+
+impl S3StorageOptions {
+    /// Query string options recognized in S3 table URIs.
+    pub const URI_OPTIONS: &'static [&'static str] = &["region", "endpoint", "profile", "provider"];
+
+    /// Creates options from the query string options of an S3 table URI, e.g.
+    /// `s3://bucket/table?region=us-west-2&profile=prod`.
+    pub fn from_uri_options(options: &HashMap<String, String>) -> Result<Self, UriError> {
+        if let Some(key) = options
+            .keys()
+            .find(|key| !Self::URI_OPTIONS.contains(&key.as_str()))
+        {
+            return Err(UriError::UnknownOption(key.clone()));
+        }
+        let provider = match options.get("provider") {
+            Some(provider) => provider.parse()?,
+            None => S3Provider::Aws,
+        };
+        if let Some(region) = options.get("region") {
+            if provider == S3Provider::Aws
+                && options.get("endpoint").is_none()
+                && Region::from_str(region).is_err()
+            {
+                return Err(UriError::InvalidOption {
+                    key: "region".to_string(),
+                    value: region.clone(),
+                });
+            }
+        }
+
+        Ok(Self {
+            region: options.get("region").cloned(),
+            endpoint_url: options.get("endpoint").cloned(),
+            profile: options.get("profile").cloned(),
+            provider,
+        })
+    }
+
+    fn region(&self) -> Result<Region, StorageError> {
+        let endpoint_url = self
+            .endpoint_url
+            .clone()
+            .or_else(|| std::env::var("AWS_ENDPOINT_URL").ok());
+        let region = match endpoint_url {
+            Some(endpoint) => Region::Custom {
+                name: self
+                    .region
+                    .clone()
+                    .or_else(|| std::env::var("AWS_REGION").ok())
+                    .unwrap_or_else(|| match self.provider {
+                        S3Provider::Aws => "custom".to_string(),
+                        S3Provider::CloudflareR2 => "auto".to_string(),
+                        S3Provider::AlibabaOss => endpoint_first_label(&endpoint).to_string(),
+                        S3Provider::Compatible => "us-east-1".to_string(),
+                    }),
+                endpoint,
+            },
+            None if self.provider != S3Provider::Aws => {
+                return Err(StorageError::S3Generic(format!(
+                    "An endpoint url is required for the {:?} provider",
+                    self.provider
+                )))
+            }
+            None => match &self.region {
+                Some(region) => Region::from_str(region).map_err(|_| {
+                    StorageError::S3Generic(format!("Invalid AWS region: {}", region))
+                })?,
+                None => Region::default(),
+            },
+        };
+
+        Ok(region)
+    }
+}
+
+/// The first label of the host of an endpoint url, e.g. `oss-cn-hangzhou` for
+/// `https://oss-cn-hangzhou.aliyuncs.com`.
+fn endpoint_first_label(endpoint: &str) -> &str {
+    let host = endpoint
+        .find("://")
+        .map_or(endpoint, |i| &endpoint[i + 3..]);
+    host.split(|c| c == '.' || c == ':' || c == '/')
+        .next()
+        .unwrap_or(host)
+}
+
+/// Adapts a `CredentialsProvider` to the rusoto credentials interface.
+#[derive(Debug)]
+struct CredentialsProviderAdapter(Arc<dyn CredentialsProvider>);
+
+#[async_trait::async_trait]
+impl ProvideAwsCredentials for CredentialsProviderAdapter {
+    async fn credentials(&self) -> Result<AwsCredentials, CredentialsError> {
+        match self.0.get_credentials().await {
+            Ok(StorageCredentials::Aws {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                expires_at,
+            }) => Ok(AwsCredentials::new(
+                access_key_id,
+                secret_access_key,
+                session_token,
+                expires_at,
+            )),
+            Ok(credentials) => Err(CredentialsError::new(format!(
+                "Expected AWS credentials, got: {:?}",
+                credentials
+            ))),
+            Err(e) => Err(CredentialsError::new(e.to_string())),
+        }
+    }
+}
+
+fn create_dispatcher() -> Result<HttpClient, StorageError> {
+    HttpClient::new()
+        .map_err(|_| StorageError::S3Generic("Failed to create request dispatcher".to_string()))
+}
+
+/// Creates the S3 client of the provider, signing its requests with the given credentials.
+fn create_client(
+    provider: S3Provider,
+    region: &Region,
+    credentials: SharedCredentialsProvider,
+) -> Result<S3Client, StorageError> {
+    if !provider.virtual_hosted() && !provider.access_points() {
+        return Ok(S3Client::new_with(
+            create_dispatcher()?,
+            credentials,
+            region.clone(),
+        ));
+    }
+
+    // rusoto only builds path style requests and signs them before dispatching, so the requests
+    // are left unsigned by the client and signed once rewritten by the dispatcher
+    let dispatcher = VirtualHostedDispatcher {
+        dispatcher: Arc::new(create_dispatcher()?),
+        credentials,
+        provider,
+    };
+    Ok(S3Client::new_with_client(
+        Client::new_not_signing(dispatcher),
+        region.clone(),
+    ))
+}
+
+/// Sends the path style requests built by rusoto, `{endpoint}/{bucket}/{key}`, in virtual-hosted
+/// style, `{bucket}.{endpoint}/{key}`, signing them once rewritten. Providers serving path style
+/// requests only have their requests through access points rewritten.
+struct VirtualHostedDispatcher {
+    dispatcher: Arc<HttpClient>,
+    credentials: SharedCredentialsProvider,
+    provider: S3Provider,
+}
+
+impl DispatchSignedRequest for VirtualHostedDispatcher {
+    fn dispatch(
+        &self,
+        mut request: SignedRequest,
+        timeout: Option<std::time::Duration>,
+    ) -> DispatchSignedRequestFuture {
+        if self.provider.virtual_hosted() || request.path.starts_with("/arn:") {
+            to_virtual_hosted(&mut request);
+        }
+        let dispatcher = self.dispatcher.clone();
+        let credentials = self.credentials.clone();
+        Box::pin(async move {
+            let credentials = credentials
+                .credentials()
+                .await
+                .map_err(|e| HttpDispatchError::new(e.message))?;
+            request.sign(&credentials);
+            dispatcher.dispatch(request, timeout).await
+        })
+    }
+}
+
+/// Moves the bucket of a path style request to its host name, e.g. `/bucket/key` on `endpoint`
+/// to `/key` on `bucket.endpoint`. Access point ARNs are moved to the host name of the access
+/// point, `{name}-{account_id}.s3-accesspoint.{region}.amazonaws.com`, and the request is signed
+/// for the region of the access point. Requests without a bucket, like listing the buckets, are
+/// left unchanged.
+fn to_virtual_hosted(request: &mut SignedRequest) {
+    let path = request.path.trim_start_matches('/');
+    let (bucket, key) = match bucket_len(path) {
+        Ok(len) => path.split_at(len),
+        Err(_) => return,
+    };
+    if bucket.is_empty() {
+        return;
+    }
+    let object = S3Object { bucket, key };
+    let (hostname, region) = match object.access_point() {
+        Some(access_point) => {
+            let hostname = format!(
+                "{}-{}.s3-accesspoint.{}.amazonaws.com",
+                access_point.name, access_point.account_id, access_point.region
+            );
+            // regions newer than rusoto are only known by their name
+            let region = Region::from_str(access_point.region).unwrap_or_else(|_| Region::Custom {
+                name: access_point.region.to_string(),
+                endpoint: format!("https://{}", hostname),
+            });
+            (hostname, Some(region))
+        }
+        None => (format!("{}.{}", bucket, request.hostname()), None),
+    };
+    let key = if key.is_empty() { "/" } else { key }.to_string();
+    if let Some(region) = region {
+        request.region = region;
+    }
+    request.set_hostname(Some(hostname));
+    request.path = key;
+}
+
+/// Shares a credentials provider between the S3 client and the signing of pre-signed URLs.
+#[derive(Clone)]
+struct SharedCredentialsProvider(Arc<dyn ProvideAwsCredentials + Send + Sync>);
+
+impl SharedCredentialsProvider {
+    fn new<P: ProvideAwsCredentials + Send + Sync + 'static>(provider: P) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+#[async_trait::async_trait]
+impl ProvideAwsCredentials for SharedCredentialsProvider {
+    async fn credentials(&self) -> Result<AwsCredentials, CredentialsError> {
+        self.0.credentials().await
+    }
+}
 
-    let client = match std::env::var("AWS_WEB_IDENTITY_TOKEN_FILE") {
+fn create_credentials_provider(
+    profile: Option<&str>,
+) -> Result<SharedCredentialsProvider, StorageError> {
+    if let Some(profile) = profile {
+        let mut profile_provider = ProfileProvider::new().map_err(|e| {
+            StorageError::S3Generic(format!(
+                "Failed to read AWS profiles with message: {}",
+                e.message
+            ))
+        })?;
+        profile_provider.set_profile(profile);
+        let provider = ChainProvider::with_profile_provider(profile_provider);
+        return Ok(SharedCredentialsProvider::new(provider));
+    }
+
+    let provider = match std::env::var("AWS_WEB_IDENTITY_TOKEN_FILE") {
         Ok(_) => {
             let provider = WebIdentityProvider::from_k8s_env();
             let provider = AutoRefreshingProvider::new(provider).map_err(|e| {
@@ -75,12 +399,12 @@ fn create_s3_client(region: Region) -> Result<S3Client, StorageError> {
                     e.message
                 ))
             })?;
-            S3Client::new_with(dispatcher, provider, region)
+            SharedCredentialsProvider::new(provider)
         }
-        Err(_) => S3Client::new_with(dispatcher, ChainProvider::new(), region),
+        Err(_) => SharedCredentialsProvider::new(ChainProvider::new()),
     };
 
-    Ok(client)
+    Ok(provider)
 }
 
 fn parse_obj_last_modified_time(
@@ -128,6 +452,7 @@ impl TryFrom<rusoto_s3::Object> for ObjectMeta {
                 StorageError::S3Generic("S3 Object missing key attribute".to_string())
             })?,
             modified: parse_obj_last_modified_time(&obj.last_modified)?,
+            size: obj.size,
         })
     }
 }
@@ -141,36 +466,141 @@ pub struct S3Object<'a> {
     pub key: &'a str,
 }
 
+impl<'a> S3Object<'a> {
+    /// Returns the parts of the access point ARN used in place of the bucket name, if any.
+    pub fn access_point(&self) -> Option<S3AccessPoint<'a>> {
+        let mut parts = self.bucket.strip_prefix("arn:")?.splitn(5, ':');
+        let _partition = parts.next()?;
+        if parts.next()? != "s3" {
+            return None;
+        }
+        let region = parts.next()?;
+        let account_id = parts.next()?;
+        let name = parts.next()?.strip_prefix("accesspoint/")?;
+
+        Some(S3AccessPoint {
+            region,
+            account_id,
+            name,
+        })
+    }
+}
+
+/// The parts of an S3 access point ARN, e.g.
+/// `arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap`.
+#[derive(Debug, PartialEq)]
+pub struct S3AccessPoint<'a> {
+    /// The region of the access point, empty for multi-region access points.
+    pub region: &'a str,
+    /// The id of the account owning the access point.
+    pub account_id: &'a str,
+    /// The name of the access point, or its alias for multi-region access points.
+    pub name: &'a str,
+}
+
 impl<'a> fmt::Display for S3Object<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "s3://{}/{}", self.bucket, self.key)
     }
 }
 
+/// Returns the length of the bucket at the start of a `{bucket}/{key}` path. Access point ARNs
+/// used in place of the bucket contain a slash, e.g.
+/// `arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap/path/to/table`.
+pub(crate) fn bucket_len(path: &str) -> Result<usize, UriError> {
+    if !path.starts_with("arn:") {
+        return Ok(path.find('/').unwrap_or_else(|| path.len()));
+    }
+    let name_start = path
+        .find(":accesspoint/")
+        .ok_or_else(|| UriError::InvalidAccessPointArn(path.to_string()))?
+        + ":accesspoint/".len();
+
+    Ok(path[name_start..]
+        .find('/')
+        .map_or(path.len(), |name_len| name_start + name_len))
+}
+
+fn parse_s3_object(path: &str) -> Result<S3Object, StorageError> {
+    let object = parse_uri(path)?.into_s3object()?;
+    if let Some(access_point) = object.access_point() {
+        // multi-region access points require SigV4A signatures, which rusoto doesn't support
+        if access_point.region.is_empty() {
+            return Err(StorageError::S3Generic(format!(
+                "Requests through S3 access point {} aren't supported, only single-region access \
+                 points are",
+                access_point.name
+            )));
+        }
+    }
+
+    Ok(object)
+}
+
+/// Longest validity of pre-signed URLs allowed by SigV4, 7 days.
+const MAX_PRESIGN_EXPIRY: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
 /// An S3 implementation of the `StorageBackend` trait
 pub struct S3StorageBackend {
     client: rusoto_s3::S3Client,
     lock_client: Option<Box<dyn LockClient>>,
+    region: Region,
+    credentials: SharedCredentialsProvider,
+    provider: S3Provider,
 }
 
 impl S3StorageBackend {
     /// Creates a new S3StorageBackend.
     pub fn new() -> Result<Self, StorageError> {
-        let region = if let Ok(url) = std::env::var("AWS_ENDPOINT_URL") {
-            Region::Custom {
-                name: std::env::var("AWS_REGION").unwrap_or_else(|_| "custom".to_string()),
-                endpoint: url,
-            }
-        } else {
-            Region::default()
-        };
+        Self::new_with_options(S3StorageOptions::default())
+    }
+
+    /// Creates a new S3StorageBackend configured with the given options.
+    pub fn new_with_options(options: S3StorageOptions) -> Result<Self, StorageError> {
+        let region = options.region()?;
 
-        let client = create_s3_client(region.clone())?;
-        let lock_client = try_create_lock_client(region)?;
+        let credentials = create_credentials_provider(options.profile.as_deref())?;
+        let client = create_client(options.provider, &region, credentials.clone())?;
+        let lock_client = try_create_lock_client(region.clone())?;
 
         Ok(Self {
             client,
             lock_client,
+            region,
+            credentials,
+            provider: options.provider,
+        })
+    }
+
+    /// Creates a new S3StorageBackend that requests AWS credentials from the given provider,
+    /// whenever the previous ones are about to expire, instead of reading them from the
+    /// environment. The `profile` option is ignored. The DynamoDB lock client, when enabled,
+    /// still reads its credentials from the environment.
+    pub fn new_with_credentials_provider(
+        options: S3StorageOptions,
+        credentials_provider: Arc<dyn CredentialsProvider>,
+    ) -> Result<Self, StorageError> {
+        let region = options.region()?;
+
+        let provider = AutoRefreshingProvider::new(CredentialsProviderAdapter(
+            credentials_provider,
+        ))
+        .map_err(|e| {
+            StorageError::S3Generic(format!(
+                "Failed to create S3 credentials provider with message: {}",
+                e.message
+            ))
+        })?;
+        let credentials = SharedCredentialsProvider::new(provider);
+        let client = create_client(options.provider, &region, credentials.clone())?;
+        let lock_client = try_create_lock_client(region.clone())?;
+
+        Ok(Self {
+            client,
+            lock_client,
+            region,
+            credentials,
+            provider: options.provider,
         })
     }
 
@@ -185,8 +615,8 @@ impl S3StorageBackend {
             Err(e) => return Err(e),
         }
 
-        let src = parse_uri(src)?.into_s3object()?;
-        let dst = parse_uri(dst)?.into_s3object()?;
+        let src = parse_s3_object(src)?;
+        let dst = parse_s3_object(dst)?;
 
         self.client
             .copy_object(CopyObjectRequest {
@@ -207,47 +637,18 @@ impl S3StorageBackend {
 
         Ok(())
     }
-}
-
-impl Default for S3StorageBackend {
-    fn default() -> Self {
-        Self::new().unwrap()
-    }
-}
-
-impl std::fmt::Debug for S3StorageBackend {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(fmt, "S3StorageBackend")
-    }
-}
-
-#[async_trait::async_trait]
-impl StorageBackend for S3StorageBackend {
-    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
-        let uri = parse_uri(path)?.into_s3object()?;
-
-        let result = self
-            .client
-            .head_object(HeadObjectRequest {
-                bucket: uri.bucket.to_string(),
-                key: uri.key.to_string(),
-                ..Default::default()
-            })
-            .await?;
 
-        Ok(ObjectMeta {
-            path: path.to_string(),
-            modified: parse_head_obj_last_modified_time(&result.last_modified)?,
-        })
-    }
-
-    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
-        debug!("fetching s3 object: {}...", path);
-
-        let uri = parse_uri(path)?.into_s3object()?;
+    /// Fetches the object, or the bytes of the given HTTP `range` of it.
+    async fn get_obj_with_range(
+        &self,
+        path: &str,
+        range: Option<String>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let uri = parse_s3_object(path)?;
         let get_req = GetObjectRequest {
             bucket: uri.bucket.to_string(),
             key: uri.key.to_string(),
+            range,
             ..Default::default()
         };
 
@@ -270,14 +671,18 @@ impl StorageBackend for S3StorageBackend {
         Ok(buf)
     }
 
-    async fn list_objs<'a>(
-        &'a self,
-        path: &'a str,
+    /// Lists the objects by `path` prefix, starting after the key `start_after` when given, in
+    /// pages of at most `page_size` objects when given.
+    fn list_objs_from(
+        &self,
+        path: &str,
+        start_after: Option<String>,
+        page_size: Option<usize>,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'static>>,
         StorageError,
     > {
-        let uri = parse_uri(path)?.into_s3object()?;
+        let uri = parse_s3_object(path)?;
 
         /// This enum is used to represent 3 states in our object metadata streaming logic:
         /// * Value(None): the initial state, prior to performing any s3 list call.
@@ -296,12 +701,16 @@ impl StorageBackend for S3StorageBackend {
             continuation_token: ContinuationToken,
             bucket: String,
             key: String,
+            start_after: Option<String>,
+            page_size: Option<i64>,
         }
         let ctx = ListContext {
             obj_iter: Vec::new().into_iter(),
             continuation_token: ContinuationToken::Value(None),
             bucket: uri.bucket.to_string(),
             key: uri.key.to_string(),
+            start_after,
+            page_size: page_size.map(|page_size| page_size as i64),
             client: self.client.clone(),
         };
 
@@ -317,6 +726,8 @@ impl StorageBackend for S3StorageBackend {
                             bucket: ctx.bucket.clone(),
                             prefix: Some(ctx.key.clone()),
                             continuation_token: v.clone(),
+                            start_after: ctx.start_after.clone(),
+                            max_keys: ctx.page_size,
                             ..Default::default()
                         };
                         let result = match ctx.client.list_objects_v2(list_req).await {
@@ -340,11 +751,98 @@ impl StorageBackend for S3StorageBackend {
 
         Ok(Box::pin(futures::stream::unfold(ctx, next_meta)))
     }
+}
+
+impl Default for S3StorageBackend {
+    fn default() -> Self {
+        Self::new().unwrap()
+    }
+}
+
+impl std::fmt::Debug for S3StorageBackend {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "S3StorageBackend")
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for S3StorageBackend {
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        let uri = parse_s3_object(path)?;
+
+        let result = self
+            .client
+            .head_object(HeadObjectRequest {
+                bucket: uri.bucket.to_string(),
+                key: uri.key.to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(ObjectMeta {
+            path: path.to_string(),
+            modified: parse_head_obj_last_modified_time(&result.last_modified)?,
+            size: result.content_length,
+        })
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        debug!("fetching s3 object: {}...", path);
+
+        self.get_obj_with_range(path, None).await
+    }
+
+    async fn get_obj_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, StorageError> {
+        debug!(
+            "fetching bytes {}..{} of s3 object: {}...",
+            range.start, range.end, path
+        );
+        if range.start >= range.end {
+            return Ok(Vec::new());
+        }
+
+        // S3 answers ranges starting past the end of the object with an InvalidRange error
+        match self
+            .get_obj_with_range(
+                path,
+                Some(format!("bytes={}-{}", range.start, range.end - 1)),
+            )
+            .await
+        {
+            Err(StorageError::S3Get {
+                source: RusotoError::Unknown(response),
+            }) if response.status == 416 => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        self.list_objs_from(path, None, None)
+    }
+
+    async fn list_objs_after<'a>(
+        &'a self,
+        path: &'a str,
+        start_after: &'a str,
+        page_size: Option<usize>,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        let start_after = parse_s3_object(start_after)?;
+        self.list_objs_from(path, Some(start_after.key.to_string()), page_size)
+    }
 
     async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
         debug!("put s3 object: {}...", path);
 
-        let uri = parse_uri(path)?.into_s3object()?;
+        let uri = parse_s3_object(path)?;
         let put_req = PutObjectRequest {
             bucket: uri.bucket.to_string(),
             key: uri.key.to_string(),
@@ -368,7 +866,7 @@ impl StorageBackend for S3StorageBackend {
     async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
         debug!("delete s3 object: {}...", path);
 
-        let uri = parse_uri(path)?.into_s3object()?;
+        let uri = parse_s3_object(path)?;
         let put_req = DeleteObjectRequest {
             bucket: uri.bucket.to_string(),
             key: uri.key.to_string(),
@@ -379,6 +877,100 @@ impl StorageBackend for S3StorageBackend {
 
         Ok(())
     }
+
+    fn capabilities(&self) -> StorageCapabilities {
+        StorageCapabilities {
+            // renames are only safe against concurrent writers holding the lock
+            atomic_rename: self.lock_client.is_some(),
+            conditional_put: false,
+            list_after: true,
+            range_reads: true,
+            presign: true,
+        }
+    }
+
+    async fn presign(
+        &self,
+        path: &str,
+        expiry: std::time::Duration,
+    ) -> Result<String, StorageError> {
+        if expiry > MAX_PRESIGN_EXPIRY {
+            return Err(StorageError::S3Generic(format!(
+                "Pre-signed URLs expire after at most {} seconds, got: {}",
+                MAX_PRESIGN_EXPIRY.as_secs(),
+                expiry.as_secs()
+            )));
+        }
+        let uri = parse_s3_object(path)?;
+        let credentials = self.credentials.credentials().await.map_err(|e| {
+            StorageError::S3Generic(format!(
+                "Failed to retrieve S3 credentials with message: {}",
+                e.message
+            ))
+        })?;
+        if self.provider.virtual_hosted() || uri.access_point().is_some() {
+            // like `GetObjectRequest::get_presigned_url`, with the bucket in the host name
+            let path = format!("/{}/{}", uri.bucket, uri.key);
+            let mut request = SignedRequest::new("GET", "s3", &self.region, &path);
+            to_virtual_hosted(&mut request);
+            return Ok(request.generate_presigned_url(&credentials, &expiry, false));
+        }
+        let request = GetObjectRequest {
+            bucket: uri.bucket.to_string(),
+            key: uri.key.to_string(),
+            ..Default::default()
+        };
+
+        Ok(request.get_presigned_url(
+            &self.region,
+            &credentials,
+            &PreSignedRequestOption { expires_in: expiry },
+        ))
+    }
+
+    async fn verify_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        let uri = parse_s3_object(path)?;
+        let result = self
+            .client
+            .head_object(HeadObjectRequest {
+                bucket: uri.bucket.to_string(),
+                key: uri.key.to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        let mismatch = |reason: String| StorageError::UploadMismatch {
+            path: path.to_string(),
+            reason,
+        };
+        if let Some(size) = result.content_length {
+            if size != obj_bytes.len() as i64 {
+                return Err(mismatch(format!(
+                    "expected {} bytes, found {}",
+                    obj_bytes.len(),
+                    size
+                )));
+            }
+        }
+        // The ETag is the MD5 digest of the content only for single-part uploads that aren't
+        // encrypted with KMS or customer keys. Multipart ETags have a `-<parts>` suffix.
+        let plain_etag = result.server_side_encryption.as_deref() != Some("aws:kms")
+            && result.sse_customer_algorithm.is_none();
+        if let Some(etag) = result
+            .e_tag
+            .filter(|etag| plain_etag && !etag.contains('-'))
+        {
+            let digest = format!("{:x}", md5::compute(obj_bytes));
+            if etag.trim_matches('"') != digest {
+                return Err(mismatch(format!(
+                    "expected ETag {}, found {}",
+                    digest, etag
+                )));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// A lock that has been successfully acquired
@@ -493,4 +1085,144 @@ mod tests {
         assert_eq!(&backend.join_paths(&["foo"]), "foo",);
         assert_eq!(&backend.join_paths(&[]), "",);
     }
+
+    #[test]
+    fn s3_options_from_uri_options() {
+        let mut options = HashMap::new();
+        options.insert("region".to_string(), "us-west-2".to_string());
+        options.insert("profile".to_string(), "prod".to_string());
+        assert_eq!(
+            S3StorageOptions::from_uri_options(&options).unwrap(),
+            S3StorageOptions {
+                region: Some("us-west-2".to_string()),
+                endpoint_url: None,
+                profile: Some("prod".to_string()),
+                provider: S3Provider::Aws,
+            }
+        );
+
+        options.insert("region".to_string(), "nowhere".to_string());
+        assert!(S3StorageOptions::from_uri_options(&options).is_err());
+
+        options.insert("endpoint".to_string(), "http://localhost:4566".to_string());
+        assert!(S3StorageOptions::from_uri_options(&options).is_ok());
+
+        options.insert("provider".to_string(), "r2".to_string());
+        let r2_options = S3StorageOptions::from_uri_options(&options).unwrap();
+        assert_eq!(r2_options.provider, S3Provider::CloudflareR2);
+        options.remove("region");
+        let r2_options = S3StorageOptions::from_uri_options(&options).unwrap();
+        assert_eq!(
+            r2_options.region().unwrap(),
+            Region::Custom {
+                name: std::env::var("AWS_REGION").unwrap_or_else(|_| "auto".to_string()),
+                endpoint: "http://localhost:4566".to_string(),
+            }
+        );
+
+        options.insert("provider".to_string(), "cos".to_string());
+        assert_eq!(
+            S3StorageOptions::from_uri_options(&options),
+            Err(UriError::InvalidOption {
+                key: "provider".to_string(),
+                value: "cos".to_string(),
+            })
+        );
+
+        options.insert("provider".to_string(), "oss".to_string());
+        options.insert(
+            "endpoint".to_string(),
+            "https://oss-cn-hangzhou.aliyuncs.com".to_string(),
+        );
+        let oss_options = S3StorageOptions::from_uri_options(&options).unwrap();
+        assert_eq!(oss_options.provider, S3Provider::AlibabaOss);
+        assert_eq!(
+            oss_options.region().unwrap(),
+            Region::Custom {
+                name: std::env::var("AWS_REGION").unwrap_or_else(|_| "oss-cn-hangzhou".to_string()),
+                endpoint: "https://oss-cn-hangzhou.aliyuncs.com".to_string(),
+            }
+        );
+        options.remove("provider");
+
+        options.insert("bucket".to_string(), "foo".to_string());
+        assert_eq!(
+            S3StorageOptions::from_uri_options(&options),
+            Err(UriError::UnknownOption("bucket".to_string()))
+        );
+    }
+
+    #[test]
+    fn virtual_hosted_requests() {
+        let region = Region::Custom {
+            name: "oss-cn-hangzhou".to_string(),
+            endpoint: "https://oss-cn-hangzhou.aliyuncs.com".to_string(),
+        };
+        let mut request =
+            SignedRequest::new("GET", "s3", &region, "/deltars/simple/_delta_log/0.json");
+        to_virtual_hosted(&mut request);
+        assert_eq!(request.hostname(), "deltars.oss-cn-hangzhou.aliyuncs.com");
+        assert_eq!(request.path, "/simple/_delta_log/0.json");
+
+        // listing the objects of a bucket
+        let mut request = SignedRequest::new("GET", "s3", &region, "/deltars");
+        to_virtual_hosted(&mut request);
+        assert_eq!(request.hostname(), "deltars.oss-cn-hangzhou.aliyuncs.com");
+        assert_eq!(request.path, "/");
+
+        assert_eq!(endpoint_first_label("http://localhost:4566"), "localhost");
+    }
+
+    #[test]
+    fn access_point_requests() {
+        let mut request = SignedRequest::new(
+            "GET",
+            "s3",
+            &Region::UsEast1,
+            "/arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap/simple/_delta_log/0.json",
+        );
+        to_virtual_hosted(&mut request);
+        assert_eq!(
+            request.hostname(),
+            "my-ap-123456789012.s3-accesspoint.us-west-2.amazonaws.com"
+        );
+        assert_eq!(request.path, "/simple/_delta_log/0.json");
+        assert_eq!(request.region, Region::UsWest2);
+
+        let mut request = SignedRequest::new(
+            "GET",
+            "s3",
+            &Region::UsEast1,
+            "/arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap",
+        );
+        to_virtual_hosted(&mut request);
+        assert_eq!(
+            request.hostname(),
+            "my-ap-123456789012.s3-accesspoint.us-west-2.amazonaws.com"
+        );
+        assert_eq!(request.path, "/");
+    }
+
+    #[test]
+    fn parse_s3_objects() {
+        assert_eq!(
+            parse_s3_object("s3://deltars/simple/_delta_log/0.json").unwrap(),
+            S3Object {
+                bucket: "deltars",
+                key: "simple/_delta_log/0.json",
+            }
+        );
+        assert_eq!(
+            parse_s3_object("s3://arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap/simple")
+                .unwrap(),
+            S3Object {
+                bucket: "arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap",
+                key: "simple",
+            }
+        );
+        assert!(matches!(
+            parse_s3_object("s3://arn:aws:s3::123456789012:accesspoint/mfzwi23gnjvgw.mrap/simple"),
+            Err(StorageError::S3Generic(_))
+        ));
+    }
 }
@@ -0,0 +1,100 @@
+//! Helpers for streaming writers committing micro-batches to a Delta Table.
+//!
+//! Streaming writers make their commits idempotent by recording the epoch of each micro-batch in
+//! a `txn` action keyed by an application id. After a restart, micro-batches up to the latest
+//! recorded epoch are already in the table and must not be committed again.
+//!
+//! ```rust,no_run
+//! # use deltalake::action::{Action, OutputMode};
+//! # async fn write(table: &mut deltalake::DeltaTable, epoch: i64, actions: Vec<Action>) {
+//! let mut streaming_commit =
+//!     deltalake::streaming::StreamingCommit::new(table, "my-stream", OutputMode::Append);
+//! if let Some(version) = streaming_commit.commit_epoch(epoch, &actions).await.unwrap() {
+//!     println!("Committed epoch {} at version {}", epoch, version);
+//! }
+//! # }
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::action::{Action, DeltaOperation, OutputMode, Txn};
+use crate::delta::{DeltaTable, DeltaTransactionError, DeltaTransactionOptions};
+use crate::schema::DeltaDataTypeVersion;
+
+/// Commits the micro-batches of a streaming writer, identified by its application id, with
+/// `StreamingUpdate` operation info and a `txn` action recording the epoch of the micro-batch.
+///
+/// The epochs of an application id are expected to be committed by a single writer at a time.
+#[derive(Debug)]
+pub struct StreamingCommit<'a> {
+    table: &'a mut DeltaTable,
+    app_id: String,
+    output_mode: OutputMode,
+}
+
+impl<'a> StreamingCommit<'a> {
+    /// Creates a helper committing the micro-batches of `app_id` to `table`.
+    pub fn new(table: &'a mut DeltaTable, app_id: &str, output_mode: OutputMode) -> Self {
+        Self {
+            table,
+            app_id: app_id.to_string(),
+            output_mode,
+        }
+    }
+
+    /// Returns the latest epoch committed by the application in the loaded table state, `None`
+    /// if it hasn't committed any.
+    pub fn latest_epoch(&self) -> Option<i64> {
+        self.table
+            .get_app_transaction_version()
+            .get(&self.app_id)
+            .copied()
+    }
+
+    /// Commits the actions of the micro-batch with the given epoch, returning the committed
+    /// version. Returns `None` without committing when the epoch isn't newer than the latest
+    /// committed one, i.e. when the micro-batch is replayed after a restart.
+    pub async fn commit_epoch(
+        &mut self,
+        epoch: i64,
+        actions: &[Action],
+    ) -> Result<Option<DeltaDataTypeVersion>, DeltaTransactionError> {
+        self.commit_epoch_with_options(epoch, actions, None).await
+    }
+
+    /// Like `commit_epoch`, with custom options for the underlying `DeltaTransaction`.
+    pub async fn commit_epoch_with_options(
+        &mut self,
+        epoch: i64,
+        actions: &[Action],
+        options: Option<DeltaTransactionOptions>,
+    ) -> Result<Option<DeltaDataTypeVersion>, DeltaTransactionError> {
+        if matches!(self.latest_epoch(), Some(latest) if epoch <= latest) {
+            return Ok(None);
+        }
+
+        let last_updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let mut actions_with_txn = Vec::with_capacity(actions.len() + 1);
+        actions_with_txn.push(Action::txn(Txn {
+            appId: self.app_id.clone(),
+            version: epoch,
+            lastUpdated: last_updated,
+        }));
+        actions_with_txn.extend_from_slice(actions);
+
+        let operation = DeltaOperation::StreamingUpdate {
+            outputMode: self.output_mode,
+            queryId: self.app_id.clone(),
+            epochId: epoch,
+        };
+        let mut transaction = self.table.create_transaction(options);
+        let version = transaction
+            .commit_with(&actions_with_txn, Some(operation))
+            .await?;
+
+        Ok(Some(version))
+    }
+}
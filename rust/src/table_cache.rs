@@ -0,0 +1,111 @@
+//! Cache of loaded Delta Tables for services that serve many requests against the same tables.
+//!
+//! ```rust
+//! async {
+//!   let loader = deltalake::table_cache::DeltaTableLoader::new(std::time::Duration::from_secs(60));
+//!   let snapshot = loader.load("./tests/data/simple_table").await.unwrap();
+//!   println!("{:?}", snapshot.get_files());
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::delta::{open_table, DeltaTable, DeltaTableError, DeltaTableSnapshot};
+
+#[derive(Default)]
+struct CacheEntry {
+    table: Option<DeltaTable>,
+}
+
+struct CacheSlot {
+    entry: Arc<tokio::sync::Mutex<CacheEntry>>,
+    last_used: Instant,
+}
+
+/// Loads Delta Tables keyed by table uri and hands out snapshots of them.
+///
+/// Concurrent loads of the same uri are deduplicated: the first caller loads the table while the
+/// others wait for its snapshot. Once the cached table was loaded or updated more than the
+/// configured ttl ago, the next load refreshes it with `DeltaTable::refresh_if_stale`, which only
+/// reads the log entries committed since, instead of opening the table again. Tables that
+/// weren't loaded for the max idle time, when set, are dropped from the cache.
+pub struct DeltaTableLoader {
+    ttl: Duration,
+    max_idle: Option<Duration>,
+    entries: Mutex<HashMap<String, CacheSlot>>,
+}
+
+impl DeltaTableLoader {
+    /// Creates an empty loader that refreshes tables loaded or updated more than `ttl` ago.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_idle: None,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drops the cached tables that weren't loaded for `max_idle` from the cache, so services
+    /// touching many tables only keep the ones in use.
+    pub fn with_max_idle(mut self, max_idle: Duration) -> Self {
+        self.max_idle = Some(max_idle);
+        self
+    }
+
+    /// Returns a snapshot of the table at `table_uri`, loading the table when it isn't cached and
+    /// refreshing it when it was loaded or updated more than the ttl ago.
+    pub async fn load(&self, table_uri: &str) -> Result<DeltaTableSnapshot, DeltaTableError> {
+        let entry = {
+            let now = Instant::now();
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(max_idle) = self.max_idle {
+                entries.retain(|_, slot| now.duration_since(slot.last_used) < max_idle);
+            }
+            let slot = entries
+                .entry(table_uri.to_string())
+                .or_insert_with(|| CacheSlot {
+                    entry: Arc::default(),
+                    last_used: now,
+                });
+            slot.last_used = now;
+            slot.entry.clone()
+        };
+        let mut entry = entry.lock().await;
+        let entry = &mut *entry;
+
+        match entry.table.as_mut() {
+            Some(table) => {
+                if let Err(e) = table.refresh_if_stale(self.ttl).await {
+                    // the table may be left partially updated, open it again on the next load
+                    entry.table = None;
+                    return Err(e);
+                }
+            }
+            None => entry.table = Some(open_table(table_uri).await?),
+        }
+
+        Ok(entry.table.as_ref().unwrap().snapshot())
+    }
+
+    /// Drops the cached table for `table_uri`, so the next load opens it again.
+    pub fn invalidate(&self, table_uri: &str) {
+        self.entries.lock().unwrap().remove(table_uri);
+    }
+
+    /// Drops all cached tables.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl std::fmt::Debug for DeltaTableLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "DeltaTableLoader <ttl: {:?}, max_idle: {:?}>",
+            self.ttl, self.max_idle
+        )
+    }
+}
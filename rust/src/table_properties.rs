@@ -0,0 +1,241 @@
+//! Validation of the `delta.*` properties in the configuration of tables.
+//!
+//! Engines silently ignore properties they don't know, so a typo like
+//! `delta.logRetentionDurationn` leaves the table with the default retention instead of failing.
+//! The properties set by the metadata of a commit, e.g. when creating a table or with
+//! `DeltaTable::set_properties`, are checked with [`validate_table_property`]: keys starting with
+//! `delta.` must be known properties, or close ones are suggested, and their values must have the
+//! expected format. Other keys are left to the applications that set them.
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use crate::delta::DeltaTableError;
+
+/// The format of the value of a table property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PropertyFormat {
+    /// `true` or `false`, case-insensitively.
+    Boolean,
+    /// An integer greater than or equal to the given minimum.
+    Integer(i64),
+    /// A calendar interval without months or years, e.g. `interval 30 days`.
+    Interval,
+    /// One of the given values, case-insensitively.
+    OneOf(&'static [&'static str]),
+    /// Any value.
+    Any,
+}
+
+/// The known properties of the Delta protocol and of the Delta Lake engines.
+const TABLE_PROPERTIES: &[(&str, PropertyFormat)] = &[
+    ("delta.appendOnly", PropertyFormat::Boolean),
+    ("delta.autoOptimize.autoCompact", PropertyFormat::Boolean),
+    ("delta.autoOptimize.optimizeWrite", PropertyFormat::Boolean),
+    ("delta.checkpoint.writeStatsAsJson", PropertyFormat::Boolean),
+    (
+        "delta.checkpoint.writeStatsAsStruct",
+        PropertyFormat::Boolean,
+    ),
+    ("delta.checkpointInterval", PropertyFormat::Integer(1)),
+    (
+        "delta.columnMapping.mode",
+        PropertyFormat::OneOf(&["none", "name", "id"]),
+    ),
+    (
+        "delta.columnMapping.maxColumnId",
+        PropertyFormat::Integer(0),
+    ),
+    (
+        "delta.compatibility.symlinkFormatManifest.enabled",
+        PropertyFormat::Boolean,
+    ),
+    (
+        "delta.dataSkippingNumIndexedCols",
+        PropertyFormat::Integer(-1),
+    ),
+    (
+        "delta.deletedFileRetentionDuration",
+        PropertyFormat::Interval,
+    ),
+    ("delta.enableChangeDataFeed", PropertyFormat::Boolean),
+    ("delta.enableExpiredLogCleanup", PropertyFormat::Boolean),
+    (
+        "delta.isolationLevel",
+        PropertyFormat::OneOf(&["Serializable", "WriteSerializable", "SnapshotIsolation"]),
+    ),
+    ("delta.logRetentionDuration", PropertyFormat::Interval),
+    ("delta.minReaderVersion", PropertyFormat::Integer(1)),
+    ("delta.minWriterVersion", PropertyFormat::Integer(1)),
+    ("delta.randomizeFilePrefixes", PropertyFormat::Boolean),
+    ("delta.randomPrefixLength", PropertyFormat::Integer(1)),
+    (
+        "delta.setTransactionRetentionDuration",
+        PropertyFormat::Interval,
+    ),
+    ("delta.targetFileSize", PropertyFormat::Any),
+    ("delta.tuneFileSizesForRewrites", PropertyFormat::Boolean),
+];
+
+/// Prefixes of the properties named by the user, e.g. `delta.constraints.<name>` for CHECK
+/// constraints.
+const TABLE_PROPERTY_PREFIXES: &[&str] = &["delta.constraints."];
+
+/// Maximum edit distance between an unknown property and a known one suggested instead.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Fails with `InvalidTableProperty` when `key` is an unknown `delta.*` property, suggesting the
+/// closest known one, or when `value` doesn't have the format of the property. Keys outside of
+/// the `delta.` namespace are always valid.
+pub fn validate_table_property(key: &str, value: &str) -> Result<(), DeltaTableError> {
+    if !key.starts_with("delta.")
+        || TABLE_PROPERTY_PREFIXES
+            .iter()
+            .any(|prefix| key.len() > prefix.len() && key.starts_with(prefix))
+    {
+        return Ok(());
+    }
+    let invalid = |reason: String| DeltaTableError::InvalidTableProperty {
+        key: key.to_string(),
+        reason,
+    };
+    let format = match TABLE_PROPERTIES.iter().find(|(name, _)| *name == key) {
+        Some((_, format)) => *format,
+        None => {
+            return Err(invalid(match suggest_property(key) {
+                Some(suggestion) => format!("unknown property, did you mean {}?", suggestion),
+                None => "unknown property".to_string(),
+            }))
+        }
+    };
+
+    let is_valid = match format {
+        PropertyFormat::Boolean => {
+            value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+        }
+        PropertyFormat::Integer(min) => value.parse::<i64>().map_or(false, |v| v >= min),
+        PropertyFormat::Interval => parse_interval(value).is_some(),
+        PropertyFormat::OneOf(values) => values.iter().any(|v| v.eq_ignore_ascii_case(value)),
+        PropertyFormat::Any => true,
+    };
+    if !is_valid {
+        return Err(invalid(format!(
+            "{:?} isn't {}",
+            value,
+            describe_format(format)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Parses a calendar interval in the format of the retention properties, e.g. `interval 30 days`
+/// or `1 week 12 hours`. Units go from microseconds to weeks; months and years aren't supported
+/// since their duration varies. Returns `None` when the value isn't a valid interval.
+pub fn parse_interval(value: &str) -> Option<Duration> {
+    let mut words = value.split_whitespace().peekable();
+    if words
+        .peek()
+        .map_or(false, |word| word.eq_ignore_ascii_case("interval"))
+    {
+        words.next();
+    }
+
+    let mut interval = Duration::from_secs(0);
+    let mut has_units = false;
+    while let Some(count) = words.next() {
+        let count = u32::try_from(count.parse::<u64>().ok()?).ok()?;
+        let unit = match words.next()?.to_ascii_lowercase().trim_end_matches('s') {
+            "microsecond" => Duration::from_micros(1),
+            "millisecond" => Duration::from_millis(1),
+            "second" => Duration::from_secs(1),
+            "minute" => Duration::from_secs(60),
+            "hour" => Duration::from_secs(3600),
+            "day" => Duration::from_secs(24 * 3600),
+            "week" => Duration::from_secs(7 * 24 * 3600),
+            _ => return None,
+        };
+        interval = interval.checked_add(unit.checked_mul(count)?)?;
+        has_units = true;
+    }
+
+    if has_units {
+        Some(interval)
+    } else {
+        None
+    }
+}
+
+fn describe_format(format: PropertyFormat) -> String {
+    match format {
+        PropertyFormat::Boolean => "a boolean".to_string(),
+        PropertyFormat::Integer(min) => format!("an integer greater than or equal to {}", min),
+        PropertyFormat::Interval => "an interval like \"interval 7 days\"".to_string(),
+        PropertyFormat::OneOf(values) => format!("one of {}", values.join(", ")),
+        PropertyFormat::Any => "a value".to_string(),
+    }
+}
+
+/// Returns the known property closest to `key`, ignoring case, when it's close enough to be a
+/// typo.
+fn suggest_property(key: &str) -> Option<&'static str> {
+    let key = key.to_ascii_lowercase();
+    TABLE_PROPERTIES
+        .iter()
+        .map(|(name, _)| (edit_distance(&key, &name.to_ascii_lowercase()), *name))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// The Levenshtein distance between two strings, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(
+            parse_interval("interval 30 days"),
+            Some(Duration::from_secs(30 * 24 * 3600))
+        );
+        assert_eq!(
+            parse_interval("INTERVAL 1 week 12 hours"),
+            Some(Duration::from_secs(7 * 24 * 3600 + 12 * 3600))
+        );
+        assert_eq!(parse_interval("1 minute"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_interval("interval"), None);
+        assert_eq!(parse_interval("interval 1 month"), None);
+        assert_eq!(parse_interval("interval 30"), None);
+        assert_eq!(parse_interval("30 days ago"), None);
+        assert_eq!(parse_interval("-1 days"), None);
+    }
+
+    #[test]
+    fn test_suggest_property() {
+        assert_eq!(
+            suggest_property("delta.logRetentionDurationn"),
+            Some("delta.logRetentionDuration")
+        );
+        assert_eq!(
+            suggest_property("delta.appendonly"),
+            Some("delta.appendOnly")
+        );
+        assert_eq!(suggest_property("delta.somethingElse"), None);
+    }
+}
@@ -0,0 +1,220 @@
+//! Deduplication of record batches against the files recently added to a Delta Table, for append
+//! sinks fed by sources delivering records at least once.
+//!
+//! A [`Deduplicator`] drops the rows of a batch whose key columns have the same values as a row of
+//! one of the files added within its [`Lookback`] window. Only the files whose stats overlap the
+//! keys of the batch are read, so deduplicating against a window of small appends stays cheap.
+//! Parquet bloom filters aren't readable with the parquet version this crate uses, so files
+//! without stats, or whose key ranges overlap the batch, are read in full.
+//!
+//! Duplicates are only looked for in the table, rows repeated within the batch itself are kept,
+//! and two writers deduplicating concurrently can still both append the same row.
+//!
+//! ```rust
+//! # use deltalake::writer::dedup::{Deduplicator, Lookback};
+//! let deduplicator = Deduplicator::new(vec!["id".to_string()], Lookback::Versions(10));
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::array::BooleanArray;
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::Schema as ArrowSchema;
+use arrow::json::writer::record_batches_to_json_rows;
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::file::reader::SerializedFileReader;
+use parquet::file::serialized_reader::SliceableCursor;
+use serde_json::Value;
+
+use super::stats::{compare_values, count_stat, stats_from_record_batch, value_stat};
+use crate::action::{Action, Add, Stats};
+use crate::delta::{DeltaTable, DeltaTableError};
+
+const READ_BATCH_SIZE: usize = 8192;
+
+/// The recently added files a [`Deduplicator`] looks for duplicate keys in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lookback {
+    /// The active files added by the last N versions of the table.
+    Versions(usize),
+    /// The active files modified at or after the timestamp.
+    Since(DateTime<Utc>),
+}
+
+/// Filters out the rows of record batches whose keys already exist in recently added files.
+#[derive(Clone, Debug)]
+pub struct Deduplicator {
+    key_columns: Vec<String>,
+    lookback: Lookback,
+}
+
+impl Deduplicator {
+    /// Creates a deduplicator comparing rows by the values of the given top-level columns.
+    pub fn new(key_columns: Vec<String>, lookback: Lookback) -> Self {
+        Self {
+            key_columns,
+            lookback,
+        }
+    }
+
+    /// Returns the rows of `batch` whose keys aren't in any of the files of the lookback window
+    /// of the loaded version of `table`. Rows are compared by the JSON representation of their key
+    /// values, so the key columns must have the same types in the batch and in the files.
+    ///
+    /// Partition columns aren't stored in the data files, so they can't be used as keys and result
+    /// in a `ColumnNotFound` error like columns missing from the batch.
+    pub async fn filter_batch(
+        &self,
+        table: &DeltaTable,
+        batch: &RecordBatch,
+    ) -> Result<RecordBatch, DeltaTableError> {
+        let partition_columns = &table.get_metadata()?.partition_columns;
+        if let Some(column) = self
+            .key_columns
+            .iter()
+            .find(|column| partition_columns.contains(column))
+        {
+            return Err(DeltaTableError::ColumnNotFound {
+                column: column.clone(),
+            });
+        }
+        let batch_keys = self.key_batch(batch)?;
+        if batch.num_rows() == 0 {
+            return Ok(batch.clone());
+        }
+
+        let batch_stats = stats_from_record_batch(&batch_keys);
+        let storage = table.storage_backend();
+        let mut existing_keys = HashSet::new();
+        for add in self.lookback_files(table).await? {
+            if !self.may_contain_keys(add, &batch_stats) {
+                continue;
+            }
+            let obj = storage
+                .get_obj(&storage.join_path(&table.table_path, &add.path))
+                .await?;
+            let file_reader = SerializedFileReader::new(SliceableCursor::new(obj))?;
+            let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+            for file_batch in arrow_reader.get_record_reader(READ_BATCH_SIZE)? {
+                let file_keys = self.key_batch(&file_batch?)?;
+                existing_keys.extend(self.keys(&file_keys));
+            }
+        }
+        if existing_keys.is_empty() {
+            return Ok(batch.clone());
+        }
+
+        let mask = self
+            .keys(&batch_keys)
+            .iter()
+            .map(|key| Some(!existing_keys.contains(key)))
+            .collect::<BooleanArray>();
+        Ok(filter_record_batch(batch, &mask)?)
+    }
+
+    /// Returns the active files of the lookback window.
+    async fn lookback_files<'a>(
+        &self,
+        table: &'a DeltaTable,
+    ) -> Result<Vec<&'a Add>, DeltaTableError> {
+        match &self.lookback {
+            Lookback::Versions(num_versions) => {
+                let mut added_paths = HashSet::new();
+                let first_version = (table.version - *num_versions as i64 + 1).max(0);
+                for version in first_version..=table.version {
+                    for action in table.get_commit_actions(version).await? {
+                        if let Action::add(add) = action {
+                            added_paths.insert(add.path);
+                        }
+                    }
+                }
+                Ok(table
+                    .get_actions()
+                    .iter()
+                    .filter(|add| added_paths.contains(&add.path))
+                    .collect())
+            }
+            Lookback::Since(timestamp) => {
+                let timestamp = timestamp.timestamp_millis();
+                Ok(table
+                    .get_actions()
+                    .iter()
+                    .filter(|add| add.modificationTime >= timestamp)
+                    .collect())
+            }
+        }
+    }
+
+    /// Whether the stats of a file don't rule out that it contains keys of the batch. Files
+    /// without stats, or without comparable bounds for the key columns, may contain any key.
+    fn may_contain_keys(&self, add: &Add, batch_stats: &Stats) -> bool {
+        let file_stats = match add.get_stats() {
+            Ok(Some(stats)) => stats,
+            _ => return true,
+        };
+        if file_stats.numRecords == 0 {
+            return false;
+        }
+        self.key_columns.iter().all(|column| {
+            let path = [column.clone()];
+            // null keys compare equal, so the bounds of the other values don't rule them out
+            let batch_nulls = count_stat(&batch_stats.nullCount, &path).unwrap_or(1);
+            let file_nulls = count_stat(&file_stats.nullCount, &path).unwrap_or(1);
+            if batch_nulls > 0 && file_nulls > 0 {
+                return true;
+            }
+            let bounds = (
+                value_stat(&batch_stats.minValues, &path),
+                value_stat(&batch_stats.maxValues, &path),
+                value_stat(&file_stats.minValues, &path),
+                value_stat(&file_stats.maxValues, &path),
+            );
+            match bounds {
+                (Some(batch_min), Some(batch_max), Some(file_min), Some(file_max)) => {
+                    compare_values(batch_max, file_min) != Some(Ordering::Less)
+                        && compare_values(file_max, batch_min) != Some(Ordering::Less)
+                }
+                _ => true,
+            }
+        })
+    }
+
+    /// Projects a batch on the key columns.
+    fn key_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, DeltaTableError> {
+        let schema = batch.schema();
+        let mut fields = Vec::with_capacity(self.key_columns.len());
+        let mut columns = Vec::with_capacity(self.key_columns.len());
+        for column in &self.key_columns {
+            let index = schema
+                .index_of(column)
+                .map_err(|_| DeltaTableError::ColumnNotFound {
+                    column: column.clone(),
+                })?;
+            fields.push(schema.field(index).clone());
+            columns.push(batch.column(index).clone());
+        }
+        Ok(RecordBatch::try_new(
+            Arc::new(ArrowSchema::new(fields)),
+            columns,
+        )?)
+    }
+
+    /// Returns the JSON-encoded keys of the rows of a batch projected on the key columns.
+    fn keys(&self, key_batch: &RecordBatch) -> Vec<String> {
+        record_batches_to_json_rows(&[key_batch.clone()])
+            .iter()
+            .map(|row| {
+                let key = self
+                    .key_columns
+                    .iter()
+                    .map(|column| row.get(column).cloned().unwrap_or(Value::Null))
+                    .collect::<Vec<_>>();
+                Value::Array(key).to_string()
+            })
+            .collect()
+    }
+}
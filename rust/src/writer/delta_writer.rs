@@ -0,0 +1,285 @@
+//! Writing record batches to the data files of a Delta Table.
+//!
+//! A [`DeltaWriter`] buffers the rows of the record batches it is given, split by the values of
+//! the partition columns of the table, and writes each partition to a snappy compressed parquet
+//! file on `flush`. Partition columns aren't stored in the files, like Spark writes them, but in
+//! the `partitionValues` of the returned add actions, which also carry the statistics of the
+//! files. The add actions are committed with a [`DeltaTransaction`](crate::DeltaTransaction);
+//! files that are never committed are left for vacuum to delete. Files replacing the data of the
+//! table, or of some of its partitions, are committed with
+//! [`DeltaTransaction::overwrite_with`](crate::DeltaTransaction::overwrite_with) instead.
+//!
+//! A writer is created for a loaded table with [`DeltaWriter::for_table`], or configured with the
+//! schema and partition columns of a table with [`DeltaWriter::new`].
+//!
+//! ```rust,no_run
+//! use arrow::record_batch::RecordBatch;
+//! use deltalake::action::Action;
+//! use deltalake::writer::DeltaWriter;
+//!
+//! async fn append(table: &mut deltalake::DeltaTable, batch: &RecordBatch) {
+//!     let mut writer = DeltaWriter::for_table(table).unwrap();
+//!     writer.write(batch).unwrap();
+//!     let adds = writer.flush().await.unwrap();
+//!     let actions: Vec<Action> = adds.into_iter().map(Action::add).collect();
+//!     table.create_transaction(None).commit_with(&actions, None).await.unwrap();
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use arrow::array::{ArrayRef, UInt32Array};
+use arrow::compute::take;
+use arrow::datatypes::{Schema as ArrowSchema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+
+use super::stats::stats_from_record_batches;
+use super::{
+    next_data_file_name, normalize_array, partition_value, record_batches_to_parquet_bytes,
+};
+use crate::action::Add;
+use crate::delta::{Clock, DeltaTable, DeltaTableError, SystemClock};
+use crate::partitions::partition_path;
+use crate::storage::StorageBackend;
+
+/// Writes record batches to parquet files under the path of a table and returns the add actions
+/// of the files, see the module docs.
+#[derive(Debug)]
+pub struct DeltaWriter {
+    storage: Arc<dyn StorageBackend>,
+    table_path: String,
+    verify_uploads: bool,
+    /// The clock of the modification times of the written files.
+    clock: Arc<dyn Clock>,
+    /// The schema of the table, the batches are converted to.
+    table_schema: SchemaRef,
+    /// The schema of the data files, without the partition columns.
+    data_schema: SchemaRef,
+    partition_columns: Vec<String>,
+    /// The rows buffered for each partition, by partition values.
+    partitions: HashMap<Vec<Option<String>>, Vec<RecordBatch>>,
+}
+
+impl DeltaWriter {
+    /// Creates a writer for the table at `table_path`, writing the batches with `table_schema`,
+    /// which includes the partition columns. The rows are split by the values of
+    /// `partition_columns`, in their order, into hive-style `column=value/` directories. Fails
+    /// with `ColumnNotFound` when a partition column isn't in the schema.
+    pub fn new(
+        storage: Arc<dyn StorageBackend>,
+        table_path: &str,
+        table_schema: SchemaRef,
+        partition_columns: Vec<String>,
+    ) -> Result<Self, DeltaTableError> {
+        if let Some(column) = partition_columns
+            .iter()
+            .find(|column| table_schema.field_with_name(column).is_err())
+        {
+            return Err(DeltaTableError::ColumnNotFound {
+                column: column.clone(),
+            });
+        }
+        let data_fields = table_schema
+            .fields()
+            .iter()
+            .filter(|field| !partition_columns.contains(field.name()))
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            storage,
+            table_path: table_path.to_string(),
+            verify_uploads: false,
+            clock: Arc::new(SystemClock),
+            table_schema,
+            data_schema: Arc::new(ArrowSchema::new(data_fields)),
+            partition_columns,
+            partitions: HashMap::new(),
+        })
+    }
+
+    /// Creates a writer for the loaded version of `table`, writing the batches with its schema
+    /// and partition columns, verifying the uploads when the table is configured to, and taking
+    /// the modification times of the files from the table clock.
+    pub fn for_table(table: &DeltaTable) -> Result<Self, DeltaTableError> {
+        let metadata = table.get_metadata()?;
+        let table_schema = Arc::new(ArrowSchema::try_from(&metadata.schema)?);
+
+        Ok(Self::new(
+            table.storage_backend(),
+            &table.table_path,
+            table_schema,
+            metadata.partition_columns.clone(),
+        )?
+        .with_verify_uploads(table.config().verify_uploads)
+        .with_clock(table.clock()))
+    }
+
+    /// Sets whether the written files are read back and compared with the written bytes, see
+    /// `StorageBackend::verify_obj`.
+    pub fn with_verify_uploads(mut self, verify_uploads: bool) -> Self {
+        self.verify_uploads = verify_uploads;
+        self
+    }
+
+    /// Uses the given clock for the modification times of the written files instead of the
+    /// system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Buffers the rows of `batch` until the next `flush`. The columns of the batch are matched
+    /// to the columns of the table by name and converted to their types, see
+    /// [`can_normalize`](super::can_normalize). All the columns of the table, partition columns
+    /// included, must be in the batch.
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<(), DeltaTableError> {
+        let batch = self.to_table_batch(batch)?;
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        if self.partition_columns.is_empty() {
+            let data_batch = self.data_batch(&batch)?;
+            self.partitions.entry(vec![]).or_default().push(data_batch);
+            return Ok(());
+        }
+
+        let partition_arrays = self
+            .partition_columns
+            .iter()
+            .map(|column| Ok(batch.column(self.table_schema.index_of(column)?).clone()))
+            .collect::<Result<Vec<ArrayRef>, ArrowError>>()?;
+        let mut partition_rows: HashMap<Vec<Option<String>>, Vec<u32>> = HashMap::new();
+        for row in 0..batch.num_rows() {
+            let values = partition_arrays
+                .iter()
+                .map(|array| partition_value(array, row))
+                .collect::<Result<Vec<_>, _>>()?;
+            partition_rows.entry(values).or_default().push(row as u32);
+        }
+        for (values, rows) in partition_rows {
+            let indices = UInt32Array::from(rows);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|column| take(column.as_ref(), &indices, None))
+                .collect::<Result<Vec<_>, _>>()?;
+            let partition_batch = RecordBatch::try_new(self.table_schema.clone(), columns)?;
+            let data_batch = self.data_batch(&partition_batch)?;
+            self.partitions.entry(values).or_default().push(data_batch);
+        }
+
+        Ok(())
+    }
+
+    /// The number of rows buffered since the last `flush`.
+    pub fn buffered_rows(&self) -> usize {
+        self.partitions
+            .values()
+            .flatten()
+            .map(|batch| batch.num_rows())
+            .sum()
+    }
+
+    /// Writes the buffered rows of each partition to a new data file and returns the add actions
+    /// of the files, to be committed by the caller. The buffers are only cleared once all the
+    /// files are written, so a failed flush can be retried.
+    pub async fn flush(&mut self) -> Result<Vec<Add>, DeltaTableError> {
+        let mut adds = Vec::with_capacity(self.partitions.len());
+        for (values, batches) in self.partitions.iter() {
+            let partition_values: HashMap<String, Option<String>> = self
+                .partition_columns
+                .iter()
+                .cloned()
+                .zip(values.iter().cloned())
+                .collect();
+            let path = if self.partition_columns.is_empty() {
+                next_data_file_name()
+            } else {
+                format!(
+                    "{}/{}",
+                    partition_path(&self.partition_columns, &partition_values),
+                    next_data_file_name()
+                )
+            };
+
+            let bytes = record_batches_to_parquet_bytes(self.data_schema.clone(), batches)?;
+            let full_path = self.storage.join_path(&self.table_path, &path);
+            self.storage.put_obj(&full_path, &bytes).await?;
+            if self.verify_uploads {
+                self.storage.verify_obj(&full_path, &bytes).await?;
+            }
+
+            let stats = stats_from_record_batches(self.data_schema.clone(), batches)?;
+            let modification_time = self
+                .clock
+                .now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0);
+            adds.push(Add {
+                path,
+                size: bytes.len() as i64,
+                partitionValues: partition_values,
+                modificationTime: modification_time,
+                dataChange: true,
+                stats: Some(serde_json::to_string(&stats)?),
+                ..Default::default()
+            });
+        }
+        self.partitions.clear();
+
+        Ok(adds)
+    }
+
+    /// Converts the columns of the batch to the table schema, in its order.
+    fn to_table_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, DeltaTableError> {
+        let batch_schema = batch.schema();
+        if let Some(field) = batch_schema
+            .fields()
+            .iter()
+            .find(|field| self.table_schema.field_with_name(field.name()).is_err())
+        {
+            return Err(ArrowError::SchemaError(format!(
+                "Column {} isn't in the table schema",
+                field.name()
+            ))
+            .into());
+        }
+        let columns = self
+            .table_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let i = batch_schema.index_of(field.name()).map_err(|_| {
+                    DeltaTableError::ColumnNotFound {
+                        column: field.name().clone(),
+                    }
+                })?;
+                Ok(normalize_array(batch.column(i), field.data_type())?)
+            })
+            .collect::<Result<Vec<_>, DeltaTableError>>()?;
+
+        Ok(RecordBatch::try_new(self.table_schema.clone(), columns)?)
+    }
+
+    /// Drops the partition columns of a batch with the table schema.
+    fn data_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let columns = self
+            .data_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                Ok(batch
+                    .column(self.table_schema.index_of(field.name())?)
+                    .clone())
+            })
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+
+        RecordBatch::try_new(self.data_schema.clone(), columns)
+    }
+}
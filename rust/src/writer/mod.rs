@@ -0,0 +1,202 @@
+//! Helpers for writers of the data files of Delta Tables.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, BinaryArray, Date32Array, LargeBinaryArray, LargeStringArray, StringArray,
+    TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+    TimestampSecondArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Schema as ArrowSchema, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::InMemoryWriteableCursor;
+use uuid::Uuid;
+
+use crate::partitions::{format_date_partition_value, format_timestamp_partition_value};
+
+pub mod dedup;
+mod delta_writer;
+pub mod stats;
+
+pub use self::delta_writer::DeltaWriter;
+
+/// Returns a new unique name for a data file, following the naming of Spark.
+pub fn next_data_file_name() -> String {
+    format!("part-00000-{}-c000.snappy.parquet", Uuid::new_v4())
+}
+
+/// Serializes the record batches into a snappy compressed parquet file.
+pub fn record_batches_to_parquet_bytes(
+    schema: Arc<ArrowSchema>,
+    batches: &[RecordBatch],
+) -> Result<Vec<u8>, ParquetError> {
+    let writer_properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let cursor = InMemoryWriteableCursor::default();
+    {
+        let mut writer = ArrowWriter::try_new(cursor.clone(), schema, Some(writer_properties))?;
+        for batch in batches {
+            writer.write(batch)?;
+        }
+        writer.close()?;
+    }
+
+    cursor
+        .into_inner()
+        .ok_or_else(|| ParquetError::General("Parquet buffer is still in use".to_string()))
+}
+
+/// Whether arrays of type `from` can be written to a column of the table type `to`, once
+/// converted by `normalize_array`. Besides the table type itself, dictionary arrays of a type
+/// that can be normalized, large strings and binaries, and timestamps with a timezone or another
+/// unit are accepted, since that's what DataFusion and IPC sources commonly produce. Lists are
+/// accepted when their elements can be normalized, whatever the name of their element field, e.g.
+/// `item` for lists built by arrow, and are written with the `element` name of the table schema,
+/// like Spark writes them.
+///
+/// Map columns can't be written, arrow has no map type yet.
+pub fn can_normalize(from: &DataType, to: &DataType) -> bool {
+    match (from, to) {
+        (from, to) if from == to => true,
+        (DataType::Dictionary(_, value_type), to) => can_normalize(value_type, to),
+        (DataType::List(from_field), DataType::List(to_field))
+        | (DataType::LargeList(from_field), DataType::List(to_field)) => {
+            can_normalize(from_field.data_type(), to_field.data_type())
+                && (to_field.is_nullable() || !from_field.is_nullable())
+        }
+        (DataType::LargeUtf8, DataType::Utf8) | (DataType::LargeBinary, DataType::Binary) => true,
+        // arrow timestamps are instants since the epoch in UTC, the timezone only affects how
+        // they are displayed
+        (DataType::Timestamp(_, _), DataType::Timestamp(_, None)) => true,
+        _ => false,
+    }
+}
+
+/// Converts an array to the table type `to`, see `can_normalize`.
+pub fn normalize_array(array: &ArrayRef, to: &DataType) -> Result<ArrayRef, ArrowError> {
+    match (array.data_type(), to) {
+        (from, to) if from == to => Ok(array.clone()),
+        (DataType::Dictionary(_, value_type), to) => {
+            let values = cast(array, value_type)?;
+            normalize_array(&values, to)
+        }
+        (DataType::LargeUtf8, DataType::Utf8) => {
+            let array = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            let values: Vec<Option<&str>> = array.iter().collect();
+            Ok(Arc::new(StringArray::from(values)))
+        }
+        (DataType::LargeBinary, DataType::Binary) => {
+            let array = array.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
+            let values: Vec<Option<&[u8]>> = array.iter().collect();
+            Ok(Arc::new(BinaryArray::from(values)))
+        }
+        (DataType::Timestamp(_, _), DataType::Timestamp(_, None)) => cast(array, to),
+        // casts convert the elements and rename the element field
+        (DataType::List(_), DataType::List(_)) | (DataType::LargeList(_), DataType::List(_))
+            if can_normalize(array.data_type(), to) =>
+        {
+            cast(array, to)
+        }
+        (from, to) => Err(ArrowError::CastError(format!(
+            "Can't write {:?} values to a {:?} column",
+            from, to
+        ))),
+    }
+}
+
+/// Returns the partition value of a row of a partition column, formatted like Spark formats the
+/// values recorded in `partitionValues`, or `None` for nulls. Dates and timestamps are formatted
+/// with [`format_date_partition_value`] and [`format_timestamp_partition_value`]. Fails for
+/// binary and nested columns, which can't be partitioned on.
+pub fn partition_value(array: &ArrayRef, row: usize) -> Result<Option<String>, ArrowError> {
+    if array.is_null(row) {
+        return Ok(None);
+    }
+    let timestamp = match array.data_type() {
+        DataType::Date32 => {
+            let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
+            return Ok(array.value_as_date(row).map(format_date_partition_value));
+        }
+        DataType::Timestamp(TimeUnit::Second, _) => array
+            .as_any()
+            .downcast_ref::<TimestampSecondArray>()
+            .unwrap()
+            .value_as_datetime(row),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => array
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap()
+            .value_as_datetime(row),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap()
+            .value_as_datetime(row),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => array
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap()
+            .value_as_datetime(row),
+        DataType::Utf8
+        | DataType::LargeUtf8
+        | DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::Float32
+        | DataType::Float64 => return array_value_to_string(array, row).map(Some),
+        data_type => {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Can't partition on {:?} columns",
+                data_type
+            )))
+        }
+    };
+
+    Ok(timestamp.map(format_timestamp_partition_value))
+}
+
+/// Returns an array of `num_rows` copies of a column default value, parsed from its string form
+/// into the type of the column the way arrow casts strings, e.g. `2021-01-01` for dates.
+pub fn default_value_array(
+    value: &str,
+    data_type: &DataType,
+    num_rows: usize,
+) -> Result<ArrayRef, ArrowError> {
+    let values: ArrayRef = Arc::new(StringArray::from(vec![value; num_rows]));
+    let array = cast(&values, data_type)?;
+    // casts turn the values they can't parse into nulls
+    if array.null_count() > 0 {
+        return Err(ArrowError::CastError(format!(
+            "Can't parse default value {:?} as {:?}",
+            value, data_type
+        )));
+    }
+
+    Ok(array)
+}
+
+/// Converts the columns of a record batch to the types of the table schema, see
+/// `can_normalize`. The columns must be in the order of the schema.
+pub fn normalize_record_batch(
+    batch: &RecordBatch,
+    schema: Arc<ArrowSchema>,
+) -> Result<RecordBatch, ArrowError> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| normalize_array(column, field.data_type()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    RecordBatch::try_new(schema, columns)
+}
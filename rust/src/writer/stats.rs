@@ -0,0 +1,802 @@
+//! Collection of the file statistics recorded in add actions, used by readers to skip files.
+//!
+//! Statistics are recorded the way Spark records them, so data skipping works across engines:
+//!
+//! - strings are truncated to their first [`STRING_PREFIX_LENGTH`] characters
+//! - dates are formatted as `yyyy-MM-dd`
+//! - timestamps are normalized to UTC and formatted with millisecond precision, rounding maximum
+//!   values up so they stay an upper bound
+//! - the statistics of struct fields are nested under the name of the struct column
+//! - binary and boolean columns only record null counts
+//! - list columns record no statistics
+//!
+//! ```rust
+//! # use std::sync::Arc;
+//! # use arrow::array::Int32Array;
+//! # use arrow::datatypes::{DataType, Field, Schema};
+//! # use arrow::record_batch::RecordBatch;
+//! let schema = Schema::new(vec![Field::new("id", DataType::Int32, true)]);
+//! let batch = RecordBatch::try_new(
+//!     Arc::new(schema),
+//!     vec![Arc::new(Int32Array::from(vec![Some(3), None, Some(1)]))],
+//! ).unwrap();
+//! let stats = deltalake::writer::stats::stats_from_record_batch(&batch);
+//! assert_eq!(stats.numRecords, 3);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, TimeZone, Utc};
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::statistics::Statistics;
+use serde_json::Value;
+
+use crate::action::{ColumnCountStat, ColumnValueStat, Stats};
+
+/// Number of characters string statistics are truncated to.
+pub const STRING_PREFIX_LENGTH: usize = 32;
+
+/// Computes the statistics of the given record batch.
+pub fn stats_from_record_batch(batch: &RecordBatch) -> Stats {
+    let mut stats = Stats {
+        numRecords: batch.num_rows() as i64,
+        ..Default::default()
+    };
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        collect_column_stats(
+            field.name(),
+            column,
+            &mut stats.minValues,
+            &mut stats.maxValues,
+            &mut stats.nullCount,
+        );
+    }
+    stats
+}
+
+/// Computes the statistics of the rows of all the given record batches, which must have the given
+/// schema.
+pub fn stats_from_record_batches(
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+) -> arrow::error::Result<Stats> {
+    if batches.is_empty() {
+        return Ok(Stats::default());
+    }
+    let columns = (0..schema.fields().len())
+        .map(|i| {
+            let arrays: Vec<_> = batches
+                .iter()
+                .map(|batch| batch.column(i).as_ref())
+                .collect();
+            arrow::compute::concat(&arrays)
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+
+    Ok(stats_from_record_batch(&RecordBatch::try_new(
+        schema, columns,
+    )?))
+}
+
+fn collect_column_stats(
+    name: &str,
+    array: &ArrayRef,
+    min_values: &mut HashMap<String, ColumnValueStat>,
+    max_values: &mut HashMap<String, ColumnValueStat>,
+    null_counts: &mut HashMap<String, ColumnCountStat>,
+) {
+    if let DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _) =
+        array.data_type()
+    {
+        return;
+    }
+    if let DataType::Struct(fields) = array.data_type() {
+        let struct_array = array.as_any().downcast_ref::<StructArray>().unwrap();
+        let mut struct_min_values = HashMap::new();
+        let mut struct_max_values = HashMap::new();
+        let mut struct_null_counts = HashMap::new();
+        for (i, field) in fields.iter().enumerate() {
+            collect_column_stats(
+                field.name(),
+                struct_array.column(i),
+                &mut struct_min_values,
+                &mut struct_max_values,
+                &mut struct_null_counts,
+            );
+        }
+        if !struct_min_values.is_empty() {
+            min_values.insert(name.to_string(), ColumnValueStat::Column(struct_min_values));
+            max_values.insert(name.to_string(), ColumnValueStat::Column(struct_max_values));
+        }
+        null_counts.insert(
+            name.to_string(),
+            ColumnCountStat::Column(struct_null_counts),
+        );
+        return;
+    }
+
+    null_counts.insert(
+        name.to_string(),
+        ColumnCountStat::Value(array.null_count() as i64),
+    );
+    if let Some((min, max)) = min_max_values(array) {
+        min_values.insert(name.to_string(), ColumnValueStat::Value(min));
+        max_values.insert(name.to_string(), ColumnValueStat::Value(max));
+    }
+}
+
+/// Returns the minimum and maximum values of the array as recorded in the statistics, `None` if
+/// the array only contains nulls or its type has no min/max statistics.
+fn min_max_values(array: &ArrayRef) -> Option<(Value, Value)> {
+    match array.data_type() {
+        DataType::Int8 => primitive_min_max::<Int8Type>(array),
+        DataType::Int16 => primitive_min_max::<Int16Type>(array),
+        DataType::Int32 => primitive_min_max::<Int32Type>(array),
+        DataType::Int64 => primitive_min_max::<Int64Type>(array),
+        DataType::UInt8 => primitive_min_max::<UInt8Type>(array),
+        DataType::UInt16 => primitive_min_max::<UInt16Type>(array),
+        DataType::UInt32 => primitive_min_max::<UInt32Type>(array),
+        DataType::UInt64 => primitive_min_max::<UInt64Type>(array),
+        DataType::Float32 => primitive_min_max::<Float32Type>(array),
+        DataType::Float64 => primitive_min_max::<Float64Type>(array),
+        DataType::Utf8 => {
+            let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+            let (min, max) = min_max(array.iter())?;
+            Some((
+                Value::String(truncate_min_string(min)),
+                Value::String(truncate_max_string(max)),
+            ))
+        }
+        DataType::LargeUtf8 => {
+            let array = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            let (min, max) = min_max(array.iter())?;
+            Some((
+                Value::String(truncate_min_string(min)),
+                Value::String(truncate_max_string(max)),
+            ))
+        }
+        DataType::Date32 => {
+            let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
+            let (min, max) = min_max(array.iter())?;
+            Some((format_date(min as i64), format_date(max as i64)))
+        }
+        DataType::Date64 => {
+            let array = array.as_any().downcast_ref::<Date64Array>().unwrap();
+            let (min, max) = min_max(array.iter())?;
+            Some((
+                format_date(min.div_euclid(MILLIS_PER_DAY)),
+                format_date(max.div_euclid(MILLIS_PER_DAY)),
+            ))
+        }
+        DataType::Timestamp(unit, _) => {
+            // arrow timestamps are instants since the epoch in UTC, the timezone only affects
+            // how they are displayed
+            let (min, max) = match unit {
+                TimeUnit::Second => timestamp_min_max::<TimestampSecondType>(array),
+                TimeUnit::Millisecond => timestamp_min_max::<TimestampMillisecondType>(array),
+                TimeUnit::Microsecond => timestamp_min_max::<TimestampMicrosecondType>(array),
+                TimeUnit::Nanosecond => timestamp_min_max::<TimestampNanosecondType>(array),
+            }?;
+            let (min, max) = match unit {
+                TimeUnit::Second => (min * 1000, max * 1000),
+                TimeUnit::Millisecond => (min, max),
+                TimeUnit::Microsecond => (min.div_euclid(1000), ceil_div(max, 1000)),
+                TimeUnit::Nanosecond => (min.div_euclid(1_000_000), ceil_div(max, 1_000_000)),
+            };
+            Some((format_timestamp_millis(min), format_timestamp_millis(max)))
+        }
+        // TODO: decimals
+        _ => None,
+    }
+}
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Returns the minimum and maximum of the non null values, ignoring values that can't be
+/// compared, like NaN.
+fn min_max<T, I>(values: I) -> Option<(T, T)>
+where
+    T: PartialOrd + Copy,
+    I: Iterator<Item = Option<T>>,
+{
+    let mut min_max: Option<(T, T)> = None;
+    for value in values.flatten() {
+        if value.partial_cmp(&value).is_none() {
+            continue;
+        }
+        min_max = match min_max {
+            Some((min, max)) => Some((
+                if value < min { value } else { min },
+                if value > max { value } else { max },
+            )),
+            None => Some((value, value)),
+        };
+    }
+    min_max
+}
+
+fn primitive_min_max<T>(array: &ArrayRef) -> Option<(Value, Value)>
+where
+    T: ArrowPrimitiveType,
+    T::Native: PartialOrd + Into<Value>,
+{
+    let array = array.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+    let (min, max) = min_max(array.iter())?;
+    Some((min.into(), max.into()))
+}
+
+fn timestamp_min_max<T>(array: &ArrayRef) -> Option<(i64, i64)>
+where
+    T: ArrowPrimitiveType<Native = i64>,
+{
+    let array = array.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+    min_max(array.iter())
+}
+
+fn ceil_div(value: i64, divisor: i64) -> i64 {
+    -(-value).div_euclid(divisor)
+}
+
+fn format_date(days_since_epoch: i64) -> Value {
+    let date = NaiveDate::from_ymd(1970, 1, 1) + chrono::Duration::days(days_since_epoch);
+    Value::String(date.format("%Y-%m-%d").to_string())
+}
+
+fn format_timestamp_millis(millis: i64) -> Value {
+    Value::String(
+        Utc.timestamp_millis(millis)
+            .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .to_string(),
+    )
+}
+
+/// Truncates a minimum string value, a prefix is never greater than the value.
+fn truncate_min_string(value: &str) -> String {
+    value.chars().take(STRING_PREFIX_LENGTH).collect()
+}
+
+/// Truncates a maximum string value, appending the greatest character to the prefix so the
+/// result stays an upper bound of the value.
+fn truncate_max_string(value: &str) -> String {
+    if value.chars().count() <= STRING_PREFIX_LENGTH {
+        return value.to_string();
+    }
+    let mut truncated: String = value.chars().take(STRING_PREFIX_LENGTH).collect();
+    truncated.push(char::MAX);
+    truncated
+}
+
+/// A statistic recorded in an add action that disagrees with the statistics in the footer of the
+/// data file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsMismatch {
+    /// Dot separated path of the column, empty for `numRecords`.
+    pub column: String,
+    /// Name of the statistic, e.g. `minValues`.
+    pub stat: &'static str,
+    /// The value recorded in the add action.
+    pub recorded: Value,
+    /// The value found in the parquet footer.
+    pub actual: Value,
+}
+
+/// Cross-checks statistics recorded in an add action against the row group statistics in the
+/// footer of its data file. Record and null counts must match exactly, and the recorded minimum
+/// and maximum values must bound the values in the footer. Statistics missing from the add action
+/// aren't reported, and bounds are only checked for integer, floating point and string columns.
+pub fn verify_stats(stats: &Stats, metadata: &ParquetMetaData) -> Vec<StatsMismatch> {
+    let mut mismatches = Vec::new();
+    let num_rows: i64 = metadata.row_groups().iter().map(|rg| rg.num_rows()).sum();
+    if num_rows != stats.numRecords {
+        mismatches.push(StatsMismatch {
+            column: String::new(),
+            stat: "numRecords",
+            recorded: Value::from(stats.numRecords),
+            actual: Value::from(num_rows),
+        });
+    }
+
+    let schema = metadata.file_metadata().schema_descr();
+    for i in 0..schema.num_columns() {
+        let column = schema.column(i);
+        // values of repeated columns aren't recorded
+        if column.max_rep_level() > 0 {
+            continue;
+        }
+        let path = column.path().parts();
+        let column_stats: Option<Vec<&Statistics>> = metadata
+            .row_groups()
+            .iter()
+            .map(|rg| rg.column(i).statistics())
+            .collect();
+        let column_stats = match column_stats {
+            Some(column_stats) => column_stats,
+            None => continue,
+        };
+
+        let null_count: u64 = column_stats.iter().map(|s| s.null_count()).sum();
+        match count_stat(&stats.nullCount, path) {
+            Some(recorded) if recorded != null_count as i64 => mismatches.push(StatsMismatch {
+                column: path.join("."),
+                stat: "nullCount",
+                recorded: Value::from(recorded),
+                actual: Value::from(null_count),
+            }),
+            _ => {}
+        }
+
+        let (actual_min, actual_max) =
+            match footer_column_bounds(&column_stats, column.converted_type()) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+        if let Some(recorded) = value_stat(&stats.minValues, path) {
+            if !matches!(
+                compare_values(recorded, &actual_min),
+                Some(Ordering::Less) | Some(Ordering::Equal)
+            ) {
+                mismatches.push(StatsMismatch {
+                    column: path.join("."),
+                    stat: "minValues",
+                    recorded: recorded.clone(),
+                    actual: actual_min,
+                });
+            }
+        }
+        if let Some(recorded) = value_stat(&stats.maxValues, path) {
+            if !matches!(
+                compare_values(recorded, &actual_max),
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            ) {
+                mismatches.push(StatsMismatch {
+                    column: path.join("."),
+                    stat: "maxValues",
+                    recorded: recorded.clone(),
+                    actual: actual_max,
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+/// Computes the statistics of a parquet file from the row group statistics in its footer, without
+/// reading its data. Returns them along with the indices of the leaf columns whose minimum and
+/// maximum values can't be taken from the footer, either because the footer lacks them or because
+/// they are recorded in another format, e.g. dates and timestamps. Their statistics can be
+/// computed from their values with [`stats_from_record_batches`] and merged with
+/// [`fill_missing_stats`].
+pub fn stats_from_footer(metadata: &ParquetMetaData) -> (Stats, Vec<usize>) {
+    let mut stats = Stats {
+        numRecords: metadata.row_groups().iter().map(|rg| rg.num_rows()).sum(),
+        ..Default::default()
+    };
+    let mut missing_columns = Vec::new();
+    if metadata.row_groups().is_empty() {
+        return (stats, missing_columns);
+    }
+
+    let schema = metadata.file_metadata().schema_descr();
+    for i in 0..schema.num_columns() {
+        let column = schema.column(i);
+        // list columns record no statistics
+        if column.max_rep_level() > 0 {
+            continue;
+        }
+        let path = column.path().parts();
+        let column_stats: Option<Vec<&Statistics>> = metadata
+            .row_groups()
+            .iter()
+            .map(|rg| rg.column(i).statistics())
+            .collect();
+        let column_stats = match column_stats {
+            Some(column_stats) => column_stats,
+            None => {
+                missing_columns.push(i);
+                continue;
+            }
+        };
+
+        let null_count: u64 = column_stats.iter().map(|s| s.null_count()).sum();
+        insert_count_stat(&mut stats.nullCount, path, null_count as i64);
+        // binary and boolean columns only record null counts
+        if let (PhysicalType::BOOLEAN, _) | (PhysicalType::BYTE_ARRAY, ConvertedType::NONE) =
+            (column.physical_type(), column.converted_type())
+        {
+            continue;
+        }
+        match footer_column_bounds(&column_stats, column.converted_type()) {
+            Some((Value::String(min), Value::String(max))) => {
+                let min = Value::String(truncate_min_string(&min));
+                let max = Value::String(truncate_max_string(&max));
+                insert_value_stat(&mut stats.minValues, path, min);
+                insert_value_stat(&mut stats.maxValues, path, max);
+            }
+            Some((min, max)) => {
+                insert_value_stat(&mut stats.minValues, path, min);
+                insert_value_stat(&mut stats.maxValues, path, max);
+            }
+            None => missing_columns.push(i),
+        }
+    }
+
+    (stats, missing_columns)
+}
+
+/// Adds the column statistics of `other` that are missing from `stats`, keeping the statistics
+/// already in `stats`.
+pub fn fill_missing_stats(stats: &mut Stats, other: Stats) {
+    fill_missing_value_stats(&mut stats.minValues, other.minValues);
+    fill_missing_value_stats(&mut stats.maxValues, other.maxValues);
+    fill_missing_count_stats(&mut stats.nullCount, other.nullCount);
+}
+
+fn fill_missing_value_stats(
+    stats: &mut HashMap<String, ColumnValueStat>,
+    other: HashMap<String, ColumnValueStat>,
+) {
+    for (name, stat) in other {
+        match (stats.get_mut(&name), stat) {
+            (Some(ColumnValueStat::Column(nested)), ColumnValueStat::Column(other)) => {
+                fill_missing_value_stats(nested, other)
+            }
+            (None, stat) => {
+                stats.insert(name, stat);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn fill_missing_count_stats(
+    stats: &mut HashMap<String, ColumnCountStat>,
+    other: HashMap<String, ColumnCountStat>,
+) {
+    for (name, stat) in other {
+        match (stats.get_mut(&name), stat) {
+            (Some(ColumnCountStat::Column(nested)), ColumnCountStat::Column(other)) => {
+                fill_missing_count_stats(nested, other)
+            }
+            (None, stat) => {
+                stats.insert(name, stat);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn insert_value_stat(stats: &mut HashMap<String, ColumnValueStat>, path: &[String], value: Value) {
+    let (name, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if rest.is_empty() {
+        stats.insert(name.clone(), ColumnValueStat::Value(value));
+        return;
+    }
+    if let ColumnValueStat::Column(nested) = stats
+        .entry(name.clone())
+        .or_insert_with(|| ColumnValueStat::Column(HashMap::new()))
+    {
+        insert_value_stat(nested, rest, value);
+    }
+}
+
+fn insert_count_stat(stats: &mut HashMap<String, ColumnCountStat>, path: &[String], count: i64) {
+    let (name, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if rest.is_empty() {
+        stats.insert(name.clone(), ColumnCountStat::Value(count));
+        return;
+    }
+    if let ColumnCountStat::Column(nested) = stats
+        .entry(name.clone())
+        .or_insert_with(|| ColumnCountStat::Column(HashMap::new()))
+    {
+        insert_count_stat(nested, rest, count);
+    }
+}
+
+pub(crate) fn count_stat(stats: &HashMap<String, ColumnCountStat>, path: &[String]) -> Option<i64> {
+    let (name, rest) = path.split_first()?;
+    match stats.get(name)? {
+        ColumnCountStat::Value(count) if rest.is_empty() => Some(*count),
+        ColumnCountStat::Column(nested) => count_stat(nested, rest),
+        _ => None,
+    }
+}
+
+pub(crate) fn value_stat<'a>(
+    stats: &'a HashMap<String, ColumnValueStat>,
+    path: &[String],
+) -> Option<&'a Value> {
+    let (name, rest) = path.split_first()?;
+    match stats.get(name)? {
+        ColumnValueStat::Value(value) if rest.is_empty() => Some(value),
+        ColumnValueStat::Column(nested) => value_stat(nested, rest),
+        _ => None,
+    }
+}
+
+/// Returns the minimum and maximum value in the footer statistics of a column chunk, for the
+/// types whose recorded statistics are comparable with them.
+fn footer_bounds(stats: &Statistics, converted_type: ConvertedType) -> Option<(Value, Value)> {
+    let float_bounds = |min: f64, max: f64| {
+        if min.is_nan() || max.is_nan() {
+            return None;
+        }
+        Some((Value::from(min), Value::from(max)))
+    };
+    match (stats, converted_type) {
+        (Statistics::Int32(s), ConvertedType::NONE) => {
+            Some((Value::from(*s.min()), Value::from(*s.max())))
+        }
+        (Statistics::Int64(s), ConvertedType::NONE) => {
+            Some((Value::from(*s.min()), Value::from(*s.max())))
+        }
+        (Statistics::Float(s), ConvertedType::NONE) => {
+            float_bounds(*s.min() as f64, *s.max() as f64)
+        }
+        (Statistics::Double(s), ConvertedType::NONE) => float_bounds(*s.min(), *s.max()),
+        (Statistics::ByteArray(s), ConvertedType::UTF8) => Some((
+            Value::from(s.min().as_utf8().ok()?),
+            Value::from(s.max().as_utf8().ok()?),
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the minimum and maximum value of a column over the footer statistics of its chunks in
+/// all the row groups, `None` unless each chunk has bounds comparable with recorded statistics.
+fn footer_column_bounds(
+    column_stats: &[&Statistics],
+    converted_type: ConvertedType,
+) -> Option<(Value, Value)> {
+    if !column_stats.iter().all(|s| s.has_min_max_set()) {
+        return None;
+    }
+    let mut bounds = column_stats
+        .iter()
+        .map(|s| footer_bounds(s, converted_type));
+    let (mut column_min, mut column_max) = bounds.next()??;
+    for chunk_bounds in bounds {
+        let (min, max) = chunk_bounds?;
+        if compare_values(&min, &column_min) == Some(Ordering::Less) {
+            column_min = min;
+        }
+        if compare_values(&max, &column_max) == Some(Ordering::Greater) {
+            column_max = max;
+        }
+    }
+    Some((column_min, column_max))
+}
+
+pub(crate) fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        },
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn record_batch_stats() {
+        let long_string = "a".repeat(40);
+        let struct_array = StructArray::from(vec![(
+            Field::new("count", DataType::Int64, true),
+            Arc::new(Int64Array::from(vec![Some(7), Some(-2), None])) as ArrayRef,
+        )]);
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("value", DataType::Float64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("date", DataType::Date32, true),
+            Field::new(
+                "modified",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("+08:00".to_string())),
+                true,
+            ),
+            Field::new("data", DataType::Binary, true),
+            Field::new("nested", struct_array.data_type().clone(), true),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from(vec![Some(3), None, Some(1)])),
+            Arc::new(Float64Array::from(vec![
+                Some(1.5),
+                Some(f64::NAN),
+                Some(-0.5),
+            ])),
+            Arc::new(StringArray::from(vec![
+                Some("b"),
+                Some(long_string.as_str()),
+                None,
+            ])),
+            Arc::new(Date32Array::from(vec![Some(18628), Some(0), None])),
+            Arc::new(TimestampMicrosecondArray::from_opt_vec(
+                vec![
+                    Some(1_609_459_200_000_000),
+                    Some(1_609_459_200_000_001),
+                    None,
+                ],
+                Some("+08:00".to_string()),
+            )),
+            Arc::new(BinaryArray::from(vec![
+                "ab".as_bytes(),
+                "c".as_bytes(),
+                "d".as_bytes(),
+            ])),
+            Arc::new(struct_array),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), columns).unwrap();
+
+        let stats = stats_from_record_batch(&batch);
+        assert_eq!(stats.numRecords, 3);
+        assert_eq!(
+            serde_json::to_value(&stats.minValues).unwrap(),
+            json!({
+                "id": 1,
+                "value": -0.5,
+                "name": "a".repeat(32),
+                "date": "1970-01-01",
+                "modified": "2021-01-01T00:00:00.000Z",
+                "nested": {"count": -2},
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&stats.maxValues).unwrap(),
+            json!({
+                "id": 3,
+                "value": 1.5,
+                "name": "b",
+                "date": "2021-01-01",
+                "modified": "2021-01-01T00:00:00.001Z",
+                "nested": {"count": 7},
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&stats.nullCount).unwrap(),
+            json!({
+                "id": 1,
+                "value": 0,
+                "name": 1,
+                "date": 1,
+                "modified": 1,
+                "data": 0,
+                "nested": {"count": 1},
+            })
+        );
+
+        assert_eq!(
+            truncate_max_string(&long_string),
+            format!("{}{}", "a".repeat(32), char::MAX)
+        );
+    }
+
+    #[test]
+    fn verify_stats_against_footer() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::file::serialized_reader::SliceableCursor;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(3), None, Some(1)])),
+                Arc::new(StringArray::from(vec![Some("b"), Some("a"), None])),
+            ],
+        )
+        .unwrap();
+        let bytes =
+            crate::writer::record_batches_to_parquet_bytes(schema, &[batch.clone()]).unwrap();
+        let reader = SerializedFileReader::new(SliceableCursor::new(bytes)).unwrap();
+
+        let mut stats = stats_from_record_batch(&batch);
+        assert_eq!(verify_stats(&stats, reader.metadata()), vec![]);
+
+        stats.numRecords = 2;
+        stats
+            .minValues
+            .insert("id".to_string(), ColumnValueStat::Value(json!(2)));
+        stats.nullCount.remove("name");
+        assert_eq!(
+            verify_stats(&stats, reader.metadata()),
+            vec![
+                StatsMismatch {
+                    column: "".to_string(),
+                    stat: "numRecords",
+                    recorded: json!(2),
+                    actual: json!(3),
+                },
+                StatsMismatch {
+                    column: "id".to_string(),
+                    stat: "minValues",
+                    recorded: json!(2),
+                    actual: json!(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn footer_stats() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::file::serialized_reader::SliceableCursor;
+
+        let long_string = "a".repeat(40);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("date", DataType::Date32, true),
+            Field::new("data", DataType::Binary, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(3), None, Some(1)])),
+                Arc::new(StringArray::from(vec![
+                    Some("b"),
+                    Some(long_string.as_str()),
+                    None,
+                ])),
+                Arc::new(Date32Array::from(vec![Some(18628), Some(0), None])),
+                Arc::new(BinaryArray::from(vec![
+                    "ab".as_bytes(),
+                    "c".as_bytes(),
+                    "d".as_bytes(),
+                ])),
+            ],
+        )
+        .unwrap();
+        let bytes =
+            crate::writer::record_batches_to_parquet_bytes(schema, &[batch.clone()]).unwrap();
+        let reader = SerializedFileReader::new(SliceableCursor::new(bytes)).unwrap();
+
+        // dates are recorded as formatted strings, not as the days in the footer
+        let (mut stats, missing_columns) = stats_from_footer(reader.metadata());
+        assert_eq!(missing_columns, vec![2]);
+        assert_eq!(stats.numRecords, 3);
+        assert_eq!(
+            serde_json::to_value(&stats.minValues).unwrap(),
+            json!({"id": 1, "name": "a".repeat(32)})
+        );
+
+        let date_batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new(
+                "date",
+                DataType::Date32,
+                true,
+            )])),
+            vec![batch.column(2).clone()],
+        )
+        .unwrap();
+        let date_stats = stats_from_record_batch(&date_batch);
+        fill_missing_stats(&mut stats, date_stats);
+        let batch_stats = stats_from_record_batch(&batch);
+        assert_eq!(stats.minValues, batch_stats.minValues);
+        assert_eq!(stats.maxValues, batch_stats.maxValues);
+        assert_eq!(stats.nullCount, batch_stats.nullCount);
+    }
+}
@@ -33,12 +33,10 @@ mod azure {
         assert_eq!(tombstones.len(), 31);
         assert_eq!(
             tombstones[0],
-            deltalake::action::Remove {
+            deltalake::Tombstone {
                 path: "part-00006-63ce9deb-bc0f-482d-b9a1-7e717b67f294-c000.snappy.parquet"
                     .to_string(),
-                deletionTimestamp: 1587968596250,
-                dataChange: true,
-                ..Default::default()
+                deletion_timestamp: 1587968596250,
             }
         );
     }
@@ -0,0 +1,39 @@
+#[cfg(feature = "bench-utils")]
+mod bench_utils {
+    use deltalake::bench_utils::LogGenerator;
+
+    #[tokio::test]
+    async fn test_generated_table() {
+        let generator = LogGenerator {
+            num_commits: 5,
+            files_per_commit: 4,
+            num_partitions: 2,
+            removes_per_commit: 1,
+            with_stats: true,
+        };
+        let tmp_dir = tempdir::TempDir::new("bench_utils").unwrap();
+        generator.generate(tmp_dir.path()).unwrap();
+
+        let table = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(table.version, 5);
+        // the first commit adding files has nothing to remove
+        assert_eq!(table.get_files().len(), 5 * 4 - 4);
+        assert_eq!(table.get_tombstones().len(), 4);
+        assert_eq!(
+            table.get_metadata().unwrap().partition_columns,
+            vec!["part".to_string()]
+        );
+        assert!(table
+            .get_actions()
+            .iter()
+            .all(|add| add.get_stats().unwrap().unwrap().numRecords == 1000));
+
+        let filters = vec![deltalake::PartitionFilter {
+            key: "part",
+            value: deltalake::PartitionValue::Equal("1"),
+        }];
+        assert_eq!(table.get_files_by_partitions(&filters).unwrap().len(), 8);
+    }
+}
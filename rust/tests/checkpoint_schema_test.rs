@@ -0,0 +1,94 @@
+extern crate deltalake;
+
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::schema::types::SchemaDescriptor;
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[tokio::test]
+async fn checkpoint_schema_of_table_schema() {
+    let table_dir = tempdir::TempDir::new("checkpoint_schema").unwrap();
+    let nested = json!({
+        "type": "struct",
+        "fields": [
+            {"name": "ts", "type": "timestamp", "nullable": true, "metadata": {}},
+            {
+                "name": "tags",
+                "type": {"type": "array", "elementType": "string", "containsNull": true},
+                "nullable": true,
+                "metadata": {},
+            },
+        ],
+    });
+    fs_common::write_table(
+        table_dir.path(),
+        &[
+            ("id", json!("long")),
+            ("data", json!("binary")),
+            ("price", json!("decimal(10,2)")),
+            ("nested", nested),
+            ("day", json!("date")),
+        ],
+        &["day"],
+        &[],
+    );
+    let table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+
+    let schema = deltalake::checkpoints::checkpoint_schema(table.get_metadata().unwrap()).unwrap();
+    let descriptor = SchemaDescriptor::new(schema);
+    let column = |path: &str| {
+        descriptor
+            .columns()
+            .iter()
+            .find(|column| column.path().string() == path)
+            .cloned()
+    };
+    let paths = descriptor
+        .columns()
+        .iter()
+        .map(|column| column.path().string())
+        .collect::<Vec<_>>();
+
+    for path in &[
+        "txn.appId",
+        "add.path",
+        "add.partitionValues.key_value.key",
+        "add.stats",
+        "add.stats_parsed.numRecords",
+        "remove.path",
+        "metaData.partitionColumns.list.element",
+        "metaData.configuration.key_value.value",
+        "protocol.minWriterVersion",
+    ] {
+        assert!(paths.contains(&path.to_string()), "missing {}", path);
+    }
+
+    let day = column("add.partitionValues_parsed.day").unwrap();
+    assert_eq!(day.physical_type(), PhysicalType::INT32);
+    assert_eq!(day.converted_type(), ConvertedType::DATE);
+
+    let price = column("add.stats_parsed.minValues.price").unwrap();
+    assert_eq!(price.physical_type(), PhysicalType::INT64);
+    assert_eq!(price.converted_type(), ConvertedType::DECIMAL);
+    assert_eq!((price.type_precision(), price.type_scale()), (10, 2));
+
+    let ts = column("add.stats_parsed.maxValues.nested.ts").unwrap();
+    assert_eq!(ts.physical_type(), PhysicalType::INT64);
+    assert_eq!(ts.converted_type(), ConvertedType::TIMESTAMP_MICROS);
+
+    // binary and array columns have no min and max values, partition columns have no stats
+    assert!(column("add.stats_parsed.minValues.id").is_some());
+    assert!(column("add.stats_parsed.minValues.data").is_none());
+    assert!(column("add.stats_parsed.maxValues.nested.tags.list.element").is_none());
+    assert!(column("add.stats_parsed.minValues.day").is_none());
+    for path in &["id", "data", "price", "nested.ts"] {
+        let null_count = column(&format!("add.stats_parsed.nullCount.{}", path)).unwrap();
+        assert_eq!(null_count.physical_type(), PhysicalType::INT64);
+    }
+    assert!(column("add.stats_parsed.nullCount.nested.tags").is_none());
+    assert!(column("add.stats_parsed.nullCount.day").is_none());
+}
@@ -0,0 +1,70 @@
+extern crate deltalake;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn gzip_file(src: &Path, dst: &Path) {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&fs::read(src).unwrap()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    fs::remove_file(src).unwrap();
+    fs::write(dst, compressed).unwrap();
+}
+
+#[tokio::test]
+async fn read_gzip_compressed_commit() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let commit_path = table_dir
+        .path()
+        .join("_delta_log/00000000000000000004.json");
+    gzip_file(&commit_path, &commit_path);
+
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    assert_eq!(table.version, 4);
+    assert_eq!(table.get_files().len(), 5);
+    let actions = table.get_commit_actions(4).await.unwrap();
+    assert_eq!(actions.len(), 4);
+}
+
+#[tokio::test]
+async fn read_compressed_log_sidecars() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let commit_path = table_dir
+        .path()
+        .join("_delta_log/00000000000000000004.json");
+    gzip_file(&commit_path, &commit_path.with_extension("json.gz"));
+
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    assert_eq!(table.version, 3);
+
+    let table = deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_config(deltalake::DeltaTableConfig {
+            compressed_log_sidecars: true,
+            ..Default::default()
+        })
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.version, 4);
+    assert_eq!(table.get_files().len(), 5);
+
+    let table = deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_version(4)
+        .with_config(deltalake::DeltaTableConfig {
+            compressed_log_sidecars: true,
+            ..Default::default()
+        })
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.get_commit_actions(4).await.unwrap().len(), 4);
+}
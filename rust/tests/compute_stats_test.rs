@@ -0,0 +1,35 @@
+extern crate deltalake;
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[tokio::test]
+async fn test_compute_missing_stats() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/delta-0.8.0");
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(table.version, 1);
+    assert!(table.get_actions().iter().all(|add| add.stats.is_none()));
+
+    let version = table.compute_missing_stats().await.unwrap();
+    assert_eq!(version, Some(2));
+    assert_eq!(table.get_files().len(), 2);
+
+    let mut num_records = 0;
+    for add in table.get_actions() {
+        assert!(!add.dataChange);
+        let stats = add.get_stats().unwrap().unwrap();
+        assert!(stats.minValues.contains_key("value"));
+        assert!(stats.maxValues.contains_key("value"));
+        num_records += stats.numRecords;
+    }
+    assert_eq!(num_records, 4);
+
+    let history = table.history(Some(1)).await.unwrap();
+    assert_eq!(history[0].operation.as_deref(), Some("COMPUTE STATS"));
+
+    // all the files have stats now
+    assert_eq!(table.compute_missing_stats().await.unwrap(), None);
+    assert_eq!(table.version, 2);
+}
@@ -1,4 +1,4 @@
-#[cfg(feature = "s3")]
+#[cfg(feature = "integration-testing")]
 #[allow(dead_code)]
 mod s3_common;
 
@@ -13,7 +13,7 @@ use std::iter::FromIterator;
 use std::time::Duration;
 
 #[tokio::test]
-#[cfg(all(feature = "s3", feature = "dynamodb"))]
+#[cfg(all(feature = "integration-testing", feature = "dynamodb"))]
 async fn concurrent_writes_s3() {
     prepare_s3().await;
     run_test(|name| Worker::new("s3://deltars/concurrent_workers", name)).await;
@@ -117,7 +117,7 @@ fn prepare_fs() {
     );
 }
 
-#[cfg(feature = "s3")]
+#[cfg(all(feature = "integration-testing", feature = "dynamodb"))]
 async fn prepare_s3() {
     s3_common::setup_dynamodb("concurrent_writes");
     s3_common::cleanup_dir_except(
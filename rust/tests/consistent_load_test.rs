@@ -0,0 +1,105 @@
+extern crate deltalake;
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use deltalake::storage::file::FileStorageBackend;
+use deltalake::storage::ObjectMeta;
+use deltalake::{StorageBackend, StorageError};
+use futures::Stream;
+
+#[allow(dead_code)]
+mod fs_common;
+
+/// Commits version 5 of the table right after version 4 is read, like a concurrent writer would
+/// while the table is being loaded.
+#[derive(Debug)]
+struct ConcurrentWriter {
+    inner: FileStorageBackend,
+    committed: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for ConcurrentWriter {
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        self.inner.head_obj(path).await
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        let obj = self.inner.get_obj(path).await?;
+        if path.ends_with("00000000000000000004.json")
+            && !self.committed.swap(true, Ordering::SeqCst)
+        {
+            let next_path = path.replace("04.json", "05.json");
+            let commit = r#"{"remove":{"path":"part-00000-2befed33-c358-4768-a43c-3eda0d2a499d-c000.snappy.parquet","deletionTimestamp":1587968614187,"dataChange":true}}"#;
+            self.inner.put_obj(&next_path, commit.as_bytes()).await?;
+        }
+        Ok(obj)
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        self.inner.list_objs(path).await
+    }
+
+    async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.inner.put_obj(path, obj_bytes).await
+    }
+
+    async fn rename_obj(&self, src: &str, dst: &str) -> Result<(), StorageError> {
+        self.inner.rename_obj(src, dst).await
+    }
+
+    async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
+        self.inner.delete_obj(path).await
+    }
+}
+
+fn table_with_concurrent_writer(table_uri: &str) -> deltalake::DeltaTable {
+    let storage = ConcurrentWriter {
+        inner: FileStorageBackend::new(table_uri),
+        committed: AtomicBool::new(false),
+    };
+    deltalake::DeltaTable::new(table_uri, Box::new(storage)).unwrap()
+}
+
+#[tokio::test]
+async fn load_ignores_commits_written_while_loading() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let mut table = table_with_concurrent_writer(table_uri);
+    table.load_consistent().await.unwrap();
+    assert_eq!(table.version, 4);
+    assert_eq!(table.get_files().len(), 5);
+
+    // the commit landed, and a later load picks it up
+    table.load_consistent().await.unwrap();
+    assert_eq!(table.version, 5);
+    assert_eq!(table.get_files().len(), 4);
+}
+
+#[tokio::test]
+async fn load_applies_commits_written_while_loading() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let mut table = table_with_concurrent_writer(table_uri);
+    table.load().await.unwrap();
+    assert_eq!(table.version, 5);
+}
+
+#[tokio::test]
+async fn load_consistent_empty_table() {
+    let table_dir = tempdir::TempDir::new("load_consistent").unwrap();
+    let mut table = deltalake::DeltaTableBuilder::from_uri(table_dir.path().to_str().unwrap())
+        .build()
+        .unwrap();
+    assert!(matches!(
+        table.load_consistent().await,
+        Err(deltalake::DeltaTableError::NotATable)
+    ));
+}
@@ -3,8 +3,15 @@ mod datafusion {
     use std::sync::Arc;
 
     use arrow::array::*;
+    use datafusion::catalog::schema::SchemaProvider;
+    use datafusion::datasource::datasource::TableProviderFilterPushDown;
+    use datafusion::datasource::TableProvider;
     use datafusion::error::Result;
     use datafusion::execution::context::ExecutionContext;
+    use datafusion::logical_plan::{col, lit};
+    use deltalake::delta_datafusion::{
+        group_files_by_size, DeltaSchemaProvider, DirectoryTableResolver,
+    };
 
     #[tokio::test]
     async fn test_datafusion_simple_query() -> Result<()> {
@@ -50,4 +57,616 @@ mod datafusion {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_datafusion_partition_and_stats_pruning() -> Result<()> {
+        let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned")
+            .await
+            .unwrap();
+        let filter = col("year").eq(lit("2021")).and(col("month").gt(lit("3")));
+        assert!(matches!(
+            table.supports_filter_pushdown(&filter)?,
+            TableProviderFilterPushDown::Inexact
+        ));
+        // partition values are strings, only year=2021/month=4 is greater than "3"
+        let plan = table.scan(&None, 1024, &[filter.clone()], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 1);
+
+        // partition values are read from the add actions, not from the file paths
+        let table = deltalake::open_table("./tests/data/delta-0.8.0-random-layout")
+            .await
+            .unwrap();
+        let plan = table.scan(&None, 1024, &[filter], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 0);
+        let plan = table.scan(&None, 1024, &[col("year").eq(lit("2021"))], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 2);
+
+        // the files of the COVID-19 table cover successive date ranges
+        let table = deltalake::open_table("./tests/data/COVID-19_NYT")
+            .await
+            .unwrap();
+        let plan = table.scan(&None, 1024, &[], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 8);
+        let plan = table.scan(&None, 1024, &[col("date").eq(lit("2020-06-01"))], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 1);
+        let plan = table.scan(&None, 1024, &[lit("2020-05-19").lt_eq(col("date"))], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 8);
+        let plan = table.scan(&None, 1024, &[col("cases").gt(lit(1_000_000_000i64))], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_insert_into() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("insert_into").unwrap();
+        let log_dir = tmp_dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        for entry in std::fs::read_dir("./tests/data/simple_table/_delta_log").unwrap() {
+            let entry = entry.unwrap();
+            std::fs::copy(entry.path(), log_dir.join(entry.file_name())).unwrap();
+        }
+        let table_dir = tmp_dir.path();
+        let mut target = deltalake::open_table(table_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(target.version, 4);
+        assert_eq!(target.get_files().len(), 5);
+
+        let mut ctx = ExecutionContext::new();
+        let source = deltalake::open_table("./tests/data/simple_table")
+            .await
+            .unwrap();
+        ctx.register_table("source", Arc::new(source))?;
+        let df = ctx.sql("SELECT id FROM source WHERE id > 5")?;
+        let plan = ctx.create_physical_plan(&df.to_logical_plan())?;
+
+        let version = deltalake::delta_datafusion::insert_into(&mut target, plan).await?;
+        assert_eq!(version, 5);
+        assert_eq!(target.get_files().len(), 6);
+
+        let mut ctx = ExecutionContext::new();
+        let path = format!("{}/{}", table_dir.to_str().unwrap(), target.get_files()[5]);
+        ctx.register_parquet("written", &path)?;
+        let batches = ctx
+            .sql("SELECT id FROM written ORDER BY id ASC")?
+            .collect()
+            .await?;
+        assert_eq!(
+            batches[0].column(0).as_ref(),
+            Arc::new(Int64Array::from(vec![7, 9])).as_ref(),
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_insert_into_normalizes_types() -> Result<()> {
+        use arrow::datatypes::{DataType, Field, Int32Type, Schema, TimeUnit};
+        use arrow::record_batch::RecordBatch;
+        use datafusion::physical_plan::memory::MemoryExec;
+
+        let tmp_dir = tempdir::TempDir::new("insert_into_types").unwrap();
+        let log_dir = tmp_dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::write(
+            log_dir.join("00000000000000000000.json"),
+            [
+                r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#,
+                r#"{"metaData":{"id":"1","format":{"provider":"parquet","options":{}},"schemaString":"{\"type\":\"struct\",\"fields\":[{\"name\":\"name\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}},{\"name\":\"data\",\"type\":\"binary\",\"nullable\":true,\"metadata\":{}},{\"name\":\"ts\",\"type\":\"timestamp\",\"nullable\":true,\"metadata\":{}}]}","partitionColumns":[],"configuration":{},"createdTime":0}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let table_dir = tmp_dir.path();
+        let mut target = deltalake::open_table(table_dir.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "name",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("data", DataType::LargeBinary, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+                true,
+            ),
+        ]));
+        let names: DictionaryArray<Int32Type> = vec!["b", "a", "b"].into_iter().collect();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(names),
+                Arc::new(LargeBinaryArray::from(vec![
+                    "x".as_bytes(),
+                    "y".as_bytes(),
+                    "z".as_bytes(),
+                ])),
+                Arc::new(TimestampMicrosecondArray::from_opt_vec(
+                    vec![
+                        Some(1_609_459_200_000_000),
+                        None,
+                        Some(1_609_459_260_000_000),
+                    ],
+                    Some("UTC".to_string()),
+                )),
+            ],
+        )?;
+        let plan = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+
+        let version = deltalake::delta_datafusion::insert_into(&mut target, plan).await?;
+        assert_eq!(version, 1);
+        let stats = target.get_actions()[0].get_stats().unwrap().unwrap();
+        assert_eq!(stats.numRecords, 3);
+        assert_eq!(
+            stats.minValues["ts"].as_value().unwrap(),
+            "2021-01-01T00:00:00.000Z"
+        );
+        assert_eq!(
+            stats.maxValues["ts"].as_value().unwrap(),
+            "2021-01-01T00:01:00.000Z"
+        );
+
+        let mut ctx = ExecutionContext::new();
+        let path = format!("{}/{}", table_dir.to_str().unwrap(), target.get_files()[0]);
+        ctx.register_parquet("written", &path)?;
+        let batches = ctx.sql("SELECT name, data FROM written")?.collect().await?;
+        assert_eq!(
+            batches[0].column(0).as_ref(),
+            Arc::new(StringArray::from(vec!["b", "a", "b"])).as_ref(),
+        );
+        assert_eq!(
+            batches[0].column(1).as_ref(),
+            Arc::new(BinaryArray::from(vec![
+                "x".as_bytes(),
+                "y".as_bytes(),
+                "z".as_bytes()
+            ]))
+            .as_ref(),
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_insert_into_fills_default_values() -> Result<()> {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use datafusion::physical_plan::memory::MemoryExec;
+
+        let tmp_dir = tempdir::TempDir::new("insert_into_defaults").unwrap();
+        let log_dir = tmp_dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::write(
+            log_dir.join("00000000000000000000.json"),
+            [
+                r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#,
+                r#"{"metaData":{"id":"1","format":{"provider":"parquet","options":{}},"schemaString":"{\"type\":\"struct\",\"fields\":[{\"name\":\"name\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}},{\"name\":\"visits\",\"type\":\"long\",\"nullable\":true,\"metadata\":{\"delta.defaultValue\":\"0\"}},{\"name\":\"region\",\"type\":\"string\",\"nullable\":true,\"metadata\":{\"delta.defaultValue\":\"unknown\"}}]}","partitionColumns":[],"configuration":{},"createdTime":0}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let table_dir = tmp_dir.path();
+        let mut target = deltalake::open_table(table_dir.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("region", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(StringArray::from(vec![Some("eu"), None])),
+            ],
+        )?;
+        let plan = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+
+        let version = deltalake::delta_datafusion::insert_into(&mut target, plan).await?;
+        assert_eq!(version, 1);
+        let history = target.history(Some(1)).await.unwrap();
+        let metrics = history[0].operationMetrics.as_ref().unwrap();
+        assert_eq!(metrics["defaultedColumns"], r#"["visits"]"#);
+        assert_eq!(metrics["numOutputRows"], "2");
+        assert_eq!(metrics["numFiles"], "1");
+
+        let mut ctx = ExecutionContext::new();
+        let path = format!("{}/{}", table_dir.to_str().unwrap(), target.get_files()[0]);
+        ctx.register_parquet("written", &path)?;
+        let batches = ctx
+            .sql("SELECT visits, region FROM written ORDER BY name")?
+            .collect()
+            .await?;
+        assert_eq!(
+            batches[0].column(0).as_ref(),
+            Arc::new(Int64Array::from(vec![0, 0])).as_ref(),
+        );
+        // only omitted columns are defaulted, nulls are written as they are
+        assert_eq!(
+            batches[0].column(1).as_ref(),
+            Arc::new(StringArray::from(vec![Some("eu"), None])).as_ref(),
+        );
+
+        // columns without a default can't be omitted
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "region",
+            DataType::Utf8,
+            true,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(StringArray::from(vec!["eu"]))],
+        )?;
+        let plan = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        assert!(deltalake::delta_datafusion::insert_into(&mut target, plan)
+            .await
+            .is_err());
+        assert_eq!(target.version, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_insert_into_partitioned_table() -> Result<()> {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use datafusion::physical_plan::memory::MemoryExec;
+
+        let tmp_dir = tempdir::TempDir::new("insert_into_partitioned").unwrap();
+        let log_dir = tmp_dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::copy(
+            "./tests/data/delta-0.8.0-partitioned/_delta_log/00000000000000000000.json",
+            log_dir.join("00000000000000000000.json"),
+        )
+        .unwrap();
+        let table_dir = tmp_dir.path();
+        let mut target = deltalake::open_table(table_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        let num_files = target.get_files().len();
+
+        let schema = Arc::new(Schema::new(
+            ["value", "year", "month", "day"]
+                .iter()
+                .map(|name| Field::new(name, DataType::Utf8, true))
+                .collect(),
+        ));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                Arc::new(StringArray::from(vec!["2021", "2021", "2021"])),
+                Arc::new(StringArray::from(vec!["4", "4", "4"])),
+                Arc::new(StringArray::from(vec!["1", "2", "1"])),
+            ],
+        )?;
+        let plan = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+
+        let version = deltalake::delta_datafusion::insert_into(&mut target, plan).await?;
+        assert_eq!(version, 1);
+        let mut adds = target.get_actions()[num_files..].to_vec();
+        adds.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(adds.len(), 2);
+        assert!(adds[0].path.starts_with("year=2021/month=4/day=1/"));
+        assert_eq!(adds[0].partitionValues["day"], Some("1".to_string()));
+        assert!(adds[1].path.starts_with("year=2021/month=4/day=2/"));
+        assert_eq!(adds[1].get_stats().unwrap().unwrap().numRecords, 1);
+        let history = target.history(Some(1)).await.unwrap();
+        assert_eq!(
+            history[0].operationMetrics.as_ref().unwrap()["numFiles"],
+            "2"
+        );
+
+        Ok(())
+    }
+
+    /// Creates a table partitioned like delta-0.8.0-partitioned, with the values `a`, `b` and
+    /// null on day 1 and `d` on day 2 of the month 6 of 2021.
+    async fn partitioned_table_with_rows(
+        table_dir: &std::path::Path,
+    ) -> Result<deltalake::DeltaTable> {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use datafusion::physical_plan::memory::MemoryExec;
+
+        let log_dir = table_dir.join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::copy(
+            "./tests/data/delta-0.8.0-partitioned/_delta_log/00000000000000000000.json",
+            log_dir.join("00000000000000000000.json"),
+        )
+        .unwrap();
+        let mut table = deltalake::open_table(table_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        let schema = Arc::new(Schema::new(
+            ["value", "year", "month", "day"]
+                .iter()
+                .map(|name| Field::new(name, DataType::Utf8, true))
+                .collect(),
+        ));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    Some("a"),
+                    Some("b"),
+                    None,
+                    Some("d"),
+                ])),
+                Arc::new(StringArray::from(vec!["2021", "2021", "2021", "2021"])),
+                Arc::new(StringArray::from(vec!["6", "6", "6", "6"])),
+                Arc::new(StringArray::from(vec!["1", "1", "1", "2"])),
+            ],
+        )?;
+        let plan = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        deltalake::delta_datafusion::insert_into(&mut table, plan).await?;
+
+        Ok(table)
+    }
+
+    #[derive(Debug)]
+    struct FixedClock(std::time::SystemTime);
+
+    impl deltalake::Clock for FixedClock {
+        fn now(&self) -> std::time::SystemTime {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_delete() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("delete").unwrap();
+        let mut table = partitioned_table_with_rows(tmp_dir.path()).await?;
+        let now = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1615043776198);
+        table.set_clock(Arc::new(FixedClock(now)));
+        let num_files = table.get_files().len();
+        // the files of the initial version aren't in the temporary directory
+        let month = || col("month").eq(lit("6"));
+
+        // no file is rewritten when no row matches
+        let predicate = month().and(col("value").eq(lit("ab")));
+        assert_eq!(table.delete(predicate).await?, None);
+        assert_eq!(table.version, 1);
+
+        // the other rows of the file of day 1, null values included, are rewritten
+        let predicate = month().and(col("value").eq(lit("a")));
+        assert_eq!(table.delete(predicate).await?, Some(2));
+        assert_eq!(table.get_files().len(), num_files);
+        // removes are timestamped with the table clock
+        assert_eq!(table.get_tombstones().len(), 1);
+        assert_eq!(table.get_tombstones()[0].deletion_timestamp, 1615043776198);
+        let add = table
+            .get_actions()
+            .iter()
+            .find(|add| add.path.starts_with("year=2021/month=6/day=1/"))
+            .unwrap();
+        assert_eq!(add.get_stats().unwrap().unwrap().numRecords, 2);
+        let history = table.history(Some(1)).await.unwrap();
+        assert_eq!(history[0].operation.as_deref(), Some("DELETE"));
+        let metrics = history[0].operationMetrics.as_ref().unwrap();
+        assert_eq!(metrics["numRemovedFiles"], "1");
+        assert_eq!(metrics["numAddedFiles"], "1");
+        assert_eq!(metrics["numDeletedRows"], "1");
+        assert_eq!(metrics["numCopiedRows"], "2");
+
+        // files whose rows all match are only removed
+        let predicate = month().and(col("day").eq(lit("2")));
+        assert_eq!(table.delete(predicate).await?, Some(3));
+        assert_eq!(table.get_files().len(), num_files - 1);
+        let history = table.history(Some(1)).await.unwrap();
+        assert_eq!(
+            history[0].operationMetrics.as_ref().unwrap()["numAddedFiles"],
+            "0"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_concurrent_deletes() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("concurrent_deletes").unwrap();
+        let mut table = partitioned_table_with_rows(tmp_dir.path()).await?;
+        let mut other = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        // the files of the initial version aren't in the temporary directory
+        let day = |day: &str| col("month").eq(lit("6")).and(col("day").eq(lit(day)));
+
+        // deletes reading different files don't conflict
+        assert_eq!(table.delete(day("2")).await?, Some(2));
+        let predicate = day("1").and(col("value").eq(lit("a")));
+        assert_eq!(other.delete(predicate).await?, Some(3));
+
+        // the file of day 1 read by `table` was removed by the delete of `other`, so committing
+        // would bring back the deleted row
+        let predicate = day("1").and(col("value").eq(lit("b")));
+        let error = table.delete(predicate).await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("changed by concurrent commit at version 3"),
+            "{}",
+            error
+        );
+        table.update().await.unwrap();
+        assert_eq!(table.version, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_update() -> Result<()> {
+        use std::collections::HashMap;
+
+        let tmp_dir = tempdir::TempDir::new("update").unwrap();
+        let mut table = partitioned_table_with_rows(tmp_dir.path()).await?;
+        let mut other = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        let num_files = table.get_files().len();
+        // the files of the initial version aren't in the temporary directory
+        let month = || col("month").eq(lit("6"));
+
+        let mut assignments = HashMap::new();
+        assignments.insert("hour".to_string(), lit("1"));
+        let result = table.update(month(), assignments).await;
+        assert!(result.is_err());
+
+        // only the file of day 1 is rewritten, with the other rows copied
+        let mut assignments = HashMap::new();
+        assignments.insert("value".to_string(), lit("B"));
+        let predicate = month().and(col("value").eq(lit("b")));
+        assert_eq!(table.update(predicate, assignments).await?, Some(2));
+        assert_eq!(table.get_files().len(), num_files);
+        let add = table
+            .get_actions()
+            .iter()
+            .find(|add| add.path.starts_with("year=2021/month=6/day=1/"))
+            .unwrap();
+        let stats = add.get_stats().unwrap().unwrap();
+        assert_eq!(stats.numRecords, 3);
+        assert_eq!(stats.minValues["value"].as_value().unwrap(), "B");
+        assert_eq!(stats.maxValues["value"].as_value().unwrap(), "a");
+        let history = table.history(Some(1)).await.unwrap();
+        assert_eq!(history[0].operation.as_deref(), Some("UPDATE"));
+        let metrics = history[0].operationMetrics.as_ref().unwrap();
+        assert_eq!(metrics["numRemovedFiles"], "1");
+        assert_eq!(metrics["numAddedFiles"], "1");
+        assert_eq!(metrics["numUpdatedRows"], "1");
+        assert_eq!(metrics["numCopiedRows"], "2");
+
+        // the file of day 1 read by `other` was removed by the update of `table`, so committing
+        // would bring back the old value of the updated row
+        let mut assignments = HashMap::new();
+        assignments.insert("value".to_string(), lit("A"));
+        let predicate = month().and(col("value").eq(lit("a")));
+        let error = other.update(predicate, assignments).await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("changed by concurrent commit at version 2"),
+            "{}",
+            error
+        );
+
+        // rows assigned other partition values move to the files of their new partitions
+        let mut assignments = HashMap::new();
+        assignments.insert("day".to_string(), lit("3"));
+        let predicate = month().and(col("day").eq(lit("2")));
+        assert_eq!(table.update(predicate, assignments).await?, Some(3));
+        assert_eq!(table.get_files().len(), num_files);
+        assert!(table
+            .get_files()
+            .iter()
+            .any(|path| path.starts_with("year=2021/month=6/day=3/")));
+        assert!(!table
+            .get_files()
+            .iter()
+            .any(|path| path.starts_with("year=2021/month=6/day=2/")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_schema_provider() -> Result<()> {
+        let provider =
+            DeltaSchemaProvider::new(Arc::new(DirectoryTableResolver::new("./tests/data")));
+        let names = provider.table_names();
+        assert!(names.contains(&"simple_table".to_string()));
+
+        let table = provider.table("simple_table").unwrap();
+        assert_eq!(table.schema().field(0).name(), "id");
+        assert!(provider.table("missing_table").is_none());
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("demo", table)?;
+        let batches = ctx
+            .sql("SELECT id FROM demo WHERE id > 5 ORDER BY id ASC")?
+            .collect()
+            .await?;
+        assert_eq!(
+            batches[0].column(0).as_ref(),
+            Arc::new(Int64Array::from(vec![7, 9])).as_ref(),
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_footer_pruning() -> Result<()> {
+        // the add actions of the simple table have no stats
+        let filter = col("id").gt(lit(100i64));
+        let table = deltalake::open_table("./tests/data/simple_table")
+            .await
+            .unwrap();
+        let plan = table.scan(&None, 1024, &[filter.clone()], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 5);
+
+        let backend = deltalake::get_backend_for_uri("./tests/data/simple_table").unwrap();
+        let mut table = deltalake::DeltaTable::new_with_config(
+            "./tests/data/simple_table",
+            backend,
+            deltalake::DeltaTableConfig {
+                footer_pruning: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        table.load().await.unwrap();
+        let plan = table.scan(&None, 1024, &[filter], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 0);
+        let plan = table.scan(&None, 1024, &[col("id").gt(lit(5i64))], None)?;
+        assert!(plan.output_partitioning().partition_count() > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_datafusion_scan_partitions() -> Result<()> {
+        let backend = deltalake::get_backend_for_uri("./tests/data/simple_table").unwrap();
+        let mut table = deltalake::DeltaTable::new_with_config(
+            "./tests/data/simple_table",
+            backend,
+            deltalake::DeltaTableConfig {
+                scan_partitions: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        table.load().await.unwrap();
+        let plan = table.scan(&None, 1024, &[], None)?;
+        assert_eq!(plan.output_partitioning().partition_count(), 2);
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_table("demo", Arc::new(table))?;
+        let batches = ctx
+            .sql("SELECT id FROM demo WHERE id > 5 ORDER BY id ASC")?
+            .collect()
+            .await?;
+        assert_eq!(
+            batches[0].column(0).as_ref(),
+            Arc::new(Int64Array::from(vec![7, 9])).as_ref(),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_files_by_size() {
+        assert_eq!(
+            group_files_by_size(&[10, 50, 20, 30, 40], 2),
+            vec![vec![0, 1, 2], vec![3, 4]]
+        );
+        assert_eq!(group_files_by_size(&[10, 20], 4), vec![vec![1], vec![0]]);
+        assert_eq!(group_files_by_size(&[10, 20], 0), vec![vec![0, 1]]);
+        assert!(group_files_by_size(&[], 2).is_empty());
+    }
 }
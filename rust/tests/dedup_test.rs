@@ -0,0 +1,152 @@
+extern crate deltalake;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{Int32Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use chrono::{TimeZone, Utc};
+use deltalake::writer::dedup::{Deduplicator, Lookback};
+use deltalake::writer::{record_batches_to_parquet_bytes, stats::stats_from_record_batch};
+use serde_json::json;
+
+fn batch(ids: Vec<i32>) -> RecordBatch {
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int32, true),
+        Field::new("value", DataType::Utf8, true),
+    ]);
+    let values = ids
+        .iter()
+        .map(|id| format!("value-{}", id))
+        .collect::<Vec<_>>();
+    RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(Int32Array::from(ids)),
+            Arc::new(StringArray::from(
+                values.iter().map(String::as_str).collect::<Vec<_>>(),
+            )),
+        ],
+    )
+    .unwrap()
+}
+
+fn batch_ids(batch: &RecordBatch) -> Vec<i32> {
+    let ids = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    ids.values().to_vec()
+}
+
+/// Writes a data file with the given ids and commits its add action as `version`.
+fn commit_file(table_dir: &Path, version: i64, ids: Vec<i32>, with_stats: bool) {
+    let batch = batch(ids);
+    let bytes = record_batches_to_parquet_bytes(batch.schema(), &[batch.clone()]).unwrap();
+    let path = format!("part-{}.parquet", version);
+    fs::write(table_dir.join(&path), &bytes).unwrap();
+
+    let mut actions = Vec::new();
+    if version == 0 {
+        let schema = r#"{"type":"struct","fields":[{"name":"id","type":"integer","nullable":true,"metadata":{}},{"name":"value","type":"string","nullable":true,"metadata":{}}]}"#;
+        actions.push(json!({"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}}));
+        actions.push(json!({
+            "metaData": {
+                "id": "5fba94ed-9794-4965-ba6e-6ee3c0d22af9",
+                "format": {"provider": "parquet", "options": {}},
+                "schemaString": schema,
+                "partitionColumns": [],
+                "configuration": {},
+                "createdTime": 1000,
+            }
+        }));
+    }
+    let stats = if with_stats {
+        Some(serde_json::to_string(&stats_from_record_batch(&batch)).unwrap())
+    } else {
+        None
+    };
+    actions.push(json!({
+        "add": {
+            "path": path,
+            "partitionValues": {},
+            "size": bytes.len(),
+            "modificationTime": (version + 1) * 1000,
+            "dataChange": true,
+            "stats": stats,
+        }
+    }));
+    let log = actions
+        .iter()
+        .map(|action| action.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let log_dir = table_dir.join("_delta_log");
+    fs::create_dir_all(&log_dir).unwrap();
+    fs::write(log_dir.join(format!("{:020}.json", version)), log).unwrap();
+}
+
+#[tokio::test]
+async fn filter_keys_of_recent_files() {
+    let table_dir = tempdir::TempDir::new("dedup_table").unwrap();
+    commit_file(table_dir.path(), 0, vec![1, 2, 3], true);
+    commit_file(table_dir.path(), 1, vec![10, 11], false);
+    commit_file(table_dir.path(), 2, vec![20, 21, 22], true);
+    let table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let incoming = batch(vec![2, 5, 11, 21]);
+
+    let deduplicator = Deduplicator::new(vec!["id".to_string()], Lookback::Versions(2));
+    let filtered = deduplicator.filter_batch(&table, &incoming).await.unwrap();
+    assert_eq!(batch_ids(&filtered), vec![2, 5]);
+    assert_eq!(filtered.num_columns(), 2);
+
+    let deduplicator = Deduplicator::new(vec!["id".to_string()], Lookback::Versions(10));
+    let filtered = deduplicator.filter_batch(&table, &incoming).await.unwrap();
+    assert_eq!(batch_ids(&filtered), vec![5]);
+
+    let deduplicator = Deduplicator::new(
+        vec!["id".to_string(), "value".to_string()],
+        Lookback::Since(Utc.timestamp_millis(2000)),
+    );
+    let filtered = deduplicator.filter_batch(&table, &incoming).await.unwrap();
+    assert_eq!(batch_ids(&filtered), vec![2, 5]);
+
+    let deduplicator = Deduplicator::new(
+        vec!["id".to_string()],
+        Lookback::Since(Utc.timestamp_millis(5000)),
+    );
+    let filtered = deduplicator.filter_batch(&table, &incoming).await.unwrap();
+    assert_eq!(filtered.num_rows(), 4);
+
+    let deduplicator = Deduplicator::new(vec!["missing".to_string()], Lookback::Versions(1));
+    match deduplicator.filter_batch(&table, &incoming).await {
+        Err(deltalake::DeltaTableError::ColumnNotFound { column }) => {
+            assert_eq!(column, "missing")
+        }
+        _ => panic!("the key column should be missing"),
+    }
+}
+
+#[tokio::test]
+async fn skip_files_by_stats() {
+    let table_dir = tempdir::TempDir::new("dedup_table").unwrap();
+    commit_file(table_dir.path(), 0, vec![1, 2, 3], true);
+    commit_file(table_dir.path(), 1, vec![20, 21, 22], true);
+    let table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+
+    // the stats of the second file don't overlap the keys, so it isn't read
+    fs::remove_file(table_dir.path().join("part-1.parquet")).unwrap();
+    let deduplicator = Deduplicator::new(vec!["id".to_string()], Lookback::Versions(2));
+    let filtered = deduplicator
+        .filter_batch(&table, &batch(vec![2, 5]))
+        .await
+        .unwrap();
+    assert_eq!(batch_ids(&filtered), vec![5]);
+}
@@ -0,0 +1,190 @@
+#[cfg(feature = "delta-sharing-server")]
+mod delta_sharing_server {
+    use deltalake::delta_sharing::server::{
+        DeltaSharingServer, RecipientConfig, SchemaConfig, ShareConfig, SharingServerConfig,
+        TableConfig,
+    };
+    use hyper::{Body, Request, Response};
+    use serde_json::{json, Value};
+
+    fn server() -> DeltaSharingServer {
+        DeltaSharingServer::new(SharingServerConfig {
+            shares: vec![
+                ShareConfig {
+                    name: "share1".to_string(),
+                    schemas: vec![SchemaConfig {
+                        name: "default".to_string(),
+                        tables: vec![
+                            TableConfig {
+                                name: "simple".to_string(),
+                                location: "./tests/data/simple_table".to_string(),
+                                id: None,
+                            },
+                            TableConfig {
+                                name: "partitioned".to_string(),
+                                location: "./tests/data/delta-0.8.0-partitioned".to_string(),
+                                id: Some("00000000-0000-0000-0000-000000000001".to_string()),
+                            },
+                        ],
+                    }],
+                },
+                ShareConfig {
+                    name: "share2".to_string(),
+                    schemas: vec![],
+                },
+            ],
+            recipients: vec![RecipientConfig {
+                bearer_token: "token".to_string(),
+                shares: vec!["share1".to_string()],
+            }],
+        })
+    }
+
+    fn request(method: &str, path: &str, body: Option<Value>) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .header("Authorization", "Bearer token")
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap()
+    }
+
+    async fn body(response: Response<Body>) -> String {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    async fn json_body(response: Response<Body>) -> Value {
+        serde_json::from_str(&body(response).await).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_endpoints() {
+        let server = server();
+
+        let response = server.handle(request("GET", "/shares", None)).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            json_body(response).await,
+            json!({ "items": [{ "name": "share1" }] })
+        );
+
+        let response = server
+            .handle(request("GET", "/shares/share1/schemas", None))
+            .await;
+        assert_eq!(
+            json_body(response).await,
+            json!({ "items": [{ "name": "default", "share": "share1" }] })
+        );
+
+        let response = server
+            .handle(request(
+                "GET",
+                "/shares/share1/schemas/default/tables?maxResults=1",
+                None,
+            ))
+            .await;
+        assert_eq!(
+            json_body(response).await,
+            json!({
+                "items": [{ "name": "simple", "schema": "default", "share": "share1" }],
+                "nextPageToken": "1",
+            })
+        );
+        let response = server
+            .handle(request(
+                "GET",
+                "/shares/share1/all-tables?maxResults=1&pageToken=1",
+                None,
+            ))
+            .await;
+        assert_eq!(
+            json_body(response).await,
+            json!({
+                "items": [{
+                    "name": "partitioned",
+                    "schema": "default",
+                    "share": "share1",
+                    "id": "00000000-0000-0000-0000-000000000001",
+                }],
+            })
+        );
+
+        // shares the recipient can't access don't exist for them
+        let response = server
+            .handle(request("GET", "/shares/share2/schemas", None))
+            .await;
+        assert_eq!(response.status(), 404);
+        assert_eq!(
+            json_body(response).await["errorCode"],
+            "RESOURCE_DOES_NOT_EXIST"
+        );
+
+        let response = server
+            .handle(
+                Request::builder()
+                    .uri("/shares")
+                    .header("Authorization", "Bearer other")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await;
+        assert_eq!(response.status(), 401);
+    }
+
+    #[tokio::test]
+    async fn test_table_endpoints() {
+        let server = server();
+        let table_path = "/shares/share1/schemas/default/tables/partitioned";
+
+        let response = server.handle(request("HEAD", table_path, None)).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["delta-table-version"], "0");
+
+        let response = server
+            .handle(request("GET", &format!("{}/metadata", table_path), None))
+            .await;
+        assert_eq!(response.headers()["delta-table-version"], "0");
+        let lines = body(response).await;
+        let lines = lines
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], json!({ "protocol": { "minReaderVersion": 1 } }));
+        assert_eq!(
+            lines[1]["metaData"]["partitionColumns"],
+            json!(["year", "month", "day"])
+        );
+
+        // no file matches the hint, so no url has to be signed
+        let response = server
+            .handle(request(
+                "POST",
+                &format!("{}/query", table_path),
+                Some(json!({ "predicateHints": ["year = '2019'", "value > 'a'"] })),
+            ))
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(body(response).await.lines().count(), 2);
+
+        // local tables can't hand out pre-signed urls
+        let response = server
+            .handle(request(
+                "POST",
+                &format!("{}/query", table_path),
+                Some(json!({ "predicateHints": ["YEAR IS NOT NULL"] })),
+            ))
+            .await;
+        assert_eq!(response.status(), 500);
+
+        let response = server
+            .handle(request(
+                "GET",
+                "/shares/share1/schemas/default/tables/missing/metadata",
+                None,
+            ))
+            .await;
+        assert_eq!(response.status(), 404);
+    }
+}
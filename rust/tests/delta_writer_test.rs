@@ -0,0 +1,368 @@
+extern crate deltalake;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use arrow::array::{ArrayRef, LargeStringArray, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use deltalake::action::{Action, Add, DeltaOperation, SaveMode};
+use deltalake::writer::DeltaWriter;
+use deltalake::{
+    Clock, DeltaTableError, DeltaTransactionError, PartitionFilter, PartitionValue, StorageBackend,
+};
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::file::reader::SerializedFileReader;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn string_batch(columns: Vec<(&str, Vec<Option<&str>>)>) -> RecordBatch {
+    let fields = columns
+        .iter()
+        .map(|(name, _)| Field::new(name, DataType::Utf8, true))
+        .collect();
+    let arrays = columns
+        .into_iter()
+        .map(|(_, values)| Arc::new(StringArray::from(values)) as ArrayRef)
+        .collect();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap()
+}
+
+#[derive(Debug)]
+struct FixedClock(SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn write_partitioned_batches() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/delta-0.8.0-partitioned");
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let num_files = table.get_files().len();
+    table.set_clock(Arc::new(FixedClock(
+        UNIX_EPOCH + Duration::from_millis(1615043776198),
+    )));
+
+    let mut writer = DeltaWriter::for_table(&table).unwrap();
+    // columns are matched by name
+    writer
+        .write(&string_batch(vec![
+            ("day", vec![Some("1"), Some("2"), Some("1")]),
+            ("value", vec![Some("a"), Some("b"), Some("c")]),
+            ("year", vec![Some("2021"), Some("2021"), Some("2021")]),
+            ("month", vec![Some("1"), Some("1"), Some("1")]),
+        ]))
+        .unwrap();
+    let large_values: ArrayRef = Arc::new(LargeStringArray::from(vec![Some("d")]));
+    let batch = string_batch(vec![
+        ("year", vec![Some("2021")]),
+        ("month", vec![Some("1")]),
+        ("day", vec![None]),
+    ]);
+    let mut columns = batch.columns().to_vec();
+    columns.insert(0, large_values);
+    let mut fields = batch.schema().fields().clone();
+    fields.insert(0, Field::new("value", DataType::LargeUtf8, true));
+    writer
+        .write(&RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap())
+        .unwrap();
+    assert_eq!(4, writer.buffered_rows());
+
+    let mut adds = writer.flush().await.unwrap();
+    assert_eq!(0, writer.buffered_rows());
+    adds.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(3, adds.len());
+    let expected_dirs = [
+        "year=2021/month=1/day=1/",
+        "year=2021/month=1/day=2/",
+        "year=2021/month=1/day=__HIVE_DEFAULT_PARTITION__/",
+    ];
+    for (add, dir) in adds.iter().zip(&expected_dirs) {
+        assert!(add.path.starts_with(dir), "{}", add.path);
+        assert!(add.dataChange);
+        // the modification times come from the table clock
+        assert_eq!(1615043776198, add.modificationTime);
+    }
+    let mut partition_values = HashMap::new();
+    partition_values.insert("year".to_string(), Some("2021".to_string()));
+    partition_values.insert("month".to_string(), Some("1".to_string()));
+    partition_values.insert("day".to_string(), Some("1".to_string()));
+    assert_eq!(partition_values, adds[0].partitionValues);
+    assert_eq!(None, adds[2].partitionValues["day"]);
+    let stats = adds[0].get_stats().unwrap().unwrap();
+    assert_eq!(2, stats.numRecords);
+
+    // partition columns aren't stored in the data files
+    let file = std::fs::File::open(table_dir.path().join(&adds[0].path)).unwrap();
+    assert_eq!(adds[0].size as u64, file.metadata().unwrap().len());
+    let mut reader =
+        ParquetFileArrowReader::new(Arc::new(SerializedFileReader::new(file).unwrap()));
+    let batches = reader
+        .get_record_reader(1024)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let values = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(1, batches[0].num_columns());
+    assert_eq!(
+        vec![Some("a"), Some("c")],
+        values.iter().collect::<Vec<_>>()
+    );
+
+    let actions: Vec<Action> = adds.into_iter().map(Action::add).collect();
+    let operation = DeltaOperation::Write {
+        mode: SaveMode::Append,
+        partitionBy: None,
+        predicate: None,
+    };
+    table
+        .create_transaction(None)
+        .commit_with(&actions, Some(operation))
+        .await
+        .unwrap();
+    assert_eq!(num_files + 3, table.get_files().len());
+}
+
+#[tokio::test]
+async fn write_batches_not_matching_the_schema() {
+    let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned")
+        .await
+        .unwrap();
+    let mut writer = DeltaWriter::for_table(&table).unwrap();
+
+    let result = writer.write(&string_batch(vec![
+        ("value", vec![Some("a")]),
+        ("year", vec![Some("2021")]),
+        ("month", vec![Some("1")]),
+    ]));
+    assert!(matches!(
+        result,
+        Err(DeltaTableError::ColumnNotFound { column }) if column == "day"
+    ));
+
+    let result = writer.write(&string_batch(vec![
+        ("value", vec![Some("a")]),
+        ("year", vec![Some("2021")]),
+        ("month", vec![Some("1")]),
+        ("day", vec![Some("1")]),
+        ("hour", vec![Some("1")]),
+    ]));
+    assert!(matches!(result, Err(DeltaTableError::ArrowError { .. })));
+    assert_eq!(0, writer.buffered_rows());
+}
+
+#[tokio::test]
+async fn write_with_configured_partition_columns() {
+    let tmp_dir = tempdir::TempDir::new("delta_writer").unwrap();
+    let table_path = tmp_dir.path().to_str().unwrap();
+    let storage: Arc<dyn StorageBackend> =
+        deltalake::get_backend_for_uri(table_path).unwrap().into();
+    let schema = string_batch(vec![("value", vec![]), ("region", vec![])]).schema();
+
+    let result = DeltaWriter::new(
+        storage.clone(),
+        table_path,
+        schema.clone(),
+        vec!["country".to_string()],
+    );
+    assert!(matches!(
+        result,
+        Err(DeltaTableError::ColumnNotFound { column }) if column == "country"
+    ));
+
+    let mut writer =
+        DeltaWriter::new(storage, table_path, schema, vec!["region".to_string()]).unwrap();
+    writer
+        .write(&string_batch(vec![
+            ("value", vec![Some("a"), Some("b")]),
+            ("region", vec![Some("eu/west"), Some("us")]),
+        ]))
+        .unwrap();
+    let mut adds = writer.flush().await.unwrap();
+    adds.sort_by(|a, b| a.path.cmp(&b.path));
+    assert!(
+        adds[0].path.starts_with("region=eu%2Fwest/"),
+        "{}",
+        adds[0].path
+    );
+    assert_eq!(
+        Some("eu/west".to_string()),
+        adds[0].partitionValues["region"]
+    );
+    assert!(tmp_dir.path().join(&adds[1].path).exists());
+}
+
+#[tokio::test]
+async fn overwrite_partitions() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/delta-0.8.0-partitioned");
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let filters = [PartitionFilter {
+        key: "year",
+        value: PartitionValue::Equal("2021"),
+    }];
+
+    let mut writer = DeltaWriter::for_table(&table).unwrap();
+    writer
+        .write(&string_batch(vec![
+            ("value", vec![Some("a"), Some("b")]),
+            ("year", vec![Some("2021"), Some("2020")]),
+            ("month", vec![Some("1"), Some("1")]),
+            ("day", vec![Some("1"), Some("1")]),
+        ]))
+        .unwrap();
+    let adds = writer.flush().await.unwrap();
+    let result = table
+        .create_transaction(None)
+        .overwrite_with(adds, &filters)
+        .await;
+    assert!(matches!(
+        result,
+        Err(DeltaTransactionError::DeltaTable {
+            source: DeltaTableError::OverwriteOutsidePredicate { .. }
+        })
+    ));
+    assert_eq!(0, table.version);
+
+    // replaceWhere: only the files of 2021 are replaced
+    let mut writer = DeltaWriter::for_table(&table).unwrap();
+    writer
+        .write(&string_batch(vec![
+            ("value", vec![Some("a")]),
+            ("year", vec![Some("2021")]),
+            ("month", vec![Some("1")]),
+            ("day", vec![Some("1")]),
+        ]))
+        .unwrap();
+    let adds = writer.flush().await.unwrap();
+    table
+        .create_transaction(None)
+        .overwrite_with(adds, &filters)
+        .await
+        .unwrap();
+    let mut files = table.get_files();
+    files.sort();
+    assert_eq!(4, files.len());
+    assert!(files[3].starts_with("year=2021/month=1/day=1/"));
+    let history = table.history(Some(1)).await.unwrap();
+    assert_eq!(
+        history[0].operationParameters.as_ref().unwrap()["mode"],
+        "Overwrite"
+    );
+    assert_eq!(
+        history[0].operationParameters.as_ref().unwrap()["predicate"],
+        "year = '2021'"
+    );
+
+    // without filters, all the files are replaced
+    let mut writer = DeltaWriter::for_table(&table).unwrap();
+    writer
+        .write(&string_batch(vec![
+            ("value", vec![Some("b")]),
+            ("year", vec![Some("2022")]),
+            ("month", vec![Some("1")]),
+            ("day", vec![Some("1")]),
+        ]))
+        .unwrap();
+    let adds = writer.flush().await.unwrap();
+    table
+        .create_transaction(None)
+        .overwrite_with(adds, &[])
+        .await
+        .unwrap();
+    assert_eq!(1, table.get_files().len());
+    assert_eq!(7, table.get_tombstones().len());
+}
+
+async fn write_partition(table: &deltalake::DeltaTable, year: &str) -> Vec<Add> {
+    let mut writer = DeltaWriter::for_table(table).unwrap();
+    writer
+        .write(&string_batch(vec![
+            ("value", vec![Some("a")]),
+            ("year", vec![Some(year)]),
+            ("month", vec![Some("1")]),
+            ("day", vec![Some("1")]),
+        ]))
+        .unwrap();
+    writer.flush().await.unwrap()
+}
+
+#[tokio::test]
+async fn overwrite_conflicts_with_concurrent_commits() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/delta-0.8.0-partitioned");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let mut table = deltalake::open_table(table_uri).await.unwrap();
+    let mut other = deltalake::open_table(table_uri).await.unwrap();
+    let filters = [PartitionFilter {
+        key: "year",
+        value: PartitionValue::Equal("2021"),
+    }];
+    let append = DeltaOperation::Write {
+        mode: SaveMode::Append,
+        partitionBy: None,
+        predicate: None,
+    };
+
+    // data added outside of the replaced partitions is kept
+    let actions: Vec<Action> = write_partition(&other, "2020")
+        .await
+        .into_iter()
+        .map(Action::add)
+        .collect();
+    other
+        .create_transaction(None)
+        .commit_with(&actions, Some(append.clone()))
+        .await
+        .unwrap();
+    let adds = write_partition(&table, "2021").await;
+    table
+        .create_transaction(None)
+        .overwrite_with(adds, &filters)
+        .await
+        .unwrap();
+    assert_eq!(2, table.version);
+
+    // the files replaced by `other` were removed by the overwrite of `table`
+    let adds = write_partition(&other, "2021").await;
+    let result = other
+        .create_transaction(None)
+        .overwrite_with(adds, &filters)
+        .await;
+    assert!(matches!(
+        result,
+        Err(DeltaTransactionError::ReadFilesChanged { version: 2, .. })
+    ));
+
+    // data added by `other` in the replaced partitions would be lost
+    let appended = write_partition(&other, "2021").await;
+    let actions: Vec<Action> = appended.iter().cloned().map(Action::add).collect();
+    other
+        .create_transaction(None)
+        .commit_with(&actions, Some(append))
+        .await
+        .unwrap();
+    let adds = write_partition(&table, "2021").await;
+    let result = table
+        .create_transaction(None)
+        .overwrite_with(adds, &filters)
+        .await;
+    match result {
+        Err(DeltaTransactionError::ReadFilesChanged { version, path }) => {
+            assert_eq!(3, version);
+            assert_eq!(appended[0].path, path);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
@@ -1,8 +1,8 @@
-#[cfg(feature = "s3")]
+#[cfg(feature = "integration-testing")]
 #[allow(dead_code)]
 mod s3_common;
 
-#[cfg(feature = "dynamodb")]
+#[cfg(all(feature = "integration-testing", feature = "dynamodb"))]
 mod dynamodb {
     use deltalake::storage::s3::dynamodb_lock::{
         attr, DynamoDbLockClient, Options, PARTITION_KEY_NAME,
@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::Path;
 
+use serde_json::{json, Value};
+
 pub fn cleanup_dir_except<P: AsRef<Path>>(path: P, ignore_files: Vec<String>) {
     for p in fs::read_dir(path).unwrap() {
         if let Ok(d) = p {
@@ -13,3 +15,83 @@ pub fn cleanup_dir_except<P: AsRef<Path>>(path: P, ignore_files: Vec<String>) {
         }
     }
 }
+
+/// Copies the data files and the log of the table at `path` into a new temporary directory, which
+/// is deleted when the returned handle is dropped.
+pub fn copy_table_to_temp_dir<P: AsRef<Path>>(path: P) -> tempdir::TempDir {
+    let tmp_dir = tempdir::TempDir::new("delta-rs").unwrap();
+    let table_dir = tmp_dir.path();
+    for dir in &["", "_delta_log"] {
+        fs::create_dir_all(table_dir.join(dir)).unwrap();
+        for entry in fs::read_dir(path.as_ref().join(dir)).unwrap() {
+            let entry = entry.unwrap();
+            if entry.path().is_file() {
+                fs::copy(entry.path(), table_dir.join(dir).join(entry.file_name())).unwrap();
+            }
+        }
+    }
+    tmp_dir
+}
+
+/// Returns the `metaData` action of a test table whose schema has the given nullable fields, as
+/// pairs of a name and a Delta type, e.g. `("value", json!("string"))`.
+pub fn metadata_action(fields: &[(&str, Value)], partition_columns: &[&str]) -> Value {
+    let fields: Vec<Value> = fields
+        .iter()
+        .map(|(name, data_type)| {
+            json!({"name": name, "type": data_type, "nullable": true, "metadata": {}})
+        })
+        .collect();
+    let schema = json!({"type": "struct", "fields": fields});
+    json!({
+        "metaData": {
+            "id": "5fba94ed-9794-4965-ba6e-6ee3c0d22af9",
+            "format": {"provider": "parquet", "options": {}},
+            "schemaString": schema.to_string(),
+            "partitionColumns": partition_columns,
+            "configuration": {},
+            "createdTime": 1615555644000i64,
+        }
+    })
+}
+
+/// Returns the `add` action of a data file of a test table.
+pub fn add_action(path: &str, partition_values: Value, size: i64) -> Value {
+    json!({
+        "add": {
+            "path": path,
+            "partitionValues": partition_values,
+            "size": size,
+            "modificationTime": 1615555646000i64,
+            "dataChange": true,
+        }
+    })
+}
+
+/// Writes the actions as the commit of the given version to the log of the table at `table_dir`.
+pub fn write_commit(table_dir: &Path, version: i64, actions: &[Value]) {
+    let log_dir = table_dir.join("_delta_log");
+    fs::create_dir_all(&log_dir).unwrap();
+    let lines: Vec<String> = actions.iter().map(|action| action.to_string()).collect();
+    fs::write(
+        log_dir.join(format!("{:020}.json", version)),
+        lines.join("\n"),
+    )
+    .unwrap();
+}
+
+/// Writes the first commit of a test table at `table_dir`, with the metadata of
+/// [`metadata_action`] and the given actions, e.g. the adds of its files.
+pub fn write_table(
+    table_dir: &Path,
+    fields: &[(&str, Value)],
+    partition_columns: &[&str],
+    actions: &[Value],
+) {
+    let mut version_0 = vec![
+        json!({"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}}),
+        metadata_action(fields, partition_columns),
+    ];
+    version_0.extend_from_slice(actions);
+    write_commit(table_dir, 0, &version_0);
+}
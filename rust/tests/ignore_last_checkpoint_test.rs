@@ -0,0 +1,51 @@
+extern crate deltalake;
+extern crate tempdir;
+
+use std::fs;
+use std::path::Path;
+
+use deltalake::{DeltaTable, DeltaTableConfig};
+
+#[tokio::test]
+async fn load_table_ignoring_wrong_last_checkpoint() {
+    let tmp_dir = tempdir::TempDir::new("ignore_last_checkpoint").unwrap();
+    let log_dir = tmp_dir.path().join("_delta_log");
+    fs::create_dir(&log_dir).unwrap();
+    for entry in fs::read_dir("./tests/data/simple_table_with_checkpoint/_delta_log").unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), log_dir.join(entry.file_name())).unwrap();
+    }
+    // point the hint at a checkpoint that doesn't exist
+    fs::write(
+        Path::new(&log_dir).join("_last_checkpoint"),
+        r#"{"version":20,"size":13}"#,
+    )
+    .unwrap();
+    let table_path = tmp_dir.path().to_str().unwrap();
+
+    assert!(deltalake::open_table(table_path).await.is_err());
+
+    let backend = deltalake::get_backend_for_uri(table_path).unwrap();
+    let mut table = DeltaTable::new_with_config(
+        table_path,
+        backend,
+        DeltaTableConfig {
+            ignore_last_checkpoint: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    table.load().await.unwrap();
+    assert_eq!(table.version, 10);
+
+    assert!(table
+        .find_latest_check_point_for_version(9)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(table
+        .find_latest_check_point_for_version(10)
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,219 @@
+extern crate deltalake;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use deltalake::action::{Action, Protocol};
+use deltalake::{Clock, DeltaTableError, DeltaTableMetaData, DeltaTransactionError, Schema};
+
+#[derive(Debug)]
+struct FixedClock(SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn initialize_table_with_actions() {
+    let source = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let mut metadata = None;
+    let mut protocol = None;
+    for action in source.get_commit_actions(0).await.unwrap() {
+        match action {
+            Action::metaData(m) => metadata = Some(m),
+            Action::protocol(p) => protocol = Some(p),
+            _ => {}
+        }
+    }
+    let (metadata, protocol) = (metadata.unwrap(), protocol.unwrap());
+    let adds = source.get_actions().clone();
+
+    let tmp_dir = tempdir::TempDir::new("initialize_table").unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    table
+        .initialize_with_actions(metadata.clone(), protocol.clone(), adds.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(table.version, 0);
+    assert_eq!(table.get_files(), source.get_files());
+    assert_eq!(table.get_metadata().unwrap().id, metadata.id);
+    let history = table.history(None).await.unwrap();
+    assert_eq!(history[0].operation.as_deref(), Some("CONVERT"));
+
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    assert_eq!(table.version, 0);
+    assert_eq!(table.get_files().len(), 5);
+
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    let result = table
+        .initialize_with_actions(metadata, protocol, adds)
+        .await;
+    assert!(matches!(
+        result,
+        Err(DeltaTransactionError::VersionAlreadyExists { .. })
+    ));
+}
+
+#[tokio::test]
+async fn create_table() {
+    let schema: Schema = serde_json::from_str(
+        r#"{"type":"struct","fields":[
+            {"name":"id","type":"long","nullable":true,"metadata":{}},
+            {"name":"year","type":"string","nullable":true,"metadata":{}}]}"#,
+    )
+    .unwrap();
+    let mut configuration = HashMap::new();
+    configuration.insert("delta.appendOnly".to_string(), "true".to_string());
+    let metadata = DeltaTableMetaData::new(
+        Some("events".to_string()),
+        None,
+        None,
+        schema,
+        vec!["year".to_string()],
+        configuration,
+    );
+    let protocol = Protocol {
+        minReaderVersion: 1,
+        minWriterVersion: 2,
+    };
+
+    let tmp_dir = tempdir::TempDir::new("create_table").unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    let created_at = SystemTime::UNIX_EPOCH + Duration::from_millis(1615555644000);
+    table.set_clock(Arc::new(FixedClock(created_at)));
+    table
+        .create(metadata.clone(), protocol.clone(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(table.version, 0);
+    assert_eq!(table.get_metadata().unwrap().created_time, 1615555644000);
+    assert!(table.get_files().is_empty());
+    assert_eq!(table.get_min_writer_version(), 2);
+    let table_metadata = table.get_metadata().unwrap();
+    assert_eq!(table_metadata.id, metadata.id);
+    assert_eq!(table_metadata.partition_columns, vec!["year"]);
+    assert_eq!(table_metadata.format.get_provider(), "parquet");
+    let history = table.history(None).await.unwrap();
+    assert_eq!(history[0].operation.as_deref(), Some("CREATE TABLE"));
+    assert_eq!(
+        history[0].operationParameters.as_ref().unwrap()["partitionBy"],
+        r#"["year"]"#
+    );
+
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    let result = table.create(metadata, protocol, None).await;
+    assert!(matches!(
+        result,
+        Err(DeltaTransactionError::VersionAlreadyExists { .. })
+    ));
+}
+
+#[tokio::test]
+async fn create_table_with_commit_info() {
+    let schema: Schema = serde_json::from_str(
+        r#"{"type":"struct","fields":[{"name":"id","type":"long","nullable":true,"metadata":{}}]}"#,
+    )
+    .unwrap();
+    let metadata = DeltaTableMetaData::new(None, None, None, schema, vec![], HashMap::new());
+    let protocol = Protocol {
+        minReaderVersion: 1,
+        minWriterVersion: 2,
+    };
+    let mut commit_info = serde_json::Map::new();
+    commit_info.insert("operation".to_string(), "CREATE TABLE".into());
+    commit_info.insert("userName".to_string(), "etl".into());
+
+    let tmp_dir = tempdir::TempDir::new("create_table").unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    table
+        .create(metadata, protocol, Some(commit_info))
+        .await
+        .unwrap();
+
+    let actions = table.get_commit_actions(0).await.unwrap();
+    assert_eq!(actions.len(), 3);
+    assert!(
+        matches!(&actions[0], Action::commitInfo(info) if info.operation.as_deref() == Some("CREATE TABLE") && info.info["userName"] == "etl")
+    );
+    assert!(matches!(actions[1], Action::protocol(_)));
+    assert!(matches!(actions[2], Action::metaData(_)));
+}
+
+#[tokio::test]
+async fn create_and_alter_table_properties() {
+    let schema: Schema = serde_json::from_str(
+        r#"{"type":"struct","fields":[{"name":"id","type":"long","nullable":true,"metadata":{}}]}"#,
+    )
+    .unwrap();
+    let protocol = Protocol {
+        minReaderVersion: 1,
+        minWriterVersion: 1,
+    };
+    let tmp_dir = tempdir::TempDir::new("table_properties").unwrap();
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+
+    let mut configuration = HashMap::new();
+    configuration.insert(
+        "delta.logRetentionDurationn".to_string(),
+        "interval 60 days".to_string(),
+    );
+    let metadata = DeltaTableMetaData::new(None, None, None, schema.clone(), vec![], configuration);
+    let result = table.create(metadata, protocol.clone(), None).await;
+    match &result {
+        Err(DeltaTransactionError::DeltaTable { source }) => assert_eq!(
+            source.to_string(),
+            "Invalid table property delta.logRetentionDurationn: unknown property, did you mean delta.logRetentionDuration?"
+        ),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    let mut configuration = HashMap::new();
+    configuration.insert(
+        "delta.logRetentionDuration".to_string(),
+        "interval 60 days".to_string(),
+    );
+    configuration.insert("owner".to_string(), "etl".to_string());
+    let metadata = DeltaTableMetaData::new(None, None, None, schema, vec![], configuration);
+    table.create(metadata, protocol, None).await.unwrap();
+
+    let mut properties = HashMap::new();
+    properties.insert("delta.appendOnly".to_string(), "yes".to_string());
+    let result = table.set_properties(properties).await;
+    assert!(matches!(
+        result,
+        Err(DeltaTransactionError::DeltaTable {
+            source: DeltaTableError::InvalidTableProperty { key, .. }
+        }) if key == "delta.appendOnly"
+    ));
+    assert_eq!(table.version, 0);
+
+    let mut properties = HashMap::new();
+    properties.insert("delta.appendOnly".to_string(), "true".to_string());
+    assert_eq!(table.set_properties(properties).await.unwrap(), 1);
+    let configuration = &table.get_metadata().unwrap().configuration;
+    assert_eq!(configuration["delta.appendOnly"], "true");
+    assert_eq!(
+        configuration["delta.logRetentionDuration"],
+        "interval 60 days"
+    );
+    assert_eq!(table.get_min_writer_version(), 2);
+    let history = table.history(Some(1)).await.unwrap();
+    assert_eq!(history[0].operation.as_deref(), Some("SET TBLPROPERTIES"));
+}
@@ -0,0 +1,108 @@
+extern crate deltalake;
+
+use std::sync::{Arc, Mutex};
+
+use deltalake::action;
+use deltalake::lineage::{LineageEmitter, OpenLineageHook, RunEvent};
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[derive(Debug, Default)]
+struct RecordedEvents(Mutex<Vec<RunEvent>>);
+
+impl LineageEmitter for RecordedEvents {
+    fn emit(&self, event: &RunEvent) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+}
+
+fn add_action(path: &str) -> action::Action {
+    action::Action::add(action::Add {
+        path: path.to_string(),
+        size: 396,
+        modificationTime: 1564524294000,
+        dataChange: true,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn emit_run_events_on_commit() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let events = Arc::new(RecordedEvents::default());
+    let hook = OpenLineageHook::new("ingest", "simple_table_writer", events.clone());
+    let mut table = deltalake::DeltaTableBuilder::from_uri(table_dir.path().to_str().unwrap())
+        .with_post_commit_hook(Arc::new(hook))
+        .load()
+        .await
+        .unwrap();
+    assert!(events.0.lock().unwrap().is_empty());
+
+    let mut operation_metrics = serde_json::Map::new();
+    operation_metrics.insert("numOutputRows".to_string(), json!("10"));
+    operation_metrics.insert("numOutputBytes".to_string(), json!("396"));
+    table
+        .create_transaction(None)
+        .with_operation_metrics(operation_metrics)
+        .commit_with(
+            &[add_action("part-00000.parquet")],
+            Some(action::DeltaOperation::Convert),
+        )
+        .await
+        .unwrap();
+    table
+        .create_transaction(None)
+        .commit_version(6, &[add_action("part-00001.parquet")], None)
+        .await
+        .unwrap();
+    // failed commits aren't reported
+    assert!(table
+        .create_transaction(None)
+        .commit_version(6, &[add_action("part-00002.parquet")], None)
+        .await
+        .is_err());
+
+    let events = events.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    let event = serde_json::to_value(&events[0]).unwrap();
+    assert_eq!(event["eventType"], "COMPLETE");
+    assert_eq!(event["producer"], deltalake::lineage::PRODUCER);
+    assert_eq!(
+        event["job"],
+        json!({"namespace": "ingest", "name": "simple_table_writer"})
+    );
+    assert_eq!(event["inputs"], json!([]));
+    let commit = &event["run"]["facets"]["deltaCommit"];
+    assert_eq!(commit["version"], 5);
+    assert_eq!(commit["operation"], "CONVERT");
+    assert_eq!(commit["numAttempts"], 1);
+
+    let output = &event["outputs"][0];
+    assert_eq!(output["namespace"], "file");
+    assert_eq!(
+        output["name"],
+        table_dir.path().to_str().unwrap().trim_end_matches('/')
+    );
+    assert_eq!(
+        output["facets"]["schema"]["fields"],
+        json!([{"name": "id", "type": "long"}])
+    );
+    assert_eq!(output["facets"]["version"]["datasetVersion"], "5");
+    let statistics = &output["outputFacets"]["outputStatistics"];
+    assert_eq!(statistics["rowCount"], 10);
+    assert_eq!(statistics["size"], 396);
+
+    let event = serde_json::to_value(&events[1]).unwrap();
+    assert_eq!(event["run"]["facets"]["deltaCommit"]["version"], 6);
+    assert_eq!(
+        event["run"]["facets"]["deltaCommit"]["operation"],
+        json!(null)
+    );
+    assert!(event["outputs"][0].get("outputFacets").is_none());
+    assert_ne!(
+        event["run"]["runId"],
+        serde_json::to_value(&events[0]).unwrap()["run"]["runId"]
+    );
+}
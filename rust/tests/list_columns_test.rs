@@ -0,0 +1,150 @@
+extern crate deltalake;
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow::array::{Array, Int32Array, Int32Builder, ListArray, ListBuilder};
+use arrow::datatypes::{DataType, Field, Schema as ArrowSchema};
+use arrow::record_batch::RecordBatch;
+use deltalake::writer::{
+    can_normalize, normalize_record_batch, record_batches_to_parquet_bytes,
+    stats::stats_from_record_batch,
+};
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::serialized_reader::SliceableCursor;
+
+const TABLE_PATH: &str = "./tests/data/golden/data-reader-array-primitives";
+/// The int, long and string list columns, by the index of their leaf column.
+const COLUMNS: [usize; 3] = [0, 1, 7];
+
+fn leaf_paths<R: FileReader>(reader: &R) -> Vec<String> {
+    reader
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| column.path().string())
+        .collect()
+}
+
+#[tokio::test]
+async fn write_list_columns_like_spark() {
+    let table = deltalake::open_table(TABLE_PATH).await.unwrap();
+    let table_schema = ArrowSchema::try_from(table.get_schema().unwrap()).unwrap();
+    let table_schema = Arc::new(ArrowSchema::new(
+        COLUMNS
+            .iter()
+            .map(|i| table_schema.field(*i).clone())
+            .collect(),
+    ));
+
+    let spark_file = File::open(format!("{}/{}", TABLE_PATH, table.get_files()[0])).unwrap();
+    let spark_reader = Arc::new(SerializedFileReader::new(spark_file).unwrap());
+    let spark_paths = leaf_paths(spark_reader.as_ref());
+    let mut arrow_reader = ParquetFileArrowReader::new(spark_reader);
+    let spark_batches = arrow_reader
+        .get_record_reader_by_columns(COLUMNS.iter().copied(), 1024)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    // lists are written with the element names of the table schema, the way Spark writes them
+    let batches = spark_batches
+        .iter()
+        .map(|batch| {
+            for (field, table_field) in batch.schema().fields().iter().zip(table_schema.fields()) {
+                assert!(can_normalize(field.data_type(), table_field.data_type()));
+            }
+            normalize_record_batch(batch, table_schema.clone()).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let bytes = record_batches_to_parquet_bytes(table_schema.clone(), &batches).unwrap();
+    let reader = Arc::new(SerializedFileReader::new(SliceableCursor::new(bytes)).unwrap());
+    let paths = leaf_paths(reader.as_ref());
+    assert_eq!(
+        paths,
+        vec![
+            "as_array_int.list.element",
+            "as_array_long.list.element",
+            "as_array_string.list.element"
+        ]
+    );
+    for path in &paths {
+        assert!(spark_paths.contains(path));
+    }
+
+    let mut arrow_reader = ParquetFileArrowReader::new(reader);
+    let written_batches = arrow_reader
+        .get_record_reader(1024)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let num_rows = |batches: &[RecordBatch]| batches.iter().map(|b| b.num_rows()).sum::<usize>();
+    assert_eq!(num_rows(&written_batches), num_rows(&spark_batches));
+    for (written, spark) in written_batches.iter().zip(&spark_batches) {
+        for i in 0..COLUMNS.len() {
+            assert_eq!(written.column(i).as_ref(), spark.column(i).as_ref());
+        }
+    }
+
+    // list columns are left out of the stats
+    let stats = stats_from_record_batch(&batches[0]);
+    assert_eq!(stats.numRecords, batches[0].num_rows() as i64);
+    assert!(stats.minValues.is_empty());
+    assert!(stats.maxValues.is_empty());
+    assert!(stats.nullCount.is_empty());
+}
+
+#[test]
+fn normalize_list_element_names() {
+    let item_field = Field::new("item", DataType::Int32, true);
+    let element_field = Field::new("element", DataType::Int32, true);
+    let mut builder = ListBuilder::new(Int32Builder::new(2));
+    builder.values().append_value(1).unwrap();
+    builder.values().append_null().unwrap();
+    builder.append(true).unwrap();
+    builder.append(false).unwrap();
+    builder.append(true).unwrap();
+    let values = builder.finish();
+    assert_eq!(values.data_type(), &DataType::List(Box::new(item_field)));
+    let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+        "values",
+        DataType::List(Box::new(element_field)),
+        true,
+    )]));
+    assert!(can_normalize(
+        values.data_type(),
+        schema.field(0).data_type()
+    ));
+    // nullable elements can't be written to lists without nulls
+    assert!(!can_normalize(
+        values.data_type(),
+        &DataType::List(Box::new(Field::new("element", DataType::Int32, false)))
+    ));
+
+    let batch = RecordBatch::try_new(
+        Arc::new(ArrowSchema::new(vec![Field::new(
+            "values",
+            values.data_type().clone(),
+            true,
+        )])),
+        vec![Arc::new(values)],
+    )
+    .unwrap();
+    let normalized = normalize_record_batch(&batch, schema.clone()).unwrap();
+    let list = normalized
+        .column(0)
+        .as_any()
+        .downcast_ref::<ListArray>()
+        .unwrap();
+    assert_eq!(list.data_type(), schema.field(0).data_type());
+    assert!(list.is_null(1));
+    let first = list.value(0);
+    let first = first.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(first.value(0), 1);
+    assert!(first.is_null(1));
+    assert_eq!(list.value_length(2), 0);
+}
@@ -0,0 +1,51 @@
+extern crate deltalake;
+extern crate tempdir;
+
+use std::fs;
+use std::path::Path;
+
+fn copy_delta_log(table_path: &str, target: &Path) {
+    let log_dir = target.join("_delta_log");
+    fs::create_dir(&log_dir).unwrap();
+    for entry in fs::read_dir(Path::new(table_path).join("_delta_log")).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), log_dir.join(entry.file_name())).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn load_table_with_log_compaction() {
+    let tmp_dir = tempdir::TempDir::new("log_compaction").unwrap();
+    copy_delta_log("./tests/data/simple_table", tmp_dir.path());
+    let table_path = tmp_dir.path().to_str().unwrap();
+
+    let expected = deltalake::open_table(table_path).await.unwrap();
+    let compacted_log_path = expected.create_log_compaction(1, 3).await.unwrap();
+    assert!(
+        compacted_log_path.ends_with("00000000000000000001.00000000000000000003.compacted.json")
+    );
+
+    // loading must not read the commits covered by the compaction anymore
+    fs::write(
+        tmp_dir
+            .path()
+            .join("_delta_log")
+            .join("00000000000000000002.json"),
+        "not json",
+    )
+    .unwrap();
+
+    let table = deltalake::open_table(table_path).await.unwrap();
+    assert_eq!(table.version, 4);
+    assert_eq!(table.get_files(), expected.get_files());
+
+    let table = deltalake::open_table_with_version(table_path, 3)
+        .await
+        .unwrap();
+    assert_eq!(table.version, 3);
+
+    // the compaction ends past version 2, so the corrupted commit has to be read
+    assert!(deltalake::open_table_with_version(table_path, 2)
+        .await
+        .is_err());
+}
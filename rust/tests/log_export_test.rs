@@ -0,0 +1,138 @@
+extern crate deltalake;
+
+use std::fs;
+use std::path::Path;
+
+use deltalake::log_export::{export_log, open_table_from_log_dir};
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn write_table(table_dir: &Path) {
+    let add = |path: &str, customer: &str| {
+        let mut add = fs_common::add_action(path, json!({ "customer": customer }), 100);
+        add["add"]["stats"] = json!(
+            r#"{"numRecords":2,"minValues":{"value":1},"maxValues":{"value":7},"nullCount":{"value":0}}"#
+        );
+        add["add"]["tags"] = json!({"owner": "alice"});
+        add
+    };
+    let mut metadata = fs_common::metadata_action(
+        &[("value", json!("integer")), ("customer", json!("string"))],
+        &["customer"],
+    );
+    metadata["metaData"]["name"] = json!("payments");
+    fs_common::write_commit(
+        table_dir,
+        0,
+        &[
+            json!({"commitInfo": {"timestamp": 1615555646188i64, "operation": "WRITE", "userName": "alice", "operationParameters": {"mode": "Append"}}}),
+            json!({"protocol": {"minReaderVersion": 1, "minWriterVersion": 2}}),
+            metadata,
+            add("customer=acme/1.parquet", "acme"),
+            add("customer=acme/2.parquet", "acme"),
+            add("customer=initech/1.parquet", "initech"),
+        ],
+    );
+    fs_common::write_commit(
+        table_dir,
+        1,
+        &[json!({
+            "remove": {
+                "path": "customer=acme/1.parquet",
+                "deletionTimestamp": 1615555647000i64,
+                "dataChange": true,
+                "extendedFileMetadata": true,
+                "partitionValues": {"customer": "acme"},
+                "size": 100,
+            }
+        })],
+    );
+}
+
+#[tokio::test]
+async fn export_and_replay_log() {
+    let output_dir = tempdir::TempDir::new("export_log").unwrap();
+    let output_uri = output_dir.path().to_str().unwrap();
+
+    let summary = export_log(
+        "./tests/data/simple_table_with_checkpoint",
+        output_uri,
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.exported_files.len(), 13);
+    assert_eq!(summary.exported_files[12], "_last_checkpoint");
+    assert!(summary.skipped_files.is_empty());
+
+    let original = deltalake::open_table("./tests/data/simple_table_with_checkpoint")
+        .await
+        .unwrap();
+    let replayed = open_table_from_log_dir(output_dir.path().join("_delta_log").to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(replayed.version, 10);
+    assert_eq!(replayed.get_files(), original.get_files());
+
+    // the log files in the output directory are never overwritten
+    assert!(export_log(
+        "./tests/data/simple_table_with_checkpoint",
+        output_uri,
+        false
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn export_redacted_log() {
+    let table_dir = tempdir::TempDir::new("export_log_table").unwrap();
+    write_table(table_dir.path());
+    let output_dir = tempdir::TempDir::new("export_log").unwrap();
+    let output_uri = output_dir.path().to_str().unwrap();
+
+    let summary = export_log(table_dir.path().to_str().unwrap(), output_uri, true)
+        .await
+        .unwrap();
+    assert_eq!(summary.exported_files.len(), 2);
+
+    let exported = fs::read_to_string(
+        output_dir
+            .path()
+            .join("_delta_log/00000000000000000000.json"),
+    )
+    .unwrap();
+    for secret in &[
+        "alice",
+        "acme",
+        "initech",
+        "payments",
+        "Append",
+        r#"\"value\":7"#,
+    ] {
+        assert!(!exported.contains(secret), "{} wasn't redacted", secret);
+    }
+
+    let table = open_table_from_log_dir(output_uri).await.unwrap();
+    assert_eq!(table.version, 1);
+    assert_eq!(
+        table.get_files(),
+        vec!["redacted-00001.parquet", "redacted-00002.parquet"]
+    );
+    let actions = table.get_actions();
+    assert_eq!(
+        actions[0].partitionValues["customer"].as_deref(),
+        Some("value-0")
+    );
+    assert_eq!(
+        actions[1].partitionValues["customer"].as_deref(),
+        Some("value-1")
+    );
+    let stats = actions[0].get_stats().unwrap().unwrap();
+    assert_eq!(stats.numRecords, 2);
+    assert!(stats.minValues.is_empty());
+    assert_eq!(stats.nullCount.len(), 1);
+    assert_eq!(table.get_metadata().unwrap().name, None);
+}
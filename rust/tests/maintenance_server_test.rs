@@ -0,0 +1,139 @@
+#[cfg(feature = "maintenance-server")]
+mod maintenance_server {
+    use deltalake::maintenance::proto::maintenance_server::Maintenance;
+    use deltalake::maintenance::proto::*;
+    use deltalake::maintenance::{
+        MaintenanceServerConfig, MaintenanceService, MaintenanceTableConfig,
+    };
+    use tonic::{Code, Request};
+
+    fn service() -> MaintenanceService {
+        MaintenanceService::new(MaintenanceServerConfig {
+            tables: vec![
+                MaintenanceTableConfig {
+                    name: "simple".to_string(),
+                    location: "./tests/data/simple_table".to_string(),
+                },
+                MaintenanceTableConfig {
+                    name: "partitioned".to_string(),
+                    location: "./tests/data/delta-0.8.0-partitioned".to_string(),
+                },
+            ],
+            bearer_tokens: vec!["token".to_string()],
+        })
+    }
+
+    fn request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer token".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_authorization() {
+        let service = service();
+        let verify = VerifyRequest {
+            table: "simple".to_string(),
+            sample_size: 0,
+        };
+
+        let status = service
+            .verify(Request::new(verify.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let mut wrong_token = Request::new(verify.clone());
+        wrong_token
+            .metadata_mut()
+            .insert("authorization", "Bearer other".parse().unwrap());
+        let status = service.verify(wrong_token).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+
+        let response = service.verify(request(verify)).await.unwrap().into_inner();
+        assert_eq!(response.version, 4);
+        assert!(response.mismatches.is_empty());
+
+        let status = service
+            .verify(request(VerifyRequest {
+                table: "unknown".to_string(),
+                sample_size: 0,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_operations() {
+        let service = service();
+
+        let response = service
+            .vacuum(request(VacuumRequest {
+                table: "simple".to_string(),
+                retention_hours: 0,
+                dry_run: true,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.version, 4);
+        let status = service
+            .vacuum(request(VacuumRequest {
+                table: "simple".to_string(),
+                retention_hours: 1,
+                dry_run: true,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let response = service
+            .optimize(request(OptimizeRequest {
+                table: "partitioned".to_string(),
+                target_size: 128 * 1024 * 1024,
+                partition_filters: vec![PartitionFilter {
+                    key: "year".to_string(),
+                    op: "=".to_string(),
+                    value: "2021".to_string(),
+                }],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.read_version, 0);
+        for group in &response.groups {
+            assert_eq!(group.partition_values["year"], "2021");
+            assert!(group.files.len() > 1);
+        }
+        let status = service
+            .optimize(request(OptimizeRequest {
+                table: "partitioned".to_string(),
+                target_size: 0,
+                partition_filters: vec![],
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let response = service
+            .cleanup_metadata(request(CleanupMetadataRequest {
+                table: "simple".to_string(),
+                max_age_seconds: 0,
+                dry_run: true,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.deleted_files.is_empty());
+
+        let status = service
+            .checkpoint(request(CheckpointRequest {
+                table: "simple".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+}
@@ -0,0 +1,120 @@
+extern crate deltalake;
+
+use deltalake::action;
+use deltalake::optimize::CompactionPlan;
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn write_table(table_dir: &std::path::Path) {
+    let add = |path: &str, part: Option<&str>, size: i64| {
+        fs_common::add_action(path, json!({ "part": part }), size)
+    };
+    fs_common::write_table(
+        table_dir,
+        &[("value", json!("string")), ("part", json!("string"))],
+        &["part"],
+        &[
+            add("part=a/1.parquet", Some("a"), 60),
+            add("part=a/2.parquet", Some("a"), 50),
+            add("part=a/3.parquet", Some("a"), 40),
+            add("part=a/4.parquet", Some("a"), 30),
+            add("part=a/5.parquet", Some("a"), 120),
+            add("part=b/1.parquet", Some("b"), 10),
+            add("part=__HIVE_DEFAULT_PARTITION__/1.parquet", None, 10),
+            add("part=__HIVE_DEFAULT_PARTITION__/2.parquet", None, 10),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn plan_compaction() {
+    let tmp_dir = tempdir::TempDir::new("optimize").unwrap();
+    write_table(tmp_dir.path());
+    let table_uri = tmp_dir.path().to_str().unwrap();
+    let mut table = deltalake::open_table(table_uri).await.unwrap();
+
+    let mut sizes = table.get_files_with_sizes();
+    sizes.sort();
+    assert_eq!(sizes[0], ("part=__HIVE_DEFAULT_PARTITION__/1.parquet", 10));
+    assert_eq!(sizes.len(), 8);
+
+    let plan = CompactionPlan::from_state(&table.snapshot(), 100, &[]).unwrap();
+    assert_eq!(plan.read_version, 0);
+    let groups = plan
+        .groups
+        .iter()
+        .map(|group| {
+            let files = group
+                .files
+                .iter()
+                .map(|add| add.path.as_str())
+                .collect::<Vec<_>>();
+            (
+                group.partition_values["part"].as_deref(),
+                files,
+                group.total_size,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        vec![
+            (
+                None,
+                vec![
+                    "part=__HIVE_DEFAULT_PARTITION__/1.parquet",
+                    "part=__HIVE_DEFAULT_PARTITION__/2.parquet"
+                ],
+                20
+            ),
+            (Some("a"), vec!["part=a/1.parquet", "part=a/3.parquet"], 100),
+            (Some("a"), vec!["part=a/2.parquet", "part=a/4.parquet"], 80),
+        ]
+    );
+    assert_eq!(plan.num_files(), 6);
+
+    let filters = vec![deltalake::PartitionFilter {
+        key: "part",
+        value: deltalake::PartitionValue::Equal("a"),
+    }];
+    let plan = CompactionPlan::from_state(&table.snapshot(), 100, &filters).unwrap();
+    assert_eq!(plan.groups.len(), 2);
+
+    // the plan survives a round trip through a scheduler
+    let plan: CompactionPlan =
+        serde_json::from_str(&serde_json::to_string(&plan).unwrap()).unwrap();
+
+    // committing the first group invalidates it, but not the second one
+    let mut actions = plan.groups[0].remove_actions(1615555647000);
+    actions.push(action::Action::add(action::Add {
+        path: "part=a/6.parquet".to_string(),
+        size: 100,
+        partitionValues: plan.groups[0].partition_values.clone(),
+        modificationTime: 1615555647000,
+        dataChange: false,
+        ..Default::default()
+    }));
+    let mut tx = table.create_transaction(None);
+    tx.commit_with(
+        &actions,
+        Some(action::DeltaOperation::Optimize {
+            predicate: None,
+            targetSize: plan.target_size,
+        }),
+    )
+    .await
+    .unwrap();
+    table.update().await.unwrap();
+
+    let snapshot = table.snapshot();
+    assert!(!plan.groups[0].is_applicable(&snapshot));
+    assert!(plan.groups[1].is_applicable(&snapshot));
+    assert_eq!(table.get_tombstones().len(), 2);
+
+    assert!(matches!(
+        CompactionPlan::from_state(&snapshot, 0, &[]),
+        Err(deltalake::DeltaTableError::InvalidTargetSize(0))
+    ));
+}
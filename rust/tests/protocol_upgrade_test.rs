@@ -0,0 +1,104 @@
+extern crate deltalake;
+
+use std::path::Path;
+
+use deltalake::action::Action;
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn write_table(table_dir: &Path) {
+    fs_common::write_commit(
+        table_dir,
+        0,
+        &[
+            json!({"protocol": {"minReaderVersion": 1, "minWriterVersion": 1}}),
+            fs_common::metadata_action(&[("value", json!("integer"))], &[]),
+        ],
+    );
+}
+
+fn assert_invalid_upgrade(result: Result<Option<i64>, deltalake::DeltaTransactionError>) {
+    assert!(matches!(
+        result.unwrap_err(),
+        deltalake::DeltaTransactionError::DeltaTable {
+            source: deltalake::DeltaTableError::InvalidProtocolUpgrade { .. }
+        },
+    ));
+}
+
+#[tokio::test]
+async fn upgrade_protocol() {
+    let table_dir = tempdir::TempDir::new("protocol_upgrade").unwrap();
+    write_table(table_dir.path());
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(table.get_min_writer_version(), 1);
+
+    assert_eq!(table.upgrade_protocol(1, 2).await.unwrap(), Some(1));
+    assert_eq!(table.get_min_reader_version(), 1);
+    assert_eq!(table.get_min_writer_version(), 2);
+    let history = table.history(Some(1)).await.unwrap();
+    assert_eq!(history[0].operation.as_deref(), Some("UPGRADE PROTOCOL"));
+    assert_eq!(
+        history[0].operationParameters.as_ref().unwrap()["newProtocol"],
+        r#"{"minReaderVersion":1,"minWriterVersion":2}"#
+    );
+
+    // already upgraded
+    assert_eq!(table.upgrade_protocol(1, 2).await.unwrap(), None);
+    // downgrades and versions this crate doesn't implement
+    assert_invalid_upgrade(table.upgrade_protocol(1, 1).await);
+    assert_invalid_upgrade(table.upgrade_protocol(2, 2).await);
+    assert_invalid_upgrade(table.upgrade_protocol(1, 3).await);
+    assert_eq!(table.version, 1);
+}
+
+#[tokio::test]
+async fn upgrade_protocol_when_enabling_features() {
+    let table_dir = tempdir::TempDir::new("protocol_upgrade").unwrap();
+    write_table(table_dir.path());
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let mut metadata = match &table.get_commit_actions(0).await.unwrap()[1] {
+        Action::metaData(metadata) => metadata.clone(),
+        _ => panic!("the second action should be the metadata"),
+    };
+
+    metadata
+        .configuration
+        .insert("delta.appendOnly".to_string(), "true".to_string());
+    table
+        .create_transaction(None)
+        .commit_with(&[Action::metaData(metadata.clone())], None)
+        .await
+        .unwrap();
+    assert_eq!(table.get_min_writer_version(), 2);
+    let actions = table.get_commit_actions(1).await.unwrap();
+    assert!(actions.iter().any(|action| matches!(
+        action,
+        Action::protocol(protocol) if protocol.minWriterVersion == 2
+    )));
+
+    // change data feed needs a writer version this crate doesn't implement
+    metadata
+        .configuration
+        .insert("delta.enableChangeDataFeed".to_string(), "true".to_string());
+    let result = table
+        .create_transaction(None)
+        .commit_with(&[Action::metaData(metadata)], None)
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        deltalake::DeltaTransactionError::DeltaTable {
+            source: deltalake::DeltaTableError::InvalidProtocolUpgrade {
+                min_writer_version: 4,
+                ..
+            }
+        },
+    ));
+    assert_eq!(table.version, 1);
+}
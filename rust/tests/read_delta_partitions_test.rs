@@ -10,7 +10,7 @@ fn test_create_delta_table_partition() {
         deltalake::DeltaTablePartition::try_from(path.as_ref()).unwrap(),
         deltalake::DeltaTablePartition {
             key: "year",
-            value: year
+            value: Some(year)
         }
     );
 
@@ -27,15 +27,15 @@ fn test_create_delta_table_partition() {
 fn test_match_partition() {
     let partition_2021 = deltalake::DeltaTablePartition {
         key: "year",
-        value: "2021",
+        value: Some("2021"),
     };
     let partition_2020 = deltalake::DeltaTablePartition {
         key: "year",
-        value: "2020",
+        value: Some("2020"),
     };
     let partition_2019 = deltalake::DeltaTablePartition {
         key: "year",
-        value: "2019",
+        value: Some("2019"),
     };
 
     let partition_year_2020_filter = deltalake::PartitionFilter {
@@ -70,11 +70,11 @@ fn test_match_filters() {
     let partitions = vec![
         deltalake::DeltaTablePartition {
             key: "year",
-            value: "2021",
+            value: Some("2021"),
         },
         deltalake::DeltaTablePartition {
             key: "month",
-            value: "12",
+            value: Some("12"),
         },
     ];
 
@@ -97,3 +97,131 @@ fn test_match_filters() {
     assert_eq!(valid_filter_month.match_partitions(&partitions), true);
     assert_eq!(invalid_filter.match_partitions(&partitions), false);
 }
+
+#[test]
+fn test_escape_partition_path_name() {
+    let value = "2021/01/01 12:00:00=ü%";
+    let escaped = deltalake::escape_partition_path_name(value);
+    assert_eq!(escaped, "2021%2F01%2F01 12%3A00%3A00%3Dü%25");
+    assert_eq!(deltalake::unescape_partition_path_name(&escaped), value);
+
+    assert_eq!(
+        deltalake::escape_partition_path_name("a\"#'*?\\{[]^\u{1}\u{7F}b"),
+        "a%22%23%27%2A%3F%5C%7B%5B%5D%5E%01%7Fb"
+    );
+    assert_eq!(deltalake::unescape_partition_path_name("100%"), "100%");
+    assert_eq!(deltalake::unescape_partition_path_name("%zz%2"), "%zz%2");
+}
+
+#[test]
+fn test_null_partitions() {
+    assert_eq!(
+        deltalake::DeltaTablePartition::try_from("year=__HIVE_DEFAULT_PARTITION__").unwrap(),
+        deltalake::DeltaTablePartition {
+            key: "year",
+            value: None
+        }
+    );
+
+    let null_partition = deltalake::DeltaTablePartition {
+        key: "year",
+        value: None,
+    };
+    let filter = |value| deltalake::PartitionFilter { key: "year", value };
+    assert!(filter(deltalake::PartitionValue::IsNull).match_partition(&null_partition));
+    assert!(!filter(deltalake::PartitionValue::IsNotNull).match_partition(&null_partition));
+    assert!(!filter(deltalake::PartitionValue::Equal("2020")).match_partition(&null_partition));
+    assert!(!filter(deltalake::PartitionValue::NotEqual("2020")).match_partition(&null_partition));
+    assert!(
+        !filter(deltalake::PartitionValue::NotIn(vec!["2020"])).match_partition(&null_partition)
+    );
+
+    let mut partition_values = std::collections::HashMap::new();
+    partition_values.insert("year".to_string(), Some("2021".to_string()));
+    partition_values.insert("month".to_string(), None);
+    partition_values.insert("day".to_string(), Some("".to_string()));
+    let columns = vec!["year".to_string(), "month".to_string(), "day".to_string()];
+    assert_eq!(
+        deltalake::partition_path(&columns, &partition_values),
+        "year=2021/month=__HIVE_DEFAULT_PARTITION__/day=__HIVE_DEFAULT_PARTITION__"
+    );
+}
+
+#[test]
+fn test_timestamp_partition_values() {
+    use chrono::NaiveDate;
+
+    let midnight = NaiveDate::from_ymd(2021, 1, 1).and_hms(0, 0, 0);
+    let afternoon = NaiveDate::from_ymd(2021, 1, 1).and_hms_micro(12, 30, 0, 250_000);
+    assert_eq!(
+        deltalake::format_timestamp_partition_value(midnight),
+        "2021-01-01 00:00:00"
+    );
+    assert_eq!(
+        deltalake::format_timestamp_partition_value(afternoon),
+        "2021-01-01 12:30:00.25"
+    );
+    assert_eq!(
+        deltalake::parse_timestamp_partition_value("2021-01-01 00:00:00"),
+        Some(midnight)
+    );
+    assert_eq!(
+        deltalake::parse_timestamp_partition_value("2021-01-01 12:30:00.250000"),
+        Some(afternoon)
+    );
+    assert_eq!(
+        deltalake::parse_timestamp_partition_value("2021-01-01T00:00:00Z"),
+        None
+    );
+    assert_eq!(
+        deltalake::format_date_partition_value(NaiveDate::from_ymd(2021, 1, 1)),
+        "2021-01-01"
+    );
+    assert_eq!(
+        deltalake::parse_date_partition_value("2021-01-01"),
+        Some(NaiveDate::from_ymd(2021, 1, 1))
+    );
+
+    // the same directory names as Spark
+    let columns = vec!["ts".to_string()];
+    let mut partition_values = std::collections::HashMap::new();
+    partition_values.insert(
+        "ts".to_string(),
+        Some(deltalake::format_timestamp_partition_value(midnight)),
+    );
+    let path = deltalake::partition_path(&columns, &partition_values);
+    assert_eq!(path, "ts=2021-01-01 00%3A00%3A00");
+    assert_eq!(
+        deltalake::partition_values_from_path(&format!("{}/part-00000.parquet", path), &columns),
+        Some(partition_values)
+    );
+}
+
+#[test]
+fn test_partition_values_of_arrays() {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, BinaryArray, Date32Array, Int32Array, TimestampMicrosecondArray};
+    use deltalake::writer::partition_value;
+
+    let timestamps: ArrayRef = Arc::new(TimestampMicrosecondArray::from_opt_vec(
+        vec![Some(1_609_504_200_250_000), None],
+        None,
+    ));
+    assert_eq!(
+        partition_value(&timestamps, 0).unwrap().as_deref(),
+        Some("2021-01-01 12:30:00.25")
+    );
+    assert_eq!(partition_value(&timestamps, 1).unwrap(), None);
+
+    let dates: ArrayRef = Arc::new(Date32Array::from(vec![18628]));
+    assert_eq!(
+        partition_value(&dates, 0).unwrap().as_deref(),
+        Some("2021-01-01")
+    );
+    let ints: ArrayRef = Arc::new(Int32Array::from(vec![-3]));
+    assert_eq!(partition_value(&ints, 0).unwrap().as_deref(), Some("-3"));
+
+    let binaries: ArrayRef = Arc::new(BinaryArray::from(vec!["a".as_bytes()]));
+    assert!(partition_value(&binaries, 0).is_err());
+}
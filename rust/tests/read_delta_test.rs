@@ -4,7 +4,11 @@ use deltalake::storage::file::FileStorageBackend;
 use deltalake::StorageBackend;
 use pretty_assertions::assert_eq;
 use std::collections::HashMap;
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[allow(dead_code)]
+mod fs_common;
 
 #[tokio::test]
 async fn read_delta_2_0_table_without_version() {
@@ -24,6 +28,15 @@ async fn read_delta_2_0_table_without_version() {
     );
     let tombstones = table.get_tombstones();
     assert_eq!(tombstones.len(), 4);
+    assert_eq!(
+        tombstones[0],
+        deltalake::Tombstone {
+            path: "part-00000-512e1537-8aaa-4193-b8b4-bef3de0de409-c000.snappy.parquet".to_string(),
+            deletion_timestamp: 1564524298213,
+        }
+    );
+    let tombstones = table.load_extended_tombstones().await.unwrap();
+    assert_eq!(tombstones.len(), 4);
     assert_eq!(
         tombstones[0],
         deltalake::action::Remove {
@@ -48,6 +61,46 @@ async fn read_delta_table_with_update() {
     );
 }
 
+#[tokio::test]
+async fn update_checkpointed_table_without_new_commits() {
+    let path = "./tests/data/simple_table_with_checkpoint/";
+    let mut table = deltalake::open_table(path).await.unwrap();
+    assert_eq!(table.version, 10);
+    let files = table.get_actions().clone();
+    let num_commit_infos = table.get_commit_infos().len();
+
+    // the latest version isn't applied a second time
+    table.update().await.unwrap();
+    assert_eq!(table.version, 10);
+    assert_eq!(table.get_actions().len(), files.len());
+    assert_eq!(table.get_commit_infos().len(), num_commit_infos);
+}
+
+#[tokio::test]
+async fn snapshot_is_unaffected_by_update() {
+    let path = "./tests/data/simple_table";
+    let mut table = deltalake::open_table_with_version(path, 0).await.unwrap();
+    let snapshot = table.snapshot();
+    let files_at_version_0 = table
+        .get_files_iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<String>>();
+
+    table.update().await.unwrap();
+    assert_eq!(table.version, 4);
+
+    let handle = std::thread::spawn(move || {
+        assert_eq!(snapshot.version(), 0);
+        snapshot
+            .get_files_iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>()
+    });
+    assert_eq!(handle.join().unwrap(), files_at_version_0);
+    assert_ne!(table.get_files(), files_at_version_0);
+    assert_eq!(table.snapshot().get_files(), table.get_files());
+}
+
 #[tokio::test]
 async fn read_delta_2_0_table_with_version() {
     let mut table = deltalake::open_table_with_version("./tests/data/delta-0.2.0", 0)
@@ -111,6 +164,14 @@ async fn read_delta_8_0_table_without_version() {
     );
     let tombstones = table.get_tombstones();
     assert_eq!(tombstones.len(), 1);
+    assert_eq!(
+        tombstones[0],
+        deltalake::Tombstone {
+            path: "part-00001-911a94a2-43f6-4acb-8620-5e68c2654989-c000.snappy.parquet".to_string(),
+            deletion_timestamp: 1615043776198,
+        }
+    );
+    let tombstones = table.load_extended_tombstones().await.unwrap();
     assert_eq!(
         tombstones[0],
         deltalake::action::Remove {
@@ -208,6 +269,79 @@ async fn read_delta_8_0_table_with_partitions() {
     );
 }
 
+#[tokio::test]
+async fn read_delta_8_0_table_with_partition_index() {
+    let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned")
+        .await
+        .unwrap();
+    let indexed_table =
+        deltalake::DeltaTableBuilder::from_uri("./tests/data/delta-0.8.0-partitioned")
+            .with_config(deltalake::DeltaTableConfig {
+                partition_index: true,
+                ..Default::default()
+            })
+            .load()
+            .await
+            .unwrap();
+
+    let filter_sets = vec![
+        // a lookup of a single partition
+        vec![
+            deltalake::PartitionFilter {
+                key: "day",
+                value: deltalake::PartitionValue::Equal("5"),
+            },
+            deltalake::PartitionFilter {
+                key: "month",
+                value: deltalake::PartitionValue::Equal("2"),
+            },
+            deltalake::PartitionFilter {
+                key: "year",
+                value: deltalake::PartitionValue::Equal("2020"),
+            },
+        ],
+        vec![
+            deltalake::PartitionFilter {
+                key: "day",
+                value: deltalake::PartitionValue::Equal("5"),
+            },
+            deltalake::PartitionFilter {
+                key: "month",
+                value: deltalake::PartitionValue::Equal("2"),
+            },
+            deltalake::PartitionFilter {
+                key: "year",
+                value: deltalake::PartitionValue::Equal("2021"),
+            },
+        ],
+        vec![deltalake::PartitionFilter {
+            key: "month",
+            value: deltalake::PartitionValue::In(vec!["2", "12"]),
+        }],
+        vec![deltalake::PartitionFilter {
+            key: "day",
+            value: deltalake::PartitionValue::IsNull,
+        }],
+        vec![],
+    ];
+    for filters in filter_sets {
+        assert_eq!(
+            indexed_table.get_files_by_partitions(&filters).unwrap(),
+            table.get_files_by_partitions(&filters).unwrap(),
+        );
+    }
+    assert_eq!(
+        indexed_table
+            .get_files_by_partitions(&[deltalake::PartitionFilter {
+                key: "day",
+                value: deltalake::PartitionValue::Equal("5"),
+            }])
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
 #[tokio::test]
 async fn vacuum_delta_8_0_table() {
     let mut table = deltalake::open_table("./tests/data/delta-0.8.0")
@@ -242,3 +376,730 @@ async fn vacuum_delta_8_0_table() {
 
     assert_eq!(table.vacuum(retention_hours, dry_run).await.unwrap(), empty);
 }
+
+#[tokio::test]
+async fn vacuum_delta_8_0_table_with_inventory() {
+    let mut table = deltalake::open_table("./tests/data/delta-0.8.0")
+        .await
+        .unwrap();
+    let backend = FileStorageBackend::new("./tests/data/delta-0.8.0");
+    let removed_file = backend.join_path(
+        "./tests/data/delta-0.8.0",
+        "part-00001-911a94a2-43f6-4acb-8620-5e68c2654989-c000.snappy.parquet",
+    );
+    let inventory = |paths: Vec<String>| {
+        futures::stream::iter(
+            paths
+                .into_iter()
+                .map(|path| deltalake::storage::ObjectMeta {
+                    path,
+                    modified: chrono::Utc::now(),
+                    size: None,
+                }),
+        )
+    };
+
+    let mut paths = table.get_file_paths();
+    paths.push(removed_file.clone());
+    assert_eq!(
+        table
+            .vacuum_with_inventory(inventory(paths), 169, true)
+            .await
+            .unwrap(),
+        vec![removed_file]
+    );
+
+    // the removed file can't be deleted when it's missing from the inventory
+    let empty: Vec<String> = Vec::new();
+    assert_eq!(
+        table
+            .vacuum_with_inventory(inventory(table.get_file_paths()), 169, true)
+            .await
+            .unwrap(),
+        empty
+    );
+}
+
+#[tokio::test]
+async fn vacuum_change_data_files() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_path = table_dir.path().to_str().unwrap();
+    std::fs::write(
+        table_dir
+            .path()
+            .join("_delta_log/00000000000000000005.json"),
+        r#"{"cdc":{"path":"changes/cdc-00000.snappy.parquet","partitionValues":{},"size":10,"dataChange":false}}"#,
+    )
+    .unwrap();
+    let mut table = deltalake::open_table(table_path).await.unwrap();
+    let backend = FileStorageBackend::new(table_path);
+    let old_change_data = backend.join_path(table_path, "_change_data/cdc-00000.snappy.parquet");
+    let new_change_data = backend.join_path(table_path, "_change_data/cdc-00001.snappy.parquet");
+    let cdc_file = backend.join_path(table_path, "changes/cdc-00000.snappy.parquet");
+    let other_file = backend.join_path(table_path, "changes/cdc-00001.snappy.parquet");
+    let old = chrono::Utc::now() - chrono::Duration::hours(170);
+    let inventory = futures::stream::iter(
+        vec![
+            (old_change_data.clone(), old),
+            (new_change_data, chrono::Utc::now()),
+            (cdc_file.clone(), old),
+            (other_file, old),
+        ]
+        .into_iter()
+        .map(|(path, modified)| deltalake::storage::ObjectMeta {
+            path,
+            modified,
+            size: None,
+        }),
+    );
+
+    // change data files are deleted once older than the retention period, like tombstones
+    assert_eq!(
+        table
+            .vacuum_with_inventory(inventory, 169, true)
+            .await
+            .unwrap(),
+        vec![old_change_data, cdc_file]
+    );
+}
+
+#[derive(Debug)]
+struct FixedClock(SystemTime);
+
+impl deltalake::Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn vacuum_delta_8_0_table_with_clock() {
+    let deletion_time = SystemTime::UNIX_EPOCH + Duration::from_millis(1615043776198);
+    let retention = Duration::from_secs(169 * 3600);
+    let backend = FileStorageBackend::new("./tests/data/delta-0.8.0");
+    let removed_file = backend.join_path(
+        "./tests/data/delta-0.8.0",
+        "part-00001-911a94a2-43f6-4acb-8620-5e68c2654989-c000.snappy.parquet",
+    );
+
+    let mut table = deltalake::DeltaTableBuilder::from_uri("./tests/data/delta-0.8.0")
+        .with_clock(Arc::new(FixedClock(deletion_time + retention)))
+        .load()
+        .await
+        .unwrap();
+    let empty: Vec<String> = Vec::new();
+    assert_eq!(table.vacuum(169, true).await.unwrap(), empty);
+
+    table.set_clock(Arc::new(FixedClock(
+        deletion_time + retention + Duration::from_millis(1),
+    )));
+    assert_eq!(table.vacuum(169, true).await.unwrap(), vec![removed_file]);
+}
+
+#[tokio::test]
+async fn vacuum_keeps_files_added_back_concurrently() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/delta-0.8.0");
+    let table_path = table_dir.path().to_str().unwrap();
+    let mut table = deltalake::open_table(table_path).await.unwrap();
+    let removed_file = "part-00001-911a94a2-43f6-4acb-8620-5e68c2654989-c000.snappy.parquet";
+
+    // a restore committed by another writer after the table was loaded
+    let mut writer = deltalake::open_table(table_path).await.unwrap();
+    let add = deltalake::action::Action::add(deltalake::action::Add {
+        path: removed_file.to_string(),
+        size: 400,
+        modificationTime: 1615043776198,
+        dataChange: true,
+        ..Default::default()
+    });
+    writer
+        .create_transaction(None)
+        .commit_with(&[add], None)
+        .await
+        .unwrap();
+
+    let backend = FileStorageBackend::new(table_path);
+    let removed_file_path = backend.join_path(table_path, removed_file);
+    assert_eq!(
+        table.vacuum(169, true).await.unwrap(),
+        vec![removed_file_path.clone()]
+    );
+    let empty: Vec<String> = Vec::new();
+    assert_eq!(table.vacuum(169, false).await.unwrap(), empty);
+    assert!(table_dir.path().join(removed_file).exists());
+}
+
+/// Writes a checkpoint of version 1 made of the add actions of `num_files` files.
+fn write_checkpoint_of_adds(log_dir: &std::path::Path, num_files: usize) {
+    use arrow::array::{ArrayRef, BooleanArray, Int64Array, StringArray, StructArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+
+    let paths = (0..num_files)
+        .map(|i| format!("part-{:05}.parquet", i))
+        .collect::<Vec<_>>();
+    let add = StructArray::from(vec![
+        (
+            Field::new("path", DataType::Utf8, true),
+            Arc::new(StringArray::from(
+                paths.iter().map(String::as_str).collect::<Vec<_>>(),
+            )) as ArrayRef,
+        ),
+        (
+            Field::new("size", DataType::Int64, true),
+            Arc::new(Int64Array::from(vec![100; num_files])) as ArrayRef,
+        ),
+        (
+            Field::new("modificationTime", DataType::Int64, true),
+            Arc::new(Int64Array::from(vec![1615555646000; num_files])) as ArrayRef,
+        ),
+        (
+            Field::new("dataChange", DataType::Boolean, true),
+            Arc::new(BooleanArray::from(vec![false; num_files])) as ArrayRef,
+        ),
+    ]);
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "add",
+        add.data_type().clone(),
+        true,
+    )]));
+    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(add)]).unwrap();
+    let file =
+        std::fs::File::create(log_dir.join("00000000000000000001.checkpoint.parquet")).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    std::fs::write(
+        log_dir.join("_last_checkpoint"),
+        format!(r#"{{"version":1,"size":{}}}"#, num_files),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn load_checkpoint_with_many_files() {
+    let num_files = 50_000;
+    let table_dir = tempdir::TempDir::new("many_files").unwrap();
+    let log_dir = table_dir.path().join("_delta_log");
+    std::fs::create_dir(&log_dir).unwrap();
+    write_checkpoint_of_adds(&log_dir, num_files);
+    let version_2 = vec![
+        r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":1}}"#,
+        r#"{"metaData":{"id":"5fba94ed-9794-4965-ba6e-6ee3c0d22af9","format":{"provider":"parquet","options":{}},"schemaString":"{\"type\":\"struct\",\"fields\":[{\"name\":\"value\",\"type\":\"integer\",\"nullable\":true,\"metadata\":{}}]}","partitionColumns":[],"configuration":{},"createdTime":1615555644000}}"#,
+    ];
+    std::fs::write(
+        log_dir.join("00000000000000000002.json"),
+        version_2.join("\n"),
+    )
+    .unwrap();
+    // an add of an active path replaces the file, whether or not it changes data
+    let version_3 = vec![
+        r#"{"add":{"path":"part-00000.parquet","partitionValues":{},"size":200,"modificationTime":1615555647000,"dataChange":true}}"#,
+        r#"{"add":{"path":"part-00001.parquet","partitionValues":{},"size":200,"modificationTime":1615555647000,"dataChange":false}}"#,
+        r#"{"remove":{"path":"part-00002.parquet","deletionTimestamp":1615555647000,"dataChange":true}}"#,
+        r#"{"add":{"path":"part-00002.parquet","partitionValues":{},"size":300,"modificationTime":1615555647000,"dataChange":true}}"#,
+    ];
+    std::fs::write(
+        log_dir.join("00000000000000000003.json"),
+        version_3.join("\n"),
+    )
+    .unwrap();
+
+    let table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(table.version, 3);
+    let files = table.get_actions();
+    assert_eq!(files.len(), num_files);
+    let sizes = |path: &str| {
+        files
+            .iter()
+            .filter(|add| add.path == path)
+            .map(|add| add.size)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(sizes("part-00000.parquet"), vec![200]);
+    assert_eq!(sizes("part-00001.parquet"), vec![200]);
+    assert_eq!(sizes("part-00002.parquet"), vec![300]);
+    assert_eq!(sizes("part-00003.parquet"), vec![100]);
+}
+
+#[tokio::test]
+async fn read_delta_8_0_table_skipping_tombstones() {
+    let path = "./tests/data/delta-0.8.0";
+    let backend = deltalake::get_backend_for_uri(path).unwrap();
+    let mut table = deltalake::DeltaTable::new_with_config(
+        path,
+        backend,
+        deltalake::DeltaTableConfig {
+            skip_tombstones: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    table.load().await.unwrap();
+
+    assert_eq!(
+        table.get_files(),
+        vec![
+            "part-00000-c9b90f86-73e6-46c8-93ba-ff6bfaf892a1-c000.snappy.parquet",
+            "part-00000-04ec9591-0b73-459e-8d18-ba5711d6cbe1-c000.snappy.parquet"
+        ]
+    );
+    assert!(table.get_tombstones().is_empty());
+    assert!(matches!(
+        table.vacuum(169, true).await.unwrap_err(),
+        deltalake::DeltaTableError::TombstonesNotLoaded,
+    ));
+}
+
+#[tokio::test]
+async fn read_delta_table_debug_dump() {
+    let table = deltalake::open_table("./tests/data/simple_table_with_checkpoint/")
+        .await
+        .unwrap();
+    let dump = table.debug_dump();
+    assert_eq!(dump.table_uri, "./tests/data/simple_table_with_checkpoint/");
+    assert_eq!(dump.version, 10);
+    assert_eq!(dump.checkpoint_version, Some(10));
+    assert_eq!(dump.num_log_files_replayed, 0);
+    assert_eq!(dump.num_files, table.get_files().len());
+    assert_eq!(dump.min_reader_version, 1);
+
+    let table = deltalake::open_table_with_version("./tests/data/simple_table_with_checkpoint/", 9)
+        .await
+        .unwrap();
+    let dump = table.debug_dump();
+    assert_eq!(dump.checkpoint_version, None);
+    assert_eq!(dump.num_log_files_replayed, 10);
+
+    let json = serde_json::to_value(&dump).unwrap();
+    assert_eq!(json["version"], 9);
+}
+
+#[tokio::test]
+async fn read_delta_8_0_table_with_case_insensitive_partitions() {
+    let filters = vec![deltalake::PartitionFilter {
+        key: "Month",
+        value: deltalake::PartitionValue::Equal("4"),
+    }];
+
+    let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned")
+        .await
+        .unwrap();
+    assert!(matches!(
+        table.get_files_by_partitions(&filters).unwrap_err(),
+        deltalake::DeltaTableError::ColumnNotFound { column } if column == "Month",
+    ));
+
+    let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/delta-0.8.0-partitioned")
+        .with_config(deltalake::DeltaTableConfig {
+            column_resolution: deltalake::ColumnResolution::CaseInsensitive,
+            ..Default::default()
+        })
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(
+        table.get_files_by_partitions(&filters).unwrap(),
+        vec![
+            "year=2021/month=4/day=5/part-00000-c5856301-3439-4032-a6fc-22b7bc92bebb.c000.snappy.parquet".to_string()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn read_simple_table_commit_infos() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let commit_infos = table.get_commit_infos();
+    assert_eq!(commit_infos.len(), 5);
+
+    let delete = &commit_infos[4];
+    assert_eq!(delete.timestamp, Some(1587968626537));
+    assert_eq!(delete.operation.as_deref(), Some("DELETE"));
+    assert_eq!(
+        delete.operationParameters.as_ref().unwrap()["predicate"],
+        "[\"((`id` % CAST(2 AS BIGINT)) = CAST(0 AS BIGINT))\"]"
+    );
+    assert_eq!(delete.info["readVersion"], 3);
+    assert_eq!(delete.info["isBlindAppend"], false);
+    assert!(delete.userMetadata.is_none());
+
+    let json = serde_json::to_value(delete).unwrap();
+    assert_eq!(json["operation"], "DELETE");
+    assert_eq!(json["readVersion"], 3);
+    assert!(json.get("userMetadata").is_none());
+}
+
+#[tokio::test]
+async fn read_delta_8_0_table_with_random_layout() {
+    let path = "./tests/data/delta-0.8.0-random-layout";
+    let mut table = deltalake::open_table(path).await.unwrap();
+    assert_eq!(table.version, 1);
+    assert_eq!(table.get_files().len(), 5);
+
+    // the files aren't stored in partition directories
+    let filters = vec![
+        deltalake::PartitionFilter {
+            key: "month",
+            value: deltalake::PartitionValue::Equal("2"),
+        },
+        deltalake::PartitionFilter {
+            key: "year",
+            value: deltalake::PartitionValue::Equal("2020"),
+        },
+    ];
+    assert_eq!(
+        table.get_files_by_partitions(&filters).unwrap(),
+        vec![
+            "Kq/part-00000-94d16827-f2fd-42cd-a060-f67ccc63ced9.c000.snappy.parquet".to_string(),
+            "8d/part-00000-89cdd4c8-2af7-4add-8ea3-3990b2f027b5.c000.snappy.parquet".to_string(),
+        ]
+    );
+
+    let backend = FileStorageBackend::new(path);
+    let removed_file = backend.join_path(
+        path,
+        "Xa/part-00000-c5856301-3439-4032-a6fc-22b7bc92bebb.c000.snappy.parquet",
+    );
+    let mut paths = table.get_file_paths();
+    paths.push(removed_file.clone());
+    let inventory =
+        futures::stream::iter(
+            paths
+                .into_iter()
+                .map(|path| deltalake::storage::ObjectMeta {
+                    path,
+                    modified: chrono::Utc::now(),
+                    size: None,
+                }),
+        );
+    assert_eq!(
+        table
+            .vacuum_with_inventory(inventory, 169, true)
+            .await
+            .unwrap(),
+        vec![removed_file]
+    );
+}
+
+#[tokio::test]
+async fn read_simple_table_from_checkpoint() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table_with_checkpoint");
+    let table_uri = table_dir.path().to_str().unwrap();
+    std::fs::write(
+        table_dir
+            .path()
+            .join("_delta_log/00000000000000000011.json"),
+        r#"{"add":{"path":"part-00000-00000000-0000-0000-0000-000000000011-c000.snappy.parquet","partitionValues":{},"size":442,"modificationTime":1615751716698,"dataChange":true}}"#,
+    )
+    .unwrap();
+    let checkpointed = deltalake::open_table_with_version(table_uri, 10)
+        .await
+        .unwrap();
+
+    let backend = deltalake::get_backend_for_uri(table_uri).unwrap();
+    let mut table = deltalake::DeltaTable::new(table_uri, backend).unwrap();
+    table.load_from_checkpoint(10).await.unwrap();
+    assert_eq!(table.version, 10);
+    assert_eq!(table.get_files(), checkpointed.get_files());
+    assert_eq!(
+        table.get_metadata().unwrap().id,
+        checkpointed.get_metadata().unwrap().id
+    );
+    assert_eq!(table.debug_dump().checkpoint_version, Some(10));
+
+    table.update().await.unwrap();
+    assert_eq!(table.version, 11);
+    assert_eq!(table.get_files().len(), checkpointed.get_files().len() + 1);
+
+    let result = table.load_from_checkpoint(9).await;
+    assert!(matches!(
+        result,
+        Err(deltalake::DeltaTableError::LoadCheckpoint {
+            source: deltalake::LoadCheckpointError::NotFound
+        })
+    ));
+}
+
+#[tokio::test]
+async fn read_last_checkpoint_info() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table_with_checkpoint");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let mut table = deltalake::open_table(table_uri).await.unwrap();
+    let info = table.get_last_checkpoint_info().await.unwrap();
+    let last_checkpoint = info.last_checkpoint.unwrap();
+    assert_eq!(last_checkpoint.version(), 10);
+    assert_eq!(last_checkpoint.size(), 13);
+    assert_eq!(last_checkpoint.parts(), None);
+    assert_eq!(info.loaded_checkpoint.unwrap().version(), 10);
+    assert_eq!(info.num_commits_since_checkpoint, 0);
+
+    // replayed from the first commit
+    table.load_version(9).await.unwrap();
+    let info = table.get_last_checkpoint_info().await.unwrap();
+    assert_eq!(info.last_checkpoint.unwrap().version(), 10);
+    assert!(info.loaded_checkpoint.is_none());
+    assert_eq!(info.num_commits_since_checkpoint, 10);
+
+    std::fs::remove_file(table_dir.path().join("_delta_log/_last_checkpoint")).unwrap();
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    let info = table.get_last_checkpoint_info().await.unwrap();
+    assert!(info.last_checkpoint.is_none());
+    assert!(info.loaded_checkpoint.is_none());
+    assert_eq!(info.num_commits_since_checkpoint, 11);
+}
+
+#[tokio::test]
+async fn read_partition_stats() {
+    let tmp_dir = tempdir::TempDir::new("partition_stats").unwrap();
+    let log_dir = tmp_dir.path().join("_delta_log");
+    std::fs::create_dir(&log_dir).unwrap();
+    let schema = r#"{\"type\":\"struct\",\"fields\":[{\"name\":\"ts\",\"type\":\"timestamp\",\"nullable\":true,\"metadata\":{}},{\"name\":\"region\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}}]}"#;
+    let add = |region: &str, size: i64, modified: i64, ts_range: Option<(&str, &str)>| {
+        let stats = match ts_range {
+            Some((min, max)) => format!(
+                r#","stats":"{{\"numRecords\":1,\"minValues\":{{\"ts\":\"{}\"}},\"maxValues\":{{\"ts\":\"{}\"}},\"nullCount\":{{\"ts\":0}}}}""#,
+                min, max
+            ),
+            None => String::new(),
+        };
+        format!(
+            r#"{{"add":{{"path":"region={0}/{1}.parquet","partitionValues":{{"region":"{0}"}},"size":{1},"modificationTime":{2},"dataChange":true{3}}}}}"#,
+            region, size, modified, stats
+        )
+    };
+    let log_entry = vec![
+        r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#.to_string(),
+        format!(
+            r#"{{"metaData":{{"id":"1","format":{{"provider":"parquet","options":{{}}}},"schemaString":"{}","partitionColumns":["region"],"configuration":{{}},"createdTime":0}}}}"#,
+            schema
+        ),
+        add(
+            "us",
+            100,
+            1000,
+            Some(("2021-03-01T00:00:00.000Z", "2021-03-02T12:00:00.000Z")),
+        ),
+        add(
+            "us",
+            200,
+            3000,
+            Some(("2021-02-27T00:00:00.000Z", "2021-03-01T00:00:00.000Z")),
+        ),
+        add("us", 300, 2000, None),
+        add(
+            "eu",
+            50,
+            500,
+            Some(("2021-01-01T00:00:00.000Z", "2021-01-01T01:00:00.000Z")),
+        ),
+    ]
+    .join("\n");
+    std::fs::write(log_dir.join("00000000000000000000.json"), log_entry).unwrap();
+
+    let table = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let timestamp = |s: &str| Some(s.parse::<chrono::DateTime<chrono::Utc>>().unwrap());
+    let partition_values = |region: &str| {
+        let mut values = HashMap::new();
+        values.insert("region".to_string(), Some(region.to_string()));
+        values
+    };
+    assert_eq!(
+        table.get_partition_stats(Some("ts")).unwrap(),
+        vec![
+            deltalake::PartitionStats {
+                partition_values: partition_values("eu"),
+                num_files: 1,
+                total_bytes: 50,
+                min_timestamp: timestamp("2021-01-01T00:00:00Z"),
+                max_timestamp: timestamp("2021-01-01T01:00:00Z"),
+                num_files_without_timestamp_stats: 0,
+                latest_modification_time: 500,
+            },
+            deltalake::PartitionStats {
+                partition_values: partition_values("us"),
+                num_files: 3,
+                total_bytes: 600,
+                min_timestamp: timestamp("2021-02-27T00:00:00Z"),
+                max_timestamp: timestamp("2021-03-02T12:00:00Z"),
+                num_files_without_timestamp_stats: 1,
+                latest_modification_time: 3000,
+            },
+        ]
+    );
+
+    let stats = table.get_partition_stats(None).unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[1].min_timestamp, None);
+    assert_eq!(stats[1].num_files_without_timestamp_stats, 0);
+    assert!(matches!(
+        table.get_partition_stats(Some("missing")),
+        Err(deltalake::DeltaTableError::ColumnNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn read_column_stats() {
+    let tmp_dir = tempdir::TempDir::new("column_stats").unwrap();
+    let log_dir = tmp_dir.path().join("_delta_log");
+    std::fs::create_dir(&log_dir).unwrap();
+    let schema = r#"{"type":"struct","fields":[{"name":"id","type":"long","nullable":true,"metadata":{}},{"name":"nested","type":{"type":"struct","fields":[{"name":"score","type":"double","nullable":true,"metadata":{}},{"name":"name","type":"string","nullable":true,"metadata":{}}]},"nullable":true,"metadata":{}}]}"#;
+    let add = |path: &str, stats: Option<serde_json::Value>| {
+        let mut add = serde_json::json!({
+            "path": path,
+            "partitionValues": {},
+            "size": 1,
+            "modificationTime": 0,
+            "dataChange": true,
+        });
+        if let Some(stats) = stats {
+            add["stats"] = stats.to_string().into();
+        }
+        serde_json::json!({ "add": add }).to_string()
+    };
+    let log_entry = vec![
+        r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#.to_string(),
+        serde_json::json!({"metaData": {
+            "id": "1",
+            "format": {"provider": "parquet", "options": {}},
+            "schemaString": schema,
+            "partitionColumns": [],
+            "configuration": {},
+            "createdTime": 0,
+        }})
+        .to_string(),
+        add(
+            "a.parquet",
+            Some(serde_json::json!({
+                "numRecords": 3,
+                "minValues": {"id": 1, "nested": {"score": 0.5, "name": "b"}},
+                "maxValues": {"id": 5, "nested": {"score": 2.5, "name": "x"}},
+                "nullCount": {"id": 0, "nested": {"score": 1, "name": 0}},
+                "distinctCount": {"id": 3, "nested": {"name": 2}},
+            })),
+        ),
+        add(
+            "b.parquet",
+            Some(serde_json::json!({
+                "numRecords": 2,
+                "minValues": {"id": 4, "nested": {"score": -1.0}},
+                "maxValues": {"id": 9, "nested": {"score": 1.0}},
+                "nullCount": {"id": 0, "nested": {"score": 0, "name": 2}},
+                "distinctCount": {"id": 4},
+            })),
+        ),
+        add("c.parquet", None),
+    ]
+    .join("\n");
+    std::fs::write(log_dir.join("00000000000000000000.json"), log_entry).unwrap();
+
+    let table = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        table.column_stats("id").unwrap(),
+        deltalake::ColumnStats {
+            num_files: 3,
+            num_files_without_stats: 1,
+            num_records: 5,
+            min_value: Some(serde_json::json!(1)),
+            max_value: Some(serde_json::json!(9)),
+            null_count: 0,
+            // the distinct counts of the files add up to more than their values
+            distinct_count: Some(5),
+        }
+    );
+
+    let stats = table.column_stats("nested.score").unwrap();
+    assert_eq!(stats.min_value, Some(serde_json::json!(-1.0)));
+    assert_eq!(stats.max_value, Some(serde_json::json!(2.5)));
+    assert_eq!(stats.null_count, 1);
+    assert_eq!(stats.distinct_count, None);
+
+    let stats = table.column_stats("nested.name").unwrap();
+    assert_eq!(stats.min_value, Some(serde_json::json!("b")));
+    assert_eq!(stats.null_count, 2);
+
+    // struct columns have no statistics of their own
+    let stats = table.column_stats("nested").unwrap();
+    assert_eq!(stats.num_files_without_stats, 3);
+    assert_eq!(stats.min_value, None);
+
+    for column in &["missing", "nested.missing", "id.value"] {
+        assert!(matches!(
+            table.column_stats(column),
+            Err(deltalake::DeltaTableError::ColumnNotFound { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn read_table_with_null_partitions() {
+    let tmp_dir = tempdir::TempDir::new("null_partitions").unwrap();
+    let log_dir = tmp_dir.path().join("_delta_log");
+    std::fs::create_dir(&log_dir).unwrap();
+    let log_entry = [
+        r#"{"protocol":{"minReaderVersion":1,"minWriterVersion":2}}"#,
+        r#"{"metaData":{"id":"1","format":{"provider":"parquet","options":{}},"schemaString":"{\"type\":\"struct\",\"fields\":[{\"name\":\"value\",\"type\":\"integer\",\"nullable\":true,\"metadata\":{}},{\"name\":\"year\",\"type\":\"integer\",\"nullable\":true,\"metadata\":{}}]}","partitionColumns":["year"],"configuration":{},"createdTime":0}}"#,
+        r#"{"add":{"path":"year=2021/a.parquet","partitionValues":{"year":"2021"},"size":1,"modificationTime":0,"dataChange":true}}"#,
+        r#"{"add":{"path":"year=__HIVE_DEFAULT_PARTITION__/b.parquet","partitionValues":{"year":null},"size":2,"modificationTime":0,"dataChange":true}}"#,
+        r#"{"add":{"path":"year=__HIVE_DEFAULT_PARTITION__/c.parquet","partitionValues":{"year":""},"size":4,"modificationTime":0,"dataChange":true}}"#,
+    ]
+    .join("\n");
+    std::fs::write(log_dir.join("00000000000000000000.json"), log_entry).unwrap();
+
+    let table = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(table.get_actions()[1].partitionValues["year"], None);
+    assert_eq!(table.get_actions()[1].get_partition_value("year"), None);
+    assert_eq!(table.get_actions()[2].get_partition_value("year"), None);
+
+    let files = |value| {
+        table
+            .get_files_by_partitions(&[deltalake::PartitionFilter { key: "year", value }])
+            .unwrap()
+    };
+    assert_eq!(
+        files(deltalake::PartitionValue::IsNull),
+        vec![
+            "year=__HIVE_DEFAULT_PARTITION__/b.parquet",
+            "year=__HIVE_DEFAULT_PARTITION__/c.parquet",
+        ]
+    );
+    assert_eq!(
+        files(deltalake::PartitionValue::IsNotNull),
+        vec!["year=2021/a.parquet"]
+    );
+    assert_eq!(
+        files(deltalake::PartitionValue::NotEqual("2020")),
+        vec!["year=2021/a.parquet"]
+    );
+
+    let stats = table.get_partition_stats(None).unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].partition_values["year"], None);
+    assert_eq!(stats[0].num_files, 2);
+    assert_eq!(stats[0].total_bytes, 6);
+}
+
+#[tokio::test]
+async fn read_table_row_count_and_size() {
+    let table = deltalake::open_table("./tests/data/COVID-19_NYT")
+        .await
+        .unwrap();
+    assert_eq!(table.get_row_count_estimate().unwrap(), 1111930);
+    assert_eq!(table.get_table_size_bytes(), 6190485);
+    assert_eq!(table.snapshot().get_table_size_bytes(), 6190485);
+
+    // files without stats aren't counted
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    assert_eq!(table.get_row_count_estimate().unwrap(), 0);
+}
@@ -2,6 +2,9 @@ extern crate deltalake;
 
 use std::{env, matches};
 
+#[allow(dead_code)]
+mod fs_common;
+
 #[tokio::test]
 async fn read_empty_folder() {
     let dir = env::temp_dir();
@@ -12,3 +15,55 @@ async fn read_empty_folder() {
         deltalake::DeltaTableError::NotATable,
     ));
 }
+
+#[tokio::test]
+async fn read_unsupported_format() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let commit_path = table_dir
+        .path()
+        .join("_delta_log/00000000000000000000.json");
+    let commit = std::fs::read_to_string(&commit_path).unwrap();
+    std::fs::write(
+        &commit_path,
+        commit.replace(r#""provider":"parquet""#, r#""provider":"orc""#),
+    )
+    .unwrap();
+
+    let result = deltalake::open_table(table_uri).await;
+    assert!(matches!(
+        result.unwrap_err(),
+        deltalake::DeltaTableError::UnsupportedFormat { provider, .. } if provider == "orc",
+    ));
+}
+
+#[tokio::test]
+async fn commit_unsupported_format() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let mut table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let mut metadata = table.get_commit_actions(0).await.unwrap();
+    metadata.retain(|action| matches!(action, deltalake::action::Action::metaData(_)));
+    if let deltalake::action::Action::metaData(m) = &mut metadata[0] {
+        m.format = deltalake::action::Format::new("parquet", Some(format_options()));
+    }
+
+    let result = table
+        .create_transaction(None)
+        .commit_with(&metadata, None)
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        deltalake::DeltaTransactionError::DeltaTable {
+            source: deltalake::DeltaTableError::UnsupportedFormat { .. }
+        },
+    ));
+    assert_eq!(table.version, 4);
+}
+
+fn format_options() -> std::collections::HashMap<String, String> {
+    let mut options = std::collections::HashMap::new();
+    options.insert("compression".to_string(), "zstd".to_string());
+    options
+}
@@ -6,6 +6,9 @@ use std::path::Path;
 
 use self::chrono::{DateTime, FixedOffset, Utc};
 
+#[allow(dead_code)]
+mod fs_common;
+
 #[tokio::test]
 async fn read_simple_table() {
     let table = deltalake::open_table("./tests/data/simple_table")
@@ -28,11 +31,9 @@ async fn read_simple_table() {
     assert_eq!(tombstones.len(), 31);
     assert_eq!(
         tombstones[0],
-        deltalake::action::Remove {
+        deltalake::Tombstone {
             path: "part-00006-63ce9deb-bc0f-482d-b9a1-7e717b67f294-c000.snappy.parquet".to_string(),
-            deletionTimestamp: 1587968596250,
-            dataChange: true,
-            ..Default::default()
+            deletion_timestamp: 1587968596250,
         }
     );
     #[cfg(unix)]
@@ -137,8 +138,18 @@ async fn time_travel_by_ds() {
         utime::set_file_times(Path::new(log_dir).join(fname), ts, ts).unwrap();
     }
 
-    let mut table =
+    let result =
         deltalake::open_table_with_ds("./tests/data/simple_table", "2020-05-01T00:47:31-07:00")
+            .await;
+    match result {
+        Err(deltalake::DeltaTableError::TimestampEarlierThanTableCreation { earliest }) => {
+            assert_eq!(earliest.timestamp(), ds_to_ts("2020-05-01T22:47:31-07:00"));
+        }
+        _ => panic!("expected TimestampEarlierThanTableCreation error"),
+    }
+
+    let mut table =
+        deltalake::open_table_with_ds("./tests/data/simple_table", "2020-05-01T22:47:31-07:00")
             .await
             .unwrap();
     assert_eq!(table.version, 0);
@@ -178,3 +189,284 @@ async fn time_travel_by_ds() {
         .unwrap();
     assert_eq!(table.version, 4);
 }
+
+#[tokio::test]
+async fn read_simple_table_history() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let history = table.history(None).await.unwrap();
+    let operations = history
+        .iter()
+        .map(|info| info.operation.as_deref().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        operations,
+        vec!["DELETE", "UPDATE", "WRITE", "MERGE", "WRITE"]
+    );
+    assert_eq!(history[0].version, Some(4));
+
+    let history = table.history(Some(2)).await.unwrap();
+    assert_eq!(history.len(), 2);
+}
+
+#[tokio::test]
+async fn shared_table_version_timestamps() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<deltalake::DeltaTable>();
+
+    let table = std::sync::Arc::new(
+        deltalake::open_table("./tests/data/simple_table")
+            .await
+            .unwrap(),
+    );
+    let shared = table.clone();
+    let latest_version = tokio::spawn(async move { shared.get_latest_version().await.unwrap() })
+        .await
+        .unwrap();
+    assert_eq!(latest_version, table.version);
+
+    let ts = table.get_version_timestamp(0).await.unwrap();
+    assert_eq!(table.get_version_timestamp(0).await.unwrap(), ts);
+}
+
+#[tokio::test]
+async fn checkout_older_versions() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let table_at_version_2 = deltalake::open_table_with_version("./tests/data/simple_table", 2)
+        .await
+        .unwrap();
+
+    let snapshot = table.checkout(2).await.unwrap();
+    assert_eq!(snapshot.version(), 2);
+    assert_eq!(snapshot.get_files(), table_at_version_2.get_files());
+    assert_eq!(table.version, 4);
+
+    assert_eq!(
+        table.checkout(4).await.unwrap().get_files(),
+        table.get_files()
+    );
+    assert!(table.checkout(10).await.is_err());
+}
+
+#[tokio::test]
+async fn read_simple_table_commit_actions() {
+    use deltalake::action::Action;
+    use futures::StreamExt;
+
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+
+    let actions = table.get_commit_actions(4).await.unwrap();
+    assert_eq!(actions.len(), 4);
+    assert_eq!(
+        actions
+            .iter()
+            .filter(|a| matches!(a, Action::remove(_)))
+            .count(),
+        2
+    );
+
+    let commits = table
+        .get_commit_actions_range(0..=4)
+        .collect::<Vec<_>>()
+        .await;
+    let versions = commits
+        .into_iter()
+        .map(|commit| commit.unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(versions, vec![0, 1, 2, 3, 4]);
+
+    let mut commits = Box::pin(table.get_commit_actions_range(4..=5));
+    assert!(commits.next().await.unwrap().is_ok());
+    assert!(matches!(
+        commits.next().await.unwrap(),
+        Err(deltalake::DeltaTableError::InvalidVersion(5))
+    ));
+}
+
+#[derive(Default)]
+struct FileCounts {
+    versions: Vec<deltalake::DeltaDataTypeVersion>,
+    num_adds: usize,
+    num_removes: usize,
+    bytes_added: i64,
+    num_metadata: usize,
+    operations: Vec<String>,
+}
+
+impl deltalake::LogReplayVisitor for FileCounts {
+    fn on_version(&mut self, version: deltalake::DeltaDataTypeVersion) {
+        self.versions.push(version);
+    }
+
+    fn on_add(&mut self, _version: deltalake::DeltaDataTypeVersion, add: &deltalake::action::Add) {
+        self.num_adds += 1;
+        self.bytes_added += add.size;
+    }
+
+    fn on_remove(
+        &mut self,
+        _version: deltalake::DeltaDataTypeVersion,
+        _remove: &deltalake::action::Remove,
+    ) {
+        self.num_removes += 1;
+    }
+
+    fn on_metadata(
+        &mut self,
+        _version: deltalake::DeltaDataTypeVersion,
+        _metadata: &deltalake::action::MetaData,
+    ) {
+        self.num_metadata += 1;
+    }
+
+    fn on_commit_info(
+        &mut self,
+        _version: deltalake::DeltaDataTypeVersion,
+        commit_info: &deltalake::action::CommitInfo,
+    ) {
+        self.operations.extend(commit_info.operation.clone());
+    }
+}
+
+#[tokio::test]
+async fn replay_simple_table() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+
+    let counts = table.replay(0..=4, FileCounts::default()).await.unwrap();
+    assert_eq!(counts.versions, vec![0, 1, 2, 3, 4]);
+    assert_eq!(counts.num_adds, 36);
+    assert_eq!(counts.num_removes, 31);
+    assert_eq!(counts.bytes_added, 14776);
+    assert_eq!(counts.num_metadata, 1);
+    assert_eq!(
+        counts.operations,
+        vec!["WRITE", "MERGE", "WRITE", "UPDATE", "DELETE"]
+    );
+
+    let counts = table.replay(1..=4, FileCounts::default()).await.unwrap();
+    assert_eq!(counts.num_adds, 30);
+    assert_eq!(counts.bytes_added, 12369);
+    assert_eq!(counts.num_metadata, 0);
+
+    let result = table.replay(3..=5, FileCounts::default()).await;
+    assert!(matches!(
+        result,
+        Err(deltalake::DeltaTableError::InvalidVersion(5))
+    ));
+}
+
+#[tokio::test]
+async fn open_table_rejects_unknown_uri_options() {
+    let result = deltalake::open_table("file://./tests/data/simple_table?region=us-west-2").await;
+    assert!(matches!(
+        result,
+        Err(deltalake::DeltaTableError::StorageError {
+            source: deltalake::StorageError::Uri {
+                source: deltalake::UriError::UnknownOption(_)
+            }
+        })
+    ));
+}
+
+#[tokio::test]
+async fn read_simple_table_with_builder() {
+    let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+        .with_version(3)
+        .without_tombstones()
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.version, 3);
+    assert!(table.get_tombstones().is_empty());
+
+    let datetime = DateTime::<Utc>::from(
+        DateTime::<FixedOffset>::parse_from_rfc3339("2100-01-01T00:00:00Z").unwrap(),
+    );
+    let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+        .with_version(0)
+        .with_datetime(datetime)
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.version, 4);
+
+    let mut options = std::collections::HashMap::new();
+    options.insert("region".to_string(), "us-west-2".to_string());
+    assert!(matches!(
+        deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+            .with_storage_options(options)
+            .build(),
+        Err(deltalake::DeltaTableError::StorageError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn read_simple_table_uri() {
+    let table = deltalake::open_table("./tests/data/simple_table/")
+        .await
+        .unwrap();
+    assert_eq!(
+        table.storage_backend_kind().unwrap(),
+        deltalake::StorageBackendKind::Local
+    );
+    let expected = std::fs::canonicalize("./tests/data/simple_table").unwrap();
+    assert_eq!(
+        table.table_uri().unwrap(),
+        format!("file://{}", expected.display())
+    );
+}
+
+#[tokio::test]
+async fn read_simple_table_presigned_urls() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let result = table
+        .get_presigned_file_urls(&[], std::time::Duration::from_secs(60))
+        .await;
+    assert!(matches!(
+        result,
+        Err(deltalake::DeltaTableError::StorageError {
+            source: deltalake::StorageError::PresignNotSupported
+        })
+    ));
+}
+
+#[tokio::test]
+async fn read_simple_table_through_storage_backend() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table = deltalake::open_table(table_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let storage = table.storage_backend();
+
+    let file = table.get_files()[0];
+    let path = table.object_path(file);
+    assert!(table.get_file_paths().contains(&path));
+    let bytes = storage.get_obj(&path).await.unwrap();
+    assert_eq!(&bytes[..4], b"PAR1");
+    assert_eq!(
+        storage.head_obj(&path).await.unwrap().size,
+        Some(bytes.len() as i64)
+    );
+
+    // side artifacts land next to the table
+    let manifest_path = table.object_path("_symlink_format_manifest/manifest");
+    storage
+        .put_obj(&manifest_path, table.get_file_paths().join("\n").as_bytes())
+        .await
+        .unwrap();
+    assert!(table_dir
+        .path()
+        .join("_symlink_format_manifest/manifest")
+        .exists());
+    let snapshot_storage = table.snapshot().storage_backend();
+    assert!(snapshot_storage.head_obj(&manifest_path).await.is_ok());
+}
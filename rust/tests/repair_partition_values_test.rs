@@ -0,0 +1,97 @@
+extern crate deltalake;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use deltalake::{partition_values_from_path, PartitionFilter, PartitionValue};
+use serde_json::json;
+
+#[allow(dead_code)]
+mod fs_common;
+
+fn write_table(table_dir: &Path) {
+    let add = |path: &str, partition_values: serde_json::Value| {
+        fs_common::add_action(path, partition_values, 100)
+    };
+    fs_common::write_table(
+        table_dir,
+        &[
+            ("value", json!("string")),
+            ("year", json!("string")),
+            ("month", json!("string")),
+        ],
+        &["year", "month"],
+        &[
+            add("year=2021/month=1/1.parquet", json!({})),
+            add(
+                "year=2021/month=__HIVE_DEFAULT_PARTITION__/2.parquet",
+                json!({}),
+            ),
+            add(
+                "year=2020/month=12/3.parquet",
+                json!({"year": "2020", "month": "12"}),
+            ),
+            add("year=2020/4.parquet", json!({})),
+        ],
+    );
+}
+
+#[test]
+fn infer_partition_values_from_path() {
+    let columns = vec!["year".to_string(), "a=b".to_string()];
+    let values = partition_values_from_path(
+        "year=2021/a%3Db=__HIVE_DEFAULT_PARTITION__/part-00000.parquet",
+        &columns,
+    )
+    .unwrap();
+    let mut expected = HashMap::new();
+    expected.insert("year".to_string(), Some("2021".to_string()));
+    expected.insert("a=b".to_string(), None);
+    assert_eq!(values, expected);
+
+    assert_eq!(
+        partition_values_from_path("year=2021/part-00000.parquet", &columns),
+        None
+    );
+    // the file name isn't a directory
+    assert_eq!(
+        partition_values_from_path("year=2021/a%3Db=1", &columns),
+        None
+    );
+}
+
+#[tokio::test]
+async fn repair_partition_values() {
+    let tmp_dir = tempdir::TempDir::new("repair_partition_values").unwrap();
+    write_table(tmp_dir.path());
+    let mut table = deltalake::open_table(tmp_dir.path().to_str().unwrap())
+        .await
+        .unwrap();
+    let filters = vec![PartitionFilter {
+        key: "year",
+        value: PartitionValue::Equal("2021"),
+    }];
+    assert!(table.get_files_by_partitions(&filters).unwrap().is_empty());
+
+    assert_eq!(table.repair_partition_values().await.unwrap(), Some(1));
+    assert_eq!(
+        table.get_files_by_partitions(&filters).unwrap(),
+        vec![
+            "year=2021/month=1/1.parquet",
+            "year=2021/month=__HIVE_DEFAULT_PARTITION__/2.parquet"
+        ]
+    );
+    let repaired = &table.get_actions()[3];
+    assert!(!repaired.dataChange);
+    assert_eq!(repaired.partitionValues["month"], None);
+
+    let history = table.history(Some(1)).await.unwrap();
+    assert_eq!(
+        history[0].operation.as_deref(),
+        Some("REPAIR PARTITION VALUES")
+    );
+
+    // the file without a month directory can't be repaired
+    assert_eq!(table.repair_partition_values().await.unwrap(), None);
+    assert_eq!(table.version, 1);
+}
@@ -0,0 +1,67 @@
+extern crate deltalake;
+
+use std::fs;
+
+use chrono::{TimeZone, Utc};
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[tokio::test]
+async fn time_travel_to_cleaned_up_versions() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table_with_checkpoint");
+    let log_dir = table_dir.path().join("_delta_log");
+    // the commits before the checkpoint of version 10 have been cleaned up
+    for version in 0..10 {
+        fs::remove_file(log_dir.join(format!("{:020}.json", version))).unwrap();
+    }
+    let table_uri = table_dir.path().to_str().unwrap();
+    let mut table = deltalake::open_table(table_uri).await.unwrap();
+    assert_eq!(table.version, 10);
+    assert_eq!(table.get_earliest_version().await.unwrap(), 10);
+    let files = table
+        .get_files_iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<String>>();
+
+    match table.load_version(5).await {
+        Err(deltalake::DeltaTableError::VersionNotRetained {
+            version,
+            earliest_available,
+        }) => {
+            assert_eq!(version, 5);
+            assert_eq!(earliest_available, 10);
+        }
+        result => panic!("expected VersionNotRetained, got {:?}", result),
+    }
+    assert!(matches!(
+        table.load_version(11).await,
+        Err(deltalake::DeltaTableError::InvalidVersion(11))
+    ));
+
+    // the checkpoint still restores the earliest version without its commit
+    fs::remove_file(log_dir.join(format!("{:020}.json", 10))).unwrap();
+    table.load_version(10).await.unwrap();
+    assert_eq!(table.version, 10);
+    assert_eq!(table.get_files(), files);
+
+    let datetime = Utc.timestamp(1_000_000, 0);
+    assert!(matches!(
+        table.load_with_datetime(datetime).await,
+        Err(deltalake::DeltaTableError::TimestampNotRetained {
+            earliest_available: 10,
+            ..
+        })
+    ));
+
+    let mut table = deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_config(deltalake::DeltaTableConfig {
+            clamp_time_travel: true,
+            ..Default::default()
+        })
+        .load()
+        .await
+        .unwrap();
+    table.load_with_datetime(datetime).await.unwrap();
+    assert_eq!(table.version, 10);
+}
@@ -1,8 +1,8 @@
-#[cfg(feature = "s3")]
+#[cfg(feature = "integration-testing")]
 #[allow(dead_code)]
 mod s3_common;
 
-#[cfg(feature = "s3")]
+#[cfg(feature = "integration-testing")]
 mod s3 {
     use crate::s3_common::setup;
     use serial_test::serial;
@@ -38,12 +38,10 @@ mod s3 {
         assert_eq!(tombstones.len(), 31);
         assert_eq!(
             tombstones[0],
-            deltalake::action::Remove {
+            deltalake::Tombstone {
                 path: "part-00006-63ce9deb-bc0f-482d-b9a1-7e717b67f294-c000.snappy.parquet"
                     .to_string(),
-                deletionTimestamp: 1587968596250,
-                dataChange: true,
-                ..Default::default()
+                deletion_timestamp: 1587968596250,
             }
         );
     }
@@ -74,12 +72,10 @@ mod s3 {
         assert_eq!(tombstones.len(), 29);
         assert_eq!(
             tombstones[0],
-            deltalake::action::Remove {
+            deltalake::Tombstone {
                 path: "part-00006-63ce9deb-bc0f-482d-b9a1-7e717b67f294-c000.snappy.parquet"
                     .to_string(),
-                deletionTimestamp: 1587968596250,
-                dataChange: true,
-                ..Default::default()
+                deletion_timestamp: 1587968596250,
             }
         );
     }
@@ -134,4 +130,124 @@ mod s3 {
 
         assert!(matches!(err, StorageError::NotFound));
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_s3_compatible_provider() {
+        setup();
+        std::env::remove_var("AWS_ENDPOINT_URL");
+        std::env::remove_var("AWS_REGION");
+
+        let uri = format!(
+            "s3://deltars/simple?provider=s3-compatible&endpoint={}",
+            crate::s3_common::ENDPOINT
+        );
+        let table = deltalake::open_table(&uri).await.unwrap();
+        assert_eq!(table.version, 4);
+        assert_eq!(table.table_path, "s3://deltars/simple");
+
+        let path = format!(
+            "s3://deltars/compatible.snappy.parquet?provider=s3-compatible&endpoint={}",
+            crate::s3_common::ENDPOINT
+        );
+        let backend = deltalake::get_backend_for_uri(&path).unwrap();
+        backend
+            .put_obj("s3://deltars/compatible.snappy.parquet", &[])
+            .await
+            .unwrap();
+        backend
+            .delete_obj("s3://deltars/compatible.snappy.parquet")
+            .await
+            .unwrap();
+
+        let err = deltalake::get_backend_for_uri("s3://deltars/simple?provider=r2").unwrap_err();
+        assert!(matches!(err, StorageError::S3Generic(_)));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_s3_virtual_hosted_provider() {
+        setup();
+        std::env::remove_var("AWS_ENDPOINT_URL");
+
+        // localstack serves virtual-hosted style requests on its localhost.localstack.cloud
+        // domain, like OSS on the endpoints of its regions
+        let options =
+            "provider=oss&region=us-east-1&endpoint=http://s3.localhost.localstack.cloud:4566";
+        let table = deltalake::open_table(&format!("s3://deltars/simple?{}", options))
+            .await
+            .unwrap();
+        assert_eq!(table.version, 4);
+
+        let path = "s3://deltars/virtual-hosted.snappy.parquet";
+        let backend = deltalake::get_backend_for_uri(&format!("{}?{}", path, options)).unwrap();
+        backend.put_obj(path, b"data").await.unwrap();
+        assert_eq!(backend.get_obj(path).await.unwrap(), b"data");
+        let url = backend
+            .presign(path, std::time::Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(
+            url.starts_with(
+                "http://deltars.s3.localhost.localstack.cloud:4566/virtual-hosted.snappy.parquet?"
+            ),
+            "{}",
+            url
+        );
+        backend.delete_obj(path).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_s3_presigned_file_urls() {
+        setup();
+        let table = deltalake::open_table("s3://deltars/simple").await.unwrap();
+        let expiry = std::time::Duration::from_secs(300);
+        let urls = table.get_presigned_file_urls(&[], expiry).await.unwrap();
+        assert_eq!(urls.len(), 5);
+        for (add, url) in urls {
+            assert!(url.starts_with(crate::s3_common::ENDPOINT));
+            assert!(url.contains(&add.path));
+            assert!(url.contains("X-Amz-Expires=300"));
+            assert!(url.contains("X-Amz-Signature="));
+        }
+
+        let expiry = std::time::Duration::from_secs(8 * 24 * 60 * 60);
+        let err = table
+            .get_presigned_file_urls(&[], expiry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            deltalake::DeltaTableError::StorageError {
+                source: StorageError::S3Generic(_)
+            }
+        ));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_s3_test_context() {
+        let context = setup();
+        context.create_bucket("deltars-harness").await.unwrap();
+        // creating an existing bucket succeeds
+        context.create_bucket("deltars-harness").await.unwrap();
+        context
+            .upload_dir("./tests/data/simple_table", "s3://deltars-harness/simple")
+            .await
+            .unwrap();
+        let table = deltalake::open_table("s3://deltars-harness/simple")
+            .await
+            .unwrap();
+        assert_eq!(table.version, 4);
+
+        context
+            .delete_prefix("s3://deltars-harness/simple")
+            .await
+            .unwrap();
+        let err = deltalake::open_table("s3://deltars-harness/simple")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, deltalake::DeltaTableError::NotATable));
+    }
 }
@@ -0,0 +1,115 @@
+extern crate deltalake;
+
+use arrow::array::{Array, Date32Array, Int32Array, StringArray, TimestampMicrosecondArray};
+use arrow::datatypes::{DataType, TimeUnit};
+use deltalake::scan::{partition_value_array, read_scan_task, DeltaScanTask};
+
+#[tokio::test]
+async fn scan_tasks_round_trip_and_read() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let tasks = table.get_scan_tasks(None).unwrap();
+    assert_eq!(tasks.len(), table.get_files().len());
+
+    let serialized = serde_json::to_string(&tasks).unwrap();
+    let deserialized: Vec<DeltaScanTask> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(tasks, deserialized);
+
+    let backend = deltalake::get_backend_for_uri(&table.table_path).unwrap();
+    let mut num_rows = 0;
+    for task in deserialized.iter() {
+        let batches = read_scan_task(task, backend.as_ref(), 1024).await.unwrap();
+        for batch in batches {
+            assert_eq!(batch.num_columns(), 1);
+            num_rows += batch.num_rows();
+        }
+    }
+    assert_eq!(num_rows, 3);
+}
+
+#[tokio::test]
+async fn split_scan_tasks_read_each_row_group_once() {
+    let table = deltalake::open_table("./tests/data/simple_table")
+        .await
+        .unwrap();
+    let backend = deltalake::get_backend_for_uri(&table.table_path).unwrap();
+
+    let whole_file_tasks = table.get_scan_tasks(None).unwrap();
+    let split_tasks = table.get_scan_tasks(Some(100)).unwrap();
+    assert!(split_tasks.len() > whole_file_tasks.len());
+
+    let mut whole_file_rows = 0;
+    for task in whole_file_tasks.iter() {
+        for batch in read_scan_task(task, backend.as_ref(), 1024).await.unwrap() {
+            whole_file_rows += batch.num_rows();
+        }
+    }
+
+    let mut split_rows = 0;
+    for task in split_tasks.iter() {
+        for batch in read_scan_task(task, backend.as_ref(), 1024).await.unwrap() {
+            split_rows += batch.num_rows();
+        }
+    }
+
+    assert_eq!(whole_file_rows, split_rows);
+}
+
+#[tokio::test]
+async fn scan_tasks_append_partition_columns() {
+    let table = deltalake::open_table("./tests/data/delta-0.8.0-partitioned")
+        .await
+        .unwrap();
+    let backend = deltalake::get_backend_for_uri(&table.table_path).unwrap();
+
+    let mut num_rows = 0;
+    for task in table.get_scan_tasks(None).unwrap().iter() {
+        for batch in read_scan_task(task, backend.as_ref(), 1024).await.unwrap() {
+            let schema = batch.schema();
+            let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+            assert_eq!(names, vec!["value", "year", "month", "day"]);
+            for column in &["year", "month", "day"] {
+                let values = batch
+                    .column(schema.index_of(column).unwrap())
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap();
+                let expected = task.partition_values[*column].as_deref();
+                assert!(values.iter().all(|value| value == expected));
+            }
+            num_rows += batch.num_rows();
+        }
+    }
+    assert_eq!(num_rows, 7);
+}
+
+#[test]
+fn partition_values_are_decoded_to_the_column_types() {
+    let array = partition_value_array(Some("2021-03-04"), &DataType::Date32, 2).unwrap();
+    let dates = array.as_any().downcast_ref::<Date32Array>().unwrap();
+    assert_eq!(dates.len(), 2);
+    assert_eq!(dates.value(1), 18690);
+
+    let data_type = DataType::Timestamp(TimeUnit::Microsecond, None);
+    let array = partition_value_array(Some("2021-03-04 01:02:03.5"), &data_type, 1).unwrap();
+    let timestamps = array
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>()
+        .unwrap();
+    assert_eq!(timestamps.value(0), 1_614_819_723_500_000);
+
+    let array = partition_value_array(Some("42"), &DataType::Int32, 1).unwrap();
+    let ints = array.as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(ints.value(0), 42);
+
+    // empty values are nulls, except for strings
+    let array = partition_value_array(Some(""), &DataType::Int32, 3).unwrap();
+    assert_eq!(array.null_count(), 3);
+    let array = partition_value_array(Some(""), &DataType::Utf8, 1).unwrap();
+    assert_eq!(array.null_count(), 0);
+    let array = partition_value_array(None, &DataType::Utf8, 1).unwrap();
+    assert_eq!(array.null_count(), 1);
+
+    assert!(partition_value_array(Some("yesterday"), &DataType::Date32, 1).is_err());
+}
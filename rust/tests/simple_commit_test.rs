@@ -10,8 +10,19 @@ mod s3_common;
 mod fs_common;
 
 use std::collections::HashMap;
-
-use deltalake::{action, DeltaTransactionError};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use deltalake::storage::file::FileStorageBackend;
+use deltalake::storage::ObjectMeta;
+use deltalake::{
+    action, CommitMetrics, CommitMetricsRecorder, CommitSizePolicy, DeltaTransactionError,
+    DeltaTransactionOptions, StorageBackend, StorageCapabilities, StorageError,
+};
+use futures::Stream;
 
 mod simple_commit_s3 {
     use super::*;
@@ -87,6 +98,13 @@ mod simple_commit_fs {
         assert_eq!(1, result);
         assert_eq!(1, table.version);
         assert_eq!(2, table.get_files().len());
+
+        let commit_info = &table.history(Some(1)).await.unwrap()[0];
+        assert_eq!(commit_info.version, Some(1));
+        assert_eq!(commit_info.info["readVersion"], 0);
+        let engine_info = format!("delta-rs/{}", deltalake::crate_version());
+        assert_eq!(commit_info.engineInfo, Some(engine_info.clone()));
+        assert_eq!(commit_info.info["clientVersion"], engine_info);
     }
 
     #[tokio::test]
@@ -127,6 +145,484 @@ mod simple_commit_fs {
         assert!(result.is_err());
         assert_eq!(1, table.version);
         assert_eq!(2, table.get_files().len());
+
+        // the temporary commit file of the failed commit is deleted
+        let tmp_commit_files = std::fs::read_dir("./tests/data/simple_commit/_delta_log")
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_str().unwrap().starts_with("_commit_")
+            })
+            .count();
+        assert_eq!(0, tmp_commit_files);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_with_fails_if_metadata_changed() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table_a = deltalake::open_table(table_path).await.unwrap();
+        let mut table_b = deltalake::open_table(table_path).await.unwrap();
+
+        // concurrent appends are retried
+        let mut tx = table_b.create_transaction(None);
+        tx.commit_with(tx2_actions().as_slice(), None)
+            .await
+            .unwrap();
+        let mut tx = table_a.create_transaction(None);
+        let version = tx
+            .commit_with(tx1_actions().as_slice(), None)
+            .await
+            .unwrap();
+        assert_eq!(2, version);
+
+        let protocol = action::Action::protocol(action::Protocol {
+            minReaderVersion: 1,
+            minWriterVersion: 2,
+        });
+        let mut tx = table_a.create_transaction(None);
+        tx.commit_with(&[protocol], None).await.unwrap();
+
+        // table_b was loaded before the protocol changed at version 3
+        assert_eq!(1, table_b.version);
+        let mut tx = table_b.create_transaction(None);
+        let result = tx.commit_with(tx1_actions().as_slice(), None).await;
+        assert!(matches!(
+            result,
+            Err(DeltaTransactionError::MetadataChanged { version: 3 })
+        ));
+
+        table_b.update().await.unwrap();
+        assert_eq!(3, table_b.version);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_with_to_unloaded_table() {
+        prepare_fs();
+
+        // without a loaded snapshot, the metadata committed at version 0 isn't a conflict
+        let table_path = "./tests/data/simple_commit";
+        let backend = deltalake::get_backend_for_uri(table_path).unwrap();
+        let mut table = deltalake::DeltaTable::new(table_path, backend).unwrap();
+        assert!(table.get_metadata().is_err());
+        let mut tx = table.create_transaction(None);
+        let version = tx
+            .commit_with(tx1_actions().as_slice(), None)
+            .await
+            .unwrap();
+        assert_eq!(1, version);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_prepare_finalize_and_abort() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+
+        let mut tx = table.create_transaction(None);
+        let prepared = tx.prepare(tx1_actions().as_slice(), None).await.unwrap();
+        assert!(std::path::Path::new(prepared.tmp_log_path()).exists());
+        assert_eq!(1, tx.finalize(&prepared, 1).await.unwrap());
+
+        let prepared = tx.prepare(tx2_actions().as_slice(), None).await.unwrap();
+        let result = tx.finalize(&prepared, 1).await;
+        assert!(matches!(
+            result,
+            Err(DeltaTransactionError::VersionAlreadyExists { .. })
+        ));
+        assert_eq!(2, tx.finalize(&prepared, 2).await.unwrap());
+
+        let prepared = tx.prepare(tx2_actions().as_slice(), None).await.unwrap();
+        let tmp_log_path = prepared.tmp_log_path().to_string();
+        tx.abort(prepared).await.unwrap();
+        assert!(!std::path::Path::new(&tmp_log_path).exists());
+
+        assert_eq!(2, table.version);
+        assert_eq!(4, table.get_files().len());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_cleanup_temp_commit_files() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+        let mut tx = table.create_transaction(None);
+        let stale = tx.prepare(tx1_actions().as_slice(), None).await.unwrap();
+        let recent = tx.prepare(tx2_actions().as_slice(), None).await.unwrap();
+        let ts = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+            - 7200;
+        utime::set_file_times(stale.tmp_log_path(), ts, ts).unwrap();
+
+        let max_age = Duration::from_secs(3600);
+        let deleted = table
+            .cleanup_temp_commit_files(max_age, true)
+            .await
+            .unwrap();
+        assert_eq!(vec![stale.tmp_log_path().to_string()], deleted);
+        assert!(Path::new(stale.tmp_log_path()).exists());
+
+        let deleted = table
+            .cleanup_temp_commit_files(max_age, false)
+            .await
+            .unwrap();
+        assert_eq!(vec![stale.tmp_log_path().to_string()], deleted);
+        assert!(!Path::new(stale.tmp_log_path()).exists());
+        assert!(Path::new(recent.tmp_log_path()).exists());
+    }
+
+    #[tokio::test]
+    async fn test_temp_commit_dir() {
+        // not the shared table directory, which other tests commit to
+        let table_dir = tempdir::TempDir::new("temp_commit_dir").unwrap();
+        let log_dir = table_dir.path().join("_delta_log");
+        std::fs::create_dir(&log_dir).unwrap();
+        std::fs::copy(
+            "./tests/data/simple_commit/_delta_log/00000000000000000000.json",
+            log_dir.join("00000000000000000000.json"),
+        )
+        .unwrap();
+        let mut table = deltalake::DeltaTableBuilder::from_uri(table_dir.path().to_str().unwrap())
+            .with_config(deltalake::DeltaTableConfig {
+                temp_commit_dir: Some("_delta_log/.tmp/".to_string()),
+                ..Default::default()
+            })
+            .load()
+            .await
+            .unwrap();
+        let tmp_dir = table_dir.path().join("_delta_log/.tmp");
+
+        let mut tx = table.create_transaction(None);
+        let stale = tx.prepare(tx1_actions().as_slice(), None).await.unwrap();
+        assert_eq!(
+            Path::new(stale.tmp_log_path()).parent(),
+            Some(tmp_dir.as_path())
+        );
+        let prepared = tx.prepare(tx2_actions().as_slice(), None).await.unwrap();
+        assert_eq!(1, tx.finalize(&prepared, 1).await.unwrap());
+        assert!(!Path::new(prepared.tmp_log_path()).exists());
+
+        // the pending commit doesn't show up as a version
+        let reloaded = deltalake::open_table(table_dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(1, reloaded.version);
+
+        let ts = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+            - 7200;
+        utime::set_file_times(stale.tmp_log_path(), ts, ts).unwrap();
+        let deleted = table
+            .cleanup_temp_commit_files(Duration::from_secs(3600), false)
+            .await
+            .unwrap();
+        assert_eq!(vec![stale.tmp_log_path().to_string()], deleted);
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordedCommits(Mutex<Vec<(String, CommitMetrics)>>);
+
+    impl CommitMetricsRecorder for RecordedCommits {
+        fn record_commit(&self, table_path: &str, metrics: &CommitMetrics) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((table_path.to_string(), metrics.clone()));
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_metrics() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table_a = deltalake::open_table(table_path).await.unwrap();
+        let mut table_b = deltalake::open_table(table_path).await.unwrap();
+        let recorder = Arc::new(RecordedCommits::default());
+
+        let mut tx = table_b.create_transaction(None);
+        tx.commit_with(tx2_actions().as_slice(), None)
+            .await
+            .unwrap();
+        assert_eq!(1, tx.metrics().num_attempts);
+        assert_eq!(0, tx.metrics().num_conflicts);
+
+        // table_a is still at version 0, but the next attempt version is read from the log
+        let options = DeltaTransactionOptions::default().with_metrics_recorder(recorder.clone());
+        let mut tx = table_a.create_transaction(Some(options));
+        tx.commit_version(1, tx1_actions().as_slice(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(1, tx.metrics().num_conflicts);
+        tx.commit_with(tx1_actions().as_slice(), None)
+            .await
+            .unwrap();
+        assert_eq!(1, tx.metrics().num_attempts);
+
+        let recorded = recorder.0.lock().unwrap();
+        assert_eq!(2, recorded.len());
+        assert_eq!(table_path, recorded[0].0);
+        assert_eq!(1, recorded[0].1.num_attempts);
+        assert_eq!(1, recorded[0].1.num_conflicts);
+        assert_eq!(0, recorded[1].1.num_conflicts);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_size_limit() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+
+        let options =
+            DeltaTransactionOptions::default().with_max_commit_size(200, CommitSizePolicy::Reject);
+        let mut tx = table.create_transaction(Some(options));
+        match tx.commit_with(tx1_actions().as_slice(), None).await {
+            Err(DeltaTransactionError::CommitTooLarge { size, max_size }) => {
+                assert!(size > 200);
+                assert_eq!(200, max_size);
+            }
+            _ => panic!("the commit should be rejected"),
+        }
+
+        let options =
+            DeltaTransactionOptions::default().with_max_commit_size(200, CommitSizePolicy::Warn);
+        let mut tx = table.create_transaction(Some(options));
+        assert_eq!(1, tx.commit_with(&tx1_actions(), None).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_in_batches() {
+        prepare_fs();
+
+        let table_path = "./tests/data/simple_commit";
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+
+        let actions = [tx1_actions(), tx2_actions()].concat();
+        let batches = deltalake::split_actions(&actions, 1).unwrap();
+        assert_eq!(4, batches.len());
+        assert!(batches.iter().all(|batch| batch.len() == 1));
+        let batches = deltalake::split_actions(&actions, 1024 * 1024).unwrap();
+        assert_eq!(1, batches.len());
+
+        // room for the two adds of each transaction, but not for a third one
+        let max_batch_size = actions[..2]
+            .iter()
+            .map(|action| serde_json::to_string(action).unwrap().len() + 1)
+            .sum();
+        let mut tx = table.create_transaction(None);
+        let versions = tx
+            .commit_in_batches(&actions, None, max_batch_size)
+            .await
+            .unwrap();
+        assert_eq!(vec![1, 2], versions);
+        assert_eq!(2, table.version);
+        assert_eq!(4, table.get_files().len());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_with_atomic_put() {
+        prepare_fs();
+
+        for supports_atomic_put in &[true, false] {
+            let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_commit");
+            let table_path = table_dir.path().to_str().unwrap();
+            let storage = CountingStorage::new(
+                table_path,
+                StorageCapabilities {
+                    conditional_put: *supports_atomic_put,
+                    ..FileStorageBackend::new(table_path).capabilities()
+                },
+            );
+            let renames = storage.renames.clone();
+            let atomic_puts = storage.atomic_puts.clone();
+            let mut table = deltalake::DeltaTable::new(table_path, Box::new(storage)).unwrap();
+            table.load().await.unwrap();
+
+            let mut tx = table.create_transaction(None);
+            assert_eq!(1, tx.commit_with(&tx1_actions(), None).await.unwrap());
+            let mut tx = table.create_transaction(None);
+            assert_eq!(2, tx.commit_version(2, &tx2_actions(), None).await.unwrap());
+            let mut tx = table.create_transaction(None);
+            assert!(matches!(
+                tx.commit_version(2, &tx2_actions(), None).await,
+                Err(DeltaTransactionError::VersionAlreadyExists { .. })
+            ));
+            assert_eq!(2, table.version);
+            assert_eq!(4, table.get_files().len());
+
+            // backends with atomic puts get the log entries written directly, without renaming
+            // temporary files
+            let expected = if *supports_atomic_put { (3, 0) } else { (0, 3) };
+            assert_eq!(
+                expected,
+                (
+                    atomic_puts.load(Ordering::SeqCst),
+                    renames.load(Ordering::SeqCst)
+                )
+            );
+            let log_files = std::fs::read_dir(table_dir.path().join("_delta_log"))
+                .unwrap()
+                .count();
+            assert_eq!(3, log_files);
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_commit_without_atomic_commit() {
+        prepare_fs();
+
+        let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_commit");
+        let table_path = table_dir.path().to_str().unwrap();
+        let storage = CountingStorage::new(
+            table_path,
+            StorageCapabilities {
+                atomic_rename: false,
+                conditional_put: false,
+                ..FileStorageBackend::new(table_path).capabilities()
+            },
+        );
+        let mut table = deltalake::DeltaTable::new(table_path, Box::new(storage)).unwrap();
+        table.load().await.unwrap();
+
+        let mut tx = table.create_transaction(None);
+        let result = tx.commit_with(&tx1_actions(), None).await;
+        assert!(
+            matches!(
+                result,
+                Err(DeltaTransactionError::TransactionCommitAttempt {
+                    inner: deltalake::TransactionCommitAttemptError::Storage {
+                        source: StorageError::AtomicCommitNotSupported
+                    }
+                })
+            ),
+            "{:?}",
+            result
+        );
+        // nothing was written to the log
+        let log_files = std::fs::read_dir(table_dir.path().join("_delta_log"))
+            .unwrap()
+            .count();
+        assert_eq!(1, log_files);
+        assert_eq!(0, table.version);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_load_with_list_after() {
+        prepare_fs();
+
+        let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_commit");
+        let table_path = table_dir.path().to_str().unwrap();
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+        for _ in 0..2 {
+            let mut tx = table.create_transaction(None);
+            tx.commit_with(&tx1_actions(), None).await.unwrap();
+        }
+        std::fs::write(
+            table_dir
+                .path()
+                .join("_delta_log/00000000000000000004.json"),
+            std::fs::read(
+                table_dir
+                    .path()
+                    .join("_delta_log/00000000000000000001.json"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // the listing of the log stops at the missing commit of version 3, like its scan
+        let storage = CountingStorage::new(
+            table_path,
+            StorageCapabilities {
+                list_after: true,
+                ..FileStorageBackend::new(table_path).capabilities()
+            },
+        );
+        let table = deltalake::DeltaTable::new(table_path, Box::new(storage)).unwrap();
+        assert_eq!(2, table.get_latest_version().await.unwrap());
+        let table = deltalake::open_table(table_path).await.unwrap();
+        assert_eq!(2, table.version);
+
+        // the probes ahead of the missing commit don't count either
+        let storage = CountingStorage::new(
+            table_path,
+            FileStorageBackend::new(table_path).capabilities(),
+        );
+        let heads = storage.heads.clone();
+        let config = deltalake::DeltaTableConfig {
+            head_prefetch: Some(4),
+            ..Default::default()
+        };
+        let table =
+            deltalake::DeltaTable::new_with_config(table_path, Box::new(storage), config).unwrap();
+        assert_eq!(2, table.get_latest_version().await.unwrap());
+        assert!(heads.load(Ordering::SeqCst) >= 4);
+
+        let storage = CountingStorage::new(
+            table_path,
+            StorageCapabilities {
+                list_after: true,
+                ..FileStorageBackend::new(table_path).capabilities()
+            },
+        );
+        let list_page_sizes = storage.list_page_sizes.clone();
+        let config = deltalake::DeltaTableConfig {
+            list_page_size: Some(2),
+            ..Default::default()
+        };
+        let table =
+            deltalake::DeltaTable::new_with_config(table_path, Box::new(storage), config).unwrap();
+        assert_eq!(2, table.get_latest_version().await.unwrap());
+        assert_eq!(vec![Some(2)], *list_page_sizes.lock().unwrap());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_log_entry_framing() {
+        prepare_fs();
+
+        let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_commit");
+        let table_path = table_dir.path().to_str().unwrap();
+        let mut table = deltalake::open_table(table_path).await.unwrap();
+        let mut tx = table.create_transaction(None);
+        tx.commit_with(&tx1_actions(), None).await.unwrap();
+
+        // one action per line, each line terminated by a newline
+        let commit_path = table_dir
+            .path()
+            .join("_delta_log/00000000000000000001.json");
+        let commit = std::fs::read_to_string(&commit_path).unwrap();
+        assert!(commit.ends_with('\n'));
+        let lines = commit.split_terminator('\n').collect::<Vec<_>>();
+        assert_eq!(3, lines.len());
+        for line in &lines {
+            serde_json::from_str::<action::Action>(line).unwrap();
+        }
+
+        // blank lines and CRLF line endings written by other engines are tolerated
+        std::fs::write(&commit_path, format!("{}\r\n\n", lines.join("\r\n\n"))).unwrap();
+        let table = deltalake::open_table(table_path).await.unwrap();
+        assert_eq!(1, table.version);
+        assert_eq!(2, table.get_files().len());
+        assert_eq!(3, table.get_commit_actions(1).await.unwrap().len());
     }
 }
 
@@ -218,6 +714,90 @@ fn tx2_actions() -> Vec<action::Action> {
     ]
 }
 
+/// Counts the renames and atomic puts of the local file system backend, and reports the given
+/// capabilities instead of its own.
+#[derive(Debug)]
+struct CountingStorage {
+    inner: FileStorageBackend,
+    capabilities: StorageCapabilities,
+    renames: Arc<AtomicUsize>,
+    atomic_puts: Arc<AtomicUsize>,
+    heads: Arc<AtomicUsize>,
+    list_page_sizes: Arc<Mutex<Vec<Option<usize>>>>,
+}
+
+impl CountingStorage {
+    fn new(table_path: &str, capabilities: StorageCapabilities) -> Self {
+        Self {
+            inner: FileStorageBackend::new(table_path),
+            capabilities,
+            renames: Arc::new(AtomicUsize::new(0)),
+            atomic_puts: Arc::new(AtomicUsize::new(0)),
+            heads: Arc::new(AtomicUsize::new(0)),
+            list_page_sizes: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for CountingStorage {
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        self.heads.fetch_add(1, Ordering::SeqCst);
+        self.inner.head_obj(path).await
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        self.inner.get_obj(path).await
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        self.inner.list_objs(path).await
+    }
+
+    async fn list_objs_after<'a>(
+        &'a self,
+        path: &'a str,
+        start_after: &'a str,
+        page_size: Option<usize>,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        self.list_page_sizes.lock().unwrap().push(page_size);
+        self.inner
+            .list_objs_after(path, start_after, page_size)
+            .await
+    }
+
+    async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.inner.put_obj(path, obj_bytes).await
+    }
+
+    async fn rename_obj(&self, src: &str, dst: &str) -> Result<(), StorageError> {
+        self.renames.fetch_add(1, Ordering::SeqCst);
+        self.inner.rename_obj(src, dst).await
+    }
+
+    async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
+        self.inner.delete_obj(path).await
+    }
+
+    fn capabilities(&self) -> StorageCapabilities {
+        self.capabilities
+    }
+
+    async fn put_obj_atomic(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.atomic_puts.fetch_add(1, Ordering::SeqCst);
+        self.inner.put_obj_atomic(path, obj_bytes).await
+    }
+}
+
 fn prepare_fs() {
     fs_common::cleanup_dir_except(
         "./tests/data/simple_commit/_delta_log",
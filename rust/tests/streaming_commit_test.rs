@@ -0,0 +1,71 @@
+extern crate deltalake;
+extern crate tempdir;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use deltalake::action::{self, OutputMode};
+use deltalake::streaming::StreamingCommit;
+
+fn add_action(path: &str) -> action::Action {
+    action::Action::add(action::Add {
+        path: path.to_string(),
+        size: 396,
+        partitionValues: HashMap::new(),
+        partitionValues_parsed: None,
+        modificationTime: 1564524294000,
+        dataChange: true,
+        stats: None,
+        stats_parsed: None,
+        tags: None,
+    })
+}
+
+#[tokio::test]
+async fn commit_epochs_once() {
+    let tmp_dir = tempdir::TempDir::new("streaming_commit").unwrap();
+    let log_dir = tmp_dir.path().join("_delta_log");
+    fs::create_dir(&log_dir).unwrap();
+    fs::copy(
+        Path::new("./tests/data/simple_commit/_delta_log/00000000000000000000.json"),
+        log_dir.join("00000000000000000000.json"),
+    )
+    .unwrap();
+    let table_path = tmp_dir.path().to_str().unwrap();
+
+    let mut table = deltalake::open_table(table_path).await.unwrap();
+    let mut streaming_commit = StreamingCommit::new(&mut table, "stream", OutputMode::Append);
+    assert_eq!(streaming_commit.latest_epoch(), None);
+
+    let version = streaming_commit
+        .commit_epoch(0, &[add_action("part-0.parquet")])
+        .await
+        .unwrap();
+    assert_eq!(version, Some(1));
+    assert_eq!(streaming_commit.latest_epoch(), Some(0));
+
+    // a restarted writer replaying the epoch doesn't commit it again
+    let mut table = deltalake::open_table(table_path).await.unwrap();
+    let mut streaming_commit = StreamingCommit::new(&mut table, "stream", OutputMode::Append);
+    assert_eq!(streaming_commit.latest_epoch(), Some(0));
+    let version = streaming_commit
+        .commit_epoch(0, &[add_action("part-0.parquet")])
+        .await
+        .unwrap();
+    assert_eq!(version, None);
+
+    let version = streaming_commit
+        .commit_epoch(1, &[add_action("part-1.parquet")])
+        .await
+        .unwrap();
+    assert_eq!(version, Some(2));
+    assert_eq!(table.get_files(), vec!["part-0.parquet", "part-1.parquet"]);
+
+    let commit_info = &table.history(Some(1)).await.unwrap()[0];
+    assert_eq!(commit_info.operation.as_deref(), Some("STREAMING UPDATE"));
+    let parameters = commit_info.operationParameters.as_ref().unwrap();
+    assert_eq!(parameters["queryId"], "stream");
+    assert_eq!(parameters["epochId"], "1");
+    assert_eq!(parameters["outputMode"], "Append");
+}
@@ -0,0 +1,110 @@
+extern crate deltalake;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use deltalake::table_cache::DeltaTableLoader;
+
+#[derive(Debug)]
+struct ManualClock(Mutex<SystemTime>);
+
+impl ManualClock {
+    fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+}
+
+impl deltalake::Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[tokio::test]
+async fn loader_deduplicates_concurrent_loads() {
+    let loader = Arc::new(DeltaTableLoader::new(Duration::from_secs(60)));
+    let path = "./tests/data/simple_table";
+
+    let handles = (0..4)
+        .map(|_| {
+            let loader = loader.clone();
+            tokio::spawn(async move { loader.load(path).await.unwrap() })
+        })
+        .collect::<Vec<_>>();
+    let table = deltalake::open_table(path).await.unwrap();
+    for handle in handles {
+        let snapshot = handle.await.unwrap();
+        assert_eq!(snapshot.version(), table.version);
+        assert_eq!(snapshot.get_files(), table.get_files());
+    }
+}
+
+#[tokio::test]
+async fn loader_refreshes_expired_snapshots() {
+    let loader = DeltaTableLoader::new(Duration::from_secs(0));
+    let path = "./tests/data/simple_table_with_checkpoint";
+
+    let first = loader.load(path).await.unwrap();
+    let second = loader.load(path).await.unwrap();
+    assert_eq!(first.version(), second.version());
+    assert_eq!(first.get_files(), second.get_files());
+
+    loader.invalidate(path);
+    let reopened = loader.load(path).await.unwrap();
+    assert_eq!(reopened.get_files(), first.get_files());
+
+    assert!(loader.load("./tests/data/does_not_exist").await.is_err());
+}
+
+#[tokio::test]
+async fn refresh_stale_table() {
+    let clock = Arc::new(ManualClock(Mutex::new(SystemTime::UNIX_EPOCH)));
+    let mut table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+        .with_version(2)
+        .with_clock(clock.clone())
+        .load()
+        .await
+        .unwrap();
+    assert_eq!(table.refreshed_at(), Some(SystemTime::UNIX_EPOCH));
+    assert_eq!(table.snapshot().refreshed_at(), table.refreshed_at());
+
+    let max_age = Duration::from_secs(60);
+    clock.advance(Duration::from_secs(59));
+    assert!(!table.is_stale(max_age));
+    assert!(!table.refresh_if_stale(max_age).await.unwrap());
+    assert_eq!(table.version, 2);
+
+    clock.advance(Duration::from_secs(1));
+    assert!(table.is_stale(max_age));
+    assert!(table.refresh_if_stale(max_age).await.unwrap());
+    assert_eq!(table.version, 4);
+    assert_eq!(
+        table.refreshed_at(),
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+    );
+    assert!(!table.is_stale(max_age));
+
+    let table = deltalake::DeltaTableBuilder::from_uri("./tests/data/simple_table")
+        .build()
+        .unwrap();
+    assert_eq!(table.refreshed_at(), None);
+    assert!(table.is_stale(max_age));
+}
+
+#[tokio::test]
+async fn loader_drops_idle_tables() {
+    let loader =
+        DeltaTableLoader::new(Duration::from_secs(60)).with_max_idle(Duration::from_millis(50));
+    let path = "./tests/data/simple_table";
+    let other_path = "./tests/data/simple_table_with_checkpoint";
+
+    let first = loader.load(path).await.unwrap();
+    let cached = loader.load(path).await.unwrap();
+    assert_eq!(cached.refreshed_at(), first.refreshed_at());
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    loader.load(other_path).await.unwrap();
+    let reopened = loader.load(path).await.unwrap();
+    assert!(reopened.refreshed_at() > first.refreshed_at());
+    assert_eq!(reopened.get_files(), first.get_files());
+}
@@ -0,0 +1,119 @@
+extern crate deltalake;
+
+use std::sync::{Arc, Mutex};
+
+use deltalake::{action, TableEvent, TableEventListener};
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[derive(Debug, Default)]
+struct RecordedEvents(Mutex<Vec<TableEvent>>);
+
+impl TableEventListener for RecordedEvents {
+    fn on_event(&self, _table_path: &str, event: &TableEvent) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+}
+
+impl RecordedEvents {
+    fn take(&self) -> Vec<TableEvent> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+fn add_action(path: &str) -> action::Action {
+    action::Action::add(action::Add {
+        path: path.to_string(),
+        size: 396,
+        modificationTime: 1564524294000,
+        dataChange: true,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn commit_and_vacuum_events() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let events = Arc::new(RecordedEvents::default());
+    let mut table = deltalake::DeltaTableBuilder::from_uri(table_dir.path().to_str().unwrap())
+        .with_event_listener(events.clone())
+        .load()
+        .await
+        .unwrap();
+
+    let mut tx = table.create_transaction(None);
+    let version = tx
+        .commit_with(
+            &[add_action("part-00000.parquet")],
+            Some(action::DeltaOperation::Convert),
+        )
+        .await
+        .unwrap();
+    assert_eq!(version, 5);
+    match &events.take()[..] {
+        [TableEvent::CommitSucceeded {
+            version: 5,
+            operation: Some(operation),
+            num_attempts: 1,
+            num_conflicts: 0,
+            ..
+        }] => assert_eq!(operation, "CONVERT"),
+        events => panic!("unexpected events: {:?}", events),
+    }
+
+    let result = table
+        .create_transaction(None)
+        .commit_version(5, &[add_action("part-00001.parquet")], None)
+        .await;
+    assert!(result.is_err());
+    assert!(matches!(
+        &events.take()[..],
+        [TableEvent::CommitFailed {
+            operation: None,
+            num_conflicts: 1,
+            ..
+        }]
+    ));
+
+    // dry runs don't delete anything
+    table.vacuum(169, true).await.unwrap();
+    assert!(events.take().is_empty());
+    let deleted = table.vacuum(169, false).await.unwrap();
+    assert_eq!(
+        events.take(),
+        vec![TableEvent::VacuumCompleted {
+            version: 5,
+            num_deleted_files: deleted.len(),
+            num_deleted_temp_commits: 0,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn protocol_error_event() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let commit_path = table_dir
+        .path()
+        .join("_delta_log/00000000000000000000.json");
+    let commit = std::fs::read_to_string(&commit_path).unwrap();
+    std::fs::write(
+        &commit_path,
+        commit.replace(r#""provider":"parquet""#, r#""provider":"orc""#),
+    )
+    .unwrap();
+
+    let events = Arc::new(RecordedEvents::default());
+    let result = deltalake::DeltaTableBuilder::from_uri(table_dir.path().to_str().unwrap())
+        .with_event_listener(events.clone())
+        .load()
+        .await;
+    assert!(result.is_err());
+
+    let events = events.take();
+    assert_eq!(events.len(), 1);
+    let event = serde_json::to_value(&events[0]).unwrap();
+    assert_eq!(event["event"], "protocol_error");
+    assert_eq!(event["version"], 4);
+    assert!(event["error"].as_str().unwrap().contains("orc"));
+}
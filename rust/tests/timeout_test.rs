@@ -0,0 +1,143 @@
+extern crate deltalake;
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use deltalake::storage::file::FileStorageBackend;
+use deltalake::storage::ObjectMeta;
+use deltalake::{
+    DeltaTableConfig, DeltaTableError, OperationTimeouts, StorageBackend, StorageError,
+};
+use futures::Stream;
+use tokio_util::sync::CancellationToken;
+
+const TABLE_PATH: &str = "./tests/data/simple_table";
+
+/// Takes `delay` to read each object, like a store that stopped responding would.
+#[derive(Debug)]
+struct SlowStorage {
+    inner: FileStorageBackend,
+    delay: Duration,
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for SlowStorage {
+    async fn head_obj(&self, path: &str) -> Result<ObjectMeta, StorageError> {
+        self.inner.head_obj(path).await
+    }
+
+    async fn get_obj(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        tokio::time::sleep(self.delay).await;
+        self.inner.get_obj(path).await
+    }
+
+    async fn list_objs<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ObjectMeta, StorageError>> + Send + 'a>>,
+        StorageError,
+    > {
+        self.inner.list_objs(path).await
+    }
+
+    async fn put_obj(&self, path: &str, obj_bytes: &[u8]) -> Result<(), StorageError> {
+        self.inner.put_obj(path, obj_bytes).await
+    }
+
+    async fn rename_obj(&self, src: &str, dst: &str) -> Result<(), StorageError> {
+        self.inner.rename_obj(src, dst).await
+    }
+
+    async fn delete_obj(&self, path: &str) -> Result<(), StorageError> {
+        self.inner.delete_obj(path).await
+    }
+}
+
+fn slow_table(delay: Duration, config: DeltaTableConfig) -> deltalake::DeltaTable {
+    let storage = SlowStorage {
+        inner: FileStorageBackend::new(TABLE_PATH),
+        delay,
+    };
+    deltalake::DeltaTable::new_with_config(TABLE_PATH, Box::new(storage), config).unwrap()
+}
+
+#[tokio::test]
+async fn storage_op_timeout() {
+    let mut table = slow_table(
+        Duration::from_secs(10),
+        DeltaTableConfig {
+            timeouts: OperationTimeouts {
+                storage_op: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    match table.load().await {
+        Err(DeltaTableError::StorageError {
+            source: StorageError::Timeout(timeout),
+        }) => assert_eq!(timeout, Duration::from_millis(50)),
+        result => panic!("expected a storage timeout, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn load_timeout() {
+    // each read completes in time, but not the whole load
+    let timeouts = OperationTimeouts {
+        load: Some(Duration::from_millis(100)),
+        storage_op: Some(Duration::from_secs(10)),
+        ..Default::default()
+    };
+    let mut table = slow_table(
+        Duration::from_millis(60),
+        DeltaTableConfig {
+            timeouts,
+            ..Default::default()
+        },
+    );
+    match table.load_version(4).await {
+        Err(DeltaTableError::Timeout { operation, timeout }) => {
+            assert_eq!(operation, "load_version");
+            assert_eq!(timeout, Duration::from_millis(100));
+        }
+        result => panic!("expected a load timeout, got {:?}", result),
+    }
+
+    let mut table = slow_table(Duration::from_millis(1), DeltaTableConfig::default());
+    table.load().await.unwrap();
+    assert_eq!(table.version, 4);
+}
+
+#[tokio::test]
+async fn cancelled_load() {
+    let token = CancellationToken::new();
+    let mut table = slow_table(
+        Duration::from_secs(10),
+        DeltaTableConfig {
+            cancellation_token: Some(token.clone()),
+            ..Default::default()
+        },
+    );
+    let cancel = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        token.cancel();
+    };
+    let (result, _) = tokio::join!(table.load(), cancel);
+    assert!(
+        matches!(&result, Err(DeltaTableError::Cancelled { operation }) if operation == "load"),
+        "expected a cancelled load, got {:?}",
+        result
+    );
+
+    // operations started after the cancellation fail right away
+    let mut table = deltalake::DeltaTableBuilder::from_uri(TABLE_PATH)
+        .with_cancellation_token(token)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        table.update().await,
+        Err(DeltaTableError::Cancelled { .. })
+    ));
+}
@@ -0,0 +1,105 @@
+extern crate deltalake;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use deltalake::action::LogAction;
+use deltalake::{ApplyLogError, DeltaTableConfig, DeltaTableError, UnknownActionPolicy};
+
+#[allow(dead_code)]
+mod fs_common;
+
+const CDC_ACTION: &str = r#"{"cdc":{"path":"_change_data/cdc-00000.snappy.parquet","partitionValues":{},"size":1213,"dataChange":false}}"#;
+
+fn append_to_commit(table_dir: &Path, version: i64, line: &str) {
+    let mut commit = OpenOptions::new()
+        .append(true)
+        .open(table_dir.join(format!("_delta_log/{:020}.json", version)))
+        .unwrap();
+    writeln!(commit, "{}", line).unwrap();
+}
+
+async fn load_with_policy(
+    table_uri: &str,
+    unknown_actions: UnknownActionPolicy,
+) -> Result<deltalake::DeltaTable, DeltaTableError> {
+    deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_config(DeltaTableConfig {
+            unknown_actions,
+            ..Default::default()
+        })
+        .load()
+        .await
+}
+
+fn is_unknown_action_error(error: &DeltaTableError) -> bool {
+    matches!(
+        error,
+        DeltaTableError::ApplyLog {
+            source: ApplyLogError::UnknownAction { action_type },
+        } if action_type == "cdc"
+    )
+}
+
+#[tokio::test]
+async fn skip_unknown_actions() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    append_to_commit(table_dir.path(), 1, CDC_ACTION);
+
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    assert_eq!(table.version, 4);
+    assert_eq!(table.get_files().len(), 5);
+    let unknown_actions = table.get_unknown_actions();
+    assert_eq!(unknown_actions.len(), 1);
+    assert_eq!(unknown_actions[0].action_type, "cdc");
+    assert_eq!(unknown_actions[0].value["size"], 1213);
+    assert_eq!(
+        table.snapshot().get_unknown_actions(),
+        table.get_unknown_actions()
+    );
+
+    // commit actions leave them out, the raw log entry keeps them
+    let actions = table.get_commit_actions(1).await.unwrap();
+    let log_bytes = std::fs::read(
+        table_dir
+            .path()
+            .join("_delta_log/00000000000000000001.json"),
+    )
+    .unwrap();
+    let log_actions = deltalake::read_log_entry(&log_bytes).unwrap();
+    assert_eq!(log_actions.len(), actions.len() + 1);
+    assert!(matches!(log_actions.last(), Some(LogAction::Unknown(a)) if a.action_type == "cdc"));
+    assert!(is_unknown_action_error(
+        &deltalake::read_log_actions(&log_bytes).unwrap_err()
+    ));
+
+    let error = load_with_policy(table_uri, UnknownActionPolicy::Strict)
+        .await
+        .unwrap_err();
+    assert!(is_unknown_action_error(&error), "got {:?}", error);
+}
+
+#[tokio::test]
+async fn unknown_actions_of_unsupported_reader_version() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    // a reader feature this crate doesn't support was enabled, its actions can't be skipped
+    append_to_commit(
+        table_dir.path(),
+        1,
+        r#"{"protocol":{"minReaderVersion":2,"minWriterVersion":5}}"#,
+    );
+    append_to_commit(table_dir.path(), 2, CDC_ACTION);
+
+    let mut table = deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_version(1)
+        .load()
+        .await
+        .unwrap();
+    let error = table.get_commit_actions(2).await.unwrap_err();
+    assert!(is_unknown_action_error(&error), "got {:?}", error);
+    let error = table.update().await.unwrap_err();
+    assert!(is_unknown_action_error(&error), "got {:?}", error);
+}
@@ -0,0 +1,74 @@
+extern crate deltalake;
+
+use std::fs;
+
+#[allow(dead_code)]
+mod fs_common;
+
+#[tokio::test]
+async fn verify_file_sizes() {
+    let table_dir = fs_common::copy_table_to_temp_dir("./tests/data/simple_table");
+    let table_uri = table_dir.path().to_str().unwrap();
+    let table = deltalake::open_table(table_uri).await.unwrap();
+    assert!(table.verify_files(None).await.unwrap().is_empty());
+
+    let mut files = table.get_actions().clone();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let (overwritten, deleted) = (&files[0], &files[1]);
+    fs::write(table_dir.path().join(&overwritten.path), b"overwritten").unwrap();
+    fs::remove_file(table_dir.path().join(&deleted.path)).unwrap();
+
+    assert_eq!(
+        table.verify_files(None).await.unwrap(),
+        vec![
+            deltalake::FileSizeMismatch {
+                path: overwritten.path.clone(),
+                expected_size: overwritten.size,
+                actual_size: Some(11),
+            },
+            deltalake::FileSizeMismatch {
+                path: deleted.path.clone(),
+                expected_size: deleted.size,
+                actual_size: None,
+            },
+        ]
+    );
+    assert!(table.verify_files(Some(0)).await.unwrap().is_empty());
+
+    // verification is opt-in when loading
+    deltalake::open_table(table_uri).await.unwrap();
+    let result = deltalake::DeltaTableBuilder::from_uri(table_uri)
+        .with_config(deltalake::DeltaTableConfig {
+            verify_file_sizes: true,
+            ..Default::default()
+        })
+        .load()
+        .await;
+    match result {
+        Err(deltalake::DeltaTableError::FileSizeMismatch { mismatches }) => {
+            assert_eq!(mismatches.len(), 2)
+        }
+        _ => panic!("the load should fail with a size mismatch"),
+    }
+}
+
+#[tokio::test]
+async fn verify_uploaded_object() {
+    use deltalake::StorageBackend;
+
+    let tmp_dir = tempdir::TempDir::new("verify_upload").unwrap();
+    let path = tmp_dir.path().join("part-00000.parquet");
+    let path = path.to_str().unwrap();
+    let storage = deltalake::get_backend_for_uri(path).unwrap();
+    storage.put_obj(path, b"uploaded bytes").await.unwrap();
+    storage.verify_obj(path, b"uploaded bytes").await.unwrap();
+
+    // e.g. a proxy cut the upload short
+    fs::write(path, b"uploaded").unwrap();
+    match storage.verify_obj(path, b"uploaded bytes").await {
+        Err(deltalake::StorageError::UploadMismatch { reason, .. }) => {
+            assert_eq!(reason, "expected 14 bytes, found 8")
+        }
+        _ => panic!("the verification should fail with an upload mismatch"),
+    }
+}
@@ -7,15 +7,15 @@ use std::convert::TryFrom;
 use arrow::{
     array::{as_primitive_array, Array},
     datatypes::Schema as ArrowSchema,
-    // TODO: use for computing column stats
-    // compute::kernels::aggregate,
     datatypes::*,
     error::ArrowError,
     json::reader::Decoder,
     record_batch::RecordBatch,
 };
 use deltalake::{
-    action::{Action, Add, Remove, Stats},
+    action::{Action, Add, Remove},
+    partition_path,
+    writer::stats::stats_from_record_batch,
     DeltaTableError, DeltaTableMetaData, Schema, StorageError, UriError,
 };
 use parquet::{
@@ -156,7 +156,7 @@ impl DeltaWriter {
     fn next_data_path(
         &self,
         metadata: &DeltaTableMetaData,
-        partition_values: &HashMap<String, String>,
+        partition_values: &HashMap<String, Option<String>>,
     ) -> Result<String, DeltaWriterError> {
         // TODO: what does 00000 mean?
         let first_part = "00000";
@@ -169,31 +169,19 @@ impl DeltaWriter {
         let partition_cols = metadata.partition_columns.as_slice();
 
         let data_path = if partition_cols.len() > 0 {
-            let mut path_part = String::with_capacity(20);
-
-            // ugly string builder hack
-            let mut first = true;
-
             for k in partition_cols.iter() {
-                let partition_value =
-                    partition_values
-                        .get(k)
-                        .ok_or(DeltaWriterError::MissingPartitionColumn {
-                            col_name: k.to_string(),
-                        })?;
-
-                if first {
-                    first = false;
-                } else {
-                    path_part.push_str("/");
+                if !partition_values.contains_key(k) {
+                    return Err(DeltaWriterError::MissingPartitionColumn {
+                        col_name: k.to_string(),
+                    });
                 }
-
-                path_part.push_str(k);
-                path_part.push_str("=");
-                path_part.push_str(partition_value);
             }
 
-            format!("{}/{}", path_part, file_name)
+            format!(
+                "{}/{}",
+                partition_path(partition_cols, partition_values),
+                file_name
+            )
         } else {
             file_name
         };
@@ -246,7 +234,7 @@ pub fn record_batch_from_json_buffer(
 pub fn extract_partition_values(
     metadata: &DeltaTableMetaData,
     record_batch: &RecordBatch,
-) -> Result<HashMap<String, String>, DeltaWriterError> {
+) -> Result<HashMap<String, Option<String>>, DeltaWriterError> {
     let partition_cols = metadata.partition_columns.as_slice();
 
     let mut partition_values = HashMap::new();
@@ -266,19 +254,12 @@ pub fn extract_partition_values(
 }
 
 pub fn create_add(
-    partition_values: &HashMap<String, String>,
+    partition_values: &HashMap<String, Option<String>>,
     path: String,
     size: i64,
     record_batch: &RecordBatch,
 ) -> Result<Add, DeltaWriterError> {
-    let stats = Stats {
-        numRecords: record_batch.num_rows() as i64,
-        // TODO: calculate additional stats
-        // look at https://github.com/apache/arrow/blob/master/rust/arrow/src/compute/kernels/aggregate.rs for pulling these stats
-        minValues: HashMap::new(),
-        maxValues: HashMap::new(),
-        nullCount: HashMap::new(),
-    };
+    let stats = stats_from_record_batch(record_batch);
     let stats_string = serde_json::to_string(&stats).unwrap();
 
     let modification_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
@@ -294,7 +275,6 @@ pub fn create_add(
         modificationTime: modification_time,
         dataChange: true,
 
-        // TODO: calculate additional stats
         stats: Some(stats_string),
         stats_parsed: None,
         // ?
@@ -323,9 +303,13 @@ pub fn create_remove(path: String) -> Remove {
 // however, stats are optional and can be added later with `dataChange` false log entries, and it may be more appropriate to add stats _later_ to speed up the initial write.
 // a happy middle-road might be to compute stats for partition columns only on the initial write since we should validate partition values anyway, and compute additional stats later (at checkpoint time perhaps?).
 // also this does not currently support nested partition columns and many other data types.
-fn stringified_partition_value(arr: &Arc<dyn Array>) -> Result<String, DeltaWriterError> {
+fn stringified_partition_value(arr: &Arc<dyn Array>) -> Result<Option<String>, DeltaWriterError> {
     let data_type = arr.data_type();
 
+    if arr.is_null(0) {
+        return Ok(None);
+    }
+
     let s = match data_type {
         DataType::Int8 => as_primitive_array::<Int8Type>(arr).value(0).to_string(),
         DataType::Int16 => as_primitive_array::<Int16Type>(arr).value(0).to_string(),
@@ -346,7 +330,7 @@ fn stringified_partition_value(arr: &Arc<dyn Array>) -> Result<String, DeltaWrit
         }
     };
 
-    Ok(s)
+    Ok(Some(s))
 }
 
 #[tokio::test]